@@ -0,0 +1,31 @@
+#![feature(test)]
+#![deny(warnings, rust_2018_idioms)]
+
+extern crate test;
+
+use ntex_bytes::Bytes;
+use ntex_http::header::HeaderValue;
+use test::Bencher;
+
+const VALUE: &[u8] = b"Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 \
+    (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36";
+
+#[bench]
+fn from_bytes_copy(b: &mut Bencher) {
+    let buf = Bytes::from_static(VALUE);
+    b.iter(|| {
+        for _ in 0..1024 {
+            test::black_box(HeaderValue::from_bytes(&buf).unwrap());
+        }
+    })
+}
+
+#[bench]
+fn from_bytes_shared(b: &mut Bencher) {
+    let buf = Bytes::copy_from_slice(VALUE);
+    b.iter(|| {
+        for _ in 0..1024 {
+            test::black_box(HeaderValue::from_bytes_shared(buf.clone()).unwrap());
+        }
+    })
+}
@@ -44,9 +44,17 @@ impl fmt::Debug for Error {
     }
 }
 
+/// Displays full chain of errors, like
+/// `parse error: failed to parse header value`
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(self.get_ref(), f)
+        f.write_str("parse error")?;
+        let mut source = Some(self.get_ref());
+        while let Some(err) = source {
+            write!(f, ": {}", err)?;
+            source = err.source();
+        }
+        Ok(())
     }
 }
 
@@ -73,10 +81,9 @@ impl Error {
 }
 
 impl error::Error for Error {
-    // Return any available cause from the inner error. Note the inner error is
-    // not itself the cause.
+    /// Return the lower level, inner error.
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        self.get_ref().source()
+        Some(self.get_ref())
     }
 }
 
@@ -161,4 +168,26 @@ mod tests {
             panic!("Bad status allowed!");
         }
     }
+
+    #[test]
+    fn error_source_chain() {
+        let err: Error = crate::HeaderValue::from_str("\n").unwrap_err().into();
+        assert_eq!(err.to_string(), "parse error: failed to parse header value");
+
+        let mut chain = vec![];
+        let mut source: Option<&(dyn error::Error + 'static)> = Some(&err);
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[1], "failed to parse header value");
+        assert!(error::Error::source(&err)
+            .unwrap()
+            .is::<InvalidHeaderValue>());
+
+        let err: Error = "/a b".parse::<http::Uri>().unwrap_err().into();
+        assert_eq!(err.to_string(), "parse error: invalid uri character");
+        assert!(error::Error::source(&err).unwrap().is::<uri::InvalidUri>());
+    }
 }
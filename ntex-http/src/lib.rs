@@ -31,6 +31,7 @@ pub mod header {
 
     #[doc(hidden)]
     pub use crate::map::{AsName, Either, GetAll, Iter, Value};
+    pub use crate::map::{Entry, OccupiedEntry, VacantEntry};
     pub use crate::value::{HeaderValue, InvalidHeaderValue, ToStrError};
 
     pub use http::header::{HeaderName, InvalidHeaderName};
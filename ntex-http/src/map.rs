@@ -1,4 +1,4 @@
-use std::collections::{self, hash_map, hash_map::Entry, VecDeque};
+use std::collections::{self, hash_map, VecDeque};
 use std::{convert::TryFrom, iter::FromIterator};
 
 use crate::{HeaderName, HeaderValue};
@@ -46,6 +46,13 @@ impl Value {
         }
     }
 
+    fn len(&self) -> usize {
+        match self {
+            Value::One(_) => 1,
+            Value::Multi(ref val) => val.len(),
+        }
+    }
+
     fn append(&mut self, val: HeaderValue) {
         match self {
             Value::One(prev_val) => {
@@ -227,7 +234,7 @@ impl HeaderMap {
     ///
     /// The returned view does not incur any allocations and allows iterating
     /// the values associated with the key.  See [`GetAll`] for more details.
-    /// Returns `None` if there are no values associated with the key.
+    /// Iterator is empty if there are no values associated with the key.
     ///
     /// [`GetAll`]: struct.GetAll.html
     pub fn get_all<N: AsName>(&self, name: N) -> GetAll<'_> {
@@ -321,13 +328,41 @@ impl HeaderMap {
     /// identical.
     pub fn append(&mut self, key: HeaderName, value: HeaderValue) {
         match self.inner.entry(key) {
-            Entry::Occupied(mut entry) => entry.get_mut().append(value),
-            Entry::Vacant(entry) => {
+            hash_map::Entry::Occupied(mut entry) => entry.get_mut().append(value),
+            hash_map::Entry::Vacant(entry) => {
                 entry.insert(Value::One(value));
             }
         }
     }
 
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    ///
+    /// ```rust
+    /// use ntex_http::{header, HeaderMap, HeaderValue};
+    ///
+    /// let mut map = HeaderMap::new();
+    /// map.append(header::VARY, HeaderValue::from_static("origin"));
+    ///
+    /// let vary = HeaderValue::from_static("accept-encoding");
+    /// match map.entry(header::VARY) {
+    ///     header::Entry::Occupied(mut entry) => {
+    ///         if !entry.iter().any(|v| *v == vary) {
+    ///             entry.append(vary);
+    ///         }
+    ///     }
+    ///     header::Entry::Vacant(entry) => {
+    ///         entry.insert(vary);
+    ///     }
+    /// }
+    /// assert_eq!(map.get_all(header::VARY).len(), 2);
+    /// ```
+    pub fn entry(&mut self, key: HeaderName) -> Entry<'_> {
+        match self.inner.entry(key) {
+            hash_map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            hash_map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+
     /// Removes all headers for a particular header name from the map.
     pub fn remove<N: AsName>(&mut self, key: N) {
         match key.as_name() {
@@ -343,6 +378,128 @@ impl HeaderMap {
     }
 }
 
+/// A view into a single location in a `HeaderMap`, which may be vacant or occupied.
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// An occupied entry
+    Occupied(OccupiedEntry<'a>),
+    /// A vacant entry
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Returns a reference to the entry's key.
+    pub fn key(&self) -> &HeaderName {
+        match self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the default if empty.
+    ///
+    /// Returns a mutable reference to the first value in the entry.
+    pub fn or_insert(self, default: HeaderValue) -> &'a mut HeaderValue {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default
+    /// function if empty.
+    ///
+    /// Returns a mutable reference to the first value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut HeaderValue
+    where
+        F: FnOnce() -> HeaderValue,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Appends value to the entry, existing values are preserved.
+    pub fn append(self, value: HeaderValue) {
+        match self {
+            Entry::Occupied(mut entry) => entry.append(value),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+}
+
+/// A view into an occupied entry in a `HeaderMap`.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    inner: hash_map::OccupiedEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns a reference to the entry's key.
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Returns a reference to the first value in the entry.
+    pub fn get(&self) -> &HeaderValue {
+        self.inner.get().get()
+    }
+
+    /// Returns a mutable reference to the first value in the entry.
+    pub fn get_mut(&mut self) -> &mut HeaderValue {
+        self.inner.get_mut().get_mut()
+    }
+
+    /// Converts the entry into a mutable reference to its first value.
+    pub fn into_mut(self) -> &'a mut HeaderValue {
+        self.inner.into_mut().get_mut()
+    }
+
+    /// Returns an iterator visiting all values associated with the entry.
+    pub fn iter(&self) -> GetAll<'_> {
+        GetAll {
+            idx: 0,
+            item: Some(self.inner.get()),
+        }
+    }
+
+    /// Sets the value of the entry, all previous values are removed.
+    pub fn insert(&mut self, value: HeaderValue) {
+        let _ = self.inner.insert(Value::One(value));
+    }
+
+    /// Appends value to the end of the list of values of the entry.
+    pub fn append(&mut self, value: HeaderValue) {
+        self.inner.get_mut().append(value)
+    }
+
+    /// Removes the entry from the map and returns all its values.
+    pub fn remove(self) -> ValueIntoIter {
+        self.inner.remove().into_iter()
+    }
+}
+
+/// A view into a vacant entry in a `HeaderMap`.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    inner: hash_map::VacantEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns a reference to the entry's key.
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Sets the value of the entry and returns a mutable reference to it.
+    pub fn insert(self, value: HeaderValue) -> &'a mut HeaderValue {
+        self.inner.insert(Value::One(value)).get_mut()
+    }
+}
+
 #[doc(hidden)]
 pub trait AsName {
     fn as_name(&self) -> Either<&HeaderName, &str>;
@@ -409,8 +566,8 @@ where
             })
             .fold(HashMap::default(), |mut map: HashMap<_, Value>, (n, v)| {
                 match map.entry(n) {
-                    Entry::Occupied(mut oc) => oc.get_mut().extend(v),
-                    Entry::Vacant(va) => {
+                    hash_map::Entry::Occupied(mut oc) => oc.get_mut().extend(v),
+                    hash_map::Entry::Vacant(va) => {
                         let _ = va.insert(v);
                     }
                 }
@@ -445,6 +602,8 @@ impl TryFrom<&str> for Value {
     }
 }
 
+/// An iterator of all values associated with a single header name.
+#[derive(Debug, Clone)]
 pub struct GetAll<'a> {
     idx: usize,
     item: Option<&'a Value>,
@@ -476,8 +635,16 @@ impl<'a> Iterator for GetAll<'a> {
             None
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.item.map(|v| v.len() - self.idx).unwrap_or(0);
+        (len, Some(len))
+    }
 }
 
+impl<'a> ExactSizeIterator for GetAll<'a> {}
+
 pub struct Keys<'a>(hash_map::Keys<'a, HeaderName, Value>);
 
 impl<'a> Iterator for Keys<'a> {
@@ -546,7 +713,7 @@ impl<'a> Iterator for Iter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::header::{ACCEPT_ENCODING, CONTENT_TYPE};
+    use crate::header::{ACCEPT_ENCODING, CONTENT_TYPE, VARY};
 
     #[test]
     fn test_from_iter() {
@@ -622,4 +789,77 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_get_all() {
+        let mut map = HeaderMap::new();
+        assert_eq!(map.get_all(VARY).len(), 0);
+
+        map.insert(VARY, HeaderValue::from_static("origin"));
+        let mut iter = map.get_all(VARY);
+        assert_eq!(iter.len(), 1);
+        assert!(iter.next().is_some());
+        assert_eq!(iter.len(), 0);
+
+        map.append(VARY, HeaderValue::from_static("accept"));
+        map.append(VARY, HeaderValue::from_static("cookie"));
+        let mut iter = map.get_all(VARY);
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.clone().len(), 2);
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            vec![
+                &HeaderValue::from_static("accept"),
+                &HeaderValue::from_static("cookie")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut map = HeaderMap::new();
+
+        match map.entry(VARY) {
+            Entry::Occupied(_) => panic!(),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &VARY);
+                entry.insert(HeaderValue::from_static("origin"));
+            }
+        }
+        map.entry(VARY)
+            .append(HeaderValue::from_static("accept-encoding"));
+        assert_eq!(
+            map.get_all(VARY).collect::<Vec<_>>(),
+            vec![
+                &HeaderValue::from_static("origin"),
+                &HeaderValue::from_static("accept-encoding"),
+            ]
+        );
+
+        let val = map
+            .entry(CONTENT_TYPE)
+            .or_insert(HeaderValue::from_static("text/plain"));
+        assert_eq!(val, &HeaderValue::from_static("text/plain"));
+        let val = map
+            .entry(CONTENT_TYPE)
+            .or_insert_with(|| HeaderValue::from_static("text/html"));
+        assert_eq!(val, &HeaderValue::from_static("text/plain"));
+
+        if let Entry::Occupied(mut entry) = map.entry(VARY) {
+            assert_eq!(entry.key(), &VARY);
+            assert_eq!(entry.get(), &HeaderValue::from_static("origin"));
+            assert_eq!(entry.iter().len(), 2);
+            entry.insert(HeaderValue::from_static("cookie"));
+            assert_eq!(entry.iter().len(), 1);
+            *entry.get_mut() = HeaderValue::from_static("accept");
+            assert_eq!(
+                entry.remove().collect::<Vec<_>>(),
+                vec![HeaderValue::from_static("accept")]
+            );
+        } else {
+            panic!()
+        }
+        assert!(!map.contains_key(VARY));
+    }
 }
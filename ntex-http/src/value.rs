@@ -176,6 +176,32 @@ impl HeaderValue {
         })
     }
 
+    /// Convert a `Bytes` buffer to a `HeaderValue` without copying.
+    ///
+    /// Value is validated the same way as with `from_bytes()`, control
+    /// characters except tab are not allowed. Created value shares memory
+    /// with the source buffer.
+    ///
+    /// ```
+    /// # use ntex_http::header::HeaderValue;
+    /// # use ntex_bytes::Bytes;
+    /// let buf = Bytes::from_static(b"hello");
+    /// let val = HeaderValue::from_bytes_shared(buf.clone()).unwrap();
+    /// assert_eq!(val.as_bytes().as_ptr(), buf.as_ptr());
+    ///
+    /// assert!(HeaderValue::from_bytes_shared(Bytes::from_static(b"hello\n")).is_err());
+    /// ```
+    pub fn from_bytes_shared(src: Bytes) -> Result<HeaderValue, InvalidHeaderValue> {
+        if src.iter().all(|b| is_valid(*b)) {
+            Ok(HeaderValue {
+                inner: src,
+                is_sensitive: false,
+            })
+        } else {
+            Err(InvalidHeaderValue { _priv: () })
+        }
+    }
+
     /// Attempt to convert a `Bytes` buffer to a `HeaderValue`.
     ///
     /// This will try to prevent a copy if the type passed is the type used
@@ -757,6 +783,24 @@ mod tests {
         assert_eq!(hdr.as_bytes(), b"upgrade");
     }
 
+    #[test]
+    fn test_from_bytes_shared() {
+        let buf = Bytes::from("a".repeat(128));
+        let hdr = HeaderValue::from_bytes_shared(buf.slice(10..100)).unwrap();
+        assert_eq!(hdr.as_bytes().as_ptr(), buf[10..].as_ptr());
+        assert_eq!(hdr.len(), 90);
+        assert!(hdr.to_str().is_ok());
+
+        assert!(HeaderValue::from_bytes_shared(Bytes::from_static(b"a\tb c")).is_ok());
+        assert!(HeaderValue::from_bytes_shared(Bytes::from_static(b"a\nb")).is_err());
+        assert!(HeaderValue::from_bytes_shared(Bytes::from_static(b"a\x7fb")).is_err());
+
+        // obs-text is accepted, same as with `from_bytes()`
+        let hdr = HeaderValue::from_bytes_shared(Bytes::from_static(b"\xfa")).unwrap();
+        assert_eq!(hdr, HeaderValue::from_bytes(b"\xfa").unwrap());
+        assert!(hdr.to_str().is_err());
+    }
+
     #[test]
     fn test_try_from() {
         HeaderValue::try_from(vec![127]).unwrap_err();
@@ -1,14 +1,10 @@
 use std::cell::Cell;
 use std::task::{Context, Poll};
-use std::{
-    fmt, future::Future, hash, io, marker, mem, ops::Deref, pin::Pin, ptr, rc::Rc, time,
-};
+use std::{fmt, future::Future, hash, io, marker, mem, ops::Deref, pin::Pin, ptr, rc::Rc};
 
 use ntex_bytes::{BytesVec, PoolId, PoolRef};
 use ntex_codec::{Decoder, Encoder};
-use ntex_util::{
-    future::poll_fn, future::Either, task::LocalWaker, time::now, time::Millis,
-};
+use ntex_util::{future::poll_fn, future::Either, task::LocalWaker, time::Millis};
 
 use super::filter::{Base, NullFilter};
 use super::seal::Sealed;
@@ -68,7 +64,7 @@ pub(crate) struct IoState {
     pub(super) handle: Cell<Option<Box<dyn Handle>>>,
     #[allow(clippy::box_collection)]
     pub(super) on_disconnect: Cell<Option<Box<Vec<LocalWaker>>>>,
-    pub(super) keepalive: Cell<time::Instant>,
+    pub(super) keepalive: Cell<u64>,
 }
 
 impl IoState {
@@ -256,7 +252,7 @@ impl Io {
             filter: Cell::new(NullFilter::get()),
             handle: Cell::new(None),
             on_disconnect: Cell::new(None),
-            keepalive: Cell::new(now()),
+            keepalive: Cell::new(0),
         });
 
         let filter = Box::new(Base::new(IoRef(inner.clone())));
@@ -320,7 +316,7 @@ impl<F> Io<F> {
             filter: Cell::new(NullFilter::get()),
             handle: Cell::new(None),
             on_disconnect: Cell::new(None),
-            keepalive: Cell::new(now()),
+            keepalive: Cell::new(0),
         });
 
         let state = mem::replace(&mut self.0, IoRef(inner));
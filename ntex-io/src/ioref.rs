@@ -189,6 +189,7 @@ impl IoRef {
     /// Start keep-alive timer
     pub fn start_keepalive_timer(&self, timeout: time::Duration) {
         if self.flags().contains(Flags::KEEPALIVE) {
+            self.0.remove_flags(Flags::KEEPALIVE);
             timer::unregister(self.0.keepalive.get(), self);
         }
         if !timeout.is_zero() {
@@ -203,6 +204,7 @@ impl IoRef {
     pub fn stop_keepalive_timer(&self) {
         if self.flags().contains(Flags::KEEPALIVE) {
             log::debug!("unregister keep-alive timeout");
+            self.0.remove_flags(Flags::KEEPALIVE);
             timer::unregister(self.0.keepalive.get(), self)
         }
     }
@@ -255,6 +257,21 @@ mod tests {
     const BIN: &[u8] = b"GET /test HTTP/1\r\n\r\n";
     const TEXT: &str = "GET /test HTTP/1\r\n\r\n";
 
+    #[ntex::test]
+    async fn keepalive_timer() {
+        let (_client, server) = IoTest::create();
+        let state = Io::new(server);
+
+        state.start_keepalive_timer(time::Duration::from_secs(5));
+        assert!(state.flags().contains(Flags::KEEPALIVE));
+        state.stop_keepalive_timer();
+        assert!(!state.flags().contains(Flags::KEEPALIVE));
+
+        state.start_keepalive_timer(time::Duration::from_secs(5));
+        state.start_keepalive_timer(time::Duration::ZERO);
+        assert!(!state.flags().contains(Flags::KEEPALIVE));
+    }
+
     #[ntex::test]
     async fn utils() {
         let (client, server) = IoTest::create();
@@ -5,6 +5,7 @@ use std::{
 };
 
 pub mod testing;
+pub mod timer;
 pub mod types;
 
 mod dispatcher;
@@ -14,7 +15,6 @@ mod io;
 mod ioref;
 mod seal;
 mod tasks;
-mod timer;
 mod utils;
 
 use ntex_bytes::BytesVec;
@@ -1,45 +1,188 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc, time::Duration, time::Instant};
+//! Low resolution timer for keep-alive and idle timeouts.
+//!
+//! Timeouts are stored in per-thread hashed timer wheel, wheel is checked
+//! once per tick. Timer does not require separate timer entry for each
+//! connection, so large number of idle connections is cheap. Tick is
+//! configurable with `set_resolution()`, default is 500 millis.
+use std::task::{Context, Poll};
+use std::{cell::Cell, cell::RefCell, future::Future, pin::Pin, rc::Rc, time};
 
-use ntex_util::time::{now, sleep, Millis};
-use ntex_util::{spawn, HashSet};
+use ntex_util::task::LocalWaker;
+use ntex_util::time::{now, sleep as tick, Millis};
+use ntex_util::{spawn, HashMap};
 
 use crate::{io::IoState, IoRef};
 
+const SLOTS: u64 = 512;
+const DEFAULT_RESOLUTION: Millis = Millis(500);
+
 thread_local! {
-    static TIMER: Rc<RefCell<Inner>> = Rc::new(RefCell::new(
-        Inner {
-            running: false,
-            notifications: BTreeMap::default(),
-        }));
+    static TIMER: Rc<RefCell<Inner>> = Rc::new(RefCell::new(Inner::new(now())));
 }
 
 struct Inner {
     running: bool,
-    notifications: BTreeMap<Instant, HashSet<Rc<IoState>>>,
+    resolution: u64,
+    base: time::Instant,
+    current: u64,
+    len: usize,
+    slots: Vec<HashMap<usize, Entry>>,
+    // overdue entries moved by resolution change, key -> slot
+    moved: HashMap<usize, usize>,
+}
+
+struct Entry {
+    expire: u64,
+    target: Target,
+}
+
+enum Target {
+    Io(Rc<IoState>),
+    Sleep(Rc<SleepState>),
+}
+
+impl Target {
+    fn notify(self) {
+        match self {
+            Target::Io(st) => st.notify_keepalive(),
+            Target::Sleep(st) => {
+                st.elapsed.set(true);
+                st.waker.wake();
+            }
+        }
+    }
 }
 
 impl Inner {
-    fn unregister(&mut self, expire: Instant, io: &IoRef) {
-        if let Some(states) = self.notifications.get_mut(&expire) {
-            states.remove(&io.0);
-            if states.is_empty() {
-                self.notifications.remove(&expire);
+    fn new(base: time::Instant) -> Self {
+        Inner {
+            base,
+            running: false,
+            resolution: DEFAULT_RESOLUTION.0 as u64,
+            current: 0,
+            len: 0,
+            slots: (0..SLOTS).map(|_| HashMap::default()).collect(),
+            moved: HashMap::default(),
+        }
+    }
+
+    /// Millis since wheel start
+    fn elapsed(&self, now: time::Instant) -> u64 {
+        (now - self.base).as_millis() as u64
+    }
+
+    fn slot(&self, expire: u64) -> usize {
+        let tick = (expire + self.resolution - 1) / self.resolution;
+        (tick % SLOTS) as usize
+    }
+
+    /// Entry cannot expire before next tick
+    fn min_expire(&self) -> u64 {
+        (self.current + 1) * self.resolution
+    }
+
+    fn insert(
+        &mut self,
+        now: time::Instant,
+        timeout: time::Duration,
+        key: usize,
+        t: Target,
+    ) -> u64 {
+        if self.len == 0 && !self.running {
+            self.base = now;
+            self.current = 0;
+        }
+        let expire =
+            (self.elapsed(now) + timeout.as_millis() as u64).max(self.min_expire());
+        let slot = self.slot(expire);
+        if self.slots[slot]
+            .insert(key, Entry { expire, target: t })
+            .is_none()
+        {
+            self.len += 1;
+        }
+        expire
+    }
+
+    fn remove(&mut self, expire: u64, key: usize) {
+        let slot = self.moved.remove(&key).unwrap_or_else(|| self.slot(expire));
+        if self.slots[slot].remove(&key).is_some() {
+            self.len -= 1;
+        }
+    }
+
+    /// Remove expired entries, entries are returned in expiration order
+    fn expired(&mut self, now: time::Instant) -> Vec<Target> {
+        let elapsed = self.elapsed(now);
+        let tick = elapsed / self.resolution;
+        let ticks = (tick.saturating_sub(self.current)).min(SLOTS);
+
+        let mut expired = Vec::new();
+        for t in 1..=ticks {
+            let slot = &mut self.slots[((self.current + t) % SLOTS) as usize];
+            if slot.is_empty() {
+                continue;
+            }
+            let keys: Vec<_> = slot
+                .iter()
+                .filter(|(_, entry)| entry.expire <= elapsed)
+                .map(|(key, _)| *key)
+                .collect();
+            for key in keys {
+                expired.push(slot.remove(&key).unwrap());
+                if !self.moved.is_empty() {
+                    self.moved.remove(&key);
+                }
             }
         }
+        self.current = self.current.max(tick);
+        self.len -= expired.len();
+        expired.sort_by_key(|entry| entry.expire);
+        expired.into_iter().map(|entry| entry.target).collect()
+    }
+
+    fn set_resolution(&mut self, now: time::Instant, resolution: u64) {
+        let entries: Vec<_> = self
+            .slots
+            .iter_mut()
+            .flat_map(|slot| slot.drain())
+            .collect();
+        self.resolution = resolution;
+        self.current = self.elapsed(now) / resolution;
+        self.moved.clear();
+        for (key, entry) in entries {
+            // expiration time is not changed, so `remove()` finds entry
+            // by its expiration, overdue entries go to the next tick
+            let slot = if entry.expire < self.min_expire() {
+                let slot = self.slot(self.min_expire());
+                self.moved.insert(key, slot);
+                slot
+            } else {
+                self.slot(entry.expire)
+            };
+            self.slots[slot].insert(key, entry);
+        }
     }
 }
 
-pub(crate) fn register(timeout: Duration, io: &IoRef) -> Instant {
-    let expire = now() + timeout;
+/// Set timer resolution for current thread.
+///
+/// Keep-alive, client and idle timeouts are checked once per tick,
+/// so timeouts could fire up to `resolution` later. Default is 500 millis.
+pub fn set_resolution<T: Into<Millis>>(resolution: T) {
+    let resolution = resolution.into().0.max(1) as u64;
+    TIMER.with(|timer| timer.borrow_mut().set_resolution(now(), resolution))
+}
+
+/// Timer resolution for current thread
+pub fn resolution() -> Millis {
+    TIMER.with(|timer| Millis(timer.borrow().resolution as u32))
+}
 
+fn insert(timeout: time::Duration, key: usize, target: Target) -> u64 {
     TIMER.with(|timer| {
         let mut inner = timer.borrow_mut();
-
-        inner
-            .notifications
-            .entry(expire)
-            .or_insert_with(HashSet::default)
-            .insert(io.0.clone());
+        let expire = inner.insert(now(), timeout, key, target);
 
         if !inner.running {
             inner.running = true;
@@ -47,39 +190,231 @@ pub(crate) fn register(timeout: Duration, io: &IoRef) -> Instant {
 
             spawn(async move {
                 loop {
-                    sleep(Millis::ONE_SEC).await;
-                    {
-                        let mut i = inner.borrow_mut();
-                        let now_time = now();
-
-                        // notify io dispatcher
-                        while let Some(key) = i.notifications.keys().next() {
-                            let key = *key;
-                            if key <= now_time {
-                                for st in i.notifications.remove(&key).unwrap() {
-                                    st.notify_keepalive();
-                                }
-                            } else {
-                                break;
-                            }
-                        }
-
-                        // new tick
-                        if i.notifications.is_empty() {
-                            i.running = false;
-                            break;
-                        }
+                    let resolution = inner.borrow().resolution;
+                    tick(Millis(resolution as u32)).await;
+
+                    // notify expired entries
+                    let expired = inner.borrow_mut().expired(now());
+                    for target in expired {
+                        target.notify();
+                    }
+
+                    let mut i = inner.borrow_mut();
+                    if i.len == 0 {
+                        i.running = false;
+                        break;
                     }
                 }
             });
         }
+        expire
+    })
+}
+
+fn remove(expire: u64, key: usize) {
+    let _ = TIMER.try_with(|timer| timer.borrow_mut().remove(expire, key));
+}
+
+pub(crate) fn register(timeout: time::Duration, io: &IoRef) -> u64 {
+    insert(
+        timeout,
+        Rc::as_ptr(&io.0) as usize,
+        Target::Io(io.0.clone()),
+    )
+}
+
+pub(crate) fn unregister(expire: u64, io: &IoRef) {
+    remove(expire, Rc::as_ptr(&io.0) as usize)
+}
+
+/// Future that resolves after timeout, with timer resolution precision.
+///
+/// Unlike `ntex::time::sleep()`, all low resolution timers share
+/// single per-thread timer wheel.
+pub fn sleep<T: Into<Millis>>(timeout: T) -> Sleep {
+    let timeout = time::Duration::from(timeout.into());
+    let state = Rc::new(SleepState {
+        elapsed: Cell::new(timeout.is_zero()),
+        waker: LocalWaker::new(),
     });
+    let expire = if timeout.is_zero() {
+        0
+    } else {
+        insert(
+            timeout,
+            Rc::as_ptr(&state) as usize,
+            Target::Sleep(state.clone()),
+        )
+    };
+    Sleep { state, expire }
+}
 
-    expire
+/// Low resolution sleep future, created by `sleep()` function
+pub struct Sleep {
+    state: Rc<SleepState>,
+    expire: u64,
 }
 
-pub(crate) fn unregister(expire: Instant, io: &IoRef) {
-    TIMER.with(|timer| {
-        timer.borrow_mut().unregister(expire, io);
-    })
+struct SleepState {
+    elapsed: Cell<bool>,
+    waker: LocalWaker,
+}
+
+impl Sleep {
+    /// Returns `true` if timeout is elapsed
+    pub fn is_elapsed(&self) -> bool {
+        self.state.elapsed.get()
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.elapsed.get() {
+            Poll::Ready(())
+        } else {
+            self.state.waker.register(cx.waker());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if !self.state.elapsed.get() {
+            remove(self.expire, Rc::as_ptr(&self.state) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(inner: &mut Inner, now: time::Instant, millis: u64) -> Rc<SleepState> {
+        let st = Rc::new(SleepState {
+            elapsed: Cell::new(false),
+            waker: LocalWaker::new(),
+        });
+        inner.insert(
+            now,
+            time::Duration::from_millis(millis),
+            Rc::as_ptr(&st) as usize,
+            Target::Sleep(st.clone()),
+        );
+        st
+    }
+
+    fn expired(
+        inner: &mut Inner,
+        now: time::Instant,
+        st: &[&Rc<SleepState>],
+    ) -> Vec<usize> {
+        inner
+            .expired(now)
+            .into_iter()
+            .map(|t| match t {
+                Target::Sleep(t) => st.iter().position(|s| Rc::ptr_eq(s, &t)).unwrap(),
+                Target::Io(_) => panic!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wheel_expiry_order() {
+        let base = time::Instant::now();
+        let at = |millis| base + time::Duration::from_millis(millis);
+        let mut inner = Inner::new(base);
+
+        let s0 = entry(&mut inner, base, 1_200);
+        let s1 = entry(&mut inner, base, 600);
+        let s2 = entry(&mut inner, base, 1_100);
+        let s3 = entry(&mut inner, base, 900);
+        let st = [&s0, &s1, &s2, &s3];
+        assert_eq!(inner.len, 4);
+
+        assert!(expired(&mut inner, at(400), &st).is_empty());
+        assert!(expired(&mut inner, at(700), &st).is_empty());
+        assert_eq!(expired(&mut inner, at(1_000), &st), vec![1, 3]);
+        // missed ticks are processed
+        assert_eq!(expired(&mut inner, at(1_600), &st), vec![2, 0]);
+        assert_eq!(inner.len, 0);
+
+        // empty wheel is restarted, removed entry does not expire
+        let s4 = entry(&mut inner, at(1_600), 100);
+        let s5 = entry(&mut inner, at(1_600), 200);
+        assert_eq!(inner.current, 0);
+        inner.remove(500, Rc::as_ptr(&s4) as usize);
+        assert_eq!(expired(&mut inner, at(2_100), &[&s4, &s5]), vec![1]);
+        assert_eq!(inner.len, 0);
+    }
+
+    #[test]
+    fn test_wheel_rounds() {
+        let base = time::Instant::now();
+        let at = |millis| base + time::Duration::from_millis(millis);
+        let mut inner = Inner::new(base);
+        let res = inner.resolution;
+
+        // same slot, different wheel rounds
+        let s0 = entry(&mut inner, base, res * (SLOTS + 1));
+        let s1 = entry(&mut inner, base, res);
+        let st = [&s0, &s1];
+
+        assert_eq!(expired(&mut inner, at(res), &st), vec![1]);
+        assert!(expired(&mut inner, at(res * SLOTS), &st).is_empty());
+        assert_eq!(expired(&mut inner, at(res * (SLOTS + 1)), &st), vec![0]);
+    }
+
+    #[test]
+    fn test_wheel_resolution() {
+        let base = time::Instant::now();
+        let at = |millis| base + time::Duration::from_millis(millis);
+        let mut inner = Inner::new(base);
+        inner.set_resolution(base, 50);
+
+        let s0 = entry(&mut inner, base, 150);
+        let s1 = entry(&mut inner, base, 60);
+        let s2 = entry(&mut inner, base, 20);
+        let st = [&s0, &s1, &s2];
+
+        // entries are moved to new slots
+        inner.set_resolution(at(10), 20);
+        assert!(expired(&mut inner, at(50), &st).is_empty());
+        assert_eq!(expired(&mut inner, at(60), &st), vec![2, 1]);
+        inner.set_resolution(at(70), 30);
+        inner.remove(150, Rc::as_ptr(&s0) as usize);
+        assert_eq!(inner.len, 0);
+
+        // overdue entry is moved to the next tick and still could be removed
+        let s3 = entry(&mut inner, at(70), 40);
+        inner.set_resolution(at(200), 50);
+        assert_eq!(inner.moved.len(), 1);
+        inner.remove(110, Rc::as_ptr(&s3) as usize);
+        assert_eq!(inner.len, 0);
+        assert!(inner.moved.is_empty());
+
+        let s4 = entry(&mut inner, at(200), 10);
+        inner.set_resolution(at(300), 40);
+        assert_eq!(expired(&mut inner, at(320), &[&s4]), vec![0]);
+        assert!(inner.moved.is_empty());
+    }
+
+    #[ntex::test]
+    async fn test_sleep() {
+        set_resolution(Millis(10));
+        assert_eq!(resolution(), Millis(10));
+
+        let start = now();
+        sleep(Millis(50)).await;
+        assert!(now() - start >= time::Duration::from_millis(50));
+
+        let s = sleep(Millis(20));
+        drop(s);
+        sleep(Millis::ZERO).await;
+        assert!(sleep(Millis::ZERO).is_elapsed());
+        TIMER.with(|t| assert_eq!(t.borrow().len, 0));
+        set_resolution(DEFAULT_RESOLUTION);
+    }
 }
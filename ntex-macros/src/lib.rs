@@ -15,12 +15,16 @@
 //! - [options](attr.web_options.html)
 //! - [trace](attr.web_trace.html)
 //! - [patch](attr.web_patch.html)
+//! - [route](attr.web_route.html)
+//! - [openapi](attr.web_openapi.html), OpenAPI metadata for route handler
 //!
 //! ### Attributes:
 //!
 //! - `"path"` - Raw literal string with path for which to register handle. Mandatory.
 //! - `guard = "function_name"` - Registers function as guard using `ntex::web::guard::fn_guard`
+//! - `name = "resource_name"` - Resource name, by default function name is used
 //! - `error = "ErrorRenderer"` - Register handler for specified error renderer
+//! - `method = "METHOD"` - Route method, only for `route` macro, could be repeated
 //!
 //! ## Notes
 //!
@@ -55,6 +59,7 @@ use syn::parse_macro_input;
 ///
 /// - `"path"` - Raw literal string with path for which to register handler. Mandatory.
 /// - `guard = "function_name"` - Registers function as guard using `ntex::web::guard::fn_guard`
+/// - `name = "resource_name"` - Resource name, by default function name is used
 /// - `error = "ErrorRenderer"` - Register handler for different error renderer
 #[proc_macro_attribute]
 pub fn web_get(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -186,6 +191,84 @@ pub fn web_patch(args: TokenStream, input: TokenStream) -> TokenStream {
     gen.generate()
 }
 
+/// Creates route handler with multiple method guards.
+///
+/// Syntax: `#[route("path", method = "GET"[, method = "HEAD", attributes])]`
+///
+/// ## Attributes:
+///
+/// - `"path"` - Raw literal string with path for which to register handler. Mandatory.
+/// - `method = "METHOD"` - Method guard, at least one method is required.
+/// - `guard = "function_name"` - Registers function as guard using `ntex::web::guard::fn_guard`
+/// - `name = "resource_name"` - Resource name, by default function name is used
+/// - `error = "ErrorRenderer"` - Register handler for different error renderer
+///
+/// ```rust
+/// use ntex::web::{self, HttpResponse};
+///
+/// #[web::route("/test", method = "GET", method = "HEAD", name = "test")]
+/// async fn test() -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+/// ```
+///
+/// Invalid path patterns and unknown methods are reported at compile time:
+///
+/// ```rust,compile_fail
+/// #[ntex::web::route("/items/{id", method = "GET")]
+/// async fn test() -> &'static str {
+///     "unclosed segment"
+/// }
+/// ```
+///
+/// ```rust,compile_fail
+/// #[ntex::web::route("/items", method = "FETCH")]
+/// async fn test() -> &'static str {
+///     "unknown method"
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn web_route(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as syn::AttributeArgs);
+    let gen = match route::Route::new_multi(args, input) {
+        Ok(gen) => gen,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    gen.generate()
+}
+
+/// Attaches OpenAPI operation metadata to route handler.
+///
+/// Syntax: `#[openapi(summary = "...", tag = "...", request_body = T, response = U)]`
+///
+/// Attribute must be used together with one of route macros, metadata is
+/// available via `ntex::web::openapi::spec()`. Types must implement
+/// `ntex::web::openapi::Schema` trait. Requires `openapi` feature of ntex.
+///
+/// ## Attributes:
+///
+/// - `summary = "text"` - Operation summary
+/// - `tag = "name"` - Operation tag, could be repeated
+/// - `request_body = T` - Type of request body
+/// - `response = U` - Type of successful response body
+///
+/// ```rust,ignore
+/// use ntex::web::{self, types::Json};
+///
+/// #[ntex::openapi(summary = "Echo", tag = "test", request_body = Json<String>, response = Json<String>)]
+/// #[web::post("/echo")]
+/// async fn echo(body: Json<String>) -> Json<String> {
+///     body
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn web_openapi(args: TokenStream, input: TokenStream) -> TokenStream {
+    match route::openapi(args, input) {
+        Ok(gen) => gen,
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Marks async function to be executed by ntex system.
 ///
 /// ## Usage
@@ -233,19 +316,62 @@ pub fn rt_main(_: TokenStream, item: TokenStream) -> TokenStream {
 ///     assert!(true);
 /// }
 /// ```
+///
+/// ## Attributes:
+///
+/// - `service = "expression"` - Expression that constructs an application.
+///   Application get initialized with `ntex::web::test::init_service()` and
+///   passed to the test function as the only parameter. Type of the parameter
+///   is not used and could be set to `_`.
+///
+/// ```no_run
+/// use ntex::web::{self, test, App, HttpResponse};
+///
+/// #[web::get("/")]
+/// async fn index() -> HttpResponse {
+///     HttpResponse::Ok().finish()
+/// }
+///
+/// #[ntex::test(service = "App::new().service(index)")]
+/// async fn my_test(srv: _) {
+///     let req = test::TestRequest::with_uri("/").to_request();
+///     let resp = test::call_service(&srv, req).await;
+///     assert!(resp.status().is_success());
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn rt_test(_: TokenStream, item: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(item as syn::ItemFn);
-
-    let ret = &input.sig.output;
-    let name = &input.sig.ident;
-    let body = &input.block;
-    let attrs = &input.attrs;
-    let mut has_test_attr = false;
+pub fn rt_test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(args as syn::AttributeArgs);
+    let mut input = syn::parse_macro_input!(item as syn::ItemFn);
 
-    for attr in attrs {
-        if attr.path.is_ident("test") {
-            has_test_attr = true;
+    let mut service: Option<syn::Expr> = None;
+    for arg in args {
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv))
+                if nv.path.is_ident("service") =>
+            {
+                if let syn::Lit::Str(lit) = nv.lit {
+                    match syn::parse_str(&lit.value()) {
+                        Ok(expr) => service = Some(expr),
+                        Err(e) => return e.to_compile_error().into(),
+                    }
+                } else {
+                    return syn::Error::new_spanned(
+                        nv.lit,
+                        "Attribute service expects literal string with expression!",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            }
+            arg => {
+                return syn::Error::new_spanned(
+                    arg,
+                    "Unknown attribute is specified. Allowed: service",
+                )
+                .to_compile_error()
+                .into();
+            }
         }
     }
 
@@ -258,12 +384,52 @@ pub fn rt_test(_: TokenStream, item: TokenStream) -> TokenStream {
         .into();
     }
 
+    // bind initialized service to function's parameter
+    let init = if let Some(service) = service {
+        let inputs = std::mem::take(&mut input.sig.inputs);
+        let pat = match inputs.first() {
+            Some(syn::FnArg::Typed(arg)) if inputs.len() == 1 => arg.pat.clone(),
+            _ => {
+                return syn::Error::new_spanned(
+                    inputs,
+                    "test function with service attribute expects one parameter",
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+        quote! {
+            let #pat = ntex::web::test::init_service(#service).await;
+        }
+    } else if !input.sig.inputs.is_empty() {
+        return syn::Error::new_spanned(
+            &input.sig.inputs,
+            "test function does not accept parameters, use service attribute",
+        )
+        .to_compile_error()
+        .into();
+    } else {
+        quote! {}
+    };
+
+    let ret = &input.sig.output;
+    let name = &input.sig.ident;
+    let body = &input.block;
+    let attrs = &input.attrs;
+    let mut has_test_attr = false;
+
+    for attr in attrs {
+        if attr.path.is_ident("test") {
+            has_test_attr = true;
+        }
+    }
+
     let result = if has_test_attr {
         quote! {
             #(#attrs)*
             fn #name() #ret {
                 ntex::rt::System::new("test")
-                    .block_on(async { #body })
+                    .block_on(async { #init #body })
             }
         }
     } else {
@@ -272,7 +438,7 @@ pub fn rt_test(_: TokenStream, item: TokenStream) -> TokenStream {
             #(#attrs)*
             fn #name() #ret {
                 ntex::rt::System::new("test")
-                    .block_on(async { #body })
+                    .block_on(async { #init #body })
             }
         }
     };
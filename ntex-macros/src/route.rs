@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
+use syn::parse::{Parse, ParseStream};
 use syn::{AttributeArgs, Ident, NestedMeta, Path};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -32,6 +33,26 @@ impl MethodType {
     }
 }
 
+impl MethodType {
+    fn parse(lit: &syn::LitStr) -> syn::Result<Self> {
+        match lit.value().to_ascii_uppercase().as_str() {
+            "GET" => Ok(MethodType::Get),
+            "POST" => Ok(MethodType::Post),
+            "PUT" => Ok(MethodType::Put),
+            "DELETE" => Ok(MethodType::Delete),
+            "HEAD" => Ok(MethodType::Head),
+            "CONNECT" => Ok(MethodType::Connect),
+            "OPTIONS" => Ok(MethodType::Options),
+            "TRACE" => Ok(MethodType::Trace),
+            "PATCH" => Ok(MethodType::Patch),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                format!("Unsupported method {:?}", lit.value()),
+            )),
+        }
+    }
+}
+
 impl ToTokens for MethodType {
     fn to_tokens(&self, stream: &mut TokenStream2) {
         let ident = self.as_str();
@@ -42,8 +63,10 @@ impl ToTokens for MethodType {
 
 struct Args {
     path: syn::LitStr,
-    guards: Vec<Ident>,
+    guards: Vec<Path>,
     error: Path,
+    name: Option<syn::LitStr>,
+    methods: Vec<MethodType>,
 }
 
 impl Args {
@@ -51,6 +74,8 @@ impl Args {
         let mut path = None;
         let mut guards = Vec::new();
         let mut error: Option<Path> = None;
+        let mut name = None;
+        let mut methods = Vec::new();
         for arg in args {
             match arg {
                 NestedMeta::Lit(syn::Lit::Str(lit)) => match path {
@@ -67,7 +92,7 @@ impl Args {
                 NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
                     if nv.path.is_ident("guard") {
                         if let syn::Lit::Str(lit) = nv.lit {
-                            guards.push(Ident::new(&lit.value(), Span::call_site()));
+                            guards.push(lit.parse()?);
                         } else {
                             return Err(syn::Error::new_spanned(
                                 nv.lit,
@@ -83,10 +108,35 @@ impl Args {
                                 "Attribute error expects type path!",
                             ));
                         }
+                    } else if nv.path.is_ident("name") {
+                        if let syn::Lit::Str(lit) = nv.lit {
+                            name = Some(lit);
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute name expects literal string!",
+                            ));
+                        }
+                    } else if nv.path.is_ident("method") {
+                        if let syn::Lit::Str(ref lit) = nv.lit {
+                            let method = MethodType::parse(lit)?;
+                            if methods.contains(&method) {
+                                return Err(syn::Error::new_spanned(
+                                    lit,
+                                    "Method is specified multiple times",
+                                ));
+                            }
+                            methods.push(method);
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute method expects literal string!",
+                            ));
+                        }
                     } else {
                         return Err(syn::Error::new_spanned(
                             nv.path,
-                            "Unknown attribute key is specified. Allowed: guard or error",
+                            "Unknown attribute key is specified. Allowed: guard, name, method or error",
                         ));
                     }
                 }
@@ -95,20 +145,219 @@ impl Args {
                 }
             }
         }
+        let path = path
+            .ok_or_else(|| syn::Error::new(Span::call_site(), "Path is not specified"))?;
+        validate_path(&path)?;
+
         Ok(Args {
-            path: path.unwrap(),
+            path,
             guards,
+            name,
+            methods,
             error: error
                 .unwrap_or_else(|| syn::parse_str("ntex::web::DefaultError").unwrap()),
         })
     }
 }
 
+/// Check that dynamic segments of the path pattern are well-formed
+fn validate_path(path: &syn::LitStr) -> syn::Result<()> {
+    let pattern = path.value();
+    let mut depth = 0;
+    let mut segment = String::new();
+    for ch in pattern.chars() {
+        match ch {
+            '{' => {
+                if depth > 0 {
+                    segment.push(ch);
+                }
+                depth += 1;
+            }
+            '}' if depth == 0 => {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!("Invalid path pattern {:?}, unexpected '}}'", pattern),
+                ));
+            }
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let name = segment.split(':').next().unwrap_or_default();
+                    if name.is_empty()
+                        || !name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    {
+                        return Err(syn::Error::new_spanned(
+                            path,
+                            format!(
+                                "Invalid path pattern {:?}, invalid segment name {:?}",
+                                pattern, name
+                            ),
+                        ));
+                    }
+                    segment.clear();
+                } else {
+                    segment.push(ch);
+                }
+            }
+            _ if depth > 0 => segment.push(ch),
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(syn::Error::new_spanned(
+            path,
+            format!("Invalid path pattern {:?}, unclosed '{{'", pattern),
+        ));
+    }
+    Ok(())
+}
+
+/// Arguments of `#[openapi(...)]` attribute
+pub struct OpenApi {
+    summary: Option<syn::LitStr>,
+    tags: Vec<syn::LitStr>,
+    request_body: Option<syn::Type>,
+    response: Option<syn::Type>,
+}
+
+impl Parse for OpenApi {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut summary = None;
+        let mut tags = Vec::new();
+        let mut request_body = None;
+        let mut response = None;
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            if key == "summary" {
+                if summary.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "Summary is specified multiple times",
+                    ));
+                }
+                summary = Some(input.parse()?);
+            } else if key == "tag" {
+                tags.push(input.parse()?);
+            } else if key == "request_body" {
+                if request_body.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "Request body is specified multiple times",
+                    ));
+                }
+                request_body = Some(input.parse()?);
+            } else if key == "response" {
+                if response.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "Response is specified multiple times",
+                    ));
+                }
+                response = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "Unknown attribute key is specified. Allowed: summary, tag, request_body or response",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+        Ok(OpenApi {
+            summary,
+            tags,
+            request_body,
+            response,
+        })
+    }
+}
+
+impl ToTokens for OpenApi {
+    fn to_tokens(&self, stream: &mut TokenStream2) {
+        let summary = self.summary.iter();
+        let tags = &self.tags;
+        let request_body = self.request_body.iter();
+        let response = self.response.iter();
+        stream.extend(quote! {
+            ntex::web::openapi::Operation::new()
+                #(.summary(#summary))*
+                #(.tag(#tags))*
+                #(.request_body::<#request_body>())*
+                #(.response::<#response>())*
+        });
+    }
+}
+
+/// Check if attribute is `#[openapi(...)]` or `#[ntex::openapi(...)]`
+fn is_openapi_attr(attr: &syn::Attribute) -> bool {
+    let segments = &attr.path.segments;
+    match segments.len() {
+        1 => segments[0].ident == "openapi",
+        2 => segments[0].ident == "ntex" && segments[1].ident == "openapi",
+        _ => false,
+    }
+}
+
+/// Remove openapi attribute from handler function
+fn take_openapi(ast: &mut syn::ItemFn) -> syn::Result<Option<OpenApi>> {
+    let mut openapi = None;
+    let mut attrs = Vec::new();
+    for attr in ast.attrs.drain(..) {
+        if is_openapi_attr(&attr) {
+            if openapi.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "Attribute openapi is specified multiple times",
+                ));
+            }
+            openapi = Some(attr.parse_args::<OpenApi>()?);
+        } else {
+            attrs.push(attr);
+        }
+    }
+    ast.attrs = attrs;
+    Ok(openapi)
+}
+
+/// Check if attribute is one of route macros
+fn is_route_attr(attr: &syn::Attribute) -> bool {
+    const NAMES: &[&str] = &[
+        "get", "post", "put", "delete", "head", "connect", "options", "trace", "patch",
+        "route",
+    ];
+    if let Some(seg) = attr.path.segments.last() {
+        let name = seg.ident.to_string();
+        NAMES.contains(&name.strip_prefix("web_").unwrap_or(&name))
+    } else {
+        false
+    }
+}
+
+/// `#[openapi(...)]` placed above route macro, move it below so route
+/// macro could consume it
+pub fn openapi(args: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    let args = TokenStream2::from(args);
+    syn::parse2::<OpenApi>(args.clone())?;
+    let mut ast: syn::ItemFn = syn::parse(input)?;
+    let idx = ast.attrs.iter().position(is_route_attr).ok_or_else(|| {
+        syn::Error::new(
+            Span::call_site(),
+            "Attribute openapi must be used together with route macro, e.g. #[web::get(\"<path>\")]",
+        )
+    })?;
+    ast.attrs
+        .insert(idx + 1, syn::parse_quote!(#[openapi(#args)]));
+    Ok(quote!(#ast).into())
+}
+
 pub struct Route {
     name: syn::Ident,
     args: Args,
     ast: syn::ItemFn,
-    method: MethodType,
+    openapi: Option<OpenApi>,
 }
 
 impl Route {
@@ -126,26 +375,67 @@ impl Route {
                 ),
             ));
         }
-        let ast: syn::ItemFn = syn::parse(input)?;
+        let mut ast: syn::ItemFn = syn::parse(input)?;
+        let openapi = take_openapi(&mut ast)?;
+        let name = ast.sig.ident.clone();
+        let mut args = Args::new(args)?;
+        if !args.methods.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "Attribute method is supported only by route macro",
+            ));
+        }
+        args.methods.push(method);
+
+        Ok(Self {
+            name,
+            args,
+            ast,
+            openapi,
+        })
+    }
+
+    /// Route with multiple methods, `#[route("path", method = "GET", ...)]`
+    pub fn new_multi(args: AttributeArgs, input: TokenStream) -> syn::Result<Self> {
+        if args.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                r#"invalid server definition, expected #[route("<some path>", method = "<method>")]"#,
+            ));
+        }
+        let mut ast: syn::ItemFn = syn::parse(input)?;
+        let openapi = take_openapi(&mut ast)?;
         let name = ast.sig.ident.clone();
         let args = Args::new(args)?;
+        if args.methods.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "At least one method is required, use method = \"<method>\" attribute",
+            ));
+        }
 
         Ok(Self {
             name,
             args,
             ast,
-            method,
+            openapi,
         })
     }
 
     pub fn generate(&self) -> TokenStream {
         let name = &self.name;
-        let resource_name = name.to_string();
+        let resource_name = self
+            .args
+            .name
+            .as_ref()
+            .map_or_else(|| name.to_string(), |n| n.value());
         let ast = &self.ast;
         let path = &self.args.path;
         let extra_guards = &self.args.guards;
         let error = &self.args.error;
-        let method = &self.method;
+        let method = &self.args.methods[0];
+        let methods = &self.args.methods[1..];
+        let openapi = self.openapi.iter();
 
         let stream = quote! {
             #[allow(non_camel_case_types)]
@@ -158,8 +448,10 @@ impl Route {
 
                     let __resource = ntex::web::Resource::new(#path)
                         .name(#resource_name)
-                        .guard(ntex::web::guard::#method())
+                        .guard(ntex::web::guard::Any(ntex::web::guard::#method())
+                            #(.or(ntex::web::guard::#methods()))*)
                         #(.guard(ntex::web::guard::fn_guard(#extra_guards)))*
+                        #(.metadata(#openapi))*
                         .to(#name);
 
                     ntex::web::dev::WebServiceFactory::register(__resource, __config)
@@ -169,3 +461,131 @@ impl Route {
         stream.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(s: &str) -> syn::LitStr {
+        syn::LitStr::new(s, Span::call_site())
+    }
+
+    fn path_err(s: &str) -> String {
+        validate_path(&lit(s)).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_validate_path() {
+        assert!(validate_path(&lit("/")).is_ok());
+        assert!(validate_path(&lit("/items/{id}")).is_ok());
+        assert!(validate_path(&lit("/items/{id}/{sub_id}")).is_ok());
+        assert!(validate_path(&lit("/items/{id:\\d+}")).is_ok());
+        assert!(validate_path(&lit("/items/{id:\\d{2,4}}")).is_ok());
+
+        assert_eq!(
+            path_err("/items/{id"),
+            "Invalid path pattern \"/items/{id\", unclosed '{'"
+        );
+        assert_eq!(
+            path_err("/items/id}"),
+            "Invalid path pattern \"/items/id}\", unexpected '}'"
+        );
+        assert_eq!(
+            path_err("/items/{}"),
+            "Invalid path pattern \"/items/{}\", invalid segment name \"\""
+        );
+        assert_eq!(
+            path_err("/items/{:\\d+}"),
+            "Invalid path pattern \"/items/{:\\\\d+}\", invalid segment name \"\""
+        );
+        assert_eq!(
+            path_err("/items/{a-b}"),
+            "Invalid path pattern \"/items/{a-b}\", invalid segment name \"a-b\""
+        );
+    }
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!(MethodType::parse(&lit("GET")).unwrap(), MethodType::Get);
+        assert_eq!(MethodType::parse(&lit("head")).unwrap(), MethodType::Head);
+        assert_eq!(MethodType::parse(&lit("Patch")).unwrap(), MethodType::Patch);
+        assert_eq!(
+            MethodType::parse(&lit("FETCH")).unwrap_err().to_string(),
+            "Unsupported method \"FETCH\""
+        );
+        assert_eq!(
+            MethodType::parse(&lit("")).unwrap_err().to_string(),
+            "Unsupported method \"\""
+        );
+    }
+
+    fn args_err(args: AttributeArgs) -> String {
+        Args::new(args).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Args::new(vec![
+            syn::parse_quote!("/items/{id}"),
+            syn::parse_quote!(method = "GET"),
+            syn::parse_quote!(method = "HEAD"),
+        ])
+        .unwrap();
+        assert_eq!(args.methods, vec![MethodType::Get, MethodType::Head]);
+
+        assert_eq!(
+            args_err(vec![
+                syn::parse_quote!("/items"),
+                syn::parse_quote!(method = "GET"),
+                syn::parse_quote!(method = "get"),
+            ]),
+            "Method is specified multiple times"
+        );
+        assert_eq!(
+            args_err(vec![
+                syn::parse_quote!("/items"),
+                syn::parse_quote!(method = "FETCH"),
+            ]),
+            "Unsupported method \"FETCH\""
+        );
+        assert_eq!(
+            args_err(vec![
+                syn::parse_quote!("/items/{id"),
+                syn::parse_quote!(method = "GET"),
+            ]),
+            "Invalid path pattern \"/items/{id\", unclosed '{'"
+        );
+        assert_eq!(
+            args_err(vec![syn::parse_quote!(method = "GET")]),
+            "Path is not specified"
+        );
+    }
+
+    fn openapi_err(tokens: TokenStream2) -> String {
+        syn::parse2::<OpenApi>(tokens).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_openapi_args() {
+        let args: OpenApi = syn::parse_quote!(
+            summary = "Items", tag = "a", tag = "b", request_body = Json<Vec<Item>>, response = Item
+        );
+        assert_eq!(args.summary.unwrap().value(), "Items");
+        assert_eq!(args.tags.len(), 2);
+        assert!(args.request_body.is_some());
+        assert!(args.response.is_some());
+
+        assert_eq!(
+            openapi_err(quote!(summary = "a", summary = "b")),
+            "Summary is specified multiple times"
+        );
+        assert_eq!(
+            openapi_err(quote!(response = A, response = B)),
+            "Response is specified multiple times"
+        );
+        assert_eq!(
+            openapi_err(quote!(path = "/")),
+            "Unknown attribute key is specified. Allowed: summary, tag, request_body or response"
+        );
+    }
+}
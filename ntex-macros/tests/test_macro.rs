@@ -1,9 +1,11 @@
 use futures::{future, Future};
+use ntex::http::RequestHead;
 use ntex::http::{Method, StatusCode};
-use ntex::web::{test, types::Path, App, Error, HttpResponse, HttpResponseBuilder};
+use ntex::web::{self, test, types::Json, types::Path, App, Error, HttpRequest};
+use ntex::web::{HttpResponse, HttpResponseBuilder};
 use ntex_macros::{
     web_connect, web_delete, web_get, web_head, web_options, web_patch, web_post, web_put,
-    web_trace,
+    web_route, web_trace,
 };
 
 // Make sure that we can name function as 'config'
@@ -72,6 +74,101 @@ async fn get_param_test(_: Path<String>) -> HttpResponse {
     HttpResponse::Ok().finish()
 }
 
+mod guards {
+    pub fn has_token(head: &ntex::http::RequestHead) -> bool {
+        head.headers().contains_key("x-token")
+    }
+}
+
+fn is_json(head: &RequestHead) -> bool {
+    head.headers()
+        .get("content-type")
+        .map_or(false, |v| v == "application/json")
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Item {
+    name: String,
+}
+
+#[web_route("/items/{id}", method = "GET", method = "HEAD", name = "item")]
+async fn item_get(id: Path<u32>, req: HttpRequest) -> String {
+    format!("{} {}", id, req.match_name().unwrap_or_default())
+}
+
+#[web_route(
+    "/items/{id:[0-9]+}",
+    method = "put",
+    guard = "is_json",
+    guard = "guards::has_token"
+)]
+async fn item_put(id: Path<u32>, item: Json<Item>) -> HttpResponse {
+    HttpResponse::Created().body(format!("{} {}", id, item.name))
+}
+
+#[web::post("/items", name = "create_item")]
+async fn item_create(req: HttpRequest, item: Json<Item>) -> HttpResponse {
+    HttpResponse::Created().body(format!(
+        "{} {}",
+        req.match_name().unwrap_or_default(),
+        item.name
+    ))
+}
+
+#[ntex::test]
+async fn test_route() {
+    let srv = test::server(|| {
+        App::new()
+            .service(item_get)
+            .service(item_put)
+            .service(item_create)
+    });
+
+    let mut response = srv.get("/items/5").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.body().await.unwrap();
+    assert_eq!(body, "5 item");
+
+    let response = srv.head("/items/5").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = srv.delete("/items/5").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let mut response = srv
+        .put("/items/7")
+        .header("x-token", "1")
+        .send_json(&Item {
+            name: "test".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.body().await.unwrap();
+    assert_eq!(body, "7 test");
+
+    // guard rejects request
+    let response = srv
+        .put("/items/7")
+        .send_json(&Item {
+            name: "test".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let mut response = srv
+        .post("/items")
+        .send_json(&Item {
+            name: "new".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.body().await.unwrap();
+    assert_eq!(body, "create_item new");
+}
+
 #[ntex::test]
 async fn test_params() {
     let srv = test::server(|| {
@@ -150,3 +247,55 @@ async fn test_auto_async() {
     let response = request.send().await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[ntex::test(service = "App::new().service(test_handler).service(put_test)")]
+async fn test_service_attr(srv: _) {
+    let req = test::TestRequest::with_uri("/test").to_request();
+    let resp = test::call_service(&srv, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::put().uri("/test").to_request();
+    let resp = test::call_service(&srv, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+}
+
+#[ntex::openapi(summary = "Echo", tag = "test", request_body = Json<String>, response = Json<String>)]
+#[web_post("/echo")]
+async fn openapi_echo(body: Json<String>) -> Json<String> {
+    body
+}
+
+#[web_get("/items/{id}", name = "item")]
+#[ntex::openapi(summary = "Get item", response = Json<Vec<u64>>)]
+async fn openapi_item(id: Path<u64>) -> Json<Vec<u64>> {
+    Json(vec![id.into_inner()])
+}
+
+#[ntex::test]
+async fn test_openapi() {
+    let srv = test::init_service(
+        App::new().service((openapi_echo, web::scope("/api").service(openapi_item))),
+    )
+    .await;
+
+    let req = test::TestRequest::with_uri("/api/items/1").to_request();
+    let resp = test::call_service(&srv, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let spec = web::openapi::spec();
+    let echo = &spec["paths"]["/echo"]["post"];
+    assert_eq!(echo["summary"], "Echo");
+    assert_eq!(echo["tags"][0], "test");
+    assert_eq!(
+        echo["requestBody"]["content"]["application/json"]["schema"]["type"],
+        "string"
+    );
+
+    let item = &spec["paths"]["/api/items/{id}"]["get"];
+    assert_eq!(item["summary"], "Get item");
+    assert_eq!(item["parameters"][0]["name"], "id");
+    assert_eq!(
+        item["responses"]["200"]["content"]["application/json"]["schema"]["type"],
+        "array"
+    );
+}
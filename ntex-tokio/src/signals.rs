@@ -11,6 +11,9 @@ thread_local! {
 }
 
 /// Different types of process signals
+///
+/// On windows `CTRL_C` is reported as `Int`, `CTRL_BREAK` as `Quit`
+/// and `CTRL_CLOSE` as `Term`.
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum Signal {
     /// SIGHUP
@@ -38,9 +41,12 @@ pub fn signal() -> Option<oneshot::Receiver<Signal>> {
     })
 }
 
+#[cfg(not(unix))]
+type PollSignal = Box<dyn FnMut(&mut Context<'_>) -> Poll<Option<()>>>;
+
 struct Signals {
     #[cfg(not(unix))]
-    signal: Pin<Box<dyn Future<Output = std::io::Result<()>>>>,
+    signals: Vec<(Signal, PollSignal)>,
     #[cfg(unix)]
     signals: Vec<(Signal, tokio::signal::unix::Signal)>,
 }
@@ -51,9 +57,47 @@ impl Signals {
 
         #[cfg(not(unix))]
         {
-            Signals {
-                signal: Box::pin(tokio::signal::ctrl_c()),
+            use tokio::signal::windows;
+
+            fn register<T: 'static>(
+                signals: &mut Vec<(Signal, PollSignal)>,
+                sig: Signal,
+                stream: std::io::Result<T>,
+                poll: fn(&mut T, &mut Context<'_>) -> Poll<Option<()>>,
+            ) {
+                match stream {
+                    Ok(mut stream) => {
+                        signals.push((sig, Box::new(move |cx| poll(&mut stream, cx))))
+                    }
+                    Err(e) => log::error!(
+                        "Cannot initialize stream handler for {:?} err: {}",
+                        sig,
+                        e
+                    ),
+                }
             }
+
+            let mut signals = Vec::new();
+            register(
+                &mut signals,
+                Signal::Int,
+                windows::ctrl_c(),
+                windows::CtrlC::poll_recv,
+            );
+            register(
+                &mut signals,
+                Signal::Quit,
+                windows::ctrl_break(),
+                windows::CtrlBreak::poll_recv,
+            );
+            register(
+                &mut signals,
+                Signal::Term,
+                windows::ctrl_close(),
+                windows::CtrlClose::poll_recv,
+            );
+
+            Signals { signals }
         }
 
         #[cfg(unix)]
@@ -96,10 +140,12 @@ impl Future for Signals {
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         #[cfg(not(unix))]
         {
-            if self.signal.as_mut().poll(cx).is_ready() {
-                let handlers = SHANDLERS.with(|h| mem::take(&mut *h.borrow_mut()));
-                for mut sender in handlers {
-                    let _ = sender.send(Signal::Int);
+            for (sig, poll) in self.signals.iter_mut() {
+                if poll(cx).is_ready() {
+                    let handlers = SHANDLERS.with(|h| mem::take(&mut *h.borrow_mut()));
+                    for sender in handlers {
+                        let _ = sender.send(*sig);
+                    }
                 }
             }
             Poll::Pending
@@ -0,0 +1,313 @@
+//! Service that caches successful responses.
+use std::collections::BTreeMap;
+use std::{
+    cell::RefCell, future::Future, hash::Hash, marker::PhantomData, pin::Pin, rc::Rc,
+};
+use std::{task::Context, task::Poll, time::Instant};
+
+use ntex_service::{Service, Transform};
+
+use crate::future::{Either, Ready};
+use crate::ready;
+use crate::time::{now, Millis};
+use crate::HashMap;
+
+/// Cache - service factory for service that memoizes successful responses.
+///
+/// Response is cached by the key extracted from the request with `key`
+/// function and is returned for identical requests within `ttl`. Errors are
+/// not cached. Cache keeps at most `capacity` responses, least recently used
+/// response is evicted first.
+///
+/// Cache storage is shared between all clones of `Cache` and all services
+/// created by it.
+pub struct Cache<K, V, F> {
+    key: Rc<F>,
+    store: Rc<RefCell<Store<K, V>>>,
+}
+
+struct Store<K, V> {
+    capacity: usize,
+    ttl: Millis,
+    tick: u64,
+    entries: HashMap<K, Entry<V>>,
+    lru: BTreeMap<u64, K>,
+}
+
+struct Entry<V> {
+    value: V,
+    created: Instant,
+    tick: u64,
+}
+
+impl<K, V, F> Cache<K, V, F>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Create cache with `capacity` responses and responses time to live.
+    pub fn new<T: Into<Millis>>(capacity: usize, ttl: T, key: F) -> Self {
+        Cache {
+            key: Rc::new(key),
+            store: Rc::new(RefCell::new(Store {
+                capacity,
+                ttl: ttl.into(),
+                tick: 0,
+                entries: HashMap::default(),
+                lru: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Number of cached responses, including expired ones
+    pub fn len(&self) -> usize {
+        self.store.borrow().entries.len()
+    }
+
+    /// Returns `true` if cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.store.borrow().entries.is_empty()
+    }
+
+    /// Remove cached response
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut store = self.store.borrow_mut();
+        let entry = store.entries.remove(key)?;
+        store.lru.remove(&entry.tick);
+        Some(entry.value)
+    }
+
+    /// Remove all cached responses
+    pub fn clear(&self) {
+        let mut store = self.store.borrow_mut();
+        store.entries.clear();
+        store.lru.clear();
+    }
+}
+
+impl<K, V> Store<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let tick = self.next_tick();
+        let expired = match self.entries.get_mut(key) {
+            Some(entry) if now() - entry.created < self.ttl.into() => {
+                self.lru.remove(&entry.tick);
+                self.lru.insert(tick, key.clone());
+                entry.tick = tick;
+                return Some(entry.value.clone());
+            }
+            Some(entry) => entry.tick,
+            None => return None,
+        };
+        self.entries.remove(key);
+        self.lru.remove(&expired);
+        None
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        let entry = Entry {
+            value,
+            tick,
+            created: now(),
+        };
+        if let Some(old) = self.entries.insert(key.clone(), entry) {
+            self.lru.remove(&old.tick);
+        }
+        self.lru.insert(tick, key);
+
+        while self.entries.len() > self.capacity {
+            let lru = self.lru.keys().next().copied().unwrap();
+            if let Some(key) = self.lru.remove(&lru) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+impl<K, V, F> Clone for Cache<K, V, F> {
+    fn clone(&self) -> Self {
+        Cache {
+            key: self.key.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S, K, V, F> Transform<S> for Cache<K, V, F> {
+    type Service = CacheService<S, K, V, F>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        CacheService {
+            service,
+            cache: self.clone(),
+        }
+    }
+}
+
+/// Service that memoizes successful responses
+pub struct CacheService<S, K, V, F> {
+    service: S,
+    cache: Cache<K, V, F>,
+}
+
+impl<S, R, K, V, F> Service<R> for CacheService<S, K, V, F>
+where
+    S: Service<R, Response = V>,
+    F: Fn(&R) -> K,
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    type Response = V;
+    type Error = S::Error;
+    type Future = Either<Ready<V, S::Error>, CacheServiceResponse<S, R, K>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: R) -> Self::Future {
+        let key = (*self.cache.key)(&req);
+        if let Some(value) = self.cache.store.borrow_mut().get(&key) {
+            log::trace!("Cached response is found");
+            return Either::Left(Ready::Ok(value));
+        }
+
+        Either::Right(CacheServiceResponse {
+            fut: self.service.call(req),
+            key: Some(key),
+            store: self.cache.store.clone(),
+            _t: PhantomData,
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[doc(hidden)]
+    pub struct CacheServiceResponse<T: Service<R>, R, K> {
+        #[pin]
+        fut: T::Future,
+        key: Option<K>,
+        store: Rc<RefCell<Store<K, T::Response>>>,
+        _t: PhantomData<R>
+    }
+}
+
+impl<T, R, K> Future for CacheServiceResponse<T, R, K>
+where
+    T: Service<R>,
+    T::Response: Clone,
+    K: Hash + Eq + Clone,
+{
+    type Output = Result<T::Response, T::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.fut.poll(cx));
+        if let Ok(ref value) = res {
+            if let Some(key) = this.key.take() {
+                this.store.borrow_mut().insert(key, value.clone());
+            }
+        }
+        Poll::Ready(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_service::{apply, fn_factory, fn_service, Service, ServiceFactory};
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::time::sleep;
+
+    #[ntex_macros::rt_test2]
+    async fn test_cache() {
+        let counter = Rc::new(Cell::new(0));
+        let counter2 = counter.clone();
+        let cache = Cache::new(2, Millis(100), |req: &(u32, bool)| req.0);
+
+        let srv = cache.new_transform(fn_service(move |req: (u32, bool)| {
+            counter2.set(counter2.get() + 1);
+            async move {
+                if req.1 {
+                    Ok(req.0 * 2)
+                } else {
+                    Err(())
+                }
+            }
+        }));
+
+        assert_eq!(srv.call((1, true)).await, Ok(2));
+        assert_eq!(srv.call((1, true)).await, Ok(2));
+        assert_eq!(counter.get(), 1);
+        assert_eq!(cache.len(), 1);
+
+        // errors are not cached
+        assert_eq!(srv.call((2, false)).await, Err(()));
+        assert_eq!(srv.call((2, false)).await, Err(()));
+        assert_eq!(counter.get(), 3);
+        assert_eq!(cache.len(), 1);
+
+        // least recently used response is evicted
+        assert_eq!(srv.call((2, true)).await, Ok(4));
+        assert_eq!(srv.call((1, true)).await, Ok(2));
+        assert_eq!(srv.call((3, true)).await, Ok(6));
+        assert_eq!(counter.get(), 5);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(srv.call((1, true)).await, Ok(2));
+        assert_eq!(counter.get(), 5);
+        assert_eq!(srv.call((2, true)).await, Ok(4));
+        assert_eq!(counter.get(), 6);
+
+        // expired
+        sleep(Millis(150)).await;
+        assert_eq!(srv.call((2, true)).await, Ok(4));
+        assert_eq!(counter.get(), 7);
+
+        assert_eq!(cache.remove(&2), Some(4));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_shared() {
+        let counter = Rc::new(Cell::new(0));
+        let counter2 = counter.clone();
+        let factory = apply(
+            Cache::new(16, Millis(1_000), |req: &&'static str| *req),
+            fn_factory(move || {
+                let counter = counter2.clone();
+                async move {
+                    Ok::<_, ()>(fn_service(move |req: &'static str| {
+                        counter.set(counter.get() + 1);
+                        async move { Ok::<_, ()>(req.len()) }
+                    }))
+                }
+            }),
+        );
+
+        let srv1 = factory.new_service(()).await.unwrap();
+        let srv2 = factory.new_service(()).await.unwrap();
+        assert_eq!(srv1.call("test").await, Ok(4));
+        assert_eq!(srv2.call("test").await, Ok(4));
+        assert_eq!(counter.get(), 1);
+    }
+}
@@ -1,9 +1,10 @@
 //! Service that limits number of in-flight async requests.
-use std::{future::Future, marker::PhantomData, pin::Pin, task::Context, task::Poll};
+use std::{fmt, future::Future, marker::PhantomData, pin::Pin, task::Context, task::Poll};
 
 use ntex_service::{IntoService, Service, Transform};
 
 use super::counter::{Counter, CounterGuard};
+use crate::future::{Either, Ready};
 
 /// InFlight - service factory for service that can limit number of in-flight
 /// async requests.
@@ -107,6 +108,156 @@ impl<T: Service<R>, R> Future for InFlightServiceResponse<T, R> {
     }
 }
 
+/// InFlightReject - service factory for service that rejects requests
+/// above the in-flight limit instead of waiting for available slot.
+///
+/// Rejected requests fail with `InFlightError::Overflow` error.
+pub struct InFlightReject {
+    max_inflight: usize,
+}
+
+impl InFlightReject {
+    pub fn new(max: usize) -> Self {
+        Self { max_inflight: max }
+    }
+}
+
+impl Default for InFlightReject {
+    fn default() -> Self {
+        Self::new(15)
+    }
+}
+
+impl<S> Transform<S> for InFlightReject {
+    type Service = InFlightRejectService<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        InFlightRejectService {
+            service,
+            max: self.max_inflight,
+            count: Counter::new(self.max_inflight),
+        }
+    }
+}
+
+/// InFlightReject error
+pub enum InFlightError<E> {
+    /// Service error
+    Service(E),
+    /// In-flight limit exceeded
+    Overflow,
+}
+
+impl<E> From<E> for InFlightError<E> {
+    fn from(err: E) -> Self {
+        InFlightError::Service(err)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for InFlightError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InFlightError::Service(e) => write!(f, "InFlightError::Service({:?})", e),
+            InFlightError::Overflow => write!(f, "InFlightError::Overflow"),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for InFlightError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InFlightError::Service(e) => e.fmt(f),
+            InFlightError::Overflow => write!(f, "InFlight limit exceeded"),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> std::error::Error for InFlightError<E> {}
+
+impl<E: PartialEq> PartialEq for InFlightError<E> {
+    fn eq(&self, other: &InFlightError<E>) -> bool {
+        match (self, other) {
+            (InFlightError::Service(e1), InFlightError::Service(e2)) => e1 == e2,
+            (InFlightError::Overflow, InFlightError::Overflow) => true,
+            _ => false,
+        }
+    }
+}
+
+pub struct InFlightRejectService<S> {
+    max: usize,
+    count: Counter,
+    service: S,
+}
+
+impl<S> InFlightRejectService<S> {
+    pub fn new<U, R>(max: usize, service: U) -> Self
+    where
+        S: Service<R>,
+        U: IntoService<S, R>,
+    {
+        Self {
+            max,
+            count: Counter::new(max),
+            service: service.into_service(),
+        }
+    }
+}
+
+impl<T, R> Service<R> for InFlightRejectService<T>
+where
+    T: Service<R>,
+{
+    type Response = T::Response;
+    type Error = InFlightError<T::Error>;
+    type Future = Either<
+        InFlightRejectServiceResponse<T, R>,
+        Ready<T::Response, InFlightError<T::Error>>,
+    >;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx).map_err(InFlightError::Service)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    #[inline]
+    fn call(&self, req: R) -> Self::Future {
+        if self.count.total() >= self.max {
+            log::trace!("InFlight limit exceeded, reject request");
+            Either::Right(Ready::Err(InFlightError::Overflow))
+        } else {
+            Either::Left(InFlightRejectServiceResponse {
+                fut: self.service.call(req),
+                _guard: self.count.get(),
+                _t: PhantomData,
+            })
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[doc(hidden)]
+    pub struct InFlightRejectServiceResponse<T: Service<R>, R> {
+        #[pin]
+        fut: T::Future,
+        _guard: CounterGuard,
+        _t: PhantomData<R>
+    }
+}
+
+impl<T: Service<R>, R> Future for InFlightRejectServiceResponse<T, R> {
+    type Output = Result<T::Response, InFlightError<T::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().fut.poll(cx).map_err(InFlightError::Service)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ntex_service::{apply, fn_factory, Service, ServiceFactory};
@@ -169,4 +320,38 @@ mod tests {
         let _ = res.await;
         assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
     }
+
+    #[ntex_macros::rt_test2]
+    async fn test_reject() {
+        let wait_time = Duration::from_millis(50);
+
+        let srv = InFlightRejectService::new(1, SleepService(wait_time));
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+
+        let res = srv.call(());
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(srv.call(()).await, Err(InFlightError::Overflow));
+
+        assert_eq!(res.await, Ok(()));
+        assert_eq!(srv.call(()).await, Ok(()));
+        assert_eq!(
+            format!("{}", InFlightError::<String>::Overflow),
+            "InFlight limit exceeded"
+        );
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_reject_transform() {
+        let wait_time = Duration::from_millis(50);
+
+        let srv = apply(
+            InFlightReject::new(1),
+            fn_factory(|| async { Ok::<_, ()>(SleepService(wait_time)) }),
+        );
+
+        let srv = srv.new_service(&()).await.unwrap();
+        let res = srv.call(());
+        assert_eq!(srv.call(()).await, Err(InFlightError::Overflow));
+        assert_eq!(res.await, Ok(()));
+    }
 }
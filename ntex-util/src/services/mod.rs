@@ -1,4 +1,5 @@
 pub mod buffer;
+pub mod cache;
 pub mod counter;
 mod extensions;
 pub mod inflight;
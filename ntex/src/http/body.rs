@@ -20,6 +20,9 @@ impl BodySize {
 }
 
 /// Type that provides this trait can be streamed to a peer.
+///
+/// Empty chunk returned from `poll_next_chunk()` is a flush point, it does not
+/// end the body but signals the encoder to flush pending data to the peer.
 pub trait MessageBody: 'static {
     fn size(&self) -> BodySize;
 
@@ -143,6 +146,11 @@ impl Body {
         Body::Bytes(Bytes::copy_from_slice(s))
     }
 
+    /// Create body from static slice, data is not copied
+    pub const fn from_static(s: &'static [u8]) -> Body {
+        Body::Bytes(Bytes::from_static(s))
+    }
+
     /// Create body from generic message body.
     pub fn from_message<B: MessageBody + 'static>(body: B) -> Body {
         Body::Message(Box::new(body))
@@ -203,13 +211,13 @@ impl fmt::Debug for Body {
 
 impl From<&'static str> for Body {
     fn from(s: &'static str) -> Body {
-        Body::Bytes(Bytes::from_static(s.as_ref()))
+        Body::from_static(s.as_bytes())
     }
 }
 
 impl From<&'static [u8]> for Body {
     fn from(s: &'static [u8]) -> Body {
-        Body::Bytes(Bytes::from_static(s))
+        Body::from_static(s)
     }
 }
 
@@ -268,6 +276,16 @@ where
     }
 }
 
+impl<S, E> From<FlushStream<S, E>> for Body
+where
+    S: Stream<Item = Result<StreamChunk, E>> + Unpin + 'static,
+    E: Error + 'static,
+{
+    fn from(s: FlushStream<S, E>) -> Body {
+        Body::from_message(s)
+    }
+}
+
 impl MessageBody for Bytes {
     fn size(&self) -> BodySize {
         BodySize::Sized(self.len() as u64)
@@ -460,6 +478,75 @@ where
     }
 }
 
+/// Item of the [`FlushStream`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamChunk {
+    /// Chunk of the body
+    Data(Bytes),
+    /// Flush point, data sent so far is flushed to the peer
+    Flush,
+}
+
+impl From<Bytes> for StreamChunk {
+    fn from(data: Bytes) -> Self {
+        StreamChunk::Data(data)
+    }
+}
+
+/// Type represent streaming body with explicit flush points.
+///
+/// On `StreamChunk::Flush` item, chunks are not buffered by
+/// the response encoder, all data sent so far is flushed to the peer
+/// before next chunk is polled.
+pub struct FlushStream<S, E> {
+    stream: S,
+    _t: PhantomData<E>,
+}
+
+impl<S, E> FlushStream<S, E>
+where
+    S: Stream<Item = Result<StreamChunk, E>> + Unpin,
+    E: Error,
+{
+    pub fn new(stream: S) -> Self {
+        FlushStream {
+            stream,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<S, E> MessageBody for FlushStream<S, E>
+where
+    S: Stream<Item = Result<StreamChunk, E>> + Unpin + 'static,
+    E: Error + 'static,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    /// Attempts to pull out the next value of the underlying [`Stream`].
+    ///
+    /// Empty data chunks are skipped, flush point is returned as empty chunk.
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        loop {
+            return Poll::Ready(match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(StreamChunk::Data(ref bytes)))) if bytes.is_empty() => {
+                    continue
+                }
+                Poll::Ready(Some(Ok(StreamChunk::Data(bytes)))) => Some(Ok(bytes)),
+                Poll::Ready(Some(Ok(StreamChunk::Flush))) => Some(Ok(Bytes::new())),
+                Poll::Ready(Some(Err(e))) => Some(Err(e.into())),
+                Poll::Ready(None) => None,
+                Poll::Pending => return Poll::Pending,
+            });
+        }
+    }
+}
+
 /// Type represent streaming body. This body implementation should be used
 /// if total size of stream is known. Data get sent as is without using transfer encoding.
 pub struct SizedStream<S> {
@@ -534,6 +621,10 @@ mod tests {
         assert_eq!(Body::from("").size(), BodySize::Sized(0));
         assert_eq!(Body::from("test").size(), BodySize::Sized(4));
         assert_eq!(Body::from("test").get_ref(), b"test");
+        static DATA: &[u8] = b"static";
+        let body = Body::from_static(DATA);
+        assert_eq!(body.size(), BodySize::Sized(6));
+        assert_eq!(body.get_ref().as_ptr(), DATA.as_ptr());
 
         assert_eq!("test".size(), BodySize::Sized(4));
         assert_eq!(
@@ -710,6 +801,34 @@ mod tests {
         );
     }
 
+    #[crate::rt_test]
+    async fn flush_stream() {
+        let mut body = FlushStream::new(stream::iter(
+            vec![
+                StreamChunk::from(Bytes::from("1")),
+                StreamChunk::Data(Bytes::new()),
+                StreamChunk::Flush,
+                StreamChunk::Data(Bytes::from("2")),
+            ]
+            .into_iter()
+            .map(Ok::<_, io::Error>),
+        ));
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from("1")),
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::new()),
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from("2")),
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+    }
+
     #[crate::rt_test]
     async fn sized_skips_empty_chunks() {
         let mut body = SizedStream::new(
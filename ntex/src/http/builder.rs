@@ -7,6 +7,7 @@ use crate::http::config::{KeepAlive, OnRequest, ServiceConfig};
 use crate::http::error::ResponseError;
 use crate::http::h1::{Codec, ExpectHandler, H1Service, UpgradeHandler};
 use crate::http::h2::H2Service;
+use crate::http::header::HeaderValue;
 use crate::http::request::Request;
 use crate::http::response::Response;
 use crate::http::service::HttpService;
@@ -27,6 +28,12 @@ pub struct HttpServiceBuilder<F, S, X = ExpectHandler, U = UpgradeHandler<F>> {
     upgrade: Option<U>,
     on_request: Option<OnRequest>,
     h2config: h2::Config,
+    server_header: Option<HeaderValue>,
+    default_content_type: Option<HeaderValue>,
+    max_uri_length: usize,
+    h2_idle_timeout: Seconds,
+    h2_ka_interval: Seconds,
+    h2_ka_timeout: Seconds,
     _t: PhantomData<(F, S)>,
 }
 
@@ -42,6 +49,12 @@ impl<F, S> HttpServiceBuilder<F, S, ExpectHandler, UpgradeHandler<F>> {
             upgrade: None,
             on_request: None,
             h2config: h2::Config::server(),
+            server_header: None,
+            default_content_type: None,
+            max_uri_length: 0,
+            h2_idle_timeout: Seconds::ZERO,
+            h2_ka_interval: Seconds::ZERO,
+            h2_ka_timeout: Seconds(20),
             _t: PhantomData,
         }
     }
@@ -109,6 +122,105 @@ where
         self
     }
 
+    /// Set `Server` header value.
+    ///
+    /// Header is added to responses that do not contain `Server` header,
+    /// handler's value takes precedence. `None` disables header.
+    ///
+    /// By default `Server` header is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn server_header(mut self, value: Option<&str>) -> Self {
+        self.server_header =
+            value.map(|v| HeaderValue::from_str(v).expect("Invalid server header value"));
+        self
+    }
+
+    /// Set default `Content-Type` header value.
+    ///
+    /// Header is added to responses with non-empty body that do not contain
+    /// `Content-Type` header, handler's value takes precedence.
+    ///
+    /// By default content type is not set, responses without content type
+    /// are sent as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn default_content_type(self, value: &str) -> Self {
+        self.set_default_content_type(Some(
+            HeaderValue::from_str(value).expect("Invalid content type value"),
+        ))
+    }
+
+    /// Set parsed default `Content-Type` header value.
+    pub(crate) fn set_default_content_type(mut self, value: Option<HeaderValue>) -> Self {
+        self.default_content_type = value;
+        self
+    }
+
+    /// Set max length of request target for http/1 requests.
+    ///
+    /// Requests with longer request target are rejected with
+    /// `414 URI Too Long` response, connection get closed. Length is checked
+    /// before whole request line is received.
+    ///
+    /// To disable limit set value to 0, in that case request head size is
+    /// limited only by the read buffer size.
+    ///
+    /// By default limit is disabled.
+    pub fn max_uri_length(mut self, len: usize) -> Self {
+        self.max_uri_length = len;
+        self
+    }
+
+    /// Set idle timeout for http/2 connections.
+    ///
+    /// If http/2 connection has no open streams for the duration of
+    /// the timeout, server sends `GOAWAY` frame and closes connection.
+    /// Timeout starts after last stream on the connection completes.
+    ///
+    /// To disable timeout set value to 0.
+    ///
+    /// By default idle timeout is disabled.
+    pub fn h2_idle_timeout(mut self, timeout: Seconds) -> Self {
+        self.h2_idle_timeout = timeout;
+        self
+    }
+
+    /// Set keep-alive ping interval for http/2 connections.
+    ///
+    /// Server sends `PING` frame every `interval`, if peer does not
+    /// acknowledge ping within keep-alive timeout, connection is closed
+    /// with `GOAWAY` frame. Pings help to keep connection open through
+    /// load balancers that drop silent connections.
+    ///
+    /// To disable custom interval set value to 0, in that case http/2
+    /// protocol default ping settings are used.
+    ///
+    /// By default custom interval is disabled.
+    pub fn h2_keep_alive_interval(mut self, interval: Seconds) -> Self {
+        self.h2_ka_interval = interval;
+        if interval.non_zero() {
+            // disable protocol level pings
+            self.h2config.ping_timeout(Seconds::ZERO);
+        }
+        self
+    }
+
+    /// Set keep-alive ping timeout for http/2 connections.
+    ///
+    /// Used only if keep-alive interval is set. Next ping is not sent
+    /// until timeout of the previous one expires.
+    ///
+    /// By default keep-alive timeout is set to 20 seconds.
+    pub fn h2_keep_alive_timeout(mut self, timeout: Seconds) -> Self {
+        self.h2_ka_timeout = timeout;
+        self
+    }
+
     #[doc(hidden)]
     /// Configure http2 connection settings
     pub fn configure_http2<O, R>(self, f: O) -> Self
@@ -139,6 +251,12 @@ where
             upgrade: self.upgrade,
             on_request: self.on_request,
             h2config: self.h2config,
+            server_header: self.server_header,
+            default_content_type: self.default_content_type,
+            max_uri_length: self.max_uri_length,
+            h2_idle_timeout: self.h2_idle_timeout,
+            h2_ka_interval: self.h2_ka_interval,
+            h2_ka_timeout: self.h2_ka_timeout,
             _t: PhantomData,
         }
     }
@@ -163,6 +281,12 @@ where
             upgrade: Some(upgrade.into_factory()),
             on_request: self.on_request,
             h2config: self.h2config,
+            server_header: self.server_header,
+            default_content_type: self.default_content_type,
+            max_uri_length: self.max_uri_length,
+            h2_idle_timeout: self.h2_idle_timeout,
+            h2_ka_interval: self.h2_ka_interval,
+            h2_ka_timeout: self.h2_ka_timeout,
             _t: PhantomData,
         }
     }
@@ -194,7 +318,12 @@ where
             self.client_disconnect,
             self.handshake_timeout,
             self.h2config,
-        );
+        )
+        .server_header(self.server_header)
+        .default_content_type(self.default_content_type)
+        .max_uri_length(self.max_uri_length)
+        .h2_idle_timeout(self.h2_idle_timeout)
+        .h2_keep_alive(self.h2_ka_interval, self.h2_ka_timeout);
         H1Service::with_config(cfg, service.into_factory())
             .expect(self.expect)
             .upgrade(self.upgrade)
@@ -217,7 +346,12 @@ where
             self.client_disconnect,
             self.handshake_timeout,
             self.h2config,
-        );
+        )
+        .server_header(self.server_header)
+        .default_content_type(self.default_content_type)
+        .max_uri_length(self.max_uri_length)
+        .h2_idle_timeout(self.h2_idle_timeout)
+        .h2_keep_alive(self.h2_ka_interval, self.h2_ka_timeout);
 
         H2Service::with_config(cfg, service.into_factory())
     }
@@ -239,7 +373,12 @@ where
             self.client_disconnect,
             self.handshake_timeout,
             self.h2config,
-        );
+        )
+        .server_header(self.server_header)
+        .default_content_type(self.default_content_type)
+        .max_uri_length(self.max_uri_length)
+        .h2_idle_timeout(self.h2_idle_timeout)
+        .h2_keep_alive(self.h2_ka_interval, self.h2_ka_timeout);
         HttpService::with_config(cfg, service.into_factory())
             .expect(self.expect)
             .upgrade(self.upgrade)
@@ -1,11 +1,11 @@
-use std::{future::Future, net, pin::Pin};
+use std::{future::Future, net, pin::Pin, task::Context, task::Poll, time::Instant};
 
 use crate::http::body::Body;
 use crate::http::RequestHeadType;
 use crate::service::Service;
 
 use super::error::{ConnectError, SendRequestError};
-use super::response::ClientResponse;
+use super::response::{ClientResponse, Timings};
 use super::{Connect as ClientConnect, Connection};
 
 pub(super) struct ConnectorWrapper<T>(pub(crate) T);
@@ -17,6 +17,9 @@ pub(super) trait Connect {
         body: Body,
         addr: Option<net::SocketAddr>,
     ) -> Pin<Box<dyn Future<Output = Result<ClientResponse, SendRequestError>>>>;
+
+    /// Close connector
+    fn poll_close(&self, cx: &mut Context<'_>) -> Poll<()>;
 }
 
 impl<T> Connect for ConnectorWrapper<T>
@@ -37,13 +40,21 @@ where
         });
 
         Box::pin(async move {
+            let start = Instant::now();
             let connection = fut.await?;
+            let connected = Instant::now();
+            let reused = connection.is_reused();
 
             // send request
-            connection
-                .send_request(head, body)
-                .await
-                .map(|(head, payload)| ClientResponse::new(head, payload))
+            let (head, payload) = connection.send_request(head, body).await?;
+
+            let mut res = ClientResponse::new(head, payload);
+            res.timings = Timings::new(reused, connected - start, connected.elapsed());
+            Ok(res)
         })
     }
+
+    fn poll_close(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0.poll_shutdown(cx, false)
+    }
 }
@@ -27,6 +27,7 @@ pub struct Connection {
     io: Option<ConnectionType>,
     created: time::Instant,
     pool: Option<Acquired>,
+    reused: bool,
 }
 
 impl fmt::Debug for Connection {
@@ -49,9 +50,21 @@ impl Connection {
             pool,
             created,
             io: Some(io),
+            reused: false,
         }
     }
 
+    /// Mark connection as acquired from the pool
+    pub(super) fn reused(mut self) -> Self {
+        self.reused = true;
+        self
+    }
+
+    /// Returns true if connection is acquired from the pool
+    pub(super) fn is_reused(&self) -> bool {
+        self.reused
+    }
+
     pub(super) fn release(self, close: bool) {
         if let Some(mut pool) = self.pool {
             pool.release(
@@ -59,6 +72,7 @@ impl Connection {
                     io: self.io,
                     created: self.created,
                     pool: None,
+                    reused: self.reused,
                 },
                 close,
             );
@@ -159,7 +159,9 @@ impl Connector {
     /// Set max lifetime period for connection.
     ///
     /// Connection lifetime is max lifetime of any opened connection
-    /// until it is closed regardless of keep-alive period.
+    /// until it is closed regardless of keep-alive period. Limited lifetime
+    /// forces rebalancing of connections across backend addresses, with
+    /// zero lifetime every request opens new connection.
     /// Default lifetime period is 75 seconds.
     pub fn lifetime(mut self, dur: Seconds) -> Self {
         self.conn_lifetime = dur.into();
@@ -215,6 +217,10 @@ impl Connector {
     /// Finish configuration process and create connector service.
     /// The Connector builder always concludes by calling `finish()` last in
     /// its combinator chain.
+    ///
+    /// Service's `poll_shutdown()` method closes connections pool, idle
+    /// connections get closed and new connections are rejected. It resolves
+    /// when all acquired connections are released.
     pub fn finish(
         self,
     ) -> impl Service<Connect, Response = Connection, Error = ConnectError> + Clone {
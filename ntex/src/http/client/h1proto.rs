@@ -108,8 +108,14 @@ where
     loop {
         match poll_fn(|cx| body.poll_next_chunk(cx)).await {
             Some(result) => {
-                io.encode(h1::Message::Chunk(Some(result?)), codec)?;
-                io.flush(false).await?;
+                let chunk = result?;
+                if chunk.is_empty() {
+                    // flush point
+                    io.flush(true).await?;
+                } else {
+                    io.encode(h1::Message::Chunk(Some(chunk)), codec)?;
+                    io.flush(false).await?;
+                }
             }
             None => {
                 io.encode(h1::Message::Chunk(None), codec)?;
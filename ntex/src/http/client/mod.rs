@@ -37,16 +37,19 @@ pub use self::connection::Connection;
 pub use self::connector::Connector;
 pub use self::frozen::{FrozenClientRequest, FrozenSendBuilder};
 pub use self::request::ClientRequest;
-pub use self::response::{ClientResponse, JsonBody, MessageBody};
+pub use self::response::{ClientResponse, JsonBody, MessageBody, Timings};
 pub use self::sender::SendClientRequest;
 pub use self::test::TestResponse;
 
 use crate::http::error::HttpError;
 use crate::http::{HeaderMap, Method, RequestHead, Uri};
-use crate::time::Millis;
+use crate::time::{timeout_checked, Millis};
+use crate::util::poll_fn;
 
 use self::connect::{Connect as HttpConnect, ConnectorWrapper};
 
+const CLOSE_TIMEOUT: Millis = Millis(5_000);
+
 #[derive(Clone)]
 pub struct Connect {
     pub uri: Uri,
@@ -192,4 +195,24 @@ impl Client {
     {
         self.request(Method::OPTIONS, url)
     }
+
+    /// Close client's connector.
+    ///
+    /// Idle pooled connections are closed and new requests fail with
+    /// connect error. Waits until in-flight requests release their
+    /// connections and in-flight connects complete, but no longer than
+    /// client's request timeout. If request timeout is disabled, waits
+    /// no longer than 5 seconds. Connector is shared between all clones
+    /// of the client.
+    pub async fn close(&self) {
+        let timeout = if self.0.timeout.is_zero() {
+            CLOSE_TIMEOUT
+        } else {
+            self.0.timeout
+        };
+        let fut = poll_fn(|cx| self.0.connector.poll_close(cx));
+        if timeout_checked(timeout, fut).await.is_err() {
+            log::trace!("Timeout while waiting for in-flight requests");
+        }
+    }
 }
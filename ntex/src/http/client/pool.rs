@@ -1,6 +1,6 @@
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
-use std::{cell::RefCell, collections::VecDeque, future::Future, pin::Pin, rc::Rc};
+use std::{cell::RefCell, collections::VecDeque, future::Future, io, pin::Pin, rc::Rc};
 
 use ntex_h2::{self as h2};
 
@@ -73,9 +73,11 @@ where
             limit,
             h2config,
             acquired: 0,
+            closed: false,
             available: HashMap::default(),
             connecting: HashSet::default(),
             waker: LocalWaker::new(),
+            shutdown_task: LocalWaker::new(),
             waiters: waiters.clone(),
         }));
 
@@ -124,9 +126,26 @@ where
         self.connector.poll_ready(cx)
     }
 
-    #[inline]
+    /// Close connections pool.
+    ///
+    /// Idle connections are closed, pending waiters are dropped and new
+    /// connections could not be acquired. Resolves when all acquired
+    /// connections are released.
     fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
-        self.connector.poll_shutdown(cx, is_error)
+        let mut inner = self.inner.borrow_mut();
+        if !inner.closed {
+            trace!("Closing connections pool");
+            inner.close();
+            self.waiters.borrow_mut().waiters.clear();
+        }
+
+        if inner.acquired == 0 && inner.connecting.is_empty() {
+            drop(inner);
+            self.connector.poll_shutdown(cx, is_error)
+        } else {
+            inner.shutdown_task.register(cx.waker());
+            Poll::Pending
+        }
     }
 
     #[inline]
@@ -143,17 +162,23 @@ where
                 return Err(ConnectError::Unresolved);
             };
 
+            if inner.borrow().closed {
+                return Err(ConnectError::Disconnected(Some(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "Connections pool is closed",
+                ))));
+            }
+
             // acquire connection
             let result = inner.borrow_mut().acquire(&key);
             match result {
                 // use existing connection
                 Acquire::Acquired(io, created) => {
                     trace!("Use existing {:?} connection for {:?}", io, req.uri);
-                    Ok(Connection::new(
-                        io,
-                        created,
-                        Some(Acquired::new(key, inner)),
-                    ))
+                    Ok(
+                        Connection::new(io, created, Some(Acquired::new(key, inner)))
+                            .reused(),
+                    )
                 }
                 // open new tcp connection
                 Acquire::Available => {
@@ -191,9 +216,11 @@ pub(super) struct Inner {
     limit: usize,
     h2config: h2::Config,
     acquired: usize,
+    closed: bool,
     available: HashMap<Key, VecDeque<AvailableConnection>>,
     connecting: HashSet<Key>,
     waker: LocalWaker,
+    shutdown_task: LocalWaker,
     waiters: Rc<RefCell<Waiters>>,
 }
 
@@ -255,8 +282,8 @@ impl Inner {
             let now = now();
             while let Some(conn) = connections.pop_back() {
                 // check if it still usable
-                if (now - conn.used) > self.conn_keep_alive
-                    || (now - conn.created) > self.conn_lifetime
+                if (now - conn.used) >= self.conn_keep_alive
+                    || (now - conn.created) >= self.conn_lifetime
                 {
                     if let ConnectionType::H1(io) = conn.io {
                         spawn(async move {
@@ -307,7 +334,24 @@ impl Inner {
         }
     }
 
+    /// Close idle connections and reject new acquires
+    fn close(&mut self) {
+        self.closed = true;
+        for (_, connections) in self.available.drain() {
+            for conn in connections {
+                close_connection(conn.io);
+            }
+        }
+    }
+
     fn check_availibility(&mut self) {
+        if self.closed {
+            if self.acquired == 0 && self.connecting.is_empty() {
+                self.shutdown_task.wake();
+            }
+            return;
+        }
+
         let mut waiters = self.waiters.borrow_mut();
         waiters.cleanup();
         if !waiters.waiters.is_empty() && self.acquired < self.limit {
@@ -367,7 +411,8 @@ where
                             io,
                             created,
                             Some(Acquired::new(key.clone(), this.inner.clone())),
-                        )));
+                        )
+                        .reused()));
                     }
                     Acquire::Available => {
                         trace!("Connecting to {:?} and wake up waiter", req.uri);
@@ -449,6 +494,26 @@ where
             Ok(io) => {
                 io.set_disconnect_timeout(this.disconnect_timeout);
 
+                // pool is closed while connecting
+                if this.inner.borrow().closed {
+                    trace!(
+                        "Connections pool is closed, drop connection for {:?}",
+                        &this.key.authority
+                    );
+                    let _ = this.guard.take();
+                    if let Some(tx) = this.tx.take() {
+                        let _ =
+                            tx.send(Err(ConnectError::Disconnected(Some(io::Error::new(
+                                io::ErrorKind::NotConnected,
+                                "Connections pool is closed",
+                            )))));
+                    }
+                    spawn(async move {
+                        let _ = io.shutdown().await;
+                    });
+                    return Poll::Ready(());
+                }
+
                 // handle http2 proto
                 if io.query::<HttpProtocol>().get() == Some(HttpProtocol::Http2) {
                     // init http2 handshake
@@ -569,19 +634,12 @@ impl Acquired {
             let (io, created, _) = conn.into_inner();
             let mut inner = inner.borrow_mut();
             inner.acquired -= 1;
-            if close {
+            if close || inner.closed {
                 log::trace!(
                     "Releasing and closing connection for {:?}",
                     self.0.authority
                 );
-                match io {
-                    ConnectionType::H1(io) => {
-                        spawn(async move {
-                            let _ = io.shutdown().await;
-                        });
-                    }
-                    ConnectionType::H2(io) => io.close(),
-                }
+                close_connection(io);
             } else {
                 log::trace!("Releasing connection for {:?}", self.0.authority);
                 inner
@@ -609,6 +667,17 @@ impl Drop for Acquired {
     }
 }
 
+fn close_connection(io: ConnectionType) {
+    match io {
+        ConnectionType::H1(io) => {
+            spawn(async move {
+                let _ = io.shutdown().await;
+            });
+        }
+        ConnectionType::H2(io) => io.close(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{cell::RefCell, convert::TryFrom, rc::Rc};
@@ -725,4 +794,159 @@ mod tests {
         assert!(lazy(|cx| pool.poll_ready(cx)).await.is_ready());
         assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_ready());
     }
+
+    #[crate::rt_test]
+    async fn test_close() {
+        let store = Rc::new(RefCell::new(Vec::new()));
+        let store2 = store.clone();
+
+        let pool = ConnectionPool::new(
+            fn_service(move |_| {
+                let (client, server) = Io::create();
+                store2.borrow_mut().push(server);
+                Box::pin(async move { Ok(IoBoxed::from(nio::Io::new(client))) })
+            }),
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+            Millis::ZERO,
+            1,
+            h2::Config::client(),
+        );
+
+        let req = Connect {
+            uri: Uri::try_from("http://localhost/test").unwrap(),
+            addr: None,
+        };
+        let conn = pool.call(req.clone()).await.unwrap();
+        conn.release(false);
+        assert_eq!(pool.inner.borrow().available.len(), 1);
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(store.borrow().len(), 1);
+
+        // waiter for connection
+        let mut fut = pool.call(req.clone());
+        assert!(lazy(|cx| Pin::new(&mut fut).poll(cx)).await.is_pending());
+
+        // acquired connection is in use
+        assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_pending());
+        assert!(pool.waiters.borrow().waiters.is_empty());
+        assert!(matches!(fut.await, Err(ConnectError::Disconnected(None))));
+        assert!(matches!(
+            pool.call(req.clone()).await,
+            Err(ConnectError::Disconnected(Some(_)))
+        ));
+
+        // released connection is closed
+        conn.release(false);
+        assert!(pool.inner.borrow().available.is_empty());
+        assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_ready());
+        sleep(Millis(50)).await;
+        assert!(store.borrow()[0].is_closed());
+
+        // idle connections are closed
+        let pool = ConnectionPool::new(
+            fn_service(move |_| {
+                let (client, server) = Io::create();
+                store.borrow_mut().push(server);
+                Box::pin(async move { Ok(IoBoxed::from(nio::Io::new(client))) })
+            }),
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+            Millis::ZERO,
+            1,
+            h2::Config::client(),
+        );
+        let conn = pool.call(req.clone()).await.unwrap();
+        conn.release(false);
+        assert_eq!(pool.inner.borrow().available.len(), 1);
+        assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_ready());
+        assert!(pool.inner.borrow().available.is_empty());
+    }
+
+    #[crate::rt_test]
+    async fn test_close_while_connecting() {
+        let store = Rc::new(RefCell::new(Vec::new()));
+        let store2 = store.clone();
+
+        let pool = ConnectionPool::new(
+            fn_service(move |_| {
+                let (client, server) = Io::create();
+                store2.borrow_mut().push(server);
+                Box::pin(async move {
+                    sleep(Millis(100)).await;
+                    Ok(IoBoxed::from(nio::Io::new(client)))
+                })
+            }),
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+            Millis::ZERO,
+            1,
+            h2::Config::client(),
+        );
+
+        let req = Connect {
+            uri: Uri::try_from("http://localhost/test").unwrap(),
+            addr: None,
+        };
+        let mut fut = pool.call(req);
+        assert!(lazy(|cx| Pin::new(&mut fut).poll(cx)).await.is_pending());
+        assert!(!pool.inner.borrow().connecting.is_empty());
+
+        // pool waits for in-flight connect
+        assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_pending());
+        assert!(matches!(
+            fut.await,
+            Err(ConnectError::Disconnected(Some(_)))
+        ));
+
+        // established connection is closed, not returned to pool
+        assert!(pool.inner.borrow().connecting.is_empty());
+        assert!(pool.inner.borrow().available.is_empty());
+        assert!(lazy(|cx| pool.poll_shutdown(cx, false)).await.is_ready());
+        sleep(Millis(50)).await;
+        assert!(store.borrow()[0].is_closed());
+    }
+
+    #[crate::rt_test]
+    async fn test_lifetime() {
+        let store = Rc::new(RefCell::new(Vec::new()));
+        let store2 = store.clone();
+        let connector = move |lifetime| {
+            let store = store2.clone();
+            ConnectionPool::new(
+                fn_service(move |_| {
+                    let (client, server) = Io::create();
+                    store.borrow_mut().push(server);
+                    Box::pin(async move { Ok(IoBoxed::from(nio::Io::new(client))) })
+                }),
+                lifetime,
+                Duration::from_secs(10),
+                Millis::ZERO,
+                1,
+                h2::Config::client(),
+            )
+        };
+        let req = Connect {
+            uri: Uri::try_from("http://localhost/test").unwrap(),
+            addr: None,
+        };
+
+        // released connection is reused
+        let pool = connector(Duration::from_secs(10));
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert!(!conn.is_reused());
+        conn.release(false);
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert!(conn.is_reused());
+        assert_eq!(store.borrow().len(), 1);
+        conn.release(true);
+
+        // expired connection is not handed out
+        let pool = connector(Duration::ZERO);
+        let conn = pool.call(req.clone()).await.unwrap();
+        conn.release(false);
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert!(!conn.is_reused());
+        assert_eq!(store.borrow().len(), 3);
+    }
 }
@@ -6,7 +6,7 @@ use serde::Serialize;
 
 use crate::http::body::Body;
 use crate::http::error::HttpError;
-use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue, TryIntoHeaderPair};
 use crate::http::{
     uri, ConnectionType, Method, RequestHead, RequestHeadType, Uri, Version,
 };
@@ -194,6 +194,28 @@ impl ClientRequest {
         self
     }
 
+    /// Insert a header, replaces existing header.
+    ///
+    /// Accepts `(name, value)` tuple or typed header.
+    pub fn insert_header<H: TryIntoHeaderPair>(mut self, header: H) -> Self {
+        match header.try_into_pair() {
+            Ok((key, value)) => self.head.headers.insert(key, value),
+            Err(e) => self.err = Some(e),
+        }
+        self
+    }
+
+    /// Append a header, existing headers with the same name are preserved.
+    ///
+    /// Accepts `(name, value)` tuple or typed header.
+    pub fn append_header<H: TryIntoHeaderPair>(mut self, header: H) -> Self {
+        match header.try_into_pair() {
+            Ok((key, value)) => self.head.headers.append(key, value),
+            Err(e) => self.err = Some(e),
+        }
+        self
+    }
+
     /// Insert a header only if it is not yet set.
     pub fn set_header_if_none<K, V>(mut self, key: K, value: V) -> Self
     where
@@ -619,6 +641,27 @@ mod tests {
         );
     }
 
+    #[crate::rt_test]
+    async fn test_client_insert_append_header() {
+        use crate::http::header::{CacheControl, CacheDirective};
+
+        let req = Client::new()
+            .get("/")
+            .append_header((header::ACCEPT, "text/html"))
+            .append_header((header::ACCEPT, "application/json"))
+            .insert_header(CacheControl(vec![CacheDirective::MaxAge(0)]))
+            .insert_header(CacheControl(vec![CacheDirective::NoCache]));
+        assert_eq!(req.headers().get_all(header::ACCEPT).len(), 2);
+        assert_eq!(
+            req.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+        assert!(req.err.is_none());
+
+        let req = Client::new().get("/").append_header(("x test", "1"));
+        assert!(req.err.is_some());
+    }
+
     #[crate::rt_test]
     async fn test_client_header_override() {
         let req = Client::build()
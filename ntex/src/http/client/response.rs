@@ -1,17 +1,26 @@
 use std::cell::{Ref, RefMut};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::task::{Context, Poll};
-use std::{fmt, future::Future, marker::PhantomData, mem, pin::Pin};
+use std::{
+    ffi::OsString, fmt, fs, fs::File, future::Future, marker::PhantomData, mem, pin::Pin,
+    time::Duration,
+};
 
+use nanorand::{Rng, WyRand};
 use serde::de::DeserializeOwned;
 
 #[cfg(feature = "cookie")]
 use coo_kie::{Cookie, ParseError as CookieParseError};
+#[cfg(feature = "futures-io")]
+use futures_io_pkg::AsyncWrite;
 
 use crate::http::error::PayloadError;
 use crate::http::header::{AsName, HeaderValue, CONTENT_LENGTH};
 use crate::http::{HeaderMap, HttpMessage, Payload, ResponseHead, StatusCode, Version};
+use crate::rt::spawn_blocking;
 use crate::time::{Deadline, Millis};
-use crate::util::{Bytes, BytesMut, Extensions, Stream};
+use crate::util::{poll_fn, Bytes, BytesMut, Extensions, Stream};
 
 use super::error::JsonPayloadError;
 
@@ -19,6 +28,48 @@ use super::error::JsonPayloadError;
 pub struct ClientResponse {
     pub(crate) head: ResponseHead,
     pub(crate) payload: Payload,
+    pub(crate) timings: Timings,
+}
+
+/// Client request timings
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    reused: bool,
+    connect: Duration,
+    ttfb: Duration,
+}
+
+impl Timings {
+    pub(super) fn new(reused: bool, connect: Duration, ttfb: Duration) -> Self {
+        Timings {
+            reused,
+            connect,
+            ttfb,
+        }
+    }
+
+    /// Returns true if request was sent over connection from the pool
+    pub fn is_reused(&self) -> bool {
+        self.reused
+    }
+
+    /// Time spent to acquire connection.
+    ///
+    /// For new connections it includes dns resolution, tcp connect
+    /// and tls handshake.
+    pub fn connect(&self) -> Duration {
+        self.connect
+    }
+
+    /// Time from the start of sending request until response head is received
+    pub fn ttfb(&self) -> Duration {
+        self.ttfb
+    }
+
+    /// Total time until response head is received
+    pub fn total(&self) -> Duration {
+        self.connect + self.ttfb
+    }
 }
 
 impl HttpMessage for ClientResponse {
@@ -59,7 +110,11 @@ impl HttpMessage for ClientResponse {
 impl ClientResponse {
     /// Create new Request instance
     pub(crate) fn new(head: ResponseHead, payload: Payload) -> Self {
-        ClientResponse { head, payload }
+        ClientResponse {
+            head,
+            payload,
+            timings: Timings::default(),
+        }
     }
 
     pub(crate) fn with_empty_payload(head: ResponseHead) -> Self {
@@ -95,6 +150,14 @@ impl ClientResponse {
         &self.head().headers
     }
 
+    /// Request timings
+    ///
+    /// Timings are available only for responses received from a connector.
+    #[inline]
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+
     /// Set a body and return previous body value
     pub fn set_payload(&mut self, payload: Payload) {
         self.payload = payload;
@@ -134,6 +197,136 @@ impl ClientResponse {
     pub fn json<T: DeserializeOwned>(&mut self) -> JsonBody<T> {
         JsonBody::new(self)
     }
+
+    #[cfg(feature = "futures-io")]
+    /// Streams response's body to the writer.
+    ///
+    /// Next chunk of the body is not read until previous one is completely
+    /// written, so slow writer applies backpressure to the peer.
+    /// Returns number of written bytes. Requires `futures-io` feature.
+    pub async fn copy_to<W>(&mut self, writer: &mut W) -> Result<u64, PayloadError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut total = 0;
+        while let Some(chunk) = poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await {
+            let chunk = chunk?;
+            let mut written = 0;
+            while written < chunk.len() {
+                let n =
+                    poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, &chunk[written..]))
+                        .await?;
+                if n == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write whole chunk",
+                    )
+                    .into());
+                }
+                written += n;
+            }
+            total += chunk.len() as u64;
+        }
+        poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await?;
+        Ok(total)
+    }
+
+    /// Saves response's body to the file.
+    ///
+    /// Body is written to the temporary file in the same directory, which
+    /// is renamed to `path` once the whole body is received. If an error
+    /// occurs or the future is dropped, temporary file is removed and `path`
+    /// stays untouched. Chunks are buffered and written on the thread pool.
+    /// Returns number of written bytes.
+    pub async fn save_to_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<u64, PayloadError> {
+        let path = path.as_ref().to_path_buf();
+        let mut tmp = TempFile(Some(temp_path(&path)?));
+
+        let file_path = tmp.path().to_path_buf();
+        let mut file = Some(blocking(move || File::create(file_path)).await?);
+        let mut buf = BytesMut::new();
+        let mut total = 0;
+
+        loop {
+            let chunk = poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await;
+            let eof = chunk.is_none();
+            if let Some(chunk) = chunk {
+                let chunk = chunk?;
+                total += chunk.len() as u64;
+                buf.extend_from_slice(&chunk);
+            }
+
+            if eof || buf.len() >= WRITE_BUFFER_SIZE {
+                let mut f = file.take().unwrap();
+                let data = buf.split().freeze();
+                file = Some(
+                    blocking(move || {
+                        f.write_all(&data)?;
+                        if eof {
+                            f.sync_all()?;
+                        }
+                        Ok(f)
+                    })
+                    .await?,
+                );
+            }
+            if eof {
+                break;
+            }
+        }
+
+        drop(file);
+        let tmp_path = tmp.path().to_path_buf();
+        blocking(move || fs::rename(tmp_path, path)).await?;
+        tmp.0 = None;
+        Ok(total)
+    }
+}
+
+/// Size of buffered body data written to the file at once
+const WRITE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Removes temporary file on drop, unless it has been renamed
+struct TempFile(Option<PathBuf>);
+
+impl TempFile {
+    fn path(&self) -> &Path {
+        self.0.as_deref().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Temporary file path next to the target file
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "Path does not name a file")
+    })?;
+    let mut tmp = OsString::from(".");
+    tmp.push(name);
+    tmp.push(format!(".{:016x}.tmp", WyRand::new().generate::<u64>()));
+    Ok(path.with_file_name(tmp))
+}
+
+/// Run blocking file operation on the thread pool
+async fn blocking<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(_) => Err(io::Error::new(io::ErrorKind::Other, "Thread pool is gone")),
+    }
 }
 
 impl Stream for ClientResponse {
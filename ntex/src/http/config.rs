@@ -2,7 +2,7 @@ use std::{cell::Cell, ptr::copy_nonoverlapping, rc::Rc, time, time::Duration};
 
 use ntex_h2::{self as h2};
 
-use crate::http::{Request, Response};
+use crate::http::{header::HeaderValue, Request, Response};
 use crate::time::{sleep, Millis, Seconds};
 use crate::{io::IoRef, service::boxed::BoxService, util::BytesMut};
 
@@ -50,6 +50,12 @@ pub(super) struct Inner {
     pub(super) timer: DateService,
     pub(super) ssl_handshake_timeout: Millis,
     pub(super) h2config: h2::Config,
+    pub(super) server_header: Option<HeaderValue>,
+    pub(super) default_content_type: Option<HeaderValue>,
+    pub(super) max_uri_length: usize,
+    pub(super) h2_idle_timeout: Millis,
+    pub(super) h2_ka_interval: Millis,
+    pub(super) h2_ka_timeout: Millis,
 }
 
 impl Clone for ServiceConfig {
@@ -93,9 +99,55 @@ impl ServiceConfig {
             client_disconnect,
             ssl_handshake_timeout,
             h2config,
+            server_header: None,
+            default_content_type: None,
+            max_uri_length: 0,
+            h2_idle_timeout: Millis::ZERO,
+            h2_ka_interval: Millis::ZERO,
+            h2_ka_timeout: Millis::ZERO,
             timer: DateService::new(),
         }))
     }
+
+    /// Set `Server` header value for responses
+    pub(super) fn server_header(mut self, value: Option<HeaderValue>) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .server_header = value;
+        self
+    }
+
+    /// Set default `Content-Type` header value for responses
+    pub(super) fn default_content_type(mut self, value: Option<HeaderValue>) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .default_content_type = value;
+        self
+    }
+
+    /// Set max length of request target for http/1
+    pub(super) fn max_uri_length(mut self, len: usize) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .max_uri_length = len;
+        self
+    }
+
+    /// Set idle timeout for http/2 connections
+    pub(super) fn h2_idle_timeout(mut self, timeout: Seconds) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .h2_idle_timeout = timeout.into();
+        self
+    }
+
+    /// Set interval and timeout of keep-alive pings for http/2 connections
+    pub(super) fn h2_keep_alive(mut self, interval: Seconds, timeout: Seconds) -> Self {
+        let inner = Rc::get_mut(&mut self.0).expect("Multiple copies exist");
+        inner.h2_ka_interval = interval.into();
+        inner.h2_ka_timeout = timeout.into();
+        self
+    }
 }
 
 pub(super) type OnRequest = BoxService<(Request, IoRef), Request, Response>;
@@ -110,6 +162,12 @@ pub(super) struct DispatcherConfig<S, X, U> {
     pub(super) ka_enabled: bool,
     pub(super) timer: DateService,
     pub(super) on_request: Option<OnRequest>,
+    pub(super) server_header: Option<HeaderValue>,
+    pub(super) default_content_type: Option<HeaderValue>,
+    pub(super) max_uri_length: usize,
+    pub(super) h2_idle_timeout: Millis,
+    pub(super) h2_ka_interval: Millis,
+    pub(super) h2_ka_timeout: Millis,
 }
 
 impl<S, X, U> DispatcherConfig<S, X, U> {
@@ -130,6 +188,12 @@ impl<S, X, U> DispatcherConfig<S, X, U> {
             client_disconnect: cfg.0.client_disconnect,
             ka_enabled: cfg.0.ka_enabled,
             timer: cfg.0.timer.clone(),
+            server_header: cfg.0.server_header.clone(),
+            default_content_type: cfg.0.default_content_type.clone(),
+            max_uri_length: cfg.0.max_uri_length,
+            h2_idle_timeout: cfg.0.h2_idle_timeout,
+            h2_ka_interval: cfg.0.h2_ka_interval,
+            h2_ka_timeout: cfg.0.h2_ka_timeout,
         }
     }
 
@@ -16,6 +16,7 @@ const INPLACE: usize = 1024;
 
 pub struct Encoder<B> {
     eof: bool,
+    flush: bool,
     body: EncoderBody<B>,
     encoder: Option<ContentEncoder>,
     fut: Option<JoinHandle<Result<ContentEncoder, io::Error>>>,
@@ -54,6 +55,7 @@ impl<B: MessageBody + 'static> Encoder<B> {
             ResponseBody::Other(Body::from_message(Encoder {
                 body,
                 eof: false,
+                flush: false,
                 fut: None,
                 encoder: Some(encoder),
             }))
@@ -88,6 +90,10 @@ impl<B: MessageBody> MessageBody for Encoder<B> {
             if self.eof {
                 return Poll::Ready(None);
             }
+            if self.flush {
+                self.flush = false;
+                return Poll::Ready(Some(Ok(Bytes::new())));
+            }
 
             if let Some(ref mut fut) = self.fut {
                 let mut encoder = match Pin::new(fut).poll(cx) {
@@ -123,7 +129,14 @@ impl<B: MessageBody> MessageBody for Encoder<B> {
             match result {
                 Poll::Ready(Some(Ok(chunk))) => {
                     if let Some(mut encoder) = self.encoder.take() {
-                        if chunk.len() < INPLACE {
+                        if chunk.is_empty() {
+                            // flush point, emit compressed data and pass flush further
+                            encoder.flush()?;
+                            let chunk = encoder.take();
+                            self.encoder = Some(encoder);
+                            self.flush = !chunk.is_empty();
+                            return Poll::Ready(Some(Ok(chunk)));
+                        } else if chunk.len() < INPLACE {
                             encoder.write(&chunk)?;
                             let chunk = encoder.take();
                             self.encoder = Some(encoder);
@@ -222,6 +235,14 @@ impl ContentEncoder {
         }
     }
 
+    fn flush(&mut self) -> Result<(), io::Error> {
+        match *self {
+            ContentEncoder::Br(ref mut encoder) => encoder.flush(),
+            ContentEncoder::Deflate(ref mut encoder) => encoder.flush(),
+            ContentEncoder::Gzip(ref mut encoder) => encoder.flush(),
+        }
+    }
+
     fn write(&mut self, data: &[u8]) -> Result<(), io::Error> {
         match *self {
             ContentEncoder::Br(ref mut encoder) => match encoder.write_all(data) {
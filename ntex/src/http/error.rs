@@ -59,6 +59,7 @@ impl ResponseError for serde_json::error::Error {}
 
 /// A set of errors that can occur during parsing HTTP streams
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum ParseError {
     /// An invalid `Method`, such as `GE.T`.
     #[error("Invalid Method specified")]
@@ -75,6 +76,9 @@ pub enum ParseError {
     /// A message head is too large to be reasonable.
     #[error("Message head is too large")]
     TooLarge,
+    /// A request target is longer than configured limit.
+    #[error("Request uri is too long")]
+    UriTooLong,
     /// A message reached EOF, but is not complete.
     #[error("Message is incomplete")]
     Incomplete,
@@ -115,6 +119,7 @@ impl From<httparse::Error> for ParseError {
 
 #[derive(thiserror::Error, Debug)]
 /// A set of errors that can occur during payload parsing
+#[non_exhaustive]
 pub enum PayloadError {
     /// A payload reached EOF, but is not complete.
     #[error("A payload reached EOF, but is not complete. With error: {0:?}")]
@@ -128,6 +133,12 @@ pub enum PayloadError {
     /// A payload length is unknown.
     #[error("A payload length is unknown.")]
     UnknownLength,
+    /// A payload data rate is below configured minimum.
+    #[error("A payload data rate is below configured minimum.")]
+    Timeout,
+    /// Memory budget for buffered payloads is exhausted.
+    #[error("Memory budget for buffered payloads is exhausted.")]
+    MemoryLimit,
     /// Http2 payload error
     #[error("{0}")]
     Http2Payload(#[from] h2::StreamError),
@@ -246,11 +257,14 @@ pub enum ContentTypeError {
 
 /// Blocking operation execution error
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum BlockingError<E: fmt::Debug> {
     #[error("{0:?}")]
     Error(E),
     #[error("Thread pool is gone")]
     Canceled,
+    #[error("Thread pool is overloaded")]
+    Overloaded,
 }
 
 impl From<crate::rt::JoinError> for PayloadError {
@@ -270,6 +284,24 @@ impl From<BlockingError<io::Error>> for PayloadError {
                 io::ErrorKind::Other,
                 "Operation is canceled",
             )),
+            BlockingError::Overloaded => PayloadError::Io(io::Error::new(
+                io::ErrorKind::Other,
+                "Thread pool is overloaded",
+            )),
+        }
+    }
+}
+
+impl From<BlockingError<io::Error>> for io::Error {
+    fn from(err: BlockingError<io::Error>) -> Self {
+        match err {
+            BlockingError::Error(e) => e,
+            BlockingError::Canceled => {
+                io::Error::new(io::ErrorKind::Other, "Thread pool is gone")
+            }
+            BlockingError::Overloaded => {
+                io::Error::new(io::ErrorKind::Other, "Thread pool is overloaded")
+            }
         }
     }
 }
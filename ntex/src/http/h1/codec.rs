@@ -9,7 +9,7 @@ use crate::http::error::ParseError;
 use crate::http::message::ConnectionType;
 use crate::http::request::Request;
 use crate::http::response::Response;
-use crate::http::{Method, Version};
+use crate::http::{header, HeaderMap, Method, StatusCode, Version};
 use crate::util::BytesMut;
 
 use super::{decoder, decoder::PayloadType, encoder, Message};
@@ -32,6 +32,7 @@ pub struct Codec {
     // encoder part
     flags: Cell<Flags>,
     encoder: encoder::MessageEncoder<Response<()>>,
+    max_uri_length: usize,
 }
 
 impl Default for Codec {
@@ -49,6 +50,7 @@ impl Clone for Codec {
             ctype: self.ctype.clone(),
             flags: self.flags.clone(),
             encoder: self.encoder.clone(),
+            max_uri_length: self.max_uri_length,
         }
     }
 }
@@ -77,9 +79,16 @@ impl Codec {
             version: Cell::new(Version::HTTP_11),
             ctype: Cell::new(ConnectionType::Close),
             encoder: encoder::MessageEncoder::default(),
+            max_uri_length: 0,
         }
     }
 
+    /// Set max length of request target, zero disables check.
+    pub(in crate::http) fn max_uri_length(mut self, len: usize) -> Self {
+        self.max_uri_length = len;
+        self
+    }
+
     #[inline]
     /// Check if request is upgrade
     pub fn upgrade(&self) -> bool {
@@ -120,6 +129,9 @@ impl Decoder for Codec {
     type Error = ParseError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if self.max_uri_length != 0 {
+            check_uri_length(src, self.max_uri_length)?;
+        }
         if let Some((req, payload)) = self.decoder.decode(src)? {
             let head = req.head();
             let mut flags = self.flags.get();
@@ -143,6 +155,33 @@ impl Decoder for Codec {
     }
 }
 
+/// Check length of request target, request line does not need to be complete
+fn check_uri_length(src: &[u8], max: usize) -> Result<(), ParseError> {
+    if let Some(start) = src.iter().position(|b| *b == b' ') {
+        let target = &src[start + 1..];
+        let len = target
+            .iter()
+            .position(|b| matches!(b, b' ' | b'\r' | b'\n'))
+            .unwrap_or(target.len());
+        if len > max {
+            return Err(ParseError::UriTooLong);
+        }
+    }
+    Ok(())
+}
+
+/// Check if `connection` header contains `close` token
+fn has_close_token(headers: &HeaderMap) -> bool {
+    headers.get_all(header::CONNECTION).any(|val| {
+        val.to_str()
+            .map(|val| {
+                val.split(',')
+                    .any(|token| token.trim().eq_ignore_ascii_case("close"))
+            })
+            .unwrap_or(false)
+    })
+}
+
 impl Encoder for Codec {
     type Item = Message<(Response<()>, BodySize)>;
     type Error = io::Error;
@@ -153,11 +192,21 @@ impl Encoder for Codec {
                 // set response version
                 res.head_mut().version = self.version.get();
 
+                // informational response does not change connection state,
+                // `101 Switching Protocols` is the final response
+                let status = res.head().status;
+                if status.is_informational() && status != StatusCode::SWITCHING_PROTOCOLS {
+                    return self.encoder.encode_informational(dst, &res);
+                }
+
                 // connection status
                 if let Some(ct) = res.head().ctype() {
                     if ct != ConnectionType::KeepAlive {
                         self.ctype.set(ct)
                     }
+                } else if has_close_token(res.headers()) {
+                    // connection header set by the service
+                    self.ctype.set(ConnectionType::Close)
                 }
 
                 // encode message
@@ -238,4 +287,22 @@ mod tests {
         assert!(codec.upgrade());
         assert!(!codec.keepalive_enabled());
     }
+
+    #[test]
+    fn test_max_uri_length() {
+        let codec = Codec::default().max_uri_length(8);
+        let mut buf = BytesMut::from("GET /1234567 HTTP/1.1\r\n\r\n");
+        let (req, _) = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(req.path(), "/1234567");
+
+        // request line is not complete yet
+        let mut buf = BytesMut::from("GET /12345678");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ParseError::UriTooLong)
+        ));
+
+        let mut buf = BytesMut::from("GET /1234567");
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
 }
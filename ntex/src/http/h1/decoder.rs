@@ -90,6 +90,7 @@ pub(super) trait MessageType: Sized {
         let mut expect = false;
         let mut chunked = false;
         let mut seen_te = false;
+        let mut seen_cl = false;
         let mut content_length = None;
 
         {
@@ -105,10 +106,18 @@ pub(super) trait MessageType: Sized {
                     )
                 };
                 match name {
-                    header::CONTENT_LENGTH if content_length.is_some() || chunked => {
+                    header::CONTENT_LENGTH if seen_cl => {
                         log::debug!("multiple Content-Length not allowed");
                         return Err(ParseError::Header);
                     }
+                    header::CONTENT_LENGTH
+                        if chunked || (seen_te && version != Version::HTTP_11) =>
+                    {
+                        log::debug!(
+                            "both Content-Length and Transfer-Encoding are not allowed"
+                        );
+                        return Err(ParseError::Header);
+                    }
                     header::CONTENT_LENGTH => match value.to_str() {
                         Ok(s) if s.trim_start().starts_with('+') => {
                             log::debug!("illegal Content-Length: {:?}", s);
@@ -119,6 +128,7 @@ pub(super) trait MessageType: Sized {
                                 // accept 0 lengths here and remove them in `decode` after all
                                 // headers have been processed to prevent request smuggling issues
                                 content_length = Some(len);
+                                seen_cl = true;
                             } else {
                                 log::debug!("illegal Content-Length: {:?}", s);
                                 return Err(ParseError::Header);
@@ -137,8 +147,13 @@ pub(super) trait MessageType: Sized {
                     header::TRANSFER_ENCODING if version == Version::HTTP_11 => {
                         seen_te = true;
                         if let Ok(s) = value.to_str().map(str::trim) {
-                            if s.eq_ignore_ascii_case("chunked") && content_length.is_none()
-                            {
+                            if s.eq_ignore_ascii_case("chunked") {
+                                if seen_cl {
+                                    log::debug!(
+                                        "both Content-Length and Transfer-Encoding are not allowed"
+                                    );
+                                    return Err(ParseError::Header);
+                                }
                                 chunked = true
                             } else if s.eq_ignore_ascii_case("identity") {
                                 // allow silently since multiple TE headers are already checked
@@ -150,6 +165,17 @@ pub(super) trait MessageType: Sized {
                             return Err(ParseError::Header);
                         }
                     }
+                    header::TRANSFER_ENCODING => {
+                        // HTTP/1.0 does not support transfer codings, message framing
+                        // is ambiguous if Content-Length is present as well
+                        if seen_cl {
+                            log::debug!(
+                                "both Content-Length and Transfer-Encoding are not allowed"
+                            );
+                            return Err(ParseError::Header);
+                        }
+                        seen_te = true;
+                    }
                     // connection keep-alive state
                     header::CONNECTION => {
                         ka = if let Ok(conn) = value.to_str().map(|conn| conn.trim()) {
@@ -188,6 +214,12 @@ pub(super) trait MessageType: Sized {
                 headers.append(name, value);
             }
         }
+        // https://www.rfc-editor.org/rfc/rfc9112#section-6.1
+        // HTTP/1.0 message with Transfer-Encoding has faulty framing,
+        // connection must be closed after processing the message
+        if seen_te && version != Version::HTTP_11 {
+            ka = Some(ConnectionType::Close);
+        }
         self.set_connection_type(ka);
         if expect {
             self.set_expect()
@@ -990,6 +1022,42 @@ mod tests {
         assert_eq!(val[1], "c2=cookie2");
     }
 
+    #[test]
+    fn test_headers_shared_buffer() {
+        let value = "a".repeat(64);
+        let mut buf = BytesMut::with_capacity(1024);
+        for _ in 0..2 {
+            buf.extend_from_slice(b"GET /test HTTP/1.1\r\nx-test: ");
+            buf.extend_from_slice(value.as_bytes());
+            buf.extend_from_slice(b"\r\n\r\n");
+        }
+        let start = buf.as_ptr() as usize;
+        let end = start + buf.len();
+
+        let req1 = parse_ready!(&mut buf);
+        let req2 = parse_ready!(&mut buf);
+
+        // long header values are not copied from read buffer,
+        // short values are stored inline
+        for req in [&req1, &req2] {
+            let ptr = req.headers().get("x-test").unwrap().as_bytes().as_ptr() as usize;
+            assert!(ptr >= start && ptr < end);
+        }
+
+        // read buffer reuse does not affect parsed requests
+        for _ in 0..2 {
+            buf.clear();
+            buf.extend_from_slice(b"GET /test HTTP/1.1\r\nx-test: ");
+            buf.extend_from_slice(&[b'b'; 512]);
+            buf.extend_from_slice(b"\r\n\r\n");
+        }
+        let req3 = parse_ready!(&mut buf);
+
+        assert_eq!(req1.headers().get("x-test").unwrap(), value.as_str());
+        assert_eq!(req2.headers().get("x-test").unwrap(), value.as_str());
+        assert_eq!(req3.headers().get("x-test").unwrap().len(), 512);
+    }
+
     #[test]
     fn test_conn_default_1_0() {
         let mut buf = BytesMut::from("GET /test HTTP/1.0\r\n\r\n");
@@ -1416,8 +1484,7 @@ mod tests {
 
     #[test]
     fn test_content_length_and_te_http10() {
-        // in HTTP/1.0 transfer encoding is simply ignored so it's fine to have both
-
+        // in HTTP/1.0 transfer encoding is ignored, framing is ambiguous if both present
         let mut buf = BytesMut::from(
             "GET / HTTP/1.0\r\n\
             Host: example.com\r\n\
@@ -1426,8 +1493,28 @@ mod tests {
             \r\n\
             000",
         );
+        expect_parse_err!(&mut buf);
+
+        let mut buf = BytesMut::from(
+            "GET / HTTP/1.0\r\n\
+            Host: example.com\r\n\
+            Transfer-Encoding: chunked\r\n\
+            Content-Length: 3\r\n\
+            \r\n\
+            000",
+        );
+        expect_parse_err!(&mut buf);
 
-        parse_ready!(&mut buf);
+        // connection must be closed after message with transfer encoding
+        let mut buf = BytesMut::from(
+            "GET / HTTP/1.0\r\n\
+            Host: example.com\r\n\
+            Connection: keep-alive\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n",
+        );
+        let req = parse_ready!(&mut buf);
+        assert_eq!(req.head().connection_type(), ConnectionType::Close);
     }
 
     #[test]
@@ -1498,6 +1585,28 @@ mod tests {
         expect_parse_err!(&mut buf);
     }
 
+    #[test]
+    fn test_content_length_with_upgrade() {
+        // websocket upgrade resets content-length, but it is still accounted
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             Content-Length: 3\r\n\
+             Upgrade: websocket\r\n\
+             Content-Length: 5\r\n\
+             \r\n",
+        );
+        expect_parse_err!(&mut buf);
+
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             Content-Length: 3\r\n\
+             Upgrade: websocket\r\n\
+             Transfer-Encoding: chunked\r\n\
+             \r\n",
+        );
+        expect_parse_err!(&mut buf);
+    }
+
     #[test]
     fn test_transfer_encoding_content_length() {
         let mut buf = BytesMut::from(
@@ -3,13 +3,14 @@ use std::task::{Context, Poll};
 use std::{cell::RefCell, error::Error, future::Future, io, marker, pin::Pin, rc::Rc};
 
 use crate::io::{Filter, Io, IoBoxed, IoStatusUpdate, RecvError};
-use crate::{service::Service, util::ready, util::Bytes};
+use crate::{service::Service, util::ready, util::Bytes, util::Extensions};
 
 use crate::http;
 use crate::http::body::{BodySize, MessageBody, ResponseBody};
 use crate::http::config::DispatcherConfig;
 use crate::http::error::{DispatchError, ParseError, PayloadError, ResponseError};
-use crate::http::message::CurrentIo;
+use crate::http::header;
+use crate::http::message::{ConnectionType, CurrentIo, Proto};
 use crate::http::request::Request;
 use crate::http::response::Response;
 
@@ -31,6 +32,8 @@ bitflags::bitflags! {
         const UPGRADE_HND          = 0b0001_0000;
         /// Stop after sending payload
         const SENDPAYLOAD_AND_STOP = 0b0010_0000;
+        /// Flush write buffer before polling next chunk
+        const FLUSH                = 0b0100_0000;
     }
 }
 
@@ -82,6 +85,7 @@ struct DispatcherInner<F, S, B, X, U> {
     config: Rc<DispatcherConfig<S, X, U>>,
     error: Option<DispatchError>,
     payload: Option<(PayloadDecoder, PayloadSender)>,
+    conn_data: Rc<RefCell<Extensions>>,
     _t: marker::PhantomData<(S, B)>,
 }
 
@@ -98,7 +102,8 @@ where
 {
     /// Construct new `Dispatcher` instance with outgoing messages stream.
     pub(in crate::http) fn new(io: Io<F>, config: Rc<DispatcherConfig<S, X, U>>) -> Self {
-        let codec = Codec::new(config.timer.clone(), config.keep_alive_enabled());
+        let codec = Codec::new(config.timer.clone(), config.keep_alive_enabled())
+            .max_uri_length(config.max_uri_length);
         io.set_disconnect_timeout(config.client_disconnect.into());
 
         // slow-request timer
@@ -119,6 +124,7 @@ where
                 config,
                 error: None,
                 payload: None,
+                conn_data: Rc::new(RefCell::new(Extensions::new())),
                 _t: marker::PhantomData,
             },
         }
@@ -325,7 +331,9 @@ where
                             this.inner.flags.insert(Flags::SENDPAYLOAD_AND_STOP);
                         }
                         loop {
-                            let _ = ready!(this.inner.io.poll_flush(cx, false));
+                            let full = this.inner.flags.contains(Flags::FLUSH);
+                            let _ = ready!(this.inner.io.poll_flush(cx, full));
+                            this.inner.flags.remove(Flags::FLUSH);
                             let item = ready!(body.poll_next_chunk(cx));
                             if let Some(st) = this.inner.send_payload(item) {
                                 *this.st = st;
@@ -414,12 +422,18 @@ where
     where
         E: ResponseError + 'static,
     {
-        let res: Response = (&err).into();
+        let mut res: Response = (&err).into();
+
+        // check if we can continue after error
+        let close = critical || self.payload.is_some();
+        if close {
+            res.head_mut().set_connection_type(ConnectionType::Close);
+        }
         let (res, body) = res.into_parts();
         let state = self.send_response(res, body.into_body());
 
-        // check if we can continue after error
-        if critical || self.payload.take().is_some() {
+        if close {
+            self.payload.take();
             self.error = Some(DispatchError::Service(Box::new(err)));
             if matches!(state, State::SendPayload { .. }) {
                 self.flags.insert(Flags::SENDPAYLOAD_AND_STOP);
@@ -490,6 +504,8 @@ where
                         } else {
                             req.head_mut().io = CurrentIo::Ref(self.io.get_ref());
                         }
+                        req.head_mut().conn_data = Some(self.conn_data.clone());
+                        req.head_mut().proto = Proto::H1(self.config.timer.clone());
                         call_state.set(if let Some(ref f) = self.config.on_request {
                             // Handle filter fut
                             CallState::Filter {
@@ -526,7 +542,12 @@ where
                 Err(RecvError::Decoder(err)) => {
                     // Malformed requests, respond with 400
                     log::trace!("malformed request: {:?}", err);
-                    let (res, body) = Response::BadRequest().finish().into_parts();
+                    let res = if let ParseError::UriTooLong = err {
+                        Response::UriTooLong().finish()
+                    } else {
+                        Response::BadRequest().finish()
+                    };
+                    let (res, body) = res.into_parts();
                     self.error = Some(DispatchError::Parse(err));
                     Poll::Ready(self.send_response(res, body.into_body()))
                 }
@@ -555,7 +576,18 @@ where
         }
     }
 
-    fn send_response(&mut self, msg: Response<()>, body: ResponseBody<B>) -> State<B> {
+    fn send_response(&mut self, mut msg: Response<()>, body: ResponseBody<B>) -> State<B> {
+        if let Some(ref server) = self.config.server_header {
+            if !msg.headers().contains_key(header::SERVER) {
+                msg.headers_mut().insert(header::SERVER, server.clone());
+            }
+        }
+        if let Some(ref ctype) = self.config.default_content_type {
+            if !body.size().is_eof() && !msg.headers().contains_key(header::CONTENT_TYPE) {
+                msg.headers_mut()
+                    .insert(header::CONTENT_TYPE, ctype.clone());
+            }
+        }
         trace!("sending response: {:?} body: {:?}", msg, body.size());
         // we dont need to process responses if socket is disconnected
         // but we still want to handle requests with app service
@@ -599,6 +631,11 @@ where
         item: Option<Result<Bytes, Box<dyn Error>>>,
     ) -> Option<State<B>> {
         match item {
+            Some(Ok(item)) if item.is_empty() => {
+                trace!("got response flush point");
+                self.flags.insert(Flags::FLUSH);
+                None
+            }
             Some(Ok(item)) => {
                 trace!("got response chunk: {:?}", item.len());
                 match self.io.encode(Message::Chunk(Some(item)), &self.codec) {
@@ -881,6 +918,44 @@ mod tests {
         assert!(h1.inner.io.is_closed());
     }
 
+    #[crate::rt_test]
+    async fn test_req_ambiguous_framing() {
+        let reqs = vec![
+            // double Content-Length
+            "POST /test HTTP/1.1\r\ncontent-length: 5\r\ncontent-length: 4\r\n\r\n",
+            // Transfer-Encoding and Content-Length
+            "POST /test HTTP/1.1\r\ncontent-length: 5\r\ntransfer-encoding: chunked\r\n\r\n",
+            "POST /test HTTP/1.1\r\ntransfer-encoding: chunked\r\ncontent-length: 5\r\n\r\n",
+        ];
+
+        for req in reqs {
+            let num = Arc::new(AtomicUsize::new(0));
+            let num2 = num.clone();
+
+            let (client, server) = Io::create();
+            client.remote_buffer_cap(1024);
+            client.write(req);
+            client.write("0\r\n\r\nGET /test HTTP/1.1\r\n\r\n");
+
+            let mut h1 = h1(server, move |_| {
+                num2.fetch_add(1, Ordering::Relaxed);
+                Box::pin(async { Ok::<_, io::Error>(Response::Ok().finish()) })
+            });
+            sleep(Millis(50)).await;
+            let _ = lazy(|cx| Pin::new(&mut h1).poll(cx)).await.is_ready();
+            sleep(Millis(50)).await;
+
+            assert!(lazy(|cx| Pin::new(&mut h1).poll(cx)).await.is_ready());
+            assert!(h1.inner.io.is_closed());
+            sleep(Millis(50)).await;
+
+            client.local_buffer(|buf| {
+                assert_eq!(&buf[..26], b"HTTP/1.1 400 Bad Request\r\n")
+            });
+            assert_eq!(num.load(Ordering::Relaxed), 0);
+        }
+    }
+
     #[crate::rt_test]
     async fn test_pipeline() {
         let (client, server) = Io::create();
@@ -1206,6 +1281,39 @@ mod tests {
         assert!(h1.inner.io.is_closed());
         let buf = client.local_buffer(|buf| buf.split());
         assert_eq!(&buf[..28], b"HTTP/1.1 500 Internal Server");
+        assert!(String::from_utf8_lossy(&buf).contains("connection: close\r\n"));
         assert_eq!(&buf[buf.len() - 5..], b"error");
     }
+
+    #[crate::rt_test]
+    async fn test_connection_close_header() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+        spawn_h1(server, |req: Request| async move {
+            if req.path() == "/close" {
+                Ok::<_, io::Error>(
+                    Response::Ok().header(header::CONNECTION, "Close").finish(),
+                )
+            } else {
+                Ok::<_, io::Error>(Response::Ok().finish())
+            }
+        });
+
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        assert!(load(&mut decoder, &mut buf).keep_alive());
+        assert!(!client.is_server_dropped());
+
+        // connection is not reused after response with `connection: close`
+        client.write("GET /close HTTP/1.1\r\n\r\n");
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        assert!(!head.keep_alive());
+        assert!(decoder.decode(&mut buf).unwrap().is_none());
+        sleep(Millis(50)).await;
+        assert!(client.is_closed());
+    }
 }
@@ -217,7 +217,14 @@ impl MessageType for Response<()> {
 
     fn encode_status(&self, dst: &mut BytesMut) -> io::Result<()> {
         let head = self.head();
-        let reason = head.reason().as_bytes();
+        // custom reason must not break status line, unregistered
+        // status codes get empty reason phrase
+        let reason = head
+            .reason
+            .filter(|r| !r.bytes().any(|b| b == b'\r' || b == b'\n'))
+            .or_else(|| head.status.canonical_reason())
+            .unwrap_or("")
+            .as_bytes();
         dst.reserve(256 + head.headers.len() * AVERAGE_HEADER_SIZE + reason.len());
 
         // status line
@@ -314,6 +321,30 @@ impl<T: MessageType> MessageEncoder<T> {
         message.encode_status(dst)?;
         message.encode_headers(dst, version, length, ctype, timer)
     }
+
+    /// Encode informational (1xx) response
+    ///
+    /// Transfer encoding state is not changed, final response follows.
+    pub(super) fn encode_informational(
+        &self,
+        dst: &mut BytesMut,
+        message: &T,
+    ) -> io::Result<()> {
+        message.encode_status(dst)?;
+        dst.extend_from_slice(b"\r\n");
+        for (key, value) in message.headers().iter() {
+            match *key {
+                CONNECTION | TRANSFER_ENCODING | CONTENT_LENGTH => continue,
+                _ => (),
+            }
+            dst.extend_from_slice(key.as_str().as_bytes());
+            dst.extend_from_slice(b": ");
+            dst.extend_from_slice(value.as_bytes());
+            dst.extend_from_slice(b"\r\n");
+        }
+        dst.extend_from_slice(b"\r\n");
+        Ok(())
+    }
 }
 
 /// Encoders to handle different Transfer-Encodings.
@@ -632,6 +663,26 @@ mod tests {
         assert!(data.contains("date: date\r\n"));
     }
 
+    #[test]
+    fn test_status_line() {
+        let encode = |status: u16, reason: Option<&'static str>| {
+            let mut res = Response::with_body(StatusCode::from_u16(status).unwrap(), ());
+            res.head_mut().reason = reason;
+            let mut bytes = BytesMut::new();
+            res.encode_status(&mut bytes).unwrap();
+            bytes
+        };
+
+        assert_eq!(encode(200, None), b"HTTP/1.1 200 OK"[..]);
+        assert_eq!(encode(200, Some("Because")), b"HTTP/1.1 200 Because"[..]);
+        assert_eq!(encode(499, None), b"HTTP/1.1 499 "[..]);
+        assert_eq!(encode(599, Some("Custom")), b"HTTP/1.1 599 Custom"[..]);
+        assert_eq!(
+            encode(404, Some("a\r\nb: c")),
+            b"HTTP/1.1 404 Not Found"[..]
+        );
+    }
+
     #[test]
     fn test_write_content_length() {
         let mut bytes = BytesMut::new();
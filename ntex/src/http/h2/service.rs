@@ -1,4 +1,4 @@
-use std::{cell::RefCell, io, task::Context, task::Poll};
+use std::{cell::Cell, cell::RefCell, io, task::Context, task::Poll, time::Instant};
 use std::{convert::TryFrom, future::Future, marker::PhantomData, mem, pin::Pin, rc::Rc};
 
 use ntex_h2::{self as h2, frame::StreamId, server};
@@ -7,11 +7,13 @@ use crate::http::body::{BodySize, MessageBody};
 use crate::http::config::{DispatcherConfig, ServiceConfig};
 use crate::http::error::{DispatchError, H2Error, ResponseError};
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
-use crate::http::message::{CurrentIo, ResponseHead};
+use crate::http::message::{CurrentIo, H2Stream, Proto, ResponseHead};
 use crate::http::{DateService, Method, Request, Response, StatusCode, Uri, Version};
-use crate::io::{types, Filter, Io, IoBoxed, IoRef};
+use crate::io::{timer, types, Filter, Io, IoBoxed, IoRef};
 use crate::service::{IntoServiceFactory, Service, ServiceFactory};
-use crate::util::{poll_fn, Bytes, BytesMut, Either, HashMap, Ready};
+use crate::task::LocalWaker;
+use crate::time::{now, Millis};
+use crate::util::{poll_fn, select, Bytes, BytesMut, Either, Extensions, HashMap, Ready};
 
 use super::payload::{Payload, PayloadSender};
 
@@ -36,9 +38,9 @@ where
         service: U,
     ) -> Self {
         H2Service {
+            h2config: cfg.0.h2config.clone(),
             cfg,
             srv: service.into_factory(),
-            h2config: h2::Config::server(),
             _t: PhantomData,
         }
     }
@@ -217,18 +219,185 @@ where
 {
     io.set_disconnect_timeout(config.client_disconnect.into());
     let ioref = io.get_ref();
+    let inflight = Inflight::new();
+    if config.h2_idle_timeout.non_zero() {
+        crate::rt::spawn(idle_timeout(
+            ioref.clone(),
+            inflight.clone(),
+            config.h2_idle_timeout,
+        ));
+    }
+    if config.h2_ka_interval.non_zero() {
+        crate::rt::spawn(keep_alive(
+            ioref.clone(),
+            config.h2_ka_interval,
+            config.h2_ka_timeout,
+        ));
+    }
+    crate::rt::spawn(graceful_shutdown(ioref.clone(), inflight.clone()));
 
     let _ = server::handle_one(
         io,
         h2config,
         ControlService::new(),
-        PublishService::new(ioref, config),
+        PublishService::new(ioref, config, inflight),
     )
     .await;
 
     Ok(())
 }
 
+/// Close connection if it does not have active streams for `timeout` period
+async fn idle_timeout(io: IoRef, inflight: Inflight, timeout: Millis) {
+    let timeout = std::time::Duration::from(timeout);
+    loop {
+        let remaining = match inflight.idle_since() {
+            Some(since) => timeout.checked_sub(now() - since),
+            None => Some(timeout),
+        };
+        match remaining {
+            Some(remaining) if !remaining.is_zero() => {
+                timer::sleep(Millis::from(remaining)).await;
+                if io.is_closed() {
+                    break;
+                }
+            }
+            _ => {
+                log::trace!("http/2 connection is idle, closing");
+                inflight.go_away(&io);
+                io.close();
+                break;
+            }
+        }
+    }
+}
+
+/// Send `PING` frame every `interval`, next ping is not sent until
+/// timeout of the previous one expires.
+///
+/// ntex-h2 stops keep-alive timer once `PING` is acknowledged, connection
+/// is closed if timer expires.
+async fn keep_alive(io: IoRef, interval: Millis, timeout: Millis) {
+    let codec = h2::Codec::default();
+    let interval = std::cmp::max(interval, timeout + Millis(100));
+    let mut counter: u64 = 0;
+    loop {
+        if let Either::Left(_) = select(io.on_disconnect(), timer::sleep(interval)).await {
+            break;
+        }
+        counter += 1;
+        io.start_keepalive_timer(timeout.into());
+        if io
+            .encode(h2::frame::Ping::new(counter.to_be_bytes()).into(), &codec)
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Refuse new streams on server graceful shutdown, close connection
+/// after all in-flight streams complete
+async fn graceful_shutdown(io: IoRef, inflight: Inflight) {
+    if let Either::Right(_) =
+        select(io.on_disconnect(), crate::server::graceful_shutdown()).await
+    {
+        log::trace!("server is shutting down, refusing new streams");
+        inflight.go_away(&io);
+        if let Either::Right(_) = select(io.on_disconnect(), inflight.wait_idle()).await {
+            io.close();
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Number of in-flight streams on http/2 connection
+struct Inflight(Rc<InflightInner>);
+
+struct InflightInner {
+    count: Cell<usize>,
+    streams: RefCell<HashMap<StreamId, Rc<H2Stream>>>,
+    last: Cell<Instant>,
+    last_stream_id: Cell<StreamId>,
+    shutdown: Cell<bool>,
+    waker: LocalWaker,
+}
+
+impl Inflight {
+    fn new() -> Self {
+        Inflight(Rc::new(InflightInner {
+            count: Cell::new(0),
+            streams: RefCell::new(HashMap::default()),
+            last: Cell::new(now()),
+            last_stream_id: Cell::new(StreamId::zero()),
+            shutdown: Cell::new(false),
+            waker: LocalWaker::new(),
+        }))
+    }
+
+    fn start(&self, stream: Rc<H2Stream>) -> InflightGuard {
+        let id = stream.stream.id();
+        if id > self.0.last_stream_id.get() {
+            self.0.last_stream_id.set(id);
+        }
+        self.0.count.set(self.0.count.get() + 1);
+        self.0.streams.borrow_mut().insert(id, stream);
+        InflightGuard(self.clone(), id)
+    }
+
+    /// Notify request handler that stream is reset
+    fn reset(&self, id: StreamId) {
+        if let Some(stream) = self.0.streams.borrow().get(&id) {
+            stream.set_reset();
+        }
+    }
+
+    /// Refuse new streams and send `GOAWAY` frame with last processed stream
+    fn go_away(&self, io: &IoRef) {
+        if !self.0.shutdown.replace(true) {
+            let frame = h2::frame::GoAway::new(h2::frame::Reason::NO_ERROR)
+                .set_last_stream_id(self.0.last_stream_id.get());
+            let _ = io.encode(frame.into(), &h2::Codec::default());
+        }
+    }
+
+    /// Wait until all in-flight streams complete
+    async fn wait_idle(&self) {
+        poll_fn(|cx| {
+            if self.0.count.get() == 0 {
+                Poll::Ready(())
+            } else {
+                self.0.waker.register(cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Time of last stream completion, `None` if streams are active
+    fn idle_since(&self) -> Option<Instant> {
+        if self.0.count.get() == 0 {
+            Some(self.0.last.get())
+        } else {
+            None
+        }
+    }
+}
+
+struct InflightGuard(Inflight, StreamId);
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        let inner = &(self.0).0;
+        inner.streams.borrow_mut().remove(&self.1);
+        inner.count.set(inner.count.get() - 1);
+        inner.last.set(now());
+        if inner.count.get() == 0 {
+            inner.waker.wake();
+        }
+    }
+}
+
 struct ControlService {}
 
 impl ControlService {
@@ -261,7 +430,9 @@ impl Service<h2::ControlMessage<H2Error>> for ControlService {
 struct PublishService<S: Service<Request>, B, X, U> {
     io: IoRef,
     config: Rc<DispatcherConfig<S, X, U>>,
+    conn_data: Rc<RefCell<Extensions>>,
     streams: RefCell<HashMap<StreamId, PayloadSender>>,
+    inflight: Inflight,
     _t: PhantomData<B>,
 }
 
@@ -272,10 +443,12 @@ where
     S::Response: Into<Response<B>>,
     B: MessageBody,
 {
-    fn new(io: IoRef, config: Rc<DispatcherConfig<S, X, U>>) -> Self {
+    fn new(io: IoRef, config: Rc<DispatcherConfig<S, X, U>>, inflight: Inflight) -> Self {
         Self {
             io,
             config,
+            inflight,
+            conn_data: Rc::new(RefCell::new(Extensions::new())),
             streams: RefCell::new(HashMap::default()),
             _t: PhantomData,
         }
@@ -315,6 +488,11 @@ where
                 headers,
                 eof,
             } => {
+                if self.inflight.0.shutdown.get() {
+                    log::trace!("Connection is shutting down, refuse {:?}", msg.id());
+                    msg.stream().reset(h2::frame::Reason::REFUSED_STREAM);
+                    return Either::Right(Ready::Ok(()));
+                }
                 let pl = if !eof {
                     log::debug!("Creating local payload stream for {:?}", msg.id());
                     let (sender, payload) = Payload::create(msg.stream().empty_capacity());
@@ -336,6 +514,9 @@ where
             }
             h2::MessageKind::Eof(item) => {
                 log::debug!("Got payload eof for {:?}: {:?}", msg.id(), item);
+                if let h2::StreamEof::Error(_) = item {
+                    self.inflight.reset(msg.id());
+                }
                 if let Some(mut sender) = self.streams.borrow_mut().remove(&msg.id()) {
                     match item {
                         h2::StreamEof::Data(data) => {
@@ -354,14 +535,19 @@ where
                 if let Some(mut sender) = self.streams.borrow_mut().remove(&msg.id()) {
                     sender.set_error(io::Error::new(io::ErrorKind::Other, err).into());
                 }
+                self.inflight.reset(msg.id());
                 return Either::Right(Ready::Ok(()));
             }
             h2::MessageKind::Empty => return Either::Right(Ready::Ok(())),
         };
 
         let cfg = self.config.clone();
+        let conn_data = self.conn_data.clone();
+        let stream = H2Stream::new(msg.stream().clone());
+        let guard = self.inflight.start(stream.clone());
 
         Either::Left(Box::pin(async move {
+            let _guard = guard;
             log::trace!(
                 "{:?} got request (eof: {}): {:#?}\nheaders: {:#?}",
                 msg.id(),
@@ -390,6 +576,8 @@ where
             head.method = method;
             head.headers = headers;
             head.io = CurrentIo::Ref(io);
+            head.conn_data = Some(conn_data);
+            head.proto = Proto::H2(stream);
 
             let (mut res, mut body) = match cfg.service.call(req).await {
                 Ok(res) => res.into().into_parts(),
@@ -402,6 +590,16 @@ where
             let head = res.head_mut();
             let mut size = body.size();
             prepare_response(&cfg.timer, head, &mut size);
+            if let Some(ref server) = cfg.server_header {
+                if !head.headers.contains_key(header::SERVER) {
+                    head.headers.insert(header::SERVER, server.clone());
+                }
+            }
+            if let Some(ref ctype) = cfg.default_content_type {
+                if !size.is_eof() && !head.headers.contains_key(header::CONTENT_TYPE) {
+                    head.headers.insert(header::CONTENT_TYPE, ctype.clone());
+                }
+            }
 
             log::debug!("Received service response: {:?} payload: {:?}", head, size);
 
@@ -1,4 +1,7 @@
 //! Various http headers
+use std::{convert::TryFrom, fmt};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 
 pub use ntex_http::header::{HeaderName, HeaderValue, InvalidHeaderValue};
 
@@ -7,6 +10,48 @@ pub use ntex_http::header::*;
 pub use ntex_http::header::{AsName, GetAll, Value};
 pub use ntex_http::HeaderMap;
 
+use crate::http::error::HttpError;
+
+/// Typed http header
+///
+/// Typed headers could be used with `insert_header()` and `append_header()`
+/// methods of response and client request builders.
+pub trait Header {
+    /// Returns header name
+    fn name() -> HeaderName;
+
+    /// Convert header to a header value
+    fn try_into_value(self) -> Result<HeaderValue, InvalidHeaderValue>;
+}
+
+/// Type that could be converted to a header name and value pair.
+///
+/// Implemented for `(name, value)` tuples and for typed headers.
+pub trait TryIntoHeaderPair {
+    /// Convert to a header name and value pair
+    fn try_into_pair(self) -> Result<(HeaderName, HeaderValue), HttpError>;
+}
+
+impl<H: Header> TryIntoHeaderPair for H {
+    fn try_into_pair(self) -> Result<(HeaderName, HeaderValue), HttpError> {
+        Ok((H::name(), self.try_into_value()?))
+    }
+}
+
+impl<K, V> TryIntoHeaderPair for (K, V)
+where
+    HeaderName: TryFrom<K>,
+    HeaderValue: TryFrom<V>,
+    <HeaderName as TryFrom<K>>::Error: Into<HttpError>,
+    <HeaderValue as TryFrom<V>>::Error: Into<HttpError>,
+{
+    fn try_into_pair(self) -> Result<(HeaderName, HeaderValue), HttpError> {
+        let name = HeaderName::try_from(self.0).map_err(Into::into)?;
+        let value = HeaderValue::try_from(self.1).map_err(Into::into)?;
+        Ok((name, value))
+    }
+}
+
 /// Represents supported types of content encodings
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ContentEncoding {
@@ -68,6 +113,216 @@ impl<'a> From<&'a str> for ContentEncoding {
     }
 }
 
+/// Characters that must be percent-encoded in `filename*` parameter, RFC 8187
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// Disposition type of `Content-Disposition` header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DispositionType {
+    /// Content is displayed inside the web page
+    Inline,
+    /// Content should be downloaded and saved locally
+    Attachment,
+}
+
+/// `Content-Disposition` header value
+///
+/// Non-ascii file names are sent with `filename*` parameter, ascii-only
+/// `filename` parameter is used as a fallback for old clients.
+///
+/// ```rust
+/// use ntex::http::header::ContentDisposition;
+///
+/// let cd = ContentDisposition::attachment().filename("report.pdf");
+/// assert_eq!(cd.to_string(), "attachment; filename=\"report.pdf\"");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContentDisposition {
+    disposition: DispositionType,
+    filename: Option<String>,
+}
+
+impl ContentDisposition {
+    /// Create `inline` disposition
+    pub fn inline() -> Self {
+        ContentDisposition {
+            disposition: DispositionType::Inline,
+            filename: None,
+        }
+    }
+
+    /// Create `attachment` disposition
+    pub fn attachment() -> Self {
+        ContentDisposition {
+            disposition: DispositionType::Attachment,
+            filename: None,
+        }
+    }
+
+    /// Set file name
+    pub fn filename<T: Into<String>>(mut self, name: T) -> Self {
+        self.filename = Some(name.into());
+        self
+    }
+
+    #[inline]
+    /// Returns disposition type
+    pub fn disposition(&self) -> DispositionType {
+        self.disposition
+    }
+
+    #[inline]
+    /// Returns file name
+    pub fn get_filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+}
+
+impl fmt::Display for ContentDisposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.disposition {
+            DispositionType::Inline => f.write_str("inline")?,
+            DispositionType::Attachment => f.write_str("attachment")?,
+        }
+        if let Some(ref name) = self.filename {
+            f.write_str("; filename=\"")?;
+            for ch in name.chars() {
+                match ch {
+                    '"' | '\\' => write!(f, "\\{}", ch)?,
+                    ' '..='~' => write!(f, "{}", ch)?,
+                    _ => f.write_str("_")?,
+                }
+            }
+            f.write_str("\"")?;
+            if !name.chars().all(|ch| (' '..='~').contains(&ch)) {
+                write!(
+                    f,
+                    "; filename*=UTF-8''{}",
+                    utf8_percent_encode(name, ATTR_CHAR)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<ContentDisposition> for HeaderValue {
+    fn from(cd: ContentDisposition) -> HeaderValue {
+        // non-ascii and control characters are escaped
+        HeaderValue::try_from(cd.to_string()).unwrap()
+    }
+}
+
+impl Header for ContentDisposition {
+    fn name() -> HeaderName {
+        CONTENT_DISPOSITION
+    }
+
+    fn try_into_value(self) -> Result<HeaderValue, InvalidHeaderValue> {
+        Ok(self.into())
+    }
+}
+
+/// `Cache-Control` header directive
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CacheDirective {
+    /// `no-cache`
+    NoCache,
+    /// `no-store`
+    NoStore,
+    /// `no-transform`
+    NoTransform,
+    /// `only-if-cached`
+    OnlyIfCached,
+    /// `max-age=seconds`
+    MaxAge(u32),
+    /// `max-stale=seconds`
+    MaxStale(u32),
+    /// `min-fresh=seconds`
+    MinFresh(u32),
+    /// `s-maxage=seconds`
+    SMaxAge(u32),
+    /// `must-revalidate`
+    MustRevalidate,
+    /// `proxy-revalidate`
+    ProxyRevalidate,
+    /// `public`
+    Public,
+    /// `private`
+    Private,
+    /// `immutable`
+    Immutable,
+    /// Extension directive with optional argument
+    Extension(String, Option<String>),
+}
+
+impl fmt::Display for CacheDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheDirective::NoCache => f.write_str("no-cache"),
+            CacheDirective::NoStore => f.write_str("no-store"),
+            CacheDirective::NoTransform => f.write_str("no-transform"),
+            CacheDirective::OnlyIfCached => f.write_str("only-if-cached"),
+            CacheDirective::MaxAge(secs) => write!(f, "max-age={}", secs),
+            CacheDirective::MaxStale(secs) => write!(f, "max-stale={}", secs),
+            CacheDirective::MinFresh(secs) => write!(f, "min-fresh={}", secs),
+            CacheDirective::SMaxAge(secs) => write!(f, "s-maxage={}", secs),
+            CacheDirective::MustRevalidate => f.write_str("must-revalidate"),
+            CacheDirective::ProxyRevalidate => f.write_str("proxy-revalidate"),
+            CacheDirective::Public => f.write_str("public"),
+            CacheDirective::Private => f.write_str("private"),
+            CacheDirective::Immutable => f.write_str("immutable"),
+            CacheDirective::Extension(name, None) => f.write_str(name),
+            CacheDirective::Extension(name, Some(arg)) => write!(f, "{}={}", name, arg),
+        }
+    }
+}
+
+/// `Cache-Control` header
+///
+/// ```rust
+/// use ntex::http::header::{CacheControl, CacheDirective};
+///
+/// let cc = CacheControl(vec![CacheDirective::Public, CacheDirective::MaxAge(3600)]);
+/// assert_eq!(cc.to_string(), "public, max-age=3600");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CacheControl(pub Vec<CacheDirective>);
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, directive) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", directive)?;
+        }
+        Ok(())
+    }
+}
+
+impl Header for CacheControl {
+    fn name() -> HeaderName {
+        CACHE_CONTROL
+    }
+
+    fn try_into_value(self) -> Result<HeaderValue, InvalidHeaderValue> {
+        HeaderValue::try_from(self.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +334,46 @@ mod tests {
         assert!(!ContentEncoding::Auto.is_compressed());
         assert_eq!(format!("{:?}", ContentEncoding::Identity), "Identity");
     }
+
+    #[test]
+    fn disposition() {
+        let cd = ContentDisposition::inline();
+        assert_eq!(cd.disposition(), DispositionType::Inline);
+        assert_eq!(cd.to_string(), "inline");
+
+        let cd = ContentDisposition::attachment().filename("my \"file\".txt");
+        assert_eq!(cd.get_filename(), Some("my \"file\".txt"));
+        assert_eq!(
+            HeaderValue::from(cd),
+            "attachment; filename=\"my \\\"file\\\".txt\""
+        );
+
+        let cd = ContentDisposition::attachment().filename("отчёт 1.txt");
+        assert_eq!(
+            cd.to_string(),
+            "attachment; filename=\"_____ 1.txt\"; \
+             filename*=UTF-8''%D0%BE%D1%82%D1%87%D1%91%D1%82%201.txt"
+        );
+    }
+
+    #[test]
+    fn cache_control() {
+        let cc = CacheControl(vec![
+            CacheDirective::NoCache,
+            CacheDirective::Private,
+            CacheDirective::SMaxAge(10),
+            CacheDirective::Extension("stale-if-error".to_string(), Some("60".into())),
+        ]);
+        let (name, value) = cc.try_into_pair().unwrap();
+        assert_eq!(name, CACHE_CONTROL);
+        assert_eq!(value, "no-cache, private, s-maxage=10, stale-if-error=60");
+
+        let cc = CacheControl(vec![CacheDirective::Extension("a\nb".into(), None)]);
+        assert!(cc.try_into_pair().is_err());
+
+        let (name, value) = ("x-test", "1").try_into_pair().unwrap();
+        assert_eq!(name, "x-test");
+        assert_eq!(value, "1");
+        assert!(("x test", "1").try_into_pair().is_err());
+    }
 }
@@ -1,11 +1,20 @@
-use std::{cell::Ref, cell::RefCell, cell::RefMut, net, rc::Rc};
+use std::task::{Context, Poll};
+use std::{
+    cell::Cell, cell::Ref, cell::RefCell, cell::RefMut, fmt, future::Future, io, net,
+    pin::Pin, rc::Rc,
+};
 
 use bitflags::bitflags;
 
+use ntex_h2::{self as h2};
+
+use crate::channel::condition::{Condition, Waiter};
+use crate::http::body::BodySize;
+use crate::http::config::DateService;
 use crate::http::header::HeaderMap;
-use crate::http::{h1::Codec, Method, StatusCode, Uri, Version};
-use crate::io::{types, IoBoxed, IoRef};
-use crate::util::Extensions;
+use crate::http::{h1, h1::Codec, Method, Response, StatusCode, Uri, Version};
+use crate::io::{types, IoBoxed, IoRef, OnDisconnect};
+use crate::util::{BytesMut, Extensions};
 
 /// Represents various types of connection
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -62,12 +71,19 @@ pub struct RequestHead {
     pub extensions: RefCell<Extensions>,
     pub(crate) io: CurrentIo,
     pub(crate) flags: Flags,
+    // set by test requests only, boxed to keep `RequestHeadType` small
+    pub(crate) peer_addr: Option<Box<net::SocketAddr>>,
+    pub(crate) conn_data: Option<Rc<RefCell<Extensions>>>,
+    pub(crate) proto: Proto,
 }
 
 impl Default for RequestHead {
     fn default() -> RequestHead {
         RequestHead {
             io: CurrentIo::None,
+            peer_addr: None,
+            conn_data: None,
+            proto: Proto::None,
             uri: Uri::default(),
             method: Method::default(),
             version: Version::HTTP_11,
@@ -81,6 +97,9 @@ impl Default for RequestHead {
 impl Head for RequestHead {
     fn clear(&mut self) {
         self.io = CurrentIo::None;
+        self.peer_addr = None;
+        self.conn_data = None;
+        self.proto = Proto::None;
         self.flags = Flags::empty();
         self.headers.clear();
         self.extensions.get_mut().clear();
@@ -107,6 +126,26 @@ impl RequestHead {
         self.extensions.borrow_mut()
     }
 
+    /// Get a reference to a per-connection data of type `T`
+    ///
+    /// Per-connection data is shared between all requests dispatched
+    /// on the same connection (keep-alive requests for http/1 and
+    /// streams for http/2).
+    #[inline]
+    pub fn conn_data<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        self.conn_data
+            .as_ref()
+            .and_then(|data| Ref::filter_map(data.borrow(), |ext| ext.get::<T>()).ok())
+    }
+
+    /// Mutable reference to a per-connection data container
+    ///
+    /// Returns `None` if request is not bound to a connection.
+    #[inline]
+    pub fn conn_data_mut(&self) -> Option<RefMut<'_, Extensions>> {
+        self.conn_data.as_ref().map(|data| data.borrow_mut())
+    }
+
     /// Read the message headers.
     pub fn headers(&self) -> &HeaderMap {
         &self.headers
@@ -186,11 +225,52 @@ impl RequestHead {
     /// ntex http server, then peer address would be address of this proxy.
     #[inline]
     pub fn peer_addr(&self) -> Option<net::SocketAddr> {
-        self.io.as_ref().and_then(|io| {
-            io.query::<types::PeerAddr>()
-                .get()
-                .map(types::PeerAddr::into_inner)
-        })
+        self.io
+            .as_ref()
+            .and_then(|io| {
+                io.query::<types::PeerAddr>()
+                    .get()
+                    .map(types::PeerAddr::into_inner)
+            })
+            .or_else(|| self.peer_addr.as_deref().copied())
+    }
+
+    /// Get future that resolves when client disconnects
+    ///
+    /// Future resolves when underlying connection get closed or, for http/2,
+    /// when request's stream get reset. If request is not bound to
+    /// a connection, future never resolves.
+    pub fn on_disconnect(&self) -> ClientDisconnect {
+        ClientDisconnect {
+            io: self.io.as_ref().map(|io| io.on_disconnect()),
+            stream: match self.proto {
+                Proto::H2(ref stream) => Some((stream.clone(), stream.on_reset.wait())),
+                _ => None,
+            },
+        }
+    }
+
+    /// Send `103 Early Hints` informational response
+    ///
+    /// Early hints must be sent before final response is returned from
+    /// the handler, method could be called multiple times. Hints are
+    /// supported for HTTP/1.1 and HTTP/2 requests, error is returned for
+    /// HTTP/1.0 requests and for requests that are not bound to a connection.
+    pub fn send_early_hints(&self, headers: &HeaderMap) -> io::Result<()> {
+        match (self.io.as_ref(), &self.proto) {
+            (Some(io), Proto::H1(timer)) if self.version == Version::HTTP_11 => {
+                let mut res = Response::new(early_hints()).drop_body();
+                res.head_mut().reason = Some("Early Hints");
+                res.headers_mut().clone_from(headers);
+                let codec = Codec::new(timer.clone(), false);
+                io.encode(h1::Message::Item((res, BodySize::None)), &codec)
+            }
+            (Some(io), Proto::H2(stream)) => stream.send_early_hints(io, headers),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Early hints are not supported for the request",
+            )),
+        }
     }
 
     /// Take io and codec for current request
@@ -204,6 +284,121 @@ impl RequestHead {
     }
 }
 
+/// Future that resolves when client disconnects
+///
+/// Could be used by long running handlers to stop processing
+/// if client is gone.
+pub struct ClientDisconnect {
+    io: Option<OnDisconnect>,
+    stream: Option<(Rc<H2Stream>, Waiter)>,
+}
+
+impl Future for ClientDisconnect {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(ref mut io) = this.io {
+            if Pin::new(io).poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+        if let Some((ref stream, ref waiter)) = this.stream {
+            if stream.is_reset() || waiter.poll_ready(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+fn early_hints() -> StatusCode {
+    StatusCode::from_u16(103).unwrap()
+}
+
+/// Protocol specific state of the request
+pub(crate) enum Proto {
+    /// Http/1 connection
+    H1(DateService),
+    /// Http/2 stream
+    H2(Rc<H2Stream>),
+    None,
+}
+
+impl fmt::Debug for Proto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Proto::H1(_) => write!(f, "Proto::H1"),
+            Proto::H2(stream) => f.debug_tuple("Proto::H2").field(stream).finish(),
+            Proto::None => write!(f, "Proto::None"),
+        }
+    }
+}
+
+/// Http/2 stream of the request
+pub(crate) struct H2Stream {
+    pub(crate) stream: h2::StreamRef,
+    reset: Cell<bool>,
+    on_reset: Condition,
+}
+
+impl H2Stream {
+    pub(crate) fn new(stream: h2::StreamRef) -> Rc<Self> {
+        Rc::new(H2Stream {
+            stream,
+            reset: Cell::new(false),
+            on_reset: Condition::new(),
+        })
+    }
+
+    /// Stream is reset by peer or connection is gone
+    pub(crate) fn set_reset(&self) {
+        self.reset.set(true);
+        self.on_reset.notify();
+    }
+
+    fn is_reset(&self) -> bool {
+        self.reset.get()
+    }
+
+    /// Send informational HEADERS frame
+    ///
+    /// Frame is encoded with separate hpack encoder, its dynamic table
+    /// size is zero, so peer's dynamic table stays in sync with the
+    /// connection's encoder.
+    fn send_early_hints(&self, io: &IoRef, headers: &HeaderMap) -> io::Result<()> {
+        if self.is_reset() || self.stream.is_failed() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "Stream is closed",
+            ));
+        }
+        let frame = h2::frame::Headers::new(
+            self.stream.id(),
+            h2::frame::PseudoHeaders::response(early_hints()),
+            headers.clone(),
+            false,
+        );
+        let mut buf = BytesMut::new();
+        frame.encode(
+            &mut h2::hpack::Encoder::new(0, 0),
+            &mut buf,
+            h2::frame::DEFAULT_MAX_FRAME_SIZE as usize,
+        );
+        io.write(&buf)
+    }
+}
+
+impl fmt::Debug for H2Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("H2Stream")
+            .field("stream", &self.stream)
+            .field("reset", &self.reset.get())
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 pub enum RequestHeadType {
     Owned(RequestHead),
@@ -27,11 +27,13 @@ pub use self::client::Client;
 pub use self::config::{DateService, KeepAlive, ServiceConfig};
 pub use self::error::ResponseError;
 pub use self::httpmessage::HttpMessage;
-pub use self::message::{ConnectionType, RequestHead, RequestHeadType, ResponseHead};
+pub use self::message::{
+    ClientDisconnect, ConnectionType, RequestHead, RequestHeadType, ResponseHead,
+};
 pub use self::payload::{Payload, PayloadStream};
 pub use self::request::Request;
 pub use self::response::{Response, ResponseBuilder};
-pub use self::service::HttpService;
+pub use self::service::{serve_connection, HttpService};
 pub use crate::io::types::HttpProtocol;
 
 // re-exports
@@ -1,6 +1,8 @@
+use std::time::{Duration, Instant};
 use std::{fmt, mem, pin::Pin, task::Context, task::Poll};
 
 use super::{error::PayloadError, h1, h2};
+use crate::time::{Millis, Seconds, Sleep};
 use crate::util::{poll_fn, Bytes, Stream};
 
 /// Type represent boxed payload
@@ -63,6 +65,32 @@ impl Payload {
         Payload::Stream(Box::pin(stream))
     }
 
+    /// Enforce minimum data rate for the payload
+    ///
+    /// Wrapped payload fails with `PayloadError::Timeout` if less than
+    /// `bytes_per_sec * window` bytes get received during `window` time.
+    /// Rate is measured only while payload is being read and data is not
+    /// available yet. Idle time before reading starts and time spent
+    /// processing received chunks are not counted.
+    ///
+    /// To disable rate check set `window` to 0.
+    pub fn min_rate(self, bytes_per_sec: usize, window: Seconds) -> Self {
+        if window.is_zero() {
+            self
+        } else {
+            Payload::from_stream(MinRate {
+                payload: self,
+                window: Millis::from(window).into(),
+                min_bytes: bytes_per_sec * window.0 as usize,
+                received: 0,
+                waited: Duration::ZERO,
+                pending: None,
+                timer: None,
+                failed: false,
+            })
+        }
+    }
+
     #[inline]
     /// Attempt to pull out the next value of this payload.
     pub async fn recv(&mut self) -> Option<Result<Bytes, PayloadError>> {
@@ -95,9 +123,84 @@ impl Stream for Payload {
     }
 }
 
+/// Payload wrapper, checks data rate of the inner payload
+///
+/// Only time while payload is waiting for data is measured.
+struct MinRate {
+    payload: Payload,
+    window: Duration,
+    min_bytes: usize,
+    received: usize,
+    waited: Duration,
+    pending: Option<Instant>,
+    timer: Option<Sleep>,
+    failed: bool,
+}
+
+impl Stream for MinRate {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.failed {
+            return Poll::Ready(None);
+        }
+
+        match this.payload.poll_recv(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(start) = this.pending.take() {
+                    this.waited += start.elapsed();
+                    this.timer = None;
+                }
+                this.received += chunk.len();
+                if this.waited >= this.window {
+                    if this.received < this.min_bytes {
+                        return Poll::Ready(Some(Err(this.fail())));
+                    }
+                    this.waited = Duration::ZERO;
+                    this.received = 0;
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Pending => {
+                // waiting for client, measure remaining part of the window
+                if this.pending.is_none() {
+                    this.pending = Some(Instant::now());
+                }
+                let remaining = this.window.saturating_sub(this.waited);
+                let timer = this
+                    .timer
+                    .get_or_insert_with(|| Sleep::new(remaining.into()));
+                if timer.poll_elapsed(cx).is_ready() {
+                    if this.received < this.min_bytes {
+                        return Poll::Ready(Some(Err(this.fail())));
+                    }
+                    this.waited = Duration::ZERO;
+                    this.received = 0;
+                    this.pending = Some(Instant::now());
+                    timer.reset(this.window);
+                    let _ = timer.poll_elapsed(cx);
+                }
+                Poll::Pending
+            }
+            res => res,
+        }
+    }
+}
+
+impl MinRate {
+    fn fail(&mut self) -> PayloadError {
+        log::trace!("Payload data rate is too low, {}", self.received);
+        self.failed = true;
+        self.timer = None;
+        PayloadError::Timeout
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::time::sleep;
 
     #[test]
     fn payload_debug() {
@@ -110,4 +213,74 @@ mod tests {
         )
         .contains("Payload::Stream"));
     }
+
+    #[crate::rt_test]
+    async fn test_min_rate() {
+        // slow client
+        let (mut sender, pl) = h1::Payload::create(false);
+        let mut pl = Payload::from(pl).min_rate(10, Seconds(1));
+
+        sender.feed_data(Bytes::from_static(b"data"));
+        assert_eq!(pl.recv().await.unwrap().unwrap(), &b"data"[..]);
+        crate::rt::spawn(async move {
+            sleep(Millis(500)).await;
+            sender.feed_data(Bytes::from_static(b"data"));
+            sleep(Millis(2000)).await;
+            drop(sender);
+        });
+        assert_eq!(pl.recv().await.unwrap().unwrap(), &b"data"[..]);
+        assert!(matches!(
+            pl.recv().await.unwrap(),
+            Err(PayloadError::Timeout)
+        ));
+        assert!(pl.recv().await.is_none());
+
+        // client is fast enough
+        let (mut sender, pl) = h1::Payload::create(false);
+        let mut pl = Payload::from(pl).min_rate(10, Seconds(1));
+        crate::rt::spawn(async move {
+            for _ in 0..6 {
+                sleep(Millis(300)).await;
+                sender.feed_data(Bytes::from_static(b"data"));
+            }
+            sender.feed_eof();
+        });
+        let mut size = 0;
+        while let Some(item) = pl.recv().await {
+            size += item.unwrap().len();
+        }
+        assert_eq!(size, 24);
+
+        // idle time before reading is not counted
+        let (mut sender, pl) = h1::Payload::create(false);
+        let mut pl = Payload::from(pl).min_rate(10, Seconds(1));
+        sleep(Millis(1500)).await;
+        crate::rt::spawn(async move {
+            sleep(Millis(100)).await;
+            sender.feed_data(Bytes::from_static(b"0123456789"));
+            sender.feed_eof();
+        });
+        assert_eq!(pl.recv().await.unwrap().unwrap().len(), 10);
+        assert!(pl.recv().await.is_none());
+
+        // time spent processing chunks is not counted
+        let (mut sender, pl) = h1::Payload::create(false);
+        let mut pl = Payload::from(pl).min_rate(10, Seconds(1));
+        crate::rt::spawn(async move {
+            sleep(Millis(100)).await;
+            sender.feed_data(Bytes::from_static(b"data"));
+            sleep(Millis(1150)).await;
+            sender.feed_data(Bytes::from_static(b"data"));
+            sleep(Millis(150)).await;
+            sender.feed_data(Bytes::from_static(b"0123456789"));
+            sender.feed_eof();
+        });
+        assert_eq!(pl.recv().await.unwrap().unwrap(), &b"data"[..]);
+        sleep(Millis(1200)).await;
+        let mut size = 0;
+        while let Some(item) = pl.recv().await {
+            size += item.unwrap().len();
+        }
+        assert_eq!(size, 14);
+    }
 }
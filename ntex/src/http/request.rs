@@ -1,10 +1,10 @@
-use std::{cell::Ref, cell::RefMut, fmt, mem, net};
+use std::{cell::Ref, cell::RefMut, fmt, io, mem, net};
 
 use crate::http::header::{self, HeaderMap};
 use crate::http::httpmessage::HttpMessage;
-use crate::http::message::{Message, RequestHead};
+use crate::http::message::{ClientDisconnect, Message, RequestHead};
 use crate::http::{payload::Payload, Method, Uri, Version};
-use crate::io::{types, IoRef};
+use crate::io::IoRef;
 use crate::util::Extensions;
 
 /// Request
@@ -137,11 +137,25 @@ impl Request {
     /// ntex http server, then peer address would be address of this proxy.
     #[inline]
     pub fn peer_addr(&self) -> Option<net::SocketAddr> {
-        self.head().io.as_ref().and_then(|io| {
-            io.query::<types::PeerAddr>()
-                .get()
-                .map(types::PeerAddr::into_inner)
-        })
+        self.head().peer_addr()
+    }
+
+    /// Get future that resolves when client disconnects
+    ///
+    /// Future resolves when underlying connection get closed or, for http/2,
+    /// when request's stream get reset.
+    #[inline]
+    pub fn on_disconnect(&self) -> ClientDisconnect {
+        self.head().on_disconnect()
+    }
+
+    /// Send `103 Early Hints` informational response
+    ///
+    /// Hints must be sent before final response. HTTP/1.1 and HTTP/2
+    /// requests are supported.
+    #[inline]
+    pub fn send_early_hints(&self, headers: &HeaderMap) -> io::Result<()> {
+        self.head().send_early_hints(headers)
     }
 
     /// Get request's payload
@@ -171,6 +185,21 @@ impl Request {
         self.head.extensions_mut()
     }
 
+    /// Get a reference to a per-connection data of type `T`
+    ///
+    /// Per-connection data is shared between all requests dispatched
+    /// on the same connection.
+    #[inline]
+    pub fn conn_data<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        self.head.conn_data()
+    }
+
+    /// Mutable reference to a per-connection data container
+    #[inline]
+    pub fn conn_data_mut(&self) -> Option<RefMut<'_, Extensions>> {
+        self.head.conn_data_mut()
+    }
+
     #[allow(dead_code)]
     /// Split request into request head and payload
     pub(crate) fn into_parts(self) -> (Message<RequestHead>, Payload) {
@@ -8,7 +8,7 @@ use coo_kie::{Cookie, CookieJar};
 
 use crate::http::body::{Body, BodyStream, MessageBody, ResponseBody};
 use crate::http::error::{HttpError, ResponseError};
-use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue, TryIntoHeaderPair};
 use crate::http::message::{ConnectionType, Message, ResponseHead};
 use crate::http::StatusCode;
 use crate::util::{Bytes, BytesMut, Extensions, Stream};
@@ -56,6 +56,17 @@ impl Response<Body> {
 
 impl<B> Response<B> {
     /// Constructs a response with body
+    ///
+    /// Body is stored as is, use `Response::new(status).set_body(body.into())`
+    /// if `Response<Body>` is required.
+    ///
+    /// ```rust
+    /// use ntex::http::{Response, StatusCode};
+    ///
+    /// let res = Response::with_body(StatusCode::OK, "hello");
+    /// assert_eq!(res.status(), StatusCode::OK);
+    /// assert_eq!(*res.body().as_ref().unwrap(), "hello");
+    /// ```
     #[inline]
     pub fn with_body(status: StatusCode, body: B) -> Response<B> {
         Response {
@@ -159,6 +170,13 @@ impl<B> Response<B> {
         self.head.keep_alive()
     }
 
+    /// Force close connection after response is sent,
+    /// even if connection is marked as keep-alive
+    #[inline]
+    pub fn force_close(&mut self) {
+        self.head.set_connection_type(ConnectionType::Close);
+    }
+
     /// Responses extensions
     #[inline]
     pub fn extensions(&self) -> Ref<'_, Extensions> {
@@ -359,6 +377,55 @@ impl ResponseBuilder {
         self
     }
 
+    /// Insert a header, replaces existing header.
+    ///
+    /// Accepts `(name, value)` tuple or typed header.
+    ///
+    /// ```rust
+    /// use ntex::http::header::{self, CacheControl, CacheDirective};
+    /// use ntex::http::{Request, Response};
+    ///
+    /// fn index(req: Request) -> Response {
+    ///     Response::Ok()
+    ///         .insert_header((header::CONTENT_TYPE, "application/json"))
+    ///         .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+    ///         .finish()
+    /// }
+    /// ```
+    pub fn insert_header<H: TryIntoHeaderPair>(&mut self, header: H) -> &mut Self {
+        if let Some(parts) = parts(&mut self.head, &self.err) {
+            match header.try_into_pair() {
+                Ok((key, value)) => parts.headers.insert(key, value),
+                Err(e) => self.err = Some(log_error(e)),
+            }
+        }
+        self
+    }
+
+    /// Append a header, existing headers with the same name are preserved.
+    ///
+    /// Accepts `(name, value)` tuple or typed header.
+    ///
+    /// ```rust
+    /// use ntex::http::{header, Request, Response};
+    ///
+    /// fn index(req: Request) -> Response {
+    ///     Response::Ok()
+    ///         .append_header((header::VARY, "origin"))
+    ///         .append_header((header::VARY, "accept-encoding"))
+    ///         .finish()
+    /// }
+    /// ```
+    pub fn append_header<H: TryIntoHeaderPair>(&mut self, header: H) -> &mut Self {
+        if let Some(parts) = parts(&mut self.head, &self.err) {
+            match header.try_into_pair() {
+                Ok((key, value)) => parts.headers.append(key, value),
+                Err(e) => self.err = Some(log_error(e)),
+            }
+        }
+        self
+    }
+
     /// Set the custom reason for the response.
     #[inline]
     pub fn reason(&mut self, reason: &'static str) -> &mut Self {
@@ -432,6 +499,33 @@ impl ResponseBuilder {
         self.header(header::CONTENT_LENGTH, len)
     }
 
+    /// Disable caching of the response
+    ///
+    /// Sets `Cache-Control: no-store, no-cache, must-revalidate` header.
+    #[inline]
+    pub fn no_cache(&mut self) -> &mut Self {
+        self.set_header(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("no-store, no-cache, must-revalidate"),
+        )
+    }
+
+    /// Allow any cache to store the response for `secs` seconds
+    ///
+    /// Sets `Cache-Control: public, max-age=N` header.
+    #[inline]
+    pub fn cache_max_age(&mut self, secs: u32) -> &mut Self {
+        self.set_header(header::CACHE_CONTROL, format!("public, max-age={}", secs))
+    }
+
+    /// Allow only private caches to store the response for `secs` seconds
+    ///
+    /// Sets `Cache-Control: private, max-age=N` header.
+    #[inline]
+    pub fn cache_private_max_age(&mut self, secs: u32) -> &mut Self {
+        self.set_header(header::CACHE_CONTROL, format!("private, max-age={}", secs))
+    }
+
     #[cfg(feature = "cookie")]
     /// Set a cookie
     ///
@@ -801,7 +895,7 @@ impl From<BytesMut> for Response {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::http::body::Body;
+    use crate::http::body::{Body, BodySize};
     use crate::http::header::{HeaderValue, CONTENT_TYPE, COOKIE};
 
     #[test]
@@ -881,12 +975,51 @@ mod tests {
         assert_eq!((v.name(), v.value()), ("cookie3", "val300"));
     }
 
+    #[test]
+    fn test_with_body() {
+        let resp = Response::with_body(StatusCode::CREATED, "hello");
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(resp.body().size(), BodySize::Sized(5));
+        assert!(resp.headers().is_empty());
+
+        let resp: Response<Body> =
+            Response::with_body(StatusCode::OK, Body::from("hello")).into_body();
+        assert_eq!(resp.body().get_ref(), b"hello");
+    }
+
     #[test]
     fn test_basic_builder() {
         let resp = Response::Ok().header("X-TEST", "value").finish();
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn test_insert_append_header() {
+        use crate::http::header::{CacheControl, CacheDirective};
+
+        let resp = Response::Ok()
+            .append_header((header::VARY, "origin"))
+            .append_header((header::VARY, "accept-encoding"))
+            .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+            .insert_header(CacheControl(vec![
+                CacheDirective::Public,
+                CacheDirective::MaxAge(60),
+            ]))
+            .finish();
+        assert_eq!(
+            resp.headers().get_all(header::VARY).collect::<Vec<_>>(),
+            vec!["origin", "accept-encoding"]
+        );
+        assert_eq!(resp.headers().get_all(header::CACHE_CONTROL).len(), 1);
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=60"
+        );
+
+        let resp = Response::Ok().insert_header(("x test", "value")).finish();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     #[test]
     fn test_upgrade() {
         let resp = Response::build(StatusCode::OK)
@@ -902,7 +1035,12 @@ mod tests {
     #[test]
     fn test_force_close() {
         let resp = Response::build(StatusCode::OK).force_close().finish();
-        assert!(!resp.keep_alive())
+        assert!(!resp.keep_alive());
+
+        let mut resp = Response::build(StatusCode::OK).keep_alive().finish();
+        assert!(resp.keep_alive());
+        resp.force_close();
+        assert!(!resp.keep_alive());
     }
 
     #[test]
@@ -913,6 +1051,30 @@ mod tests {
         assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "text/plain")
     }
 
+    #[test]
+    fn test_cache_control() {
+        let resp = Response::build(StatusCode::OK).no_cache().finish();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store, no-cache, must-revalidate"
+        );
+
+        let resp = Response::build(StatusCode::OK)
+            .no_cache()
+            .cache_max_age(3600)
+            .finish();
+        let values: Vec<_> = resp.headers().get_all(header::CACHE_CONTROL).collect();
+        assert_eq!(values, vec!["public, max-age=3600"]);
+
+        let resp = Response::build(StatusCode::OK)
+            .cache_private_max_age(60)
+            .finish();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, max-age=60"
+        );
+    }
+
     #[test]
     fn test_json() {
         let resp = Response::build(StatusCode::OK).json(&vec!["v1", "v2", "v3"]);
@@ -4,6 +4,7 @@ use std::{cell, error, fmt, future, future::Future, marker, pin::Pin, rc::Rc};
 use crate::io::{types, Filter, Io};
 use crate::service::{IntoServiceFactory, Service, ServiceFactory};
 use crate::time::{Millis, Seconds};
+use crate::util::poll_fn;
 
 use super::body::MessageBody;
 use super::builder::HttpServiceBuilder;
@@ -290,6 +291,64 @@ where
     }
 }
 
+/// Serve single connection with http service.
+///
+/// This is an entry point for embedding http stack into existing
+/// application, `System` is not required. Service is created with
+/// `ServiceFactory::new_service()` of `HttpService` and could be used
+/// for any number of connections.
+///
+/// Http service internals are not `Send`, all futures are spawned on
+/// the current thread. For tokio runtime it means connection must be served
+/// within `tokio::task::LocalSet`. Each connection should be served in
+/// separate local task, so connections are handled concurrently.
+///
+/// ```rust,no_run
+/// use std::rc::Rc;
+///
+/// use ntex::http::{serve_connection, HttpService};
+/// use ntex::service::ServiceFactory;
+/// use ntex::web::{self, App, HttpResponse};
+///
+/// async fn serve(listener: tokio::net::TcpListener) -> std::io::Result<()> {
+///     let srv = HttpService::new(
+///         App::new().route("/", web::get().to(|| async { HttpResponse::Ok() })),
+///     )
+///     .new_service(())
+///     .await
+///     .unwrap();
+///     let srv = Rc::new(srv);
+///
+///     loop {
+///         let (stream, _) = listener.accept().await?;
+///         let io = ntex::rt::from_tcp_stream(stream.into_std()?)?;
+///         let srv = srv.clone();
+///         tokio::task::spawn_local(async move {
+///             let _ = serve_connection(io, &*srv).await;
+///         });
+///     }
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     let rt = tokio::runtime::Builder::new_current_thread()
+///         .enable_all()
+///         .build()?;
+///     let local = tokio::task::LocalSet::new();
+///     local.block_on(&rt, async {
+///         let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+///         serve(listener).await
+///     })
+/// }
+/// ```
+pub async fn serve_connection<F, S>(io: Io<F>, service: &S) -> Result<(), S::Error>
+where
+    F: Filter,
+    S: Service<Io<F>, Response = ()>,
+{
+    poll_fn(|cx| service.poll_ready(cx)).await?;
+    service.call(io).await
+}
+
 /// `Service` implementation for http transport
 pub struct HttpServiceHandler<F, S, B, X, U> {
     config: Rc<DispatcherConfig<S, X, U>>,
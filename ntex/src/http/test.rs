@@ -1,4 +1,5 @@
 //! Test helpers to use during testing.
+use std::io::{self, Read, Write};
 use std::{convert::TryFrom, net, str::FromStr, sync::mpsc, thread};
 
 #[cfg(feature = "cookie")]
@@ -6,7 +7,7 @@ use coo_kie::{Cookie, CookieJar};
 
 use crate::ws::{error::WsClientError, WsClient, WsConnection};
 use crate::{io::Filter, io::Io, rt::System, server::Server, service::ServiceFactory};
-use crate::{time::Millis, time::Seconds, util::Bytes};
+use crate::{time::Millis, time::Seconds, util::Bytes, util::BytesMut};
 
 use super::client::{Client, ClientRequest, ClientResponse, Connector};
 use super::error::{HttpError, PayloadError};
@@ -46,6 +47,7 @@ struct Inner {
     #[cfg(feature = "cookie")]
     cookies: CookieJar,
     payload: Option<Payload>,
+    peer_addr: Option<net::SocketAddr>,
 }
 
 impl Default for TestRequest {
@@ -58,6 +60,7 @@ impl Default for TestRequest {
             #[cfg(feature = "cookie")]
             cookies: CookieJar::new(),
             payload: None,
+            peer_addr: None,
         }))
     }
 }
@@ -119,6 +122,12 @@ impl TestRequest {
         self
     }
 
+    /// Set peer addr
+    pub fn peer_addr(&mut self, addr: net::SocketAddr) -> &mut Self {
+        parts(&mut self.0).peer_addr = Some(addr);
+        self
+    }
+
     /// Set request payload
     pub fn set_payload<B: Into<Bytes>>(&mut self, data: B) -> &mut Self {
         let mut payload = crate::http::h1::Payload::empty();
@@ -147,6 +156,7 @@ impl TestRequest {
         head.method = inner.method;
         head.version = inner.version;
         head.headers = inner.headers;
+        head.peer_addr = inner.peer_addr.map(Box::new);
 
         if let Some(conn) = head.headers.get(header::CONNECTION) {
             if let Ok(s) = conn.to_str() {
@@ -240,39 +250,91 @@ where
 
     let (system, addr) = rx.recv().unwrap();
 
-    let client = {
-        let connector = {
-            #[cfg(feature = "openssl")]
-            {
-                use tls_openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
-
-                let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-                builder.set_verify(SslVerifyMode::NONE);
-                let _ = builder
-                    .set_alpn_protos(b"\x02h2\x08http/1.1")
-                    .map_err(|e| log::error!("Cannot set alpn protocol: {:?}", e));
-                Connector::default()
-                    .timeout(Millis(30_000))
-                    .openssl(builder.build())
-                    .finish()
-            }
-            #[cfg(not(feature = "openssl"))]
-            {
-                Connector::default().timeout(Millis(30_000)).finish()
-            }
-        };
-
-        Client::build()
-            .timeout(Seconds(30))
-            .connector(connector)
-            .finish()
-    };
-
     TestServer {
         addr,
-        client,
+        client: client(),
         system,
+        keep_alive: true,
+    }
+}
+
+/// Build client for test server
+pub(crate) fn client() -> Client {
+    let connector = {
+        #[cfg(feature = "openssl")]
+        {
+            use tls_openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+
+            let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+            builder.set_verify(SslVerifyMode::NONE);
+            let _ = builder
+                .set_alpn_protos(b"\x02h2\x08http/1.1")
+                .map_err(|e| log::error!("Cannot set alpn protocol: {:?}", e));
+            Connector::default()
+                .keep_alive(Seconds(30))
+                .timeout(Millis(30_000))
+                .disconnect_timeout(Millis(5_000))
+                .openssl(builder.build())
+                .finish()
+        }
+        #[cfg(not(feature = "openssl"))]
+        {
+            Connector::default()
+                .keep_alive(Seconds(30))
+                .timeout(Millis(30_000))
+                .disconnect_timeout(Millis(5_000))
+                .finish()
+        }
+    };
+
+    Client::build()
+        .connector(connector)
+        .timeout(Seconds(30))
+        .finish()
+}
+
+/// Read data from raw connection.
+///
+/// Reads until the connection is closed by the peer or no data
+/// is received within `timeout`. Data read so far is returned.
+pub fn read_raw(stream: &mut net::TcpStream, timeout: Millis) -> io::Result<Bytes> {
+    stream.set_read_timeout(Some(timeout.into()))?;
+
+    let mut buf = BytesMut::new();
+    let mut chunk = [0; 4096];
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(e)
+                if e.kind() == io::ErrorKind::WouldBlock
+                    || e.kind() == io::ErrorKind::TimedOut =>
+            {
+                break
+            }
+            Err(e) if e.kind() == io::ErrorKind::ConnectionReset => break,
+            Err(e) => return Err(e),
+        }
     }
+    Ok(buf.freeze())
+}
+
+/// Send hand-crafted data over new raw connection and read response
+pub(crate) fn send_raw(
+    addr: net::SocketAddr,
+    data: &[u8],
+    timeout: Millis,
+) -> io::Result<Bytes> {
+    let mut stream = connect_tcp(addr)?;
+    stream.write_all(data)?;
+    read_raw(&mut stream, timeout)
+}
+
+pub(crate) fn connect_tcp(addr: net::SocketAddr) -> io::Result<net::TcpStream> {
+    let stream = net::TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(Seconds(5).into()))?;
+    stream.set_write_timeout(Some(Seconds(5).into()))?;
+    Ok(stream)
 }
 
 /// Test server controller
@@ -280,6 +342,7 @@ pub struct TestServer {
     addr: net::SocketAddr,
     client: Client,
     system: System,
+    keep_alive: bool,
 }
 
 impl TestServer {
@@ -297,6 +360,30 @@ impl TestServer {
         }
     }
 
+    /// Configure connection reuse for test client.
+    ///
+    /// By default connections are kept alive and reused. If keep-alive
+    /// is disabled, requests are sent with `Connection: close` header.
+    pub fn client_keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = enabled;
+        self
+    }
+
+    /// Open raw tcp connection to the server.
+    ///
+    /// Read and write timeouts are set to 5 seconds.
+    pub fn connect_tcp(&self) -> io::Result<net::TcpStream> {
+        connect_tcp(self.addr)
+    }
+
+    /// Send hand-crafted bytes over new connection and read raw response.
+    ///
+    /// Reads until the connection is closed by the server or
+    /// no data is received within `timeout`.
+    pub fn send_raw(&self, data: &[u8], timeout: Millis) -> io::Result<Bytes> {
+        send_raw(self.addr, data, timeout)
+    }
+
     /// Construct test https server url
     pub fn surl(&self, uri: &str) -> String {
         if uri.starts_with('/') {
@@ -308,14 +395,26 @@ impl TestServer {
 
     /// Create client request
     pub fn request<S: AsRef<str>>(&self, method: Method, path: S) -> ClientRequest {
-        self.client
-            .request(method, self.url(path.as_ref()).as_str())
+        self.prepare(
+            self.client
+                .request(method, self.url(path.as_ref()).as_str()),
+        )
     }
 
     /// Create secure client request
     pub fn srequest<S: AsRef<str>>(&self, method: Method, path: S) -> ClientRequest {
-        self.client
-            .request(method, self.surl(path.as_ref()).as_str())
+        self.prepare(
+            self.client
+                .request(method, self.surl(path.as_ref()).as_str()),
+        )
+    }
+
+    fn prepare(&self, req: ClientRequest) -> ClientRequest {
+        if self.keep_alive {
+            req
+        } else {
+            req.force_close()
+        }
     }
 
     /// Load response's body
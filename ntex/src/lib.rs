@@ -30,6 +30,9 @@ pub use ntex_macros::{rt_main as main, rt_test as test};
 #[cfg(test)]
 pub(crate) use ntex_macros::rt_test2 as rt_test;
 
+#[cfg(feature = "openapi")]
+pub use ntex_macros::web_openapi as openapi;
+
 pub mod http;
 pub mod server;
 pub mod web;
@@ -5,7 +5,7 @@ use async_oneshot as oneshot;
 use log::{error, info};
 use socket2::{Domain, SockAddr, Socket, Type};
 
-use crate::rt::{spawn, Signal, System};
+use crate::rt::{spawn, System};
 use crate::{
     io::Io, service::ServiceFactory, time::sleep, time::Millis, util::join_all,
     util::Stream,
@@ -18,7 +18,7 @@ use super::config::{
 use super::service::{Factory, InternalServiceFactory};
 use super::socket::Listener;
 use super::worker::{self, Worker, WorkerAvailability, WorkerClient};
-use super::{Server, ServerCommand, ServerStatus, Token};
+use super::{Server, ServerCommand, ServerStatus, SignalPolicy, Token};
 
 const STOP_DELAY: Millis = Millis(300);
 
@@ -34,6 +34,7 @@ pub struct ServerBuilder {
     exit: bool,
     shutdown_timeout: Millis,
     no_signals: bool,
+    signals: SignalPolicy,
     cmd: Receiver<ServerCommand>,
     server: Server,
     notify: Vec<oneshot::Sender<()>>,
@@ -62,6 +63,7 @@ impl ServerBuilder {
             exit: false,
             shutdown_timeout: Millis::from_secs(30),
             no_signals: false,
+            signals: SignalPolicy::default(),
             cmd: rx,
             notify: Vec::new(),
             server,
@@ -119,6 +121,15 @@ impl ServerBuilder {
         self
     }
 
+    /// Set process signals handling policy.
+    ///
+    /// By default `SIGTERM` stops server gracefully, `SIGINT` and `SIGQUIT`
+    /// stop server immediately. See [`SignalPolicy`](struct.SignalPolicy.html).
+    pub fn signals(mut self, policy: SignalPolicy) -> Self {
+        self.signals = policy;
+        self
+    }
+
     /// Timeout for graceful workers shutdown.
     ///
     /// After receiving a stop signal, workers have this much time to finish
@@ -375,34 +386,12 @@ impl ServerBuilder {
                 let _ = tx.send(());
             }
             ServerCommand::Signal(sig) => {
-                // Signals support
-                // Handle `SIGINT`, `SIGTERM`, `SIGQUIT` signals and stop ntex system
-                match sig {
-                    Signal::Int => {
-                        info!("SIGINT received, exiting");
-                        self.exit = true;
-                        self.handle_cmd(ServerCommand::Stop {
-                            graceful: false,
-                            completion: None,
-                        })
-                    }
-                    Signal::Term => {
-                        info!("SIGTERM received, stopping");
-                        self.exit = true;
-                        self.handle_cmd(ServerCommand::Stop {
-                            graceful: true,
-                            completion: None,
-                        })
-                    }
-                    Signal::Quit => {
-                        info!("SIGQUIT received, exiting");
-                        self.exit = true;
-                        self.handle_cmd(ServerCommand::Stop {
-                            graceful: false,
-                            completion: None,
-                        })
-                    }
-                    _ => (),
+                if let Some(graceful) = self.signals.dispatch(sig) {
+                    self.exit = true;
+                    self.handle_cmd(ServerCommand::Stop {
+                        graceful,
+                        completion: None,
+                    })
                 }
             }
             ServerCommand::Notify(tx) => {
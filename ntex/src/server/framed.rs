@@ -0,0 +1,191 @@
+use std::task::{Context, Poll};
+use std::{future::Future, marker::PhantomData, pin::Pin, rc::Rc};
+
+use crate::codec::{Decoder, Encoder};
+use crate::io::{DispatchItem, Dispatcher, Io};
+use crate::service::{IntoServiceFactory, Service, ServiceFactory};
+use crate::time::Seconds;
+use crate::util::{Either, Ready};
+
+/// Create service factory for framed protocol.
+///
+/// Each connection gets decoded with provided codec, decoded frames
+/// are passed to the service and service responses get encoded back to
+/// the connection. Service may return `None` if it has no response for the frame.
+/// Connection is closed on decoder error or on service error.
+///
+/// ```rust,no_run
+/// use ntex::codec::BytesCodec;
+/// use ntex::service::fn_service;
+/// use ntex::{server, util::BytesMut};
+///
+/// #[ntex::main]
+/// async fn main() -> std::io::Result<()> {
+///     server::build()
+///         .bind("echo", "127.0.0.1:8080", |_| {
+///             server::build_framed(
+///                 BytesCodec,
+///                 fn_service(|buf: BytesMut| async move {
+///                     Ok::<_, ()>(Some(buf.freeze()))
+///                 }),
+///             )
+///         })?
+///         .run()
+///         .await
+/// }
+/// ```
+pub fn build_framed<U, F, S>(codec: U, service: F) -> FramedServiceFactory<U, S>
+where
+    U: Encoder + Decoder + Clone + 'static,
+    F: IntoServiceFactory<S, <U as Decoder>::Item>,
+    S: ServiceFactory<<U as Decoder>::Item, Response = Option<<U as Encoder>::Item>>,
+{
+    FramedServiceFactory {
+        codec,
+        factory: service.into_factory(),
+        keepalive: Seconds(30),
+        disconnect: Seconds(1),
+    }
+}
+
+/// Service factory for framed protocol, created by `build_framed()` function
+pub struct FramedServiceFactory<U, S> {
+    codec: U,
+    factory: S,
+    keepalive: Seconds,
+    disconnect: Seconds,
+}
+
+impl<U, S> FramedServiceFactory<U, S> {
+    /// Set keep-alive timeout.
+    ///
+    /// Connection is closed if no frames are received during timeout.
+    /// To disable timeout set value to 0.
+    ///
+    /// By default keep-alive timeout is set to 30 seconds.
+    pub fn keepalive_timeout(mut self, timeout: Seconds) -> Self {
+        self.keepalive = timeout;
+        self
+    }
+
+    /// Set connection disconnect timeout.
+    ///
+    /// To disable timeout set value to 0.
+    ///
+    /// By default disconnect timeout is set to 1 seconds.
+    pub fn disconnect_timeout(mut self, timeout: Seconds) -> Self {
+        self.disconnect = timeout;
+        self
+    }
+}
+
+impl<U, S> ServiceFactory<Io> for FramedServiceFactory<U, S>
+where
+    U: Encoder + Decoder + Clone + 'static,
+    S: ServiceFactory<<U as Decoder>::Item, Response = Option<<U as Encoder>::Item>>,
+    S::Service: 'static,
+    S::Future: 'static,
+{
+    type Response = ();
+    type Error = S::Error;
+    type InitError = S::InitError;
+    type Service = FramedService<U, S::Service>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Service, Self::InitError>>>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        let fut = self.factory.new_service(());
+        let codec = self.codec.clone();
+        let keepalive = self.keepalive;
+        let disconnect = self.disconnect;
+
+        Box::pin(async move {
+            Ok(FramedService {
+                codec,
+                keepalive,
+                disconnect,
+                service: Rc::new(fut.await?),
+            })
+        })
+    }
+}
+
+/// Framed protocol service
+pub struct FramedService<U, S> {
+    codec: U,
+    service: Rc<S>,
+    keepalive: Seconds,
+    disconnect: Seconds,
+}
+
+impl<U, S> Service<Io> for FramedService<U, S>
+where
+    U: Encoder + Decoder + Clone + 'static,
+    S: Service<<U as Decoder>::Item, Response = Option<<U as Encoder>::Item>> + 'static,
+{
+    type Response = ();
+    type Error = S::Error;
+    type Future = Dispatcher<FramedDispatch<U, S>, U>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    #[inline]
+    fn call(&self, io: Io) -> Self::Future {
+        Dispatcher::new(
+            io,
+            self.codec.clone(),
+            FramedDispatch {
+                service: self.service.clone(),
+                _t: PhantomData,
+            },
+        )
+        .keepalive_timeout(self.keepalive)
+        .disconnect_timeout(self.disconnect)
+    }
+}
+
+#[doc(hidden)]
+/// Passes decoded frames to the service, ignores control messages
+pub struct FramedDispatch<U, S> {
+    service: Rc<S>,
+    _t: PhantomData<U>,
+}
+
+impl<U, S> Service<DispatchItem<U>> for FramedDispatch<U, S>
+where
+    U: Encoder + Decoder,
+    S: Service<<U as Decoder>::Item, Response = Option<<U as Encoder>::Item>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<S::Response, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    #[inline]
+    fn call(&self, item: DispatchItem<U>) -> Self::Future {
+        match item {
+            DispatchItem::Item(item) => Either::Left(self.service.call(item)),
+            DispatchItem::DecoderError(_) | DispatchItem::EncoderError(_) => {
+                log::trace!("Framed protocol error, closing connection");
+                Either::Right(Ready::Ok(None))
+            }
+            _ => Either::Right(Ready::Ok(None)),
+        }
+    }
+}
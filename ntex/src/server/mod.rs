@@ -8,7 +8,9 @@ mod accept;
 mod builder;
 mod config;
 mod counter;
+mod framed;
 mod service;
+mod signals;
 mod socket;
 mod test;
 mod worker;
@@ -24,7 +26,10 @@ pub use ntex_tls::max_concurrent_ssl_accept;
 pub(crate) use self::builder::create_tcp_listener;
 pub use self::builder::ServerBuilder;
 pub use self::config::{Config, ServiceConfig, ServiceRuntime};
+pub use self::framed::{build_framed, FramedService, FramedServiceFactory};
+pub use self::signals::{SignalAction, SignalPolicy};
 pub use self::test::{build_test_server, test_server, TestServer};
+pub(crate) use self::worker::graceful_shutdown;
 
 #[non_exhaustive]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
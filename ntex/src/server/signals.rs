@@ -0,0 +1,183 @@
+use std::{fmt, sync::Arc};
+
+use crate::rt::Signal;
+
+/// Action performed by server on process signal
+#[derive(Clone)]
+pub enum SignalAction {
+    /// Stop server gracefully, workers finish in-flight requests
+    Graceful,
+    /// Stop server immediately
+    Immediate,
+    /// Ignore signal
+    Ignore,
+    /// Call user callback, server keeps running
+    Callback(Arc<dyn Fn(Signal) + Send + Sync>),
+}
+
+impl SignalAction {
+    /// Create callback action
+    pub fn callback<F>(f: F) -> Self
+    where
+        F: Fn(Signal) + Send + Sync + 'static,
+    {
+        SignalAction::Callback(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for SignalAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalAction::Graceful => write!(f, "SignalAction::Graceful"),
+            SignalAction::Immediate => write!(f, "SignalAction::Immediate"),
+            SignalAction::Ignore => write!(f, "SignalAction::Ignore"),
+            SignalAction::Callback(_) => write!(f, "SignalAction::Callback"),
+        }
+    }
+}
+
+/// Process signals handling policy.
+///
+/// Default policy on unix: `SIGTERM` stops server gracefully, `SIGINT` and
+/// `SIGQUIT` stop server immediately, `SIGHUP` is ignored. On windows
+/// `CTRL_C`, `CTRL_BREAK` and `CTRL_CLOSE` stop server gracefully.
+///
+/// ```rust
+/// use ntex::rt::Signal;
+/// use ntex::server::{SignalAction, SignalPolicy};
+///
+/// let policy = SignalPolicy::new()
+///     .on(Signal::Int, SignalAction::Graceful)
+///     .on(Signal::Hup, SignalAction::callback(|_| {
+///         // reload tls certificates
+///     }));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SignalPolicy {
+    hup: SignalAction,
+    int: SignalAction,
+    term: SignalAction,
+    quit: SignalAction,
+}
+
+impl Default for SignalPolicy {
+    fn default() -> Self {
+        SignalPolicy {
+            hup: SignalAction::Ignore,
+            #[cfg(unix)]
+            int: SignalAction::Immediate,
+            #[cfg(not(unix))]
+            int: SignalAction::Graceful,
+            term: SignalAction::Graceful,
+            #[cfg(unix)]
+            quit: SignalAction::Immediate,
+            #[cfg(not(unix))]
+            quit: SignalAction::Graceful,
+        }
+    }
+}
+
+impl SignalPolicy {
+    /// Create default signal policy
+    pub fn new() -> Self {
+        SignalPolicy::default()
+    }
+
+    /// Set action for the signal
+    pub fn on(mut self, sig: Signal, action: SignalAction) -> Self {
+        *self.action_mut(sig) = action;
+        self
+    }
+
+    /// Action for the signal
+    pub fn action(&self, sig: Signal) -> &SignalAction {
+        match sig {
+            Signal::Hup => &self.hup,
+            Signal::Int => &self.int,
+            Signal::Term => &self.term,
+            Signal::Quit => &self.quit,
+        }
+    }
+
+    fn action_mut(&mut self, sig: Signal) -> &mut SignalAction {
+        match sig {
+            Signal::Hup => &mut self.hup,
+            Signal::Int => &mut self.int,
+            Signal::Term => &mut self.term,
+            Signal::Quit => &mut self.quit,
+        }
+    }
+
+    /// Handle signal, returns `Some(graceful)` if server must be stopped
+    pub(super) fn dispatch(&self, sig: Signal) -> Option<bool> {
+        match self.action(sig) {
+            SignalAction::Graceful => {
+                log::info!("{:?} signal received, stopping", sig);
+                Some(true)
+            }
+            SignalAction::Immediate => {
+                log::info!("{:?} signal received, exiting", sig);
+                Some(false)
+            }
+            SignalAction::Ignore => {
+                log::trace!("{:?} signal received, ignoring", sig);
+                None
+            }
+            SignalAction::Callback(f) => {
+                log::trace!("{:?} signal received, calling handler", sig);
+                f(sig);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = SignalPolicy::default();
+        assert_eq!(policy.dispatch(Signal::Term), Some(true));
+        assert_eq!(policy.dispatch(Signal::Hup), None);
+        #[cfg(unix)]
+        {
+            assert_eq!(policy.dispatch(Signal::Int), Some(false));
+            assert_eq!(policy.dispatch(Signal::Quit), Some(false));
+        }
+        #[cfg(not(unix))]
+        {
+            assert_eq!(policy.dispatch(Signal::Int), Some(true));
+            assert_eq!(policy.dispatch(Signal::Quit), Some(true));
+        }
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+
+        let policy = SignalPolicy::new()
+            .on(Signal::Term, SignalAction::Immediate)
+            .on(Signal::Int, SignalAction::Graceful)
+            .on(Signal::Quit, SignalAction::Ignore)
+            .on(
+                Signal::Hup,
+                SignalAction::callback(move |sig| {
+                    assert_eq!(sig, Signal::Hup);
+                    counter2.fetch_add(1, Ordering::Relaxed);
+                }),
+            );
+        assert_eq!(policy.dispatch(Signal::Term), Some(false));
+        assert_eq!(policy.dispatch(Signal::Int), Some(true));
+        assert_eq!(policy.dispatch(Signal::Quit), None);
+
+        assert_eq!(policy.dispatch(Signal::Hup), None);
+        assert_eq!(policy.dispatch(Signal::Hup), None);
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+        assert!(format!("{:?}", policy.action(Signal::Hup)).contains("Callback"));
+    }
+}
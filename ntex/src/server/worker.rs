@@ -1,12 +1,15 @@
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::{future::Future, pin::Pin, sync::Arc, task::Context, task::Poll};
+use std::{cell::Cell, future::Future, pin::Pin, sync::Arc, task::Context, task::Poll};
 
 use async_channel::{unbounded, Receiver, Sender};
 use async_oneshot as oneshot;
 
+use crate::channel::condition::Condition;
 use crate::rt::{spawn, Arbiter};
 use crate::time::{sleep, Millis, Sleep};
-use crate::util::{join_all, ready, select, stream_recv, Either, Stream as FutStream};
+use crate::util::{
+    join_all, poll_fn, ready, select, stream_recv, Either, Stream as FutStream,
+};
 
 use super::accept::{AcceptNotify, Command};
 use super::service::{BoxedServerService, InternalServiceFactory, ServerMessage};
@@ -49,6 +52,30 @@ pub(super) fn num_connections() -> usize {
 thread_local! {
     static MAX_CONNS_COUNTER: Counter =
         Counter::new(MAX_CONNS.load(Ordering::Relaxed));
+    static SHUTDOWN: (Cell<bool>, Condition) = (Cell::new(false), Condition::new());
+}
+
+/// Wait until current worker starts graceful shutdown.
+///
+/// Future never resolves outside of server worker.
+pub(crate) async fn graceful_shutdown() {
+    let waiter = SHUTDOWN.with(|st| st.1.wait());
+    poll_fn(|cx| {
+        if SHUTDOWN.with(|st| st.0.get()) {
+            Poll::Ready(())
+        } else {
+            let _ = waiter.poll_ready(cx);
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+fn notify_graceful_shutdown() {
+    SHUTDOWN.with(|st| {
+        st.0.set(true);
+        st.1.notify();
+    })
 }
 
 #[derive(Clone, Debug)]
@@ -259,6 +286,8 @@ impl Worker {
                 }
             });
         } else {
+            notify_graceful_shutdown();
+
             let timeout = self.shutdown_timeout;
             self.services.iter_mut().for_each(move |srv| {
                 if srv.status == WorkerServiceStatus::Available {
@@ -694,4 +723,16 @@ mod tests {
         assert!(lazy(|cx| Pin::new(&mut worker).poll(cx)).await.is_ready());
         let _ = rx.await;
     }
+
+    #[crate::rt_test]
+    async fn test_graceful_shutdown() {
+        let mut fut = Box::pin(graceful_shutdown());
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+
+        notify_graceful_shutdown();
+        fut.await;
+
+        // shutdown is already started
+        graceful_shutdown().await;
+    }
 }
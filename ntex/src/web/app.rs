@@ -15,13 +15,16 @@ use super::request::WebRequest;
 use super::resource::Resource;
 use super::response::WebResponse;
 use super::route::Route;
-use super::service::{AppServiceFactory, ServiceFactoryWrapper, WebServiceFactory};
+use super::service::{
+    AppServiceFactory, RouteRegistry, ServiceFactoryWrapper, WebServiceFactory,
+};
 use super::{DefaultError, ErrorRenderer};
 
 type HttpNewService<Err: ErrorRenderer> =
     BoxServiceFactory<(), WebRequest<Err>, WebResponse, Err::Container, ()>;
 type FnStateFactory =
     Box<dyn Fn(Extensions) -> Pin<Box<dyn Future<Output = Result<Extensions, ()>>>>>;
+type FinishHook = Box<dyn Fn(&RouteRegistry)>;
 
 /// Application builder - structure that follows the builder pattern
 /// for building application instances.
@@ -33,6 +36,7 @@ pub struct App<M, F, Err: ErrorRenderer = DefaultError> {
     external: Vec<ResourceDef>,
     extensions: Extensions,
     state_factories: Vec<FnStateFactory>,
+    finish_hooks: Vec<FinishHook>,
     error_renderer: Err,
     case_insensitive: bool,
 }
@@ -44,6 +48,7 @@ impl App<Identity, Filter<DefaultError>, DefaultError> {
             middleware: Identity,
             filter: pipeline_factory(Filter::new()),
             state_factories: Vec::new(),
+            finish_hooks: Vec::new(),
             services: Vec::new(),
             default: None,
             external: Vec::new(),
@@ -61,6 +66,7 @@ impl<Err: ErrorRenderer> App<Identity, Filter<Err>, Err> {
             middleware: Identity,
             filter: pipeline_factory(Filter::new()),
             state_factories: Vec::new(),
+            finish_hooks: Vec::new(),
             services: Vec::new(),
             default: None,
             external: Vec::new(),
@@ -362,6 +368,7 @@ where
             filter: self.filter.and_then(filter.into_factory()),
             middleware: self.middleware,
             state_factories: self.state_factories,
+            finish_hooks: self.finish_hooks,
             services: self.services,
             default: self.default,
             external: self.external,
@@ -402,6 +409,7 @@ where
             middleware: Stack::new(self.middleware, mw),
             filter: self.filter,
             state_factories: self.state_factories,
+            finish_hooks: self.finish_hooks,
             services: self.services,
             default: self.default,
             external: self.external,
@@ -411,6 +419,96 @@ where
         }
     }
 
+    /// Register a function that is called after all services get registered.
+    ///
+    /// Hook is called once for each application instance, it receives
+    /// registry of application routes with their metadata. For example,
+    /// it could be used for api specification generation.
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, rc::Rc};
+    /// use ntex::web::{self, types::State, App, HttpResponse};
+    ///
+    /// struct Summary(&'static str);
+    ///
+    /// fn main() {
+    ///     let spec = Rc::new(RefCell::new(String::new()));
+    ///     let spec2 = spec.clone();
+    ///
+    ///     let app = App::new()
+    ///         .state(spec)
+    ///         .finish_hook(move |routes| {
+    ///             for route in routes.iter() {
+    ///                 if let Some(summary) = route.metadata::<Summary>() {
+    ///                     spec2.borrow_mut().push_str(summary.0);
+    ///                 }
+    ///             }
+    ///         })
+    ///         .service(
+    ///             web::resource("/users")
+    ///                 .metadata(Summary("List users"))
+    ///                 .route(web::get().to(|| async { HttpResponse::Ok() })),
+    ///         )
+    ///         .route(
+    ///             "/spec.json",
+    ///             web::get().to(|spec: State<Rc<RefCell<String>>>| async move {
+    ///                 spec.borrow().clone()
+    ///             }),
+    ///         );
+    /// }
+    /// ```
+    pub fn finish_hook<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&RouteRegistry) + 'static,
+    {
+        self.finish_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Registers middleware, in the form of a closure, that runs during
+    /// inbound and/or outbound processing in the request lifecycle.
+    ///
+    /// Closure receives request and reference to the next service, it is
+    /// an alternative to implementing `Transform` for one-off middlewares.
+    /// Middleware registered with `wrap_fn()` could be combined with `wrap()`
+    /// in any order.
+    ///
+    /// ```rust
+    /// use ntex::http::header::{CONTENT_TYPE, HeaderValue};
+    /// use ntex::service::Service;
+    /// use ntex::web::{self, App};
+    ///
+    /// async fn index() -> &'static str {
+    ///     "Welcome!"
+    /// }
+    ///
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .wrap_fn(|req, srv| {
+    ///             let fut = srv.call(req);
+    ///             async move {
+    ///                 let mut res = fut.await?;
+    ///                 res.headers_mut().insert(
+    ///                     CONTENT_TYPE, HeaderValue::from_static("text/plain"),
+    ///                 );
+    ///                 Ok(res)
+    ///             }
+    ///         })
+    ///         .route("/index.html", web::get().to(index));
+    /// }
+    /// ```
+    pub fn wrap_fn<F, R>(self, f: F) -> App<Stack<M, WrapFn<F, Err>>, T, Err>
+    where
+        M: Transform<AppService<T::Service, Err>>,
+        F: Fn(WebRequest<Err>, &M::Service) -> R,
+        R: Future<Output = Result<WebResponse, Err::Container>>,
+    {
+        self.wrap(WrapFn {
+            f: Rc::new(f),
+            _t: PhantomData,
+        })
+    }
+
     /// Use ascii case-insensitive routing.
     ///
     /// Only static segments could be case-insensitive.
@@ -493,6 +591,7 @@ where
             filter: self.filter,
             middleware: Rc::new(self.middleware),
             state_factories: Rc::new(self.state_factories),
+            finish_hooks: Rc::new(self.finish_hooks),
             services: Rc::new(RefCell::new(self.services)),
             external: RefCell::new(self.external),
             default: self.default,
@@ -522,6 +621,7 @@ where
             filter: self.filter,
             middleware: Rc::new(self.middleware),
             state_factories: Rc::new(self.state_factories),
+            finish_hooks: Rc::new(self.finish_hooks),
             services: Rc::new(RefCell::new(self.services)),
             external: RefCell::new(self.external),
             default: self.default,
@@ -549,6 +649,7 @@ where
             filter: self.filter,
             middleware: Rc::new(self.middleware),
             state_factories: Rc::new(self.state_factories),
+            finish_hooks: Rc::new(self.finish_hooks),
             services: Rc::new(RefCell::new(self.services)),
             external: RefCell::new(self.external),
             default: self.default,
@@ -581,6 +682,66 @@ where
     }
 }
 
+/// Middleware created from a closure, see `App::wrap_fn()`
+pub struct WrapFn<F, Err> {
+    f: Rc<F>,
+    _t: PhantomData<Err>,
+}
+
+impl<S, F, R, Err> Transform<S> for WrapFn<F, Err>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = Err::Container>,
+    F: Fn(WebRequest<Err>, &S) -> R,
+    R: Future<Output = Result<WebResponse, Err::Container>>,
+    Err: ErrorRenderer,
+{
+    type Service = WrapFnMiddleware<S, F, Err>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        WrapFnMiddleware {
+            service,
+            f: self.f.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+pub struct WrapFnMiddleware<S, F, Err> {
+    service: S,
+    f: Rc<F>,
+    _t: PhantomData<Err>,
+}
+
+impl<S, F, R, Err> Service<WebRequest<Err>> for WrapFnMiddleware<S, F, Err>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = Err::Container>,
+    F: Fn(WebRequest<Err>, &S) -> R,
+    R: Future<Output = Result<WebResponse, Err::Container>>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = Err::Container;
+    type Future = R;
+
+    #[inline]
+    fn poll_ready(
+        &self,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut task::Context<'_>, is_error: bool) -> task::Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    #[inline]
+    fn call(&self, req: WebRequest<Err>) -> Self::Future {
+        (self.f)(req, &self.service)
+    }
+}
+
 pub struct Filter<Err>(PhantomData<Err>);
 
 impl<Err: ErrorRenderer> Filter<Err> {
@@ -624,7 +785,7 @@ mod tests {
     use crate::http::header::{self, HeaderValue};
     use crate::http::{Method, StatusCode};
     use crate::service::{fn_service, Service};
-    use crate::util::{Bytes, Ready};
+    use crate::util::{Bytes, Either, Ready};
     use crate::web::test::{call_service, init_service, read_body, TestRequest};
     use crate::web::{
         self, middleware::DefaultHeaders, request::WebRequest, DefaultError, HttpRequest,
@@ -761,6 +922,132 @@ mod tests {
         );
     }
 
+    #[crate::rt_test]
+    async fn test_finish_hook() {
+        use std::cell::RefCell;
+
+        #[derive(Debug)]
+        struct Operation(&'static str);
+        struct Tag(&'static str);
+
+        let spec = Rc::new(RefCell::new(Vec::new()));
+        let spec2 = spec.clone();
+        let calls = Rc::new(RefCell::new(0));
+        let calls2 = calls.clone();
+
+        let srv = init_service(
+            App::new()
+                .state(spec.clone())
+                .finish_hook(move |routes| {
+                    *calls2.borrow_mut() += 1;
+                    for route in routes.iter() {
+                        if let Some(op) = route.metadata::<Operation>() {
+                            spec2.borrow_mut().push(format!(
+                                "{} {:?} {:?} {} {}",
+                                route.pattern,
+                                route.name,
+                                route.methods,
+                                op.0,
+                                route.metadata::<Tag>().map(|t| t.0).unwrap_or("-")
+                            ));
+                        }
+                    }
+                    assert!(routes.get("user").is_some());
+                })
+                .service(
+                    web::resource("/users")
+                        .metadata(Operation("list_users"))
+                        .metadata(Tag("users"))
+                        .route(web::get().to(|| async { HttpResponse::Ok() })),
+                )
+                .service(
+                    web::scope("/api").service(
+                        web::resource("/users/{id}").name("user").route(
+                            web::post()
+                                .metadata(Operation("update_user"))
+                                .to(|| async { HttpResponse::Ok() }),
+                        ),
+                    ),
+                )
+                .route(
+                    "/items",
+                    web::delete()
+                        .metadata(Operation("delete_items"))
+                        .to(|| async { HttpResponse::Ok() }),
+                )
+                .route("/plain", web::get().to(|| async { HttpResponse::Ok() }))
+                .route(
+                    "/spec.json",
+                    web::get().to(|spec: web::types::State<Rc<RefCell<Vec<String>>>>| {
+                        let body = spec.borrow().join("\n");
+                        async move { body }
+                    }),
+                ),
+        )
+        .await;
+
+        assert_eq!(*calls.borrow(), 1);
+        let req = TestRequest::with_uri("/spec.json").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(
+                b"/users None [GET] list_users users\n\
+                  /api/users/{id} Some(\"user\") [POST] update_user -\n\
+                  /items None [DELETE] delete_items -"
+            )
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_wrap_fn() {
+        let srv = init_service(
+            App::new()
+                .wrap(
+                    DefaultHeaders::new()
+                        .header(header::CONTENT_TYPE, HeaderValue::from_static("0001")),
+                )
+                .wrap_fn(|req, srv| {
+                    let fut = srv.call(req);
+                    async move {
+                        let mut res = fut.await?;
+                        res.headers_mut()
+                            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no"));
+                        Ok(res)
+                    }
+                })
+                .wrap_fn(|req, srv| {
+                    if req.path() == "/blocked" {
+                        let res = req.into_response(HttpResponse::Forbidden().finish());
+                        Either::Left(Ready::Ok(res))
+                    } else {
+                        Either::Right(srv.call(req))
+                    }
+                })
+                .route("/test", web::get().to(|| async { HttpResponse::Ok() }))
+                .route("/blocked", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+        let req = TestRequest::with_uri("/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("0001")
+        );
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            HeaderValue::from_static("no")
+        );
+
+        let req = TestRequest::with_uri("/blocked").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert!(!resp.headers().contains_key(header::CACHE_CONTROL));
+    }
+
     #[crate::rt_test]
     async fn test_router_wrap() {
         let srv = init_service(
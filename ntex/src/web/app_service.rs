@@ -14,7 +14,7 @@ use super::httprequest::{HttpRequest, HttpRequestPool};
 use super::request::WebRequest;
 use super::response::WebResponse;
 use super::rmap::ResourceMap;
-use super::service::{AppServiceFactory, AppState, WebServiceConfig};
+use super::service::{AppServiceFactory, AppState, RouteRegistry, WebServiceConfig};
 
 type Guards = Vec<Box<dyn Guard>>;
 type HttpService<Err: ErrorRenderer> =
@@ -25,6 +25,7 @@ type BoxResponse<Err: ErrorRenderer> =
     Pin<Box<dyn Future<Output = Result<WebResponse, Err::Container>>>>;
 type FnStateFactory =
     Box<dyn Fn(Extensions) -> Pin<Box<dyn Future<Output = Result<Extensions, ()>>>>>;
+type FinishHook = Box<dyn Fn(&RouteRegistry)>;
 
 /// Service factory to convert `Request` to a `WebRequest<S>`.
 /// It also executes state factories.
@@ -43,6 +44,7 @@ where
     pub(super) filter: PipelineFactory<F, WebRequest<Err>>,
     pub(super) extensions: RefCell<Option<Extensions>>,
     pub(super) state_factories: Rc<Vec<FnStateFactory>>,
+    pub(super) finish_hooks: Rc<Vec<FinishHook>>,
     pub(super) services: Rc<RefCell<Vec<Box<dyn AppServiceFactory<Err>>>>>,
     pub(super) default: Option<Rc<HttpNewService<Err>>>,
     pub(super) external: RefCell<Vec<ResourceDef>>,
@@ -106,6 +108,7 @@ where
 
         let filter_fut = self.filter.new_service(());
         let state_factories = self.state_factories.clone();
+        let finish_hooks = self.finish_hooks.clone();
         let mut extensions = self
             .extensions
             .borrow_mut()
@@ -133,6 +136,14 @@ where
             services
                 .into_iter()
                 .for_each(|mut srv| srv.register(&mut config));
+
+            // notify finish hooks
+            let routes = RouteRegistry::new(config.take_routes());
+            #[cfg(feature = "openapi")]
+            super::openapi::register(&routes);
+            for hook in finish_hooks.iter() {
+                hook(&routes);
+            }
             let services = config.into_services();
 
             // resource map
@@ -223,6 +234,8 @@ where
             inner.head = head;
             inner.payload = payload;
             inner.app_state = self.state.clone();
+            inner.pattern = None;
+            inner.name = None;
             req
         } else {
             HttpRequest::new(
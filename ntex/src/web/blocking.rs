@@ -0,0 +1,298 @@
+//! Blocking operations limits.
+//!
+//! `web::block()` and static files service execute blocking operations
+//! on a thread pool. Number of concurrently executing blocking operations
+//! could be limited, operations above limit wait in a queue and get permits
+//! in FIFO order. If queue is full `web::block()` returns
+//! `BlockingError::Overloaded` error.
+//!
+//! Limits are thread-local, each worker thread has its own counters and
+//! queue. Limits do not configure size of the blocking thread pool itself.
+//! `HttpServer` configures them with `HttpServer::blocking_threads()` and
+//! `HttpServer::blocking_queue()` methods.
+use std::task::{Context, Poll, Waker};
+use std::{cell::RefCell, future::Future, pin::Pin};
+
+thread_local! {
+    static POOL: RefCell<Pool> = const {
+        RefCell::new(Pool {
+            limit: 0,
+            queue: None,
+            running: 0,
+            next_id: 0,
+            waiters: Vec::new(),
+            granted: Vec::new(),
+        })
+    };
+}
+
+struct Pool {
+    limit: usize,
+    queue: Option<usize>,
+    running: usize,
+    next_id: usize,
+    // queued operations in arrival order
+    waiters: Vec<(usize, Waker)>,
+    // dequeued operations that own a slot but are not polled yet
+    granted: Vec<usize>,
+}
+
+impl Pool {
+    fn has_free_slot(&self) -> bool {
+        self.limit == 0 || self.running < self.limit
+    }
+
+    /// Hand free slots over to waiters, in FIFO order
+    fn grant(&mut self) {
+        while !self.waiters.is_empty() && self.has_free_slot() {
+            let (id, waker) = self.waiters.remove(0);
+            self.running += 1;
+            self.granted.push(id);
+            waker.wake();
+        }
+    }
+
+    fn remove_waiter(&mut self, id: usize) {
+        if let Some(idx) = self.waiters.iter().position(|(i, _)| *i == id) {
+            self.waiters.remove(idx);
+        }
+    }
+
+    fn take_granted(&mut self, id: usize) -> bool {
+        if let Some(idx) = self.granted.iter().position(|i| *i == id) {
+            self.granted.swap_remove(idx);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Set max number of concurrently executing blocking operations
+/// started from current thread.
+///
+/// Limit is thread-local, every worker thread has its own limit. It does not
+/// change number of threads in the blocking thread pool.
+///
+/// By default number is not limited, `0` removes limit.
+pub fn set_running_limit(num: usize) {
+    POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        pool.limit = num;
+        pool.grant();
+    })
+}
+
+/// Set max number of blocking operations waiting for execution
+/// for current thread.
+///
+/// By default queue is not limited, `None` removes limit.
+pub fn set_queue_limit(num: Option<usize>) {
+    POOL.with(|pool| pool.borrow_mut().queue = num)
+}
+
+/// Number of currently executing blocking operations for current thread
+pub fn running() -> usize {
+    POOL.with(|pool| pool.borrow().running)
+}
+
+/// Number of blocking operations waiting for execution for current thread
+pub fn queued() -> usize {
+    POOL.with(|pool| pool.borrow().waiters.len())
+}
+
+/// Wait for permit to execute blocking operation
+pub(super) fn acquire() -> Acquire {
+    Acquire { id: None }
+}
+
+/// Permit for blocking operation, releases slot on drop
+pub(super) struct Permit(());
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let _ = POOL.try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            pool.running -= 1;
+            pool.grant();
+        });
+    }
+}
+
+/// Queue is full
+#[derive(Debug)]
+pub(super) struct Overloaded;
+
+pub(super) struct Acquire {
+    // waiter id, if operation is queued
+    id: Option<usize>,
+}
+
+impl Future for Acquire {
+    type Output = Result<Permit, Overloaded>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+
+            if let Some(id) = self.id {
+                // slot is already reserved by `Pool::grant()`
+                if pool.take_granted(id) {
+                    self.id = None;
+                    return Poll::Ready(Ok(Permit(())));
+                }
+                // update waker in existing slot
+                if let Some((_, waker)) = pool.waiters.iter_mut().find(|(i, _)| *i == id) {
+                    if !waker.will_wake(cx.waker()) {
+                        *waker = cx.waker().clone();
+                    }
+                }
+                return Poll::Pending;
+            }
+
+            // new operations do not overtake queued ones
+            if pool.waiters.is_empty() && pool.has_free_slot() {
+                pool.running += 1;
+                return Poll::Ready(Ok(Permit(())));
+            }
+            if pool
+                .queue
+                .map_or(false, |limit| pool.waiters.len() >= limit)
+            {
+                log::trace!("Blocking pool queue is full");
+                return Poll::Ready(Err(Overloaded));
+            }
+            let id = pool.next_id;
+            pool.next_id = pool.next_id.wrapping_add(1);
+            pool.waiters.push((id, cx.waker().clone()));
+            self.id = Some(id);
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            let _ = POOL.try_with(|pool| {
+                let mut pool = pool.borrow_mut();
+                if pool.take_granted(id) {
+                    // pass granted slot to next waiter
+                    pool.running -= 1;
+                    pool.grant();
+                } else {
+                    pool.remove_waiter(id);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::util::lazy;
+    use crate::web::{block, error::BlockingError};
+
+    #[crate::rt_test]
+    async fn test_overloaded() {
+        set_running_limit(1);
+        set_queue_limit(Some(1));
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let mut fut1 = Box::pin(block(move || rx.recv().map_err(|_| ())));
+        let mut fut2 = Box::pin(block(|| Ok::<_, ()>(2)));
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        assert_eq!(running(), 1);
+        assert_eq!(queued(), 1);
+
+        // pool of size 1 with queue of size 1 is saturated
+        let res = block(|| Ok::<_, ()>(3)).await;
+        assert!(matches!(res, Err(BlockingError::Overloaded)));
+
+        tx.send(()).unwrap();
+        assert!(fut1.await.is_ok());
+        assert_eq!(fut2.await.unwrap(), 2);
+        assert_eq!(running(), 0);
+        assert_eq!(queued(), 0);
+
+        // dropped waiter passes wake up to next one
+        let (tx, rx) = mpsc::channel::<()>();
+        let mut fut1 = Box::pin(block(move || rx.recv().map_err(|_| ())));
+        let mut fut2 = Box::pin(block(|| Ok::<_, ()>(2)));
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        set_queue_limit(None);
+        let mut fut3 = Box::pin(block(|| Ok::<_, ()>(3)));
+        assert!(lazy(|cx| fut3.as_mut().poll(cx)).await.is_pending());
+        assert_eq!(queued(), 2);
+        tx.send(()).unwrap();
+        assert!(fut1.await.is_ok());
+        drop(fut2);
+        assert_eq!(fut3.await.unwrap(), 3);
+        assert_eq!(queued(), 0);
+
+        set_running_limit(0);
+        assert_eq!(block(|| Ok::<_, ()>(4)).await.unwrap(), 4);
+    }
+
+    #[crate::rt_test]
+    async fn test_repoll_and_raise_limit() {
+        set_running_limit(1);
+        set_queue_limit(None);
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let mut fut1 = Box::pin(block(move || rx.recv().map_err(|_| ())));
+        let mut fut2 = Box::pin(block(|| Ok::<_, ()>(2)));
+        let mut fut3 = Box::pin(block(|| Ok::<_, ()>(3)));
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+
+        // repeated polls do not occupy additional slots
+        for _ in 0..3 {
+            assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+            assert!(lazy(|cx| fut3.as_mut().poll(cx)).await.is_pending());
+        }
+        assert_eq!(queued(), 2);
+
+        // raising limit wakes up all waiters that fit
+        set_running_limit(3);
+        assert_eq!(fut2.await.unwrap(), 2);
+        assert_eq!(fut3.await.unwrap(), 3);
+        assert_eq!(queued(), 0);
+
+        tx.send(()).unwrap();
+        assert!(fut1.await.is_ok());
+        assert_eq!(running(), 0);
+        set_running_limit(0);
+    }
+
+    #[crate::rt_test]
+    async fn test_fifo_order() {
+        set_running_limit(1);
+        set_queue_limit(None);
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let mut fut1 = Box::pin(block(move || rx.recv().map_err(|_| ())));
+        let mut fut2 = Box::pin(block(|| Ok::<_, ()>(2)));
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+
+        tx.send(()).unwrap();
+        assert!(fut1.await.is_ok());
+
+        // slot is handed to queued waiter, new operation waits behind it
+        assert_eq!(running(), 1);
+        let mut fut3 = Box::pin(block(|| Ok::<_, ()>(3)));
+        assert!(lazy(|cx| fut3.as_mut().poll(cx)).await.is_pending());
+        assert_eq!(queued(), 1);
+
+        assert_eq!(fut2.await.unwrap(), 2);
+        assert_eq!(fut3.await.unwrap(), 3);
+        assert_eq!(running(), 0);
+        assert_eq!(queued(), 0);
+        set_running_limit(0);
+    }
+}
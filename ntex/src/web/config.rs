@@ -2,6 +2,8 @@ use std::{net::SocketAddr, rc::Rc};
 
 use crate::{router::ResourceDef, util::Extensions};
 
+use super::info::ForwardedPrecedence;
+use super::ipnet::IpNetwork;
 use super::resource::Resource;
 use super::route::Route;
 use super::service::{AppServiceFactory, ServiceFactoryWrapper, WebServiceFactory};
@@ -16,11 +18,33 @@ struct AppConfigInner {
     secure: bool,
     host: String,
     addr: SocketAddr,
+    forwarded: ForwardedPrecedence,
+    trusted_proxies: Vec<IpNetwork>,
 }
 
 impl AppConfig {
     pub(crate) fn new(secure: bool, addr: SocketAddr, host: String) -> Self {
-        AppConfig(Rc::new(AppConfigInner { secure, host, addr }))
+        AppConfig(Rc::new(AppConfigInner {
+            secure,
+            host,
+            addr,
+            forwarded: ForwardedPrecedence::default(),
+            trusted_proxies: Vec::new(),
+        }))
+    }
+
+    pub(crate) fn set_forwarded_precedence(mut self, val: ForwardedPrecedence) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .forwarded = val;
+        self
+    }
+
+    pub(crate) fn set_trusted_proxies(mut self, val: Vec<IpNetwork>) -> Self {
+        Rc::get_mut(&mut self.0)
+            .expect("Multiple copies exist")
+            .trusted_proxies = val;
+        self
     }
 
     /// Server host name.
@@ -43,6 +67,16 @@ impl AppConfig {
     pub fn local_addr(&self) -> SocketAddr {
         self.0.addr
     }
+
+    /// Precedence of `Forwarded` header versus `X-Forwarded-*` headers
+    pub fn forwarded_precedence(&self) -> ForwardedPrecedence {
+        self.0.forwarded
+    }
+
+    /// Trusted proxy networks, empty if all proxies are trusted
+    pub fn trusted_proxies(&self) -> &[IpNetwork] {
+        &self.0.trusted_proxies
+    }
 }
 
 impl Default for AppConfig {
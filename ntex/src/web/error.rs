@@ -4,6 +4,11 @@ use std::{cell::RefCell, fmt, io::Write, marker::PhantomData};
 use thiserror::Error;
 
 pub use ntex_http::error::Error as HttpError;
+#[cfg(feature = "msgpack")]
+pub use rmp_serde::encode::Error as MsgPackError;
+#[cfg(feature = "cbor")]
+/// Cbor serialization error
+pub type CborError = ciborium::ser::Error<std::io::Error>;
 pub use serde_json::error::Error as JsonError;
 #[cfg(feature = "url")]
 pub use url_pkg::ParseError as UrlParseError;
@@ -82,6 +87,29 @@ where
 pub enum StateExtractorError {
     #[error("App state is not configured, to configure use App::state()")]
     NotConfigured,
+    #[error("App state initialization failed")]
+    InitFailed,
+}
+
+/// Request handler panicked during request processing.
+///
+/// Error is generated by `PanicHandler` middleware, panic message is
+/// not exposed to the client.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Internal server error")]
+pub struct PanicError {
+    message: String,
+}
+
+impl PanicError {
+    pub(crate) fn new(message: String) -> Self {
+        PanicError { message }
+    }
+
+    /// Panic message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
 #[deprecated]
@@ -120,6 +148,9 @@ pub enum UrlencodedError {
     /// Content type error
     #[error("Content type error")]
     ContentType,
+    /// Content type charset is not supported
+    #[error("Unsupported charset")]
+    Charset,
     /// Parse error
     #[error("Parse error")]
     Parse,
@@ -145,6 +176,60 @@ pub enum JsonPayloadError {
     Payload(#[from] error::PayloadError),
 }
 
+/// A set of errors that can occur during parsing msgpack payloads
+#[cfg(feature = "msgpack")]
+#[derive(Error, Debug)]
+pub enum MsgPackPayloadError {
+    /// Payload size is bigger than allowed. (default: 32kB)
+    #[error("MsgPack payload size is bigger than allowed")]
+    Overflow,
+    /// Content type error
+    #[error("Content type error")]
+    ContentType,
+    /// Deserialize error
+    #[error("MsgPack deserialize error: {0}")]
+    Deserialize(#[from] rmp_serde::decode::Error),
+    /// Payload error
+    #[error("Error that occur during reading payload: {0}")]
+    Payload(#[from] error::PayloadError),
+}
+
+/// A set of errors that can occur during parsing cbor payloads
+#[cfg(feature = "cbor")]
+#[derive(Error, Debug)]
+pub enum CborPayloadError {
+    /// Payload size is bigger than allowed. (default: 32kB)
+    #[error("Cbor payload size is bigger than allowed")]
+    Overflow,
+    /// Content type error
+    #[error("Content type error")]
+    ContentType,
+    /// Deserialize error
+    #[error("Cbor deserialize error: {0}")]
+    Deserialize(#[from] ciborium::de::Error<std::io::Error>),
+    /// Payload error
+    #[error("Error that occur during reading payload: {0}")]
+    Payload(#[from] error::PayloadError),
+}
+
+/// A set of errors that can occur during parsing protobuf payloads
+#[cfg(feature = "protobuf")]
+#[derive(Error, Debug)]
+pub enum ProtoBufPayloadError {
+    /// Payload size is bigger than allowed. (default: 32kB)
+    #[error("ProtoBuf payload size is bigger than allowed")]
+    Overflow,
+    /// Content type error
+    #[error("Content type error")]
+    ContentType,
+    /// Deserialize error
+    #[error("ProtoBuf deserialize error: {0}")]
+    Deserialize(#[from] prost::DecodeError),
+    /// Payload error
+    #[error("Error that occur during reading payload: {0}")]
+    Payload(#[from] error::PayloadError),
+}
+
 /// A set of errors that can occur during parsing request paths
 #[derive(Error, Debug)]
 pub enum PathError {
@@ -159,6 +244,9 @@ pub enum QueryPayloadError {
     /// Deserialize error
     #[error("Query deserialize error: {0}")]
     Deserialize(#[from] serde::de::value::Error),
+    /// Query contains more parameters than allowed
+    #[error("Query contains too many parameters, limit is {0}")]
+    TooManyParams(usize),
 }
 
 #[derive(Error, Debug)]
@@ -174,6 +262,20 @@ pub enum PayloadError {
     Decoding,
 }
 
+/// Errors which can occur when serializing uri path segments to file path
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UriSegmentError {
+    /// The segment started with the wrapped invalid character.
+    #[error("The segment started with the wrapped invalid character")]
+    BadStart(char),
+    /// The segment contained the wrapped invalid character.
+    #[error("The segment contained the wrapped invalid character")]
+    BadChar(char),
+    /// The segment ended with the wrapped invalid character.
+    #[error("The segment ended with the wrapped invalid character")]
+    BadEnd(char),
+}
+
 /// Helper type that can wrap any error and generate custom response.
 ///
 /// In following example any `io::Error` will be converted into "BAD REQUEST"
@@ -287,6 +389,93 @@ where
     }
 }
 
+/// Wrapper for any displayable error.
+///
+/// Allows to use custom error types that do not implement
+/// `WebResponseError` with `?` operator in handlers. Error is rendered
+/// as *INTERNAL SERVER ERROR* response by default, error message is used
+/// as response body. Types that implement `WebResponseError` could be
+/// converted to `web::Error` directly and control status code and response.
+///
+/// ```rust
+/// use ntex::web::{self, error::WebError, HttpResponse};
+///
+/// struct MyError(&'static str);
+///
+/// impl std::fmt::Display for MyError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "my error: {}", self.0)
+///     }
+/// }
+///
+/// fn query() -> Result<String, MyError> {
+///     Err(MyError("db is not available"))
+/// }
+///
+/// async fn index() -> Result<HttpResponse, WebError<MyError>> {
+///     Ok(HttpResponse::Ok().body(query()?))
+/// }
+/// ```
+pub struct WebError<E> {
+    cause: E,
+    status: StatusCode,
+}
+
+impl<E> WebError<E> {
+    /// Wrap error, *INTERNAL SERVER ERROR* status is used
+    pub fn new(cause: E) -> Self {
+        WebError {
+            cause,
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Wrap error with specified response status code
+    pub fn with_status(cause: E, status: StatusCode) -> Self {
+        WebError { cause, status }
+    }
+
+    /// Get reference to inner error
+    pub fn get_ref(&self) -> &E {
+        &self.cause
+    }
+
+    /// Unwrap inner error
+    pub fn into_inner(self) -> E {
+        self.cause
+    }
+}
+
+impl<E> From<E> for WebError<E> {
+    fn from(cause: E) -> Self {
+        WebError::new(cause)
+    }
+}
+
+impl<E: fmt::Display> fmt::Debug for WebError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "web::WebError({}, {})", self.status, self.cause)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for WebError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.cause, f)
+    }
+}
+
+impl<E: fmt::Display> std::error::Error for WebError<E> {}
+
+impl<E, Err> WebResponseError<Err> for WebError<E>
+where
+    E: fmt::Display + 'static,
+    Err: ErrorRenderer,
+{
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+}
+
 /// Helper function that creates wrapper of any error and generate *BAD
 /// REQUEST* response.
 #[allow(non_snake_case)]
@@ -716,6 +905,13 @@ mod tests {
         );
         assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
 
+        use crate::util::inflight::InFlightError;
+        let resp = WebResponseError::<DefaultError>::error_response(
+            &InFlightError::<UrlencodedError>::Overflow,
+            &req,
+        );
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
         let resp = WebResponseError::<DefaultError>::error_response(
             &SendRequestError::Connect(ConnectError::Timeout),
             &req,
@@ -756,6 +952,15 @@ mod tests {
         let err = PayloadError::Decoding;
         let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let err = serde_json::from_str::<i32>("bad json").unwrap_err();
+        let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let data = vec![0xff];
+        let err = std::str::from_utf8(&data).unwrap_err();
+        let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
     #[test]
@@ -777,6 +982,50 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[crate::rt_test]
+    async fn test_web_error() {
+        struct MyError;
+
+        impl fmt::Display for MyError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "my error")
+            }
+        }
+
+        fn check(fail: bool) -> Result<&'static str, MyError> {
+            if fail {
+                Err(MyError)
+            } else {
+                Ok("ok")
+            }
+        }
+
+        fn handler(fail: bool) -> Result<&'static str, WebError<MyError>> {
+            Ok(check(fail)?)
+        }
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(handler(false).unwrap(), "ok");
+
+        let err = handler(true).unwrap_err();
+        assert_eq!(
+            format!("{:?}", err),
+            "web::WebError(500 Internal Server Error, my error)"
+        );
+        let e: Error = err.into();
+        assert_eq!(e.to_string(), "my error");
+        let resp = crate::http::ResponseError::error_response(&e);
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = WebError::with_status(MyError, StatusCode::CONFLICT);
+        assert_eq!(err.get_ref().to_string(), "my error");
+        let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let body =
+            crate::web::test::read_body(crate::web::WebResponse::new(resp, req)).await;
+        assert_eq!(body, "my error");
+    }
+
     #[test]
     fn test_io_error() {
         assert_eq!(
@@ -837,6 +1086,22 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_payload_error() {
+        let req = TestRequest::default().to_http_request();
+        let resp: HttpResponse = WebResponseError::<DefaultError>::error_response(
+            &MsgPackPayloadError::Overflow,
+            &req,
+        );
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let resp: HttpResponse = WebResponseError::<DefaultError>::error_response(
+            &MsgPackPayloadError::ContentType,
+            &req,
+        );
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn test_query_payload_error() {
         let req = TestRequest::default().to_http_request();
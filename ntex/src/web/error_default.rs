@@ -8,7 +8,7 @@ use serde_urlencoded::ser::Error as FormError;
 use crate::http::body::Body;
 use crate::http::helpers::Writer;
 use crate::http::{self, header, StatusCode};
-use crate::util::{timeout::TimeoutError, BytesMut};
+use crate::util::{inflight::InFlightError, timeout::TimeoutError, BytesMut};
 use crate::ws::error::HandshakeError;
 
 use super::error::{self, ErrorContainer, ErrorRenderer, WebResponseError};
@@ -81,6 +81,18 @@ impl fmt::Debug for Error {
     }
 }
 
+/// Plain text response with error message
+fn text_response(status: StatusCode, err: &dyn fmt::Display) -> HttpResponse {
+    let mut resp = HttpResponse::new(status);
+    let mut buf = BytesMut::new();
+    let _ = write!(Writer(&mut buf), "{}", err);
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    resp.set_body(Body::from(buf))
+}
+
 /// Return `GATEWAY_TIMEOUT` for `TimeoutError`
 impl<E: WebResponseError<DefaultError>> WebResponseError<DefaultError> for TimeoutError<E> {
     fn status_code(&self) -> StatusCode {
@@ -89,13 +101,82 @@ impl<E: WebResponseError<DefaultError>> WebResponseError<DefaultError> for Timeo
             TimeoutError::Timeout => StatusCode::GATEWAY_TIMEOUT,
         }
     }
+
+    fn error_response(&self, req: &HttpRequest) -> HttpResponse {
+        match self {
+            TimeoutError::Service(e) => e.error_response(req),
+            TimeoutError::Timeout => text_response(self.status_code(), self),
+        }
+    }
+}
+
+/// Convert `TimeoutError` of web service to `Error`, `GATEWAY_TIMEOUT` for timeouts
+impl From<TimeoutError<Error>> for Error {
+    fn from(err: TimeoutError<Error>) -> Self {
+        match err {
+            TimeoutError::Service(e) => e,
+            TimeoutError::Timeout => {
+                TimeoutError::<std::convert::Infallible>::Timeout.into()
+            }
+        }
+    }
+}
+
+/// Return `SERVICE_UNAVAILABLE` for `InFlightError::Overflow`
+impl<E: WebResponseError<DefaultError>> WebResponseError<DefaultError>
+    for InFlightError<E>
+{
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InFlightError::Service(e) => e.status_code(),
+            InFlightError::Overflow => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn error_response(&self, req: &HttpRequest) -> HttpResponse {
+        match self {
+            InFlightError::Service(e) => e.error_response(req),
+            InFlightError::Overflow => text_response(self.status_code(), self),
+        }
+    }
+}
+
+/// Convert `InFlightError` of web service to `Error`, `SERVICE_UNAVAILABLE` for overflow
+impl From<InFlightError<Error>> for Error {
+    fn from(err: InFlightError<Error>) -> Self {
+        match err {
+            InFlightError::Service(e) => e,
+            InFlightError::Overflow => {
+                InFlightError::<std::convert::Infallible>::Overflow.into()
+            }
+        }
+    }
 }
 
 /// `InternalServerError` for `StateExtractorError`
 impl WebResponseError<DefaultError> for error::StateExtractorError {}
 
-/// `InternalServerError` for `JsonError`
-impl WebResponseError<DefaultError> for JsonError {}
+/// `InternalServerError` for `PanicError`
+impl WebResponseError<DefaultError> for error::PanicError {}
+
+/// `BadRequest` for `JsonError`, `InternalServerError` for io errors
+impl WebResponseError<DefaultError> for JsonError {
+    fn status_code(&self) -> StatusCode {
+        if self.is_io() {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+#[cfg(feature = "msgpack")]
+/// `InternalServerError` for `MsgPackError`
+impl WebResponseError<DefaultError> for error::MsgPackError {}
+
+#[cfg(feature = "cbor")]
+/// `InternalServerError` for `CborError`
+impl WebResponseError<DefaultError> for error::CborError {}
 
 /// `InternalServerError` for `FormError`
 impl WebResponseError<DefaultError> for FormError {}
@@ -158,6 +239,13 @@ impl WebResponseError<DefaultError> for error::UrlencodedError {
         match *self {
             error::UrlencodedError::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
             error::UrlencodedError::UnknownLength => StatusCode::LENGTH_REQUIRED,
+            error::UrlencodedError::Charset => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            error::UrlencodedError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::UrlencodedError::Payload(http::error::PayloadError::MemoryLimit) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -168,11 +256,91 @@ impl WebResponseError<DefaultError> for error::JsonPayloadError {
     fn status_code(&self) -> StatusCode {
         match *self {
             error::JsonPayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            error::JsonPayloadError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::JsonPayloadError::Payload(http::error::PayloadError::MemoryLimit) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             _ => StatusCode::BAD_REQUEST,
         }
     }
 }
 
+#[cfg(feature = "msgpack")]
+/// Return `BadRequest` for `MsgPackPayloadError`
+impl WebResponseError<DefaultError> for error::MsgPackPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            error::MsgPackPayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            error::MsgPackPayloadError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::MsgPackPayloadError::Payload(http::error::PayloadError::MemoryLimit) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(feature = "cbor")]
+/// Return `BadRequest` for `CborPayloadError`
+impl WebResponseError<DefaultError> for error::CborPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            error::CborPayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            error::CborPayloadError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::CborPayloadError::Payload(http::error::PayloadError::MemoryLimit) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(feature = "protobuf")]
+/// Return `BadRequest` for `ProtoBufPayloadError`
+///
+/// Decode error details are rendered only if
+/// [`ProtoBufConfig::debug()`](../types/struct.ProtoBufConfig.html#method.debug)
+/// is enabled.
+impl WebResponseError<DefaultError> for error::ProtoBufPayloadError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            error::ProtoBufPayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            error::ProtoBufPayloadError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::ProtoBufPayloadError::Payload(
+                http::error::PayloadError::MemoryLimit,
+            ) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self, req: &HttpRequest) -> HttpResponse {
+        let mut resp = HttpResponse::new(self.status_code());
+        let mut buf = BytesMut::new();
+        let debug = req
+            .app_state::<super::types::ProtoBufConfig>()
+            .map(|c| c.is_debug())
+            .unwrap_or(false);
+        if !debug && matches!(self, error::ProtoBufPayloadError::Deserialize(_)) {
+            let _ = write!(Writer(&mut buf), "ProtoBuf deserialize error");
+        } else {
+            let _ = write!(Writer(&mut buf), "{}", self);
+        }
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+        resp.set_body(Body::from(buf))
+    }
+}
+
 /// Error renderer for `PathError`
 impl WebResponseError<DefaultError> for error::PathError {
     fn status_code(&self) -> StatusCode {
@@ -188,19 +356,38 @@ impl WebResponseError<DefaultError> for error::QueryPayloadError {
 }
 
 impl WebResponseError<DefaultError> for error::PayloadError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            error::PayloadError::Payload(http::error::PayloadError::Timeout) => {
+                StatusCode::REQUEST_TIMEOUT
+            }
+            error::PayloadError::Payload(http::error::PayloadError::MemoryLimit) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Return `BadRequest` for `UriSegmentError`
+impl WebResponseError<DefaultError> for error::UriSegmentError {
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
 }
 
-/// `PayloadError` returns two possible results:
+/// `PayloadError` returns four possible results:
 ///
 /// - `Overflow` returns `PayloadTooLarge`
+/// - `Timeout` returns `RequestTimeout`
+/// - `MemoryLimit` returns `ServiceUnavailable`
 /// - Other errors returns `BadRequest`
 impl WebResponseError<DefaultError> for http::error::PayloadError {
     fn status_code(&self) -> StatusCode {
         match *self {
             http::error::PayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            http::error::PayloadError::Timeout => StatusCode::REQUEST_TIMEOUT,
+            http::error::PayloadError::MemoryLimit => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -0,0 +1,83 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::task::{Context, Poll};
+use std::{cmp, error::Error, fs::File, future::Future, pin::Pin};
+
+use crate::http::body::{BodySize, MessageBody};
+use crate::util::Bytes;
+use crate::web::{block, error::BlockingError};
+
+const CHUNK_SIZE: u64 = 65_536;
+
+/// Response body that reads file content on a thread pool
+pub(super) struct ChunkedReadFile {
+    size: u64,
+    offset: u64,
+    counter: u64,
+    file: Option<File>,
+    fut: Option<
+        Pin<Box<dyn Future<Output = Result<(File, Bytes), BlockingError<io::Error>>>>>,
+    >,
+}
+
+impl ChunkedReadFile {
+    pub(super) fn new(file: File, offset: u64, size: u64) -> Self {
+        ChunkedReadFile {
+            size,
+            offset,
+            counter: 0,
+            file: Some(file),
+            fut: None,
+        }
+    }
+}
+
+impl MessageBody for ChunkedReadFile {
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.size)
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        if let Some(ref mut fut) = self.fut {
+            return match Pin::new(fut).poll(cx) {
+                Poll::Ready(Ok((file, bytes))) => {
+                    self.fut = None;
+                    self.file = Some(file);
+                    self.offset += bytes.len() as u64;
+                    self.counter += bytes.len() as u64;
+                    Poll::Ready(Some(Ok(bytes)))
+                }
+                Poll::Ready(Err(e)) => {
+                    self.fut = None;
+                    Poll::Ready(Some(Err(Box::new(io::Error::from(e)))))
+                }
+                Poll::Pending => Poll::Pending,
+            };
+        }
+
+        if self.counter >= self.size {
+            return Poll::Ready(None);
+        }
+
+        let mut file = if let Some(file) = self.file.take() {
+            file
+        } else {
+            return Poll::Ready(None);
+        };
+        let offset = self.offset;
+        let max = cmp::min(self.size - self.counter, CHUNK_SIZE);
+
+        self.fut = Some(Box::pin(block(move || {
+            let mut buf = Vec::with_capacity(max as usize);
+            file.seek(SeekFrom::Start(offset))?;
+            let n = file.by_ref().take(max).read_to_end(&mut buf)?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            Ok((file, Bytes::from(buf)))
+        })));
+        self.poll_next_chunk(cx)
+    }
+}
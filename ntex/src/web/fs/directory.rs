@@ -0,0 +1,158 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{fs, io, time::SystemTime};
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::http::header;
+use crate::web::{HttpRequest, HttpResponse};
+
+/// Characters that must be escaped in a path segment of the link
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Directory listing renderer
+pub(super) type DirectoryRenderer =
+    dyn Fn(&Directory, &HttpRequest) -> io::Result<HttpResponse>;
+
+/// A directory to be listed by `Files` service.
+#[derive(Debug, Clone)]
+pub struct Directory {
+    /// Base directory of the `Files` service
+    pub base: PathBuf,
+    /// Path of the directory
+    pub path: PathBuf,
+    /// Visible directory entries, sub-directories go first.
+    /// Hidden entries (names starting with `.`) are skipped.
+    pub entries: Vec<DirectoryEntry>,
+}
+
+/// Directory listing entry
+#[derive(Debug, Clone)]
+pub struct DirectoryEntry {
+    /// File name
+    pub name: String,
+    /// Entry is a directory
+    pub is_dir: bool,
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time, if it is available on the platform
+    pub modified: Option<SystemTime>,
+}
+
+impl Directory {
+    /// Read directory entries.
+    ///
+    /// This method blocks current thread.
+    pub fn read(base: PathBuf, path: PathBuf) -> io::Result<Directory> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            // follow symlinks, broken links are skipped
+            if let Ok(md) = fs::metadata(entry.path()) {
+                entries.push(DirectoryEntry {
+                    name,
+                    is_dir: md.is_dir(),
+                    size: if md.is_dir() { 0 } else { md.len() },
+                    modified: md.modified().ok(),
+                });
+            }
+        }
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(Directory {
+            base,
+            path,
+            entries,
+        })
+    }
+
+    /// Directory path relative to the base directory
+    pub fn relative_path(&self) -> &Path {
+        self.path.strip_prefix(&self.base).unwrap_or(&self.path)
+    }
+}
+
+/// Default directory listing renderer, renders simple html page
+pub(super) fn directory_listing(
+    dir: &Directory,
+    req: &HttpRequest,
+) -> io::Result<HttpResponse> {
+    let mut base = req.path().to_string();
+    if !base.ends_with('/') {
+        base.push('/');
+    }
+    let title = escape_html(&percent_decode_str(&base).decode_utf8_lossy());
+
+    let mut body = String::new();
+    let _ = write!(
+        body,
+        "<html>\n<head><title>Index of {0}</title></head>\n<body>\n\
+         <h1>Index of {0}</h1>\n<table>\n\
+         <tr><th>Name</th><th>Size</th><th>Modified</th></tr>\n",
+        title
+    );
+    if dir.relative_path().components().next().is_some() {
+        // absolute link, request path could be without trailing slash
+        let trimmed = base.trim_end_matches('/');
+        let parent = &trimmed[..trimmed.rfind('/').map(|idx| idx + 1).unwrap_or(0)];
+        let _ = writeln!(
+            body,
+            "<tr><td><a href=\"{}\">../</a></td><td></td><td></td></tr>",
+            escape_html(parent)
+        );
+    }
+    for entry in &dir.entries {
+        let slash = if entry.is_dir { "/" } else { "" };
+        let _ = write!(
+            body,
+            "<tr><td><a href=\"{}{}{}\">{}{}</a></td>",
+            escape_html(&base),
+            utf8_percent_encode(&entry.name, SEGMENT),
+            slash,
+            escape_html(&entry.name),
+            slash,
+        );
+        if entry.is_dir {
+            body.push_str("<td>-</td>");
+        } else {
+            let _ = write!(body, "<td>{}</td>", entry.size);
+        }
+        let modified = entry.modified.map(httpdate::fmt_http_date);
+        let _ = writeln!(body, "<td>{}</td></tr>", modified.unwrap_or_default());
+    }
+    body.push_str("</table>\n</body>\n</html>\n");
+
+    Ok(HttpResponse::Ok()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(body))
+}
+
+fn escape_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            _ => res.push(ch),
+        }
+    }
+    res
+}
@@ -0,0 +1,969 @@
+use std::path::{Path, PathBuf};
+use std::task::{Context, Poll};
+use std::{fmt, fs::File, future::Future, io, pin::Pin, rc::Rc};
+
+use percent_encoding::percent_decode_str;
+
+use crate::http::header::{self, ContentEncoding, HeaderValue};
+use crate::http::Method;
+use crate::router::ResourceDef;
+use crate::service::{Service, ServiceFactory};
+use crate::util::Ready;
+use crate::web::error::{ErrorRenderer, UriSegmentError};
+use crate::web::service::{WebServiceConfig, WebServiceFactory};
+use crate::web::{block, HttpRequest, HttpResponse, WebRequest, WebResponse};
+
+use super::directory::{directory_listing, Directory, DirectoryRenderer};
+use super::named::{precompressed_encodings, NamedFile};
+
+/// Static files handling service.
+///
+/// `Files` service must be registered with `App::service()` method.
+/// Requests for directories are served with index file if it is configured,
+/// otherwise directory listing is rendered if it is enabled.
+///
+/// ```rust
+/// use ntex::web::{self, fs, App};
+///
+/// fn main() {
+///     let app = App::new()
+///         .service(fs::Files::new("/static", ".").show_files_listing());
+/// }
+/// ```
+pub struct Files {
+    path: String,
+    inner: Rc<FilesInner>,
+}
+
+struct FilesInner {
+    directory: PathBuf,
+    index: Option<String>,
+    show_index: bool,
+    prefer_utf8: bool,
+    use_etag: bool,
+    use_last_modified: bool,
+    cache_control: Option<HeaderValue>,
+    hidden_files: bool,
+    precompressed: bool,
+    fallback: bool,
+    asset_extensions: Vec<String>,
+    renderer: Rc<DirectoryRenderer>,
+}
+
+/// Extensions of files that are never replaced by index file
+const ASSET_EXTENSIONS: &[&str] = &[
+    "js", "mjs", "css", "map", "json", "wasm", "png", "jpg", "jpeg", "gif", "svg", "ico",
+    "webp", "woff", "woff2", "ttf", "txt", "xml",
+];
+
+impl fmt::Debug for Files {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Files")
+            .field("path", &self.path)
+            .field("directory", &self.inner.directory)
+            .field("index", &self.inner.index)
+            .field("show_index", &self.inner.show_index)
+            .field("prefer_utf8", &self.inner.prefer_utf8)
+            .field("use_etag", &self.inner.use_etag)
+            .field("use_last_modified", &self.inner.use_last_modified)
+            .field("cache_control", &self.inner.cache_control)
+            .field("hidden_files", &self.inner.hidden_files)
+            .field("precompressed", &self.inner.precompressed)
+            .field("fallback", &self.inner.fallback)
+            .finish()
+    }
+}
+
+impl Files {
+    /// Create new `Files` instance for specified base directory.
+    ///
+    /// `Files` service serves files from `serve_from` directory
+    /// under `mount_path` prefix.
+    pub fn new<T: Into<PathBuf>>(mount_path: &str, serve_from: T) -> Self {
+        let dir = serve_from.into();
+        let directory = match dir.canonicalize() {
+            Ok(dir) => dir,
+            Err(e) => {
+                log::error!("Specified path is not a directory {:?}: {}", dir, e);
+                dir
+            }
+        };
+
+        Files {
+            path: if mount_path.len() > 1 {
+                mount_path.trim_end_matches('/').to_string()
+            } else {
+                mount_path.to_string()
+            },
+            inner: Rc::new(FilesInner {
+                directory,
+                index: None,
+                show_index: false,
+                prefer_utf8: false,
+                use_etag: true,
+                use_last_modified: true,
+                cache_control: None,
+                hidden_files: false,
+                precompressed: false,
+                fallback: false,
+                asset_extensions: ASSET_EXTENSIONS.iter().map(|s| s.to_string()).collect(),
+                renderer: Rc::new(directory_listing),
+            }),
+        }
+    }
+
+    /// Set index file.
+    ///
+    /// Index file is served for requests to directories.
+    pub fn index_file<T: Into<String>>(mut self, index: T) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .index = Some(index.into());
+        self
+    }
+
+    /// Show files listing for directories.
+    ///
+    /// Directory listing is rendered only if index file is not
+    /// configured or does not exist. By default listing is disabled
+    /// and *Not Found* response is returned for directories.
+    pub fn show_files_listing(mut self) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .show_index = true;
+        self
+    }
+
+    /// Serve index file for unknown paths.
+    ///
+    /// Single-page applications handle routing on the client side, so
+    /// any path under the mount point must be served with index file.
+    /// Fallback applies only to requests that accept `text/html` content
+    /// and which path does not have one of the asset extensions, requests
+    /// for missing assets still get *Not Found* response. Configured index
+    /// file from the base directory is used, `index.html` otherwise.
+    pub fn fallback_to_index(mut self) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .fallback = true;
+        self
+    }
+
+    /// Set file extensions that are not replaced by index file.
+    ///
+    /// By default list contains common web asset extensions, like
+    /// `js`, `css`, `png`, `woff2`, etc.
+    pub fn fallback_asset_extensions<I, T>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .asset_extensions = extensions.into_iter().map(|ext| ext.into()).collect();
+        self
+    }
+
+    /// Specifies whether hidden files could be served.
+    ///
+    /// Hidden files and directories are the ones which name starts
+    /// with `.`. By default requests for hidden files are rejected.
+    /// Hidden files are never shown in directory listing.
+    pub fn serve_hidden_files(mut self, value: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .hidden_files = value;
+        self
+    }
+
+    /// Serve precompressed sidecar files.
+    ///
+    /// If enabled, `app.js.br` or `app.js.gz` file is served for `app.js`
+    /// request, if it exists and client accepts corresponding encoding.
+    /// Responses get `Content-Encoding` and `Vary: Accept-Encoding` headers,
+    /// content type is detected from the original file name.
+    /// Default is `false`.
+    pub fn use_precompressed(mut self, value: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .precompressed = value;
+        self
+    }
+
+    /// Specifies whether text responses should signal a UTF-8 encoding.
+    ///
+    /// If enabled, `; charset=utf-8` is appended to `text/*` and
+    /// javascript content types. Default is `false`.
+    pub fn prefer_utf8(mut self, value: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .prefer_utf8 = value;
+        self
+    }
+
+    /// Specifies whether to use `ETag` header.
+    ///
+    /// If disabled, entity tag is not computed and `If-Match`/`If-None-Match`
+    /// preconditions never match. Default is `true`.
+    pub fn use_etag(mut self, value: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .use_etag = value;
+        self
+    }
+
+    /// Specifies whether to use `Last-Modified` header.
+    ///
+    /// If disabled, `If-Modified-Since`/`If-Unmodified-Since` headers
+    /// are ignored. Default is `true`.
+    pub fn use_last_modified(mut self, value: bool) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .use_last_modified = value;
+        self
+    }
+
+    /// Set `Cache-Control` header for file responses.
+    ///
+    /// By default `Cache-Control` header is not set. Disabling `ETag` and
+    /// `Last-Modified` headers together with `no-store` value is useful
+    /// for development setups where stale caches are undesirable.
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn cache_control(mut self, value: &str) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .cache_control =
+            Some(HeaderValue::from_str(value).expect("Cannot create header value"));
+        self
+    }
+
+    /// Set custom directory listing renderer.
+    ///
+    /// Listing must be enabled with `show_files_listing()` method.
+    pub fn files_listing_renderer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Directory, &HttpRequest) -> io::Result<HttpResponse> + 'static,
+    {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .renderer = Rc::new(f);
+        self
+    }
+}
+
+impl<Err> WebServiceFactory<Err> for Files
+where
+    Err: ErrorRenderer,
+    io::Error: Into<Err::Container>,
+    UriSegmentError: Into<Err::Container>,
+{
+    fn register(self, config: &mut WebServiceConfig<Err>) {
+        let rdef = if config.is_root() || !self.path.is_empty() {
+            ResourceDef::root_prefix(self.path.as_str())
+        } else {
+            ResourceDef::prefix(self.path.as_str())
+        };
+        config.register_service(rdef, None, self, None)
+    }
+}
+
+impl<Err> ServiceFactory<WebRequest<Err>> for Files
+where
+    Err: ErrorRenderer,
+    io::Error: Into<Err::Container>,
+    UriSegmentError: Into<Err::Container>,
+{
+    type Response = WebResponse;
+    type Error = Err::Container;
+    type Service = FilesService;
+    type InitError = ();
+    type Future = Ready<FilesService, ()>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        Ready::Ok(FilesService(self.inner.clone()))
+    }
+}
+
+/// Static files service
+pub struct FilesService(Rc<FilesInner>);
+
+impl<Err> Service<WebRequest<Err>> for FilesService
+where
+    Err: ErrorRenderer,
+    io::Error: Into<Err::Container>,
+    UriSegmentError: Into<Err::Container>,
+{
+    type Response = WebResponse;
+    type Error = Err::Container;
+    type Future = Pin<Box<dyn Future<Output = Result<WebResponse, Err::Container>>>>;
+
+    #[inline]
+    fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&self, req: WebRequest<Err>) -> Self::Future {
+        let inner = self.0.clone();
+
+        Box::pin(async move {
+            let (req, _) = req.into_parts();
+            if req.method() != Method::GET && req.method() != Method::HEAD {
+                let resp = HttpResponse::MethodNotAllowed()
+                    .header(header::ALLOW, "GET, HEAD")
+                    .finish();
+                return Ok(WebResponse::new(resp, req));
+            }
+
+            let path = match parse_path(req.match_info().unprocessed(), inner.hidden_files)
+            {
+                Ok(path) => path,
+                Err(e) => return Ok(WebResponse::from_err::<Err, _>(e, req)),
+            };
+
+            let fallback = if inner.fallback && accepts_html(&req) && !inner.is_asset(&path)
+            {
+                Some(
+                    inner
+                        .index
+                        .clone()
+                        .unwrap_or_else(|| "index.html".to_string()),
+                )
+            } else {
+                None
+            };
+            let encodings = if inner.precompressed {
+                Some(precompressed_encodings(&req))
+            } else {
+                None
+            };
+            let base = inner.directory.clone();
+            let index = inner.index.clone();
+            let show_index = inner.show_index;
+            let res = block(move || {
+                let encodings = encodings.as_deref();
+                match resolve(base.clone(), path, index, show_index, encodings) {
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => match fallback {
+                        Some(index) => resolve_file(&base, base.join(index), encodings),
+                        None => Err(e),
+                    },
+                    res => res,
+                }
+            })
+            .await
+            .map_err(io::Error::from);
+
+            let res = match res {
+                Ok(Resolved::File(file)) => {
+                    let mut file = file
+                        .use_etag(inner.use_etag)
+                        .use_last_modified(inner.use_last_modified)
+                        .prefer_utf8(inner.prefer_utf8);
+                    if let Some(ref value) = inner.cache_control {
+                        file = file.with_header(header::CACHE_CONTROL, value.clone());
+                    }
+                    Ok(file.into_response(&req))
+                }
+                Ok(Resolved::Directory(dir)) => (*inner.renderer)(&dir, &req),
+                Err(e) => Err(e),
+            };
+            Ok(match res {
+                Ok(resp) => WebResponse::new(resp, req),
+                Err(e) => WebResponse::from_err::<Err, _>(e, req),
+            })
+        })
+    }
+}
+
+impl FilesInner {
+    /// Check if path has one of the asset extensions
+    fn is_asset(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| {
+                self.asset_extensions
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(ext))
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Check if request accepts html content
+fn accepts_html(req: &HttpRequest) -> bool {
+    req.headers().get_all(header::ACCEPT).any(|val| {
+        val.to_str()
+            .map(|s| s.contains("text/html"))
+            .unwrap_or(false)
+    })
+}
+
+enum Resolved {
+    File(Box<NamedFile>),
+    Directory(Directory),
+}
+
+/// Find file or directory for the request, runs on a thread pool
+fn resolve(
+    base: PathBuf,
+    path: PathBuf,
+    index: Option<String>,
+    show_index: bool,
+    encodings: Option<&[ContentEncoding]>,
+) -> io::Result<Resolved> {
+    let path = base.join(path);
+    within(&base, &path)?;
+
+    if path.is_dir() {
+        if let Some(index) = index {
+            let path = path.join(index);
+            if path.is_file() {
+                return resolve_file(&base, path, encodings);
+            }
+        }
+        if show_index {
+            Directory::read(base, path).map(Resolved::Directory)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Directory listing is disabled",
+            ))
+        }
+    } else {
+        resolve_file(&base, path, encodings)
+    }
+}
+
+fn resolve_file(
+    base: &Path,
+    path: PathBuf,
+    encodings: Option<&[ContentEncoding]>,
+) -> io::Result<Resolved> {
+    let file = match encodings {
+        Some(encodings) => NamedFile::from_precompressed(path, encodings, Some(base)),
+        None => {
+            within(base, &path)?;
+            NamedFile::from_file(File::open(&path)?, path)
+        }
+    };
+    file.map(|f| Resolved::File(Box::new(f)))
+}
+
+/// Check that path with resolved symlinks stays inside of the base directory
+pub(super) fn within(base: &Path, path: &Path) -> io::Result<()> {
+    if path.canonicalize()?.starts_with(base) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Path is outside of the base directory",
+        ))
+    }
+}
+
+/// Convert request path to relative file system path.
+///
+/// Path is percent-decoded, parent segments are rejected. Hidden
+/// segments are rejected unless `hidden` is set.
+fn parse_path(path: &str, hidden: bool) -> Result<PathBuf, UriSegmentError> {
+    let path = percent_decode_str(path).decode_utf8_lossy();
+    let mut buf = PathBuf::new();
+
+    for segment in path.split('/') {
+        if segment.is_empty() {
+            continue;
+        } else if segment == "." || segment == ".." || (!hidden && segment.starts_with('.'))
+        {
+            return Err(UriSegmentError::BadStart('.'));
+        } else if segment.starts_with('*') {
+            return Err(UriSegmentError::BadStart('*'));
+        } else if segment.ends_with(':') {
+            return Err(UriSegmentError::BadEnd(':'));
+        } else if segment.ends_with('>') {
+            return Err(UriSegmentError::BadEnd('>'));
+        } else if segment.ends_with('<') {
+            return Err(UriSegmentError::BadEnd('<'));
+        } else if segment.contains('\0') {
+            return Err(UriSegmentError::BadChar('\0'));
+        } else if cfg!(windows) && segment.contains('\\') {
+            return Err(UriSegmentError::BadChar('\\'));
+        }
+        buf.push(segment)
+    }
+
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::App;
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("/a/b/c.txt", false).unwrap(),
+            PathBuf::from("a/b/c.txt")
+        );
+        assert_eq!(parse_path("//a//b/", false).unwrap(), PathBuf::from("a/b"));
+        assert_eq!(parse_path("/a%20b", false).unwrap(), PathBuf::from("a b"));
+        assert_eq!(parse_path("", false).unwrap(), PathBuf::new());
+        assert_eq!(
+            parse_path("/../Cargo.toml", false),
+            Err(UriSegmentError::BadStart('.'))
+        );
+        assert_eq!(
+            parse_path("/a/%2e%2e/b", false),
+            Err(UriSegmentError::BadStart('.'))
+        );
+        assert_eq!(
+            parse_path("/a%2F..%2Fb", false),
+            Err(UriSegmentError::BadStart('.'))
+        );
+        assert_eq!(
+            parse_path("/.hidden", false),
+            Err(UriSegmentError::BadStart('.'))
+        );
+        assert_eq!(
+            parse_path("/*a", false),
+            Err(UriSegmentError::BadStart('*'))
+        );
+        assert_eq!(parse_path("/a:", false), Err(UriSegmentError::BadEnd(':')));
+        assert_eq!(
+            parse_path("/a%00b", false),
+            Err(UriSegmentError::BadChar('\0'))
+        );
+
+        assert_eq!(
+            parse_path("/.well-known/a", true).unwrap(),
+            PathBuf::from(".well-known/a")
+        );
+        assert_eq!(
+            parse_path("/a/../b", true),
+            Err(UriSegmentError::BadStart('.'))
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_files() {
+        let srv = init_service(App::new().service(Files::new("/static", "."))).await;
+
+        let req = TestRequest::with_uri("/static/Cargo.toml").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(body, std::fs::read("Cargo.toml").unwrap());
+
+        let req = TestRequest::with_uri("/static/missing.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/static/../Cargo.toml").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = TestRequest::with_uri("/static/Cargo.toml")
+            .method(Method::POST)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        // listing is disabled
+        let req = TestRequest::with_uri("/static/src/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn content_type<S, E>(srv: &S, path: &str) -> String
+    where
+        S: Service<crate::http::Request, Response = WebResponse, Error = E>,
+        E: fmt::Debug,
+    {
+        let req = TestRequest::with_uri(path).to_request();
+        let resp = call_service(srv, req).await;
+        resp.headers()
+            .get(header::CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[crate::rt_test]
+    async fn test_files_prefer_utf8() {
+        let dir = std::env::temp_dir().join("ntex-files-prefer-utf8");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("index.html"), "<p>привет</p>").unwrap();
+        std::fs::write(dir.join("app.js"), "alert('привет')").unwrap();
+        std::fs::write(dir.join("data.bin"), "data").unwrap();
+
+        let srv = init_service(App::new().service(Files::new("/", &dir))).await;
+        assert_eq!(content_type(&srv, "/index.html").await, "text/html");
+
+        let srv =
+            init_service(App::new().service(Files::new("/", &dir).prefer_utf8(true))).await;
+        assert_eq!(
+            content_type(&srv, "/index.html").await,
+            "text/html; charset=utf-8"
+        );
+        assert!(content_type(&srv, "/app.js")
+            .await
+            .ends_with("javascript; charset=utf-8"));
+        assert_eq!(
+            content_type(&srv, "/data.bin").await,
+            "application/octet-stream"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_files_cache_headers() {
+        let srv = init_service(App::new().service(Files::new("/", "src/web"))).await;
+        let req = TestRequest::with_uri("/app.rs").to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(resp.headers().contains_key(header::ETAG));
+        assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+        assert!(!resp.headers().contains_key(header::CACHE_CONTROL));
+        let etag = resp.headers().get(header::ETAG).unwrap().clone();
+
+        let srv = init_service(
+            App::new().service(
+                Files::new("/", "src/web")
+                    .use_etag(false)
+                    .use_last_modified(false)
+                    .cache_control("no-store"),
+            ),
+        )
+        .await;
+        let req = TestRequest::with_uri("/app.rs")
+            .header(header::IF_NONE_MATCH, etag)
+            .header(header::IF_MODIFIED_SINCE, "Fri, 01 Jan 2100 00:00:00 GMT")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(!resp.headers().contains_key(header::ETAG));
+        assert!(!resp.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_files_fallback() {
+        let dir = std::env::temp_dir().join("ntex-files-fallback");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("index.html"), "<p>app</p>").unwrap();
+        std::fs::write(dir.join("assets/app.js"), "app()").unwrap();
+        std::fs::write(dir.join(".env"), "secret").unwrap();
+
+        let srv =
+            init_service(App::new().service(Files::new("/", &dir).fallback_to_index()))
+                .await;
+
+        // deep client-side route
+        let req = TestRequest::with_uri("/users/42/profile")
+            .header(header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, "<p>app</p>");
+
+        // existing asset
+        let req = TestRequest::with_uri("/assets/app.js")
+            .header(header::ACCEPT, "text/html")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "app()");
+
+        // missing asset
+        let req = TestRequest::with_uri("/assets/missing.js")
+            .header(header::ACCEPT, "text/html")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // client does not accept html
+        let req = TestRequest::with_uri("/users/42")
+            .header(header::ACCEPT, "application/json")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // hidden file
+        let req = TestRequest::with_uri("/.env")
+            .header(header::ACCEPT, "text/html")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let srv = init_service(
+            App::new().service(
+                Files::new("/", &dir)
+                    .fallback_to_index()
+                    .fallback_asset_extensions(vec!["css"])
+                    .serve_hidden_files(true),
+            ),
+        )
+        .await;
+        let req = TestRequest::with_uri("/assets/missing.js")
+            .header(header::ACCEPT, "text/html")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "<p>app</p>");
+
+        let req = TestRequest::with_uri("/.env").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "secret");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_files_precompressed() {
+        let dir = std::env::temp_dir().join("ntex-files-precompressed");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.js"), "plain").unwrap();
+        std::fs::write(dir.join("app.js.br"), "brotli").unwrap();
+        std::fs::write(dir.join("app.js.gz"), "gzip").unwrap();
+        std::fs::write(dir.join("lib.js"), "plain").unwrap();
+        std::fs::write(dir.join("lib.js.gz"), "gzip").unwrap();
+        std::fs::write(dir.join("main.js"), "plain").unwrap();
+
+        let srv =
+            init_service(App::new().service(Files::new("/", &dir).use_precompressed(true)))
+                .await;
+        let get = |path: &'static str, accept: &'static str| {
+            let req = TestRequest::with_uri(path)
+                .header(header::ACCEPT_ENCODING, accept)
+                .to_request();
+            call_service(&srv, req)
+        };
+
+        let mut etags = Vec::new();
+        for (accept, encoding, body) in [
+            ("gzip, deflate, br", Some("br"), "brotli"),
+            ("gzip", Some("gzip"), "gzip"),
+            ("br;q=0.5, gzip", Some("gzip"), "gzip"),
+            ("*", Some("br"), "brotli"),
+            ("identity", None, "plain"),
+        ] {
+            let resp = get("/app.js", accept).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert!(resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .contains("javascript"));
+            assert_eq!(
+                resp.headers().get(header::CONTENT_ENCODING),
+                encoding.map(HeaderValue::from_static).as_ref()
+            );
+            assert_eq!(resp.headers().get(header::VARY).unwrap(), "accept-encoding");
+            etags.push(resp.headers().get(header::ETAG).unwrap().clone());
+            assert_eq!(read_body(resp).await, body);
+        }
+        assert_ne!(etags[0], etags[1]);
+        assert_ne!(etags[1], etags[4]);
+
+        // only gzip variant exists
+        let resp = get("/lib.js", "gzip, br").await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_ENCODING).unwrap(),
+            "gzip"
+        );
+        assert_eq!(read_body(resp).await, "gzip");
+
+        let resp = get("/lib.js", "br, gzip;q=0").await;
+        assert!(!resp.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(read_body(resp).await, "plain");
+
+        // no precompressed variants
+        let resp = get("/main.js", "gzip, br").await;
+        assert!(!resp.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "accept-encoding");
+        assert_eq!(read_body(resp).await, "plain");
+
+        // precompressed files are disabled by default
+        let srv = init_service(App::new().service(Files::new("/", &dir))).await;
+        let req = TestRequest::with_uri("/app.js")
+            .header(header::ACCEPT_ENCODING, "br")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(!resp.headers().contains_key(header::CONTENT_ENCODING));
+        assert!(!resp.headers().contains_key(header::VARY));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_files_index() {
+        let srv = init_service(
+            App::new().service(
+                Files::new("/", "src/web")
+                    .index_file("app.rs")
+                    .show_files_listing(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(body, std::fs::read("src/web/app.rs").unwrap());
+
+        // no index file, listing is rendered
+        let req = TestRequest::with_uri("/types/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("<a href=\"/types/json.rs\">json.rs</a>"));
+    }
+
+    #[crate::rt_test]
+    async fn test_files_listing() {
+        let dir = std::env::temp_dir().join("ntex-files-listing");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub dir")).unwrap();
+        std::fs::create_dir_all(dir.join("empty")).unwrap();
+        std::fs::write(dir.join("a <b>.txt"), "0123456789").unwrap();
+        std::fs::write(dir.join(".hidden"), "hidden").unwrap();
+
+        let srv = init_service(
+            App::new().service(Files::new("/files", &dir).show_files_listing()),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/files/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8"
+        );
+        let body = read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("Index of /files/"));
+        assert!(body.contains("<a href=\"/files/a%20%3Cb%3E.txt\">a &lt;b&gt;.txt</a>"));
+        assert!(body.contains("<td>10</td>"));
+        assert!(body.contains("<a href=\"/files/sub%20dir/\">sub dir/</a>"));
+        assert!(body.contains("<a href=\"/files/empty/\">empty/</a>"));
+        assert!(!body.contains("hidden"));
+        assert!(!body.contains("../"));
+        // directories go first
+        assert!(body.find("empty/").unwrap() < body.find("sub dir/").unwrap());
+        assert!(body.find("sub dir/").unwrap() < body.find("a &lt;b&gt;").unwrap());
+
+        let req = TestRequest::with_uri("/files/empty").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("Index of /files/empty/"));
+        assert!(body.contains("<a href=\"/files/\">../</a>"));
+        assert_eq!(body.matches("<a href").count(), 1);
+
+        let req = TestRequest::with_uri("/files/sub%20dir/").to_request();
+        let resp = call_service(&srv, req).await;
+        let body = read_body(resp).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains("<a href=\"/files/\">../</a>"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[crate::rt_test]
+    async fn test_files_symlink_outside() {
+        let root = std::env::temp_dir().join("ntex-files-symlink");
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("public");
+        std::fs::create_dir_all(dir.join("inner")).unwrap();
+        std::fs::create_dir_all(root.join("private")).unwrap();
+        std::fs::write(root.join("secret.txt"), "secret").unwrap();
+        std::fs::write(dir.join("inner/a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink(root.join("secret.txt"), dir.join("secret.txt"))
+            .unwrap();
+        std::os::unix::fs::symlink(root.join("private"), dir.join("private")).unwrap();
+        std::os::unix::fs::symlink(dir.join("inner/a.txt"), dir.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(root.join("secret.txt"), dir.join("inner/a.txt.gz"))
+            .unwrap();
+
+        let srv = init_service(
+            App::new().service(
+                Files::new("/", &dir)
+                    .show_files_listing()
+                    .use_precompressed(true),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/secret.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/private/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // links inside of the base directory are allowed
+        let req = TestRequest::with_uri("/link.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, "a");
+
+        // sidecar file outside of the base directory is ignored
+        let req = TestRequest::with_uri("/inner/a.txt")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(read_body(resp).await, "a");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[crate::rt_test]
+    async fn test_files_listing_renderer() {
+        let dir = std::env::temp_dir().join("ntex-files-listing-renderer");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+
+        let srv = init_service(
+            App::new().service(
+                Files::new("/", &dir)
+                    .show_files_listing()
+                    .files_listing_renderer(|dir, req| {
+                        let names: Vec<_> =
+                            dir.entries.iter().map(|e| e.name.as_str()).collect();
+                        Ok(HttpResponse::Ok().body(format!(
+                            "{}: {}",
+                            req.path(),
+                            names.join(",")
+                        )))
+                    }),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, "/: a.txt,b.txt");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
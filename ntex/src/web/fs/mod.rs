@@ -0,0 +1,11 @@
+//! Static files support
+mod chunked;
+mod directory;
+mod files;
+mod named;
+mod range;
+
+pub use self::directory::{Directory, DirectoryEntry};
+pub use self::files::{Files, FilesService};
+pub use self::named::NamedFile;
+pub use self::range::HttpRange;
@@ -0,0 +1,1023 @@
+use std::fs::{File, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{cmp, convert::TryFrom, fmt, io, mem};
+
+use bitflags::bitflags;
+use mime::Mime;
+
+use crate::http::body::Body;
+use crate::http::header::{
+    self, ContentDisposition, ContentEncoding, HeaderName, HeaderValue,
+};
+use crate::http::{HeaderMap, Method, Response, ResponseBuilder, StatusCode};
+use crate::web::error::ErrorRenderer;
+use crate::web::responder::{Ready, Responder};
+use crate::web::{block, HttpRequest};
+
+use super::{chunked::ChunkedReadFile, files::within, range::HttpRange};
+
+/// A file with an associated name.
+///
+/// `NamedFile` responds with file content and `Content-Type`, `Content-Length`,
+/// `Last-Modified`, `ETag` and `Accept-Ranges` headers. Conditional request
+/// headers are evaluated against file metadata, `304 Not Modified` or
+/// `412 Precondition Failed` response is returned if needed. `Range` requests
+/// are served with `206 Partial Content`, only first range of multi-range
+/// request is served.
+///
+/// ```rust
+/// use ntex::web::{self, fs::NamedFile, App};
+///
+/// async fn index() -> std::io::Result<NamedFile> {
+///     NamedFile::open("static/index.html").await
+/// }
+///
+/// fn main() {
+///     let app = App::new().route("/", web::get().to(index));
+/// }
+/// ```
+pub struct NamedFile {
+    path: PathBuf,
+    file: File,
+    md: Metadata,
+    modified: Option<SystemTime>,
+    content_type: Mime,
+    disposition: Option<ContentDisposition>,
+    status: StatusCode,
+    encoding: Option<ContentEncoding>,
+    flags: Flags,
+    headers: HeaderMap,
+}
+
+bitflags! {
+    struct Flags: u8 {
+        const ETAG = 0b0000_0001;
+        const LAST_MD = 0b0000_0010;
+        const PREFER_UTF8 = 0b0000_0100;
+        const VARY = 0b0000_1000;
+    }
+}
+
+impl fmt::Debug for NamedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedFile")
+            .field("path", &self.path)
+            .field("content_type", &self.content_type)
+            .field("encoding", &self.encoding)
+            .field("size", &self.md.len())
+            .finish()
+    }
+}
+
+impl NamedFile {
+    /// Attempt to open file in read-only mode.
+    ///
+    /// File is opened and its metadata is read on a thread pool.
+    /// Content type is detected from file extension.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<NamedFile> {
+        let path = path.as_ref().to_path_buf();
+        block(move || {
+            let file = File::open(&path)?;
+            NamedFile::from_file(file, path)
+        })
+        .await
+        .map_err(io::Error::from)
+    }
+
+    /// Attempt to open precompressed variant of the file.
+    ///
+    /// Sidecar files with `.br` and `.gz` extensions are checked for
+    /// encodings accepted by the request's `Accept-Encoding` header, best
+    /// matching one is served with `Content-Encoding` header. Plain file is
+    /// opened if no acceptable sidecar file exists. Content type is detected
+    /// from the original file name, responses get `Vary: Accept-Encoding` header.
+    pub async fn open_precompressed<P: AsRef<Path>>(
+        path: P,
+        req: &HttpRequest,
+    ) -> io::Result<NamedFile> {
+        let path = path.as_ref().to_path_buf();
+        let encodings = precompressed_encodings(req);
+        block(move || NamedFile::from_precompressed(path, &encodings, None))
+            .await
+            .map_err(io::Error::from)
+    }
+
+    /// Open best sidecar file from the list of encodings, blocks current thread
+    ///
+    /// If `base` is set, sidecar files resolving outside of it are skipped.
+    pub(super) fn from_precompressed(
+        path: PathBuf,
+        encodings: &[ContentEncoding],
+        base: Option<&Path>,
+    ) -> io::Result<NamedFile> {
+        for enc in encodings {
+            let mut sidecar = path.clone().into_os_string();
+            sidecar.push(if *enc == ContentEncoding::Br {
+                ".br"
+            } else {
+                ".gz"
+            });
+            if let Some(base) = base {
+                if within(base, Path::new(&sidecar)).is_err() {
+                    continue;
+                }
+            }
+            if let Ok(file) = File::open(&sidecar) {
+                if let Ok(mut f) = NamedFile::from_file(file, &path) {
+                    f.encoding = Some(*enc);
+                    f.flags.insert(Flags::VARY);
+                    return Ok(f);
+                }
+            }
+        }
+
+        if let Some(base) = base {
+            within(base, &path)?;
+        }
+        let file = File::open(&path)?;
+        let mut f = NamedFile::from_file(file, path)?;
+        f.flags.insert(Flags::VARY);
+        Ok(f)
+    }
+
+    /// Create `NamedFile` from already opened file.
+    ///
+    /// `path` is used for content type detection. This method reads file
+    /// metadata and blocks current thread.
+    pub fn from_file<P: AsRef<Path>>(file: File, path: P) -> io::Result<NamedFile> {
+        let md = file.metadata()?;
+        if !md.is_file() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Not a file"));
+        }
+        let path = path.as_ref().to_path_buf();
+        let modified = md.modified().ok();
+        let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+
+        Ok(NamedFile {
+            path,
+            file,
+            md,
+            modified,
+            content_type,
+            disposition: None,
+            status: StatusCode::OK,
+            encoding: None,
+            flags: Flags::ETAG | Flags::LAST_MD,
+            headers: HeaderMap::new(),
+        })
+    }
+
+    #[inline]
+    /// Returns reference to the underlying `File` object.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    #[inline]
+    /// Retrieve the path of this file.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    #[inline]
+    /// Returns file metadata.
+    pub fn metadata(&self) -> &Metadata {
+        &self.md
+    }
+
+    #[inline]
+    /// Returns detected content type.
+    pub fn content_type(&self) -> &Mime {
+        &self.content_type
+    }
+
+    #[inline]
+    /// Returns content encoding of precompressed file.
+    pub fn content_encoding(&self) -> Option<ContentEncoding> {
+        self.encoding
+    }
+
+    #[inline]
+    /// Returns file modification time, if it is available on the platform.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Specifies whether text responses should signal a UTF-8 encoding.
+    ///
+    /// If enabled, `; charset=utf-8` is appended to `text/*` and
+    /// javascript content types. Default is `false`.
+    pub fn prefer_utf8(mut self, value: bool) -> Self {
+        self.flags.set(Flags::PREFER_UTF8, value);
+        self
+    }
+
+    /// Add header to the response.
+    ///
+    /// Headers are added to all responses, including `304 Not Modified`
+    /// and `206 Partial Content` ones, and replace headers set by `NamedFile`.
+    ///
+    /// Panics if header name or value is not valid.
+    pub fn with_header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        HeaderValue: TryFrom<V>,
+    {
+        let key = HeaderName::try_from(key)
+            .unwrap_or_else(|_| panic!("Cannot create header name"));
+        let value = HeaderValue::try_from(value)
+            .unwrap_or_else(|_| panic!("Cannot create header value"));
+        self.headers.append(key, value);
+        self
+    }
+
+    /// Set content type of the response.
+    ///
+    /// By default content type is guessed from the file extension.
+    pub fn set_content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Set `Content-Disposition` header of the response.
+    ///
+    /// By default `Content-Disposition` header is not sent and content is
+    /// displayed inline. If file name is not set, name of the file is used.
+    pub fn set_content_disposition(mut self, cd: ContentDisposition) -> Self {
+        self.disposition = Some(cd);
+        self
+    }
+
+    /// Set status code of the response, default is `200 OK`.
+    ///
+    /// Conditional and range request headers are ignored for responses
+    /// with status code other than `200 OK`, `ETag` and `Last-Modified`
+    /// headers are not sent.
+    pub fn set_status_code(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Specifies whether `ETag` header is used for the response.
+    ///
+    /// If disabled, entity tag is not computed. Default is `true`.
+    pub fn use_etag(mut self, value: bool) -> Self {
+        self.flags.set(Flags::ETAG, value);
+        self
+    }
+
+    /// Specifies whether `Last-Modified` header is used for the response.
+    ///
+    /// Default is `true`.
+    pub fn use_last_modified(mut self, value: bool) -> Self {
+        self.flags.set(Flags::LAST_MD, value);
+        self
+    }
+
+    /// Returns entity tag of the file.
+    ///
+    /// Entity tag is computed from inode number, size and modification time,
+    /// precompressed files get content encoding suffix.
+    pub fn etag(&self) -> Option<String> {
+        self.modified.map(|mtime| {
+            let dur = mtime.duration_since(UNIX_EPOCH).unwrap_or_default();
+            let suffix = match self.encoding {
+                Some(enc) => format!("-{}", enc.as_str()),
+                None => String::new(),
+            };
+            format!(
+                "\"{:x}:{:x}:{:x}:{:x}{}\"",
+                inode(&self.md),
+                self.md.len(),
+                dur.as_secs(),
+                dur.subsec_nanos(),
+                suffix
+            )
+        })
+    }
+
+    /// Create response for the specified request.
+    pub fn into_response(mut self, req: &HttpRequest) -> Response {
+        let headers = mem::take(&mut self.headers);
+        let mut resp = self.build_response(req);
+        for (key, _) in headers.iter() {
+            resp.headers_mut().remove(key);
+        }
+        for (key, value) in headers.iter() {
+            resp.headers_mut().append(key.clone(), value.clone());
+        }
+        resp
+    }
+
+    fn build_response(self, req: &HttpRequest) -> Response {
+        if self.status != StatusCode::OK {
+            let mut resp = Response::build(self.status);
+            if self.flags.contains(Flags::VARY) {
+                resp.header(header::VARY, HeaderValue::from_static("accept-encoding"));
+            }
+            self.set_headers(&mut resp);
+            let size = self.md.len();
+            return resp.body(Body::from_message(ChunkedReadFile::new(self.file, 0, size)));
+        }
+
+        let etag = if self.flags.contains(Flags::ETAG) {
+            self.etag()
+        } else {
+            None
+        };
+        let last_modified = if self.flags.contains(Flags::LAST_MD) {
+            self.modified
+        } else {
+            None
+        };
+        let modified = last_modified.map(truncate_secs);
+        let headers = req.headers();
+
+        // If-Match and If-Unmodified-Since preconditions
+        let precondition_failed = if let Some(val) = headers.get(header::IF_MATCH) {
+            !etag_matches(val, etag.as_deref(), false)
+        } else if let Some(since) = header_date(req, header::IF_UNMODIFIED_SINCE) {
+            modified.map(|m| m > since).unwrap_or(true)
+        } else {
+            false
+        };
+
+        // If-None-Match takes precedence over If-Modified-Since
+        let not_modified = if let Some(val) = headers.get(header::IF_NONE_MATCH) {
+            etag_matches(val, etag.as_deref(), true)
+        } else if let Some(since) = header_date(req, header::IF_MODIFIED_SINCE) {
+            modified.map(|m| m <= since).unwrap_or(false)
+        } else {
+            false
+        };
+
+        let mut resp = Response::build(StatusCode::OK);
+        if let Some(ref etag) = etag {
+            resp.header(header::ETAG, etag.as_str());
+        }
+        if let Some(modified) = last_modified {
+            resp.header(header::LAST_MODIFIED, httpdate::fmt_http_date(modified));
+        }
+        resp.header(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        if self.flags.contains(Flags::VARY) {
+            resp.header(header::VARY, HeaderValue::from_static("accept-encoding"));
+        }
+
+        if precondition_failed {
+            return resp.status(StatusCode::PRECONDITION_FAILED).finish();
+        } else if not_modified {
+            return if req.method() == Method::GET || req.method() == Method::HEAD {
+                resp.status(StatusCode::NOT_MODIFIED).finish()
+            } else {
+                resp.status(StatusCode::PRECONDITION_FAILED).finish()
+            };
+        }
+
+        // Range is honored only for GET requests
+        let size = self.md.len();
+        let mut offset = 0;
+        let mut length = size;
+        if req.method() == Method::GET {
+            if let Some(val) = headers.get(header::RANGE) {
+                if if_range_matches(req, etag.as_deref(), modified) {
+                    // malformed header or unknown range unit is ignored
+                    match val.to_str().ok().and_then(|s| HttpRange::parse(s, size)) {
+                        // only first range is served for multi-range requests
+                        Some(ranges) if !ranges.is_empty() => {
+                            offset = ranges[0].start;
+                            length = ranges[0].length;
+                            resp.status(StatusCode::PARTIAL_CONTENT).header(
+                                header::CONTENT_RANGE,
+                                format!(
+                                    "bytes {}-{}/{}",
+                                    offset,
+                                    offset + length - 1,
+                                    size
+                                ),
+                            );
+                        }
+                        Some(_) => {
+                            return resp
+                                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                                .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                                .finish();
+                        }
+                        None => (),
+                    }
+                }
+            }
+        }
+
+        self.set_headers(&mut resp);
+        resp.body(Body::from_message(ChunkedReadFile::new(
+            self.file, offset, length,
+        )))
+    }
+
+    /// Set content type, encoding and disposition headers
+    fn set_headers(&self, resp: &mut ResponseBuilder) {
+        if let Some(enc) = self.encoding {
+            resp.header(header::CONTENT_ENCODING, enc.as_str());
+        }
+        if self.flags.contains(Flags::PREFER_UTF8) {
+            resp.content_type(equiv_utf8_text(self.content_type.clone()).as_ref());
+        } else {
+            resp.content_type(self.content_type.as_ref());
+        }
+        if let Some(ref cd) = self.disposition {
+            let cd = match cd.get_filename() {
+                Some(_) => cd.clone(),
+                None => match self.path.file_name() {
+                    Some(name) => cd.clone().filename(name.to_string_lossy()),
+                    None => cd.clone(),
+                },
+            };
+            resp.header(header::CONTENT_DISPOSITION, cd);
+        }
+    }
+}
+
+/// Precompressed encodings accepted by the request, best first
+pub(super) fn precompressed_encodings(req: &HttpRequest) -> Vec<ContentEncoding> {
+    let (mut br, mut gzip, mut any) = (None, None, None);
+    for val in req.headers().get_all(header::ACCEPT_ENCODING) {
+        for item in val.to_str().unwrap_or("").split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or("").trim();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .next()
+                .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            if name.eq_ignore_ascii_case("br") {
+                br = Some(q);
+            } else if name.eq_ignore_ascii_case("gzip") {
+                gzip = Some(q);
+            } else if name == "*" {
+                any = Some(q);
+            }
+        }
+    }
+
+    let mut encodings = Vec::new();
+    for (enc, q) in [(ContentEncoding::Br, br), (ContentEncoding::Gzip, gzip)] {
+        let q = q.or(any).unwrap_or(0.0);
+        if q > 0.0 {
+            encodings.push((enc, q));
+        }
+    }
+    // stable sort, br is preferred for equal quality
+    encodings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(cmp::Ordering::Equal));
+    encodings.into_iter().map(|(enc, _)| enc).collect()
+}
+
+impl<Err: ErrorRenderer> Responder<Err> for NamedFile {
+    type Error = Err::Container;
+    type Future = Ready<Response>;
+
+    fn respond_to(self, req: &HttpRequest) -> Self::Future {
+        Ready::from(self.into_response(req))
+    }
+}
+
+#[cfg(unix)]
+fn inode(md: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    md.ino()
+}
+
+#[cfg(not(unix))]
+fn inode(_: &Metadata) -> u64 {
+    0
+}
+
+/// Text and javascript content types with utf-8 charset parameter
+fn equiv_utf8_text(ct: Mime) -> Mime {
+    if ct.get_param(mime::CHARSET).is_some() {
+        return ct;
+    }
+    if ct.type_() == mime::TEXT
+        || (ct.type_() == mime::APPLICATION && ct.subtype() == mime::JAVASCRIPT)
+    {
+        format!("{}; charset=utf-8", ct).parse().unwrap_or(ct)
+    } else {
+        ct
+    }
+}
+
+/// Http dates have one second precision
+fn truncate_secs(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    UNIX_EPOCH + std::time::Duration::from_secs(secs)
+}
+
+fn header_date(req: &HttpRequest, name: header::HeaderName) -> Option<SystemTime> {
+    req.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| httpdate::parse_http_date(v).ok())
+}
+
+/// Range request is honored only if `If-Range` validator matches
+/// current representation, strong comparison is used for entity tags
+fn if_range_matches(
+    req: &HttpRequest,
+    etag: Option<&str>,
+    modified: Option<SystemTime>,
+) -> bool {
+    let val = if let Some(val) = req.headers().get(header::IF_RANGE) {
+        val
+    } else {
+        return true;
+    };
+    let val = if let Ok(val) = val.to_str() {
+        val.trim()
+    } else {
+        return false;
+    };
+
+    if val.starts_with('"') || val.starts_with("W/") {
+        etag.map(|etag| etag == val).unwrap_or(false)
+    } else if let Ok(date) = httpdate::parse_http_date(val) {
+        modified.map(|m| m == date).unwrap_or(false)
+    } else {
+        false
+    }
+}
+
+/// Match entity tag against list of tags, `If-None-Match` uses
+/// weak comparison, `If-Match` uses strong comparison
+fn etag_matches(val: &HeaderValue, etag: Option<&str>, weak: bool) -> bool {
+    let val = if let Ok(val) = val.to_str() {
+        val
+    } else {
+        return false;
+    };
+    if val.trim() == "*" {
+        return true;
+    }
+    let etag = if let Some(etag) = etag {
+        etag
+    } else {
+        return false;
+    };
+
+    val.split(',').map(|s| s.trim()).any(|tag| {
+        if weak {
+            tag.trim_start_matches("W/") == etag
+        } else {
+            tag == etag
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::http::header::{self, ContentDisposition, ContentEncoding, HeaderValue};
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App};
+
+    #[crate::rt_test]
+    async fn test_named_file() {
+        let file = NamedFile::open("Cargo.toml").await.unwrap();
+        assert_eq!(file.path(), Path::new("Cargo.toml"));
+        assert_eq!(file.content_type().type_(), mime::TEXT);
+        assert!(format!("{:?}", file).contains("NamedFile"));
+        let etag = file.etag().unwrap();
+        let size = file.metadata().len();
+
+        let req = TestRequest::default().to_http_request();
+        let resp = file.into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/x-toml"
+        );
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), etag.as_str());
+        assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(resp.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+        assert_eq!(
+            crate::http::body::MessageBody::size(resp.body()),
+            crate::http::body::BodySize::Sized(size)
+        );
+
+        let err = NamedFile::open("nonexistent.txt").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = NamedFile::open("src").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_disposition() {
+        let dir = std::env::temp_dir().join("ntex-named-file-disposition");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("отчёт.txt");
+        std::fs::write(&path, "report").unwrap();
+
+        let req = TestRequest::default().to_http_request();
+        let resp = NamedFile::open(&path).await.unwrap().into_response(&req);
+        assert!(!resp.headers().contains_key(header::CONTENT_DISPOSITION));
+
+        let resp = NamedFile::open(&path)
+            .await
+            .unwrap()
+            .set_content_type(mime::APPLICATION_OCTET_STREAM)
+            .set_content_disposition(ContentDisposition::attachment())
+            .into_response(&req);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"_____.txt\"; \
+             filename*=UTF-8''%D0%BE%D1%82%D1%87%D1%91%D1%82.txt"
+        );
+
+        let resp = NamedFile::open(&path)
+            .await
+            .unwrap()
+            .set_content_disposition(ContentDisposition::attachment().filename("r.txt"))
+            .into_response(&req);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"r.txt\""
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_custom_headers() {
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|| async {
+                NamedFile::open("Cargo.toml").await.map(|f| {
+                    f.set_content_type(mime::TEXT_PLAIN)
+                        .prefer_utf8(true)
+                        .set_content_disposition(ContentDisposition::attachment())
+                        .with_header(header::CACHE_CONTROL, "private, max-age=60")
+                        .with_header("x-custom", "1")
+                })
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"Cargo.toml\""
+        );
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, max-age=60"
+        );
+        assert_eq!(resp.headers().get("x-custom").unwrap(), "1");
+        let etag = resp.headers().get(header::ETAG).unwrap().clone();
+
+        // conditional and range requests
+        let req = TestRequest::with_uri("/")
+            .header(header::IF_NONE_MATCH, etag)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, max-age=60"
+        );
+
+        let req = TestRequest::with_uri("/")
+            .header(header::RANGE, "bytes=0-3")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(resp.headers().get("x-custom").unwrap(), "1");
+        assert_eq!(read_body(resp).await, "[pac");
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_precompressed() {
+        let dir = std::env::temp_dir().join("ntex-named-file-precompressed");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("style.css"), "plain").unwrap();
+        std::fs::write(dir.join("style.css.gz"), "gzip").unwrap();
+
+        let req = TestRequest::default()
+            .header(header::ACCEPT_ENCODING, "gzip;q=0.8, br")
+            .to_http_request();
+        let file = NamedFile::open_precompressed(dir.join("style.css"), &req)
+            .await
+            .unwrap();
+        assert_eq!(file.content_encoding(), Some(ContentEncoding::Gzip));
+        assert_eq!(file.content_type(), &mime::TEXT_CSS);
+        assert_eq!(file.path(), dir.join("style.css"));
+
+        let req = TestRequest::default()
+            .header(header::ACCEPT_ENCODING, "gzip;q=0")
+            .to_http_request();
+        let file = NamedFile::open_precompressed(dir.join("style.css"), &req)
+            .await
+            .unwrap();
+        assert_eq!(file.content_encoding(), None);
+        assert_eq!(file.metadata().len(), 5);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_status() {
+        let srv = init_service(App::new().default_service(web::to(|| async {
+            NamedFile::open("Cargo.toml")
+                .await
+                .map(|f| f.set_status_code(StatusCode::NOT_FOUND))
+        })))
+        .await;
+
+        // conditional and range headers are ignored
+        let req = TestRequest::with_uri("/missing")
+            .header(header::IF_NONE_MATCH, "*")
+            .header(header::RANGE, "bytes=0-1")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(!resp.headers().contains_key(header::ETAG));
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/x-toml"
+        );
+        let body = read_body(resp).await;
+        assert_eq!(body, std::fs::read("Cargo.toml").unwrap());
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_validators() {
+        let req = TestRequest::default().to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .use_etag(false)
+            .use_last_modified(false)
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(!resp.headers().contains_key(header::ETAG));
+        assert!(!resp.headers().contains_key(header::LAST_MODIFIED));
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_service() {
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|| async { NamedFile::open("Cargo.toml").await }),
+        ))
+        .await;
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(body, std::fs::read("Cargo.toml").unwrap());
+
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|| async { NamedFile::open("missing.toml").await }),
+        ))
+        .await;
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_large() {
+        let path = std::env::temp_dir().join("ntex-named-file-large.bin");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let req = TestRequest::default().to_http_request();
+        let resp = NamedFile::open(&path).await.unwrap().into_response(&req);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+        let resp = crate::web::WebResponse::new(resp, req);
+        assert_eq!(read_body(resp).await, data);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_ranges() {
+        let path = std::env::temp_dir().join("ntex-named-file-ranges.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+        let file = NamedFile::open(&path).await.unwrap();
+        let etag = file.etag().unwrap();
+        let modified = file.last_modified().unwrap();
+
+        let get = |range: &'static str| {
+            let path = path.clone();
+            async move {
+                let req = TestRequest::default()
+                    .header(header::RANGE, range)
+                    .to_http_request();
+                let resp = NamedFile::open(&path).await.unwrap().into_response(&req);
+                crate::web::WebResponse::new(resp, req)
+            }
+        };
+
+        let resp = get("bytes=2-5").await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 2-5/10"
+        );
+        assert_eq!(read_body(resp).await, "2345");
+
+        let resp = get("bytes=-3").await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 7-9/10"
+        );
+        assert_eq!(read_body(resp).await, "789");
+
+        // first range of multi-range request
+        let resp = get("bytes=8-100, 0-1").await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(read_body(resp).await, "89");
+
+        let resp = get("bytes=10-").await;
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */10"
+        );
+        assert_eq!(read_body(resp).await, "");
+
+        // malformed range and unknown unit are ignored
+        for range in &["bytes=5-1", "bytes=a-b", "items=0-1"] {
+            let resp = get(range).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert!(resp.headers().get(header::CONTENT_RANGE).is_none());
+            assert_eq!(read_body(resp).await, "0123456789");
+        }
+
+        // range is ignored for non-GET requests
+        let req = TestRequest::default()
+            .method(Method::POST)
+            .header(header::RANGE, "bytes=0-1")
+            .to_http_request();
+        let resp = NamedFile::open(&path).await.unwrap().into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // If-Range
+        for (val, status) in [
+            (etag.clone(), StatusCode::PARTIAL_CONTENT),
+            (format!("W/{}", etag), StatusCode::OK),
+            ("\"other\"".to_string(), StatusCode::OK),
+            (
+                httpdate::fmt_http_date(modified),
+                StatusCode::PARTIAL_CONTENT,
+            ),
+            (
+                httpdate::fmt_http_date(modified - Duration::from_secs(60)),
+                StatusCode::OK,
+            ),
+        ] {
+            let req = TestRequest::default()
+                .header(header::RANGE, "bytes=0-1")
+                .header(header::IF_RANGE, val)
+                .to_http_request();
+            let resp = NamedFile::open(&path).await.unwrap().into_response(&req);
+            assert_eq!(resp.status(), status);
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_not_modified() {
+        let file = NamedFile::open("Cargo.toml").await.unwrap();
+        let etag = file.etag().unwrap();
+        let modified = file.last_modified().unwrap();
+
+        // If-None-Match
+        let req = TestRequest::default()
+            .header(header::IF_NONE_MATCH, format!("\"other\", W/{}", etag))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), etag.as_str());
+
+        let req = TestRequest::default()
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::default()
+            .method(Method::PUT)
+            .header(header::IF_NONE_MATCH, "*")
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+        // If-Modified-Since
+        let req = TestRequest::default()
+            .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(modified))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+        let since = modified - Duration::from_secs(60);
+        let req = TestRequest::default()
+            .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(since))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // If-None-Match takes precedence
+        let req = TestRequest::default()
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(modified))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_precondition() {
+        let file = NamedFile::open("Cargo.toml").await.unwrap();
+        let etag = file.etag().unwrap();
+        let modified = file.last_modified().unwrap();
+
+        let req = TestRequest::default()
+            .header(header::IF_MATCH, etag.as_str())
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::default()
+            .header(header::IF_MATCH, format!("W/{}", etag))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+        let since = modified - Duration::from_secs(60);
+        let req = TestRequest::default()
+            .header(header::IF_UNMODIFIED_SINCE, httpdate::fmt_http_date(since))
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+
+        let req = TestRequest::default()
+            .header(
+                header::IF_UNMODIFIED_SINCE,
+                HeaderValue::from_str(&httpdate::fmt_http_date(modified)).unwrap(),
+            )
+            .to_http_request();
+        let resp = NamedFile::open("Cargo.toml")
+            .await
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
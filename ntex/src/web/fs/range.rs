@@ -0,0 +1,135 @@
+/// HTTP Range header representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpRange {
+    /// Start position of the range
+    pub start: u64,
+    /// Length of the range
+    pub length: u64,
+}
+
+const PREFIX: &str = "bytes=";
+
+impl HttpRange {
+    /// Parse `Range` header value.
+    ///
+    /// `size` is full size of response (file). Ranges are clamped to `size`,
+    /// ranges that are beyond `size` are skipped. Returns `None` if header
+    /// value is malformed or uses unknown range unit, such header should be
+    /// ignored. Returns empty list if none of the ranges could be satisfied.
+    pub fn parse(header: &str, size: u64) -> Option<Vec<HttpRange>> {
+        let header = header.trim();
+        match header.get(..PREFIX.len()) {
+            Some(unit) if unit.eq_ignore_ascii_case(PREFIX) => (),
+            _ => return None,
+        }
+
+        let mut ranges = Vec::new();
+        let mut specs = 0;
+        for spec in header[PREFIX.len()..].split(',').map(|s| s.trim()) {
+            if spec.is_empty() {
+                continue;
+            }
+            specs += 1;
+            let (start, end) = {
+                let mut parts = spec.splitn(2, '-');
+                (parts.next()?.trim(), parts.next()?.trim())
+            };
+
+            if start.is_empty() {
+                // suffix range, last N bytes
+                let length = parse_num(end)?;
+                if length == 0 || size == 0 {
+                    continue;
+                }
+                let length = length.min(size);
+                ranges.push(HttpRange {
+                    start: size - length,
+                    length,
+                });
+            } else {
+                let start = parse_num(start)?;
+                let end = if end.is_empty() {
+                    None
+                } else {
+                    let end = parse_num(end)?;
+                    if end < start {
+                        return None;
+                    }
+                    Some(end)
+                };
+                if start >= size {
+                    continue;
+                }
+                let end = end.map(|end| end.min(size - 1)).unwrap_or(size - 1);
+                ranges.push(HttpRange {
+                    start,
+                    length: end - start + 1,
+                });
+            }
+        }
+
+        if specs == 0 {
+            None
+        } else {
+            Some(ranges)
+        }
+    }
+}
+
+fn parse_num(s: &str) -> Option<u64> {
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u64, length: u64) -> HttpRange {
+        HttpRange { start, length }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(HttpRange::parse("bytes=0-4", 10), Some(vec![range(0, 5)]));
+        assert_eq!(HttpRange::parse("bytes=2-", 10), Some(vec![range(2, 8)]));
+        assert_eq!(HttpRange::parse("bytes=-3", 10), Some(vec![range(7, 3)]));
+        assert_eq!(HttpRange::parse("bytes=-20", 10), Some(vec![range(0, 10)]));
+        assert_eq!(HttpRange::parse("bytes=5-20", 10), Some(vec![range(5, 5)]));
+        assert_eq!(HttpRange::parse("Bytes= 9-9", 10), Some(vec![range(9, 1)]));
+        assert_eq!(
+            HttpRange::parse("bytes=0-0, -1", 10),
+            Some(vec![range(0, 1), range(9, 1)])
+        );
+        // unsatisfiable ranges are skipped
+        assert_eq!(
+            HttpRange::parse("bytes=20-30,0-1", 10),
+            Some(vec![range(0, 2)])
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(HttpRange::parse("", 10), None);
+        assert_eq!(HttpRange::parse("bytes=", 10), None);
+        assert_eq!(HttpRange::parse("bytés=0-1", 10), None);
+        assert_eq!(HttpRange::parse("items=0-1", 10), None);
+        assert_eq!(HttpRange::parse("bytes=1", 10), None);
+        assert_eq!(HttpRange::parse("bytes=5-1", 10), None);
+        assert_eq!(HttpRange::parse("bytes=a-b", 10), None);
+        assert_eq!(HttpRange::parse("bytes=+1-2", 10), None);
+        assert_eq!(HttpRange::parse("bytes=-", 10), None);
+        assert_eq!(HttpRange::parse("bytes=0-1,x", 10), None);
+    }
+
+    #[test]
+    fn test_parse_unsatisfiable() {
+        assert_eq!(HttpRange::parse("bytes=10-", 10), Some(vec![]));
+        assert_eq!(HttpRange::parse("bytes=20-30, 10-", 10), Some(vec![]));
+        assert_eq!(HttpRange::parse("bytes=-0", 10), Some(vec![]));
+        assert_eq!(HttpRange::parse("bytes=0-", 0), Some(vec![]));
+    }
+}
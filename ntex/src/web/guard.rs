@@ -28,7 +28,9 @@
 #![allow(non_snake_case)]
 use std::convert::TryFrom;
 
-use crate::http::{header, Method, RequestHead, Uri};
+use crate::http::{header, Method, RequestHead, Uri, Version};
+
+use super::{info::ConnectionInfo, ipnet::parse_ip, IpNetwork};
 
 /// Trait defines resource guards. Guards are used for route selection.
 ///
@@ -42,6 +44,10 @@ pub trait Guard {
 
 /// Create guard object for supplied function.
 ///
+/// Closures with `&RequestHead` argument implement `Guard` trait as well,
+/// so `.guard(|head: &RequestHead| head.method == Method::PATCH)` works
+/// without this wrapper. Argument type annotation is required for closures.
+///
 /// ```rust
 /// use ntex::web::{self, guard, App, HttpResponse};
 ///
@@ -236,6 +242,91 @@ pub fn Method(method: Method) -> MethodGuard {
     MethodGuard(method)
 }
 
+/// Predicate to match specified http version
+///
+/// ```rust
+/// use ntex::http::Version;
+/// use ntex::web::{self, guard, App, HttpResponse};
+///
+/// fn main() {
+///     App::new().service(web::resource("/index.html").route(
+///         web::get()
+///             .guard(guard::Version(Version::HTTP_2))
+///             .to(|| async { HttpResponse::Ok() }))
+///     );
+/// }
+/// ```
+pub fn Version(version: Version) -> VersionGuard {
+    VersionGuard(version)
+}
+
+#[doc(hidden)]
+pub struct VersionGuard(Version);
+
+impl Guard for VersionGuard {
+    fn check(&self, head: &RequestHead) -> bool {
+        head.version == self.0
+    }
+}
+
+/// Predicate to match client address within specified network
+///
+/// By default peer address of the connection is checked. Requests without
+/// known peer address do not match.
+///
+/// ```rust
+/// use ntex::web::{self, guard, App, HttpResponse};
+///
+/// fn main() {
+///     App::new().service(web::resource("/admin").route(
+///         web::get()
+///             .guard(guard::RemoteAddr("10.0.0.0/8".parse().unwrap()))
+///             .to(|| async { HttpResponse::Ok() }))
+///     );
+/// }
+/// ```
+pub fn RemoteAddr(net: IpNetwork) -> RemoteAddrGuard {
+    RemoteAddrGuard {
+        net,
+        forwarded: false,
+    }
+}
+
+#[doc(hidden)]
+pub struct RemoteAddrGuard {
+    net: IpNetwork,
+    forwarded: bool,
+}
+
+impl RemoteAddrGuard {
+    /// Use forwarded client address.
+    ///
+    /// If connection info is already resolved for the request, for example
+    /// by a middleware, real client address from `ConnectionInfo::remote()`
+    /// is checked instead of peer address. Enable only if application runs
+    /// behind trusted proxy, forwarding headers could be spoofed by client.
+    pub fn forwarded(mut self) -> Self {
+        self.forwarded = true;
+        self
+    }
+}
+
+impl Guard for RemoteAddrGuard {
+    fn check(&self, head: &RequestHead) -> bool {
+        let forwarded = if self.forwarded {
+            head.extensions()
+                .get::<ConnectionInfo>()
+                .and_then(|info| info.remote().and_then(parse_ip))
+        } else {
+            None
+        };
+        forwarded
+            .or_else(|| head.peer_addr().map(|addr| addr.ip()))
+            .map(|addr| self.net.contains(addr))
+            .unwrap_or(false)
+    }
+}
+
 /// Return predicate that matches if request contains specified header and
 /// value.
 pub fn Header(name: &'static str, value: &'static str) -> HeaderGuard {
@@ -257,6 +348,136 @@ impl Guard for HeaderGuard {
     }
 }
 
+/// Predicate that matches request `Content-Type` header against mime type.
+///
+/// Only type and subtype are compared, parameters like `charset` are ignored.
+/// `*` type or subtype matches any value. Subtype also matches structured
+/// syntax suffix, i.e. `application/json` matches `application/vnd.api+json`.
+/// Requests without or with invalid `Content-Type` header do not match.
+///
+/// ```rust
+/// use ntex::web::{self, guard, App, HttpResponse};
+///
+/// fn main() {
+///     App::new().service(
+///         web::resource("/index.html")
+///             .route(
+///                 web::post()
+///                     .guard(guard::ContentType::json())
+///                     .to(|| async { HttpResponse::Ok() }),
+///             )
+///             .route(
+///                 web::post()
+///                     .guard(guard::ContentType(mime::TEXT_STAR))
+///                     .to(|| async { HttpResponse::Ok() }),
+///             ),
+///     );
+/// }
+/// ```
+pub struct ContentType(pub mime::Mime);
+
+impl ContentType {
+    /// Matches `application/json` and `+json` suffixed content types.
+    pub fn json() -> Self {
+        ContentType(mime::APPLICATION_JSON)
+    }
+
+    /// Matches `application/x-www-form-urlencoded` content type.
+    pub fn form() -> Self {
+        ContentType(mime::APPLICATION_WWW_FORM_URLENCODED)
+    }
+}
+
+impl Guard for ContentType {
+    fn check(&self, req: &RequestHead) -> bool {
+        let mt = match req
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse::<mime::Mime>().ok())
+        {
+            Some(mt) => mt,
+            None => return false,
+        };
+
+        (self.0.type_() == mime::STAR || self.0.type_() == mt.type_())
+            && (self.0.subtype() == mime::STAR
+                || self.0.subtype() == mt.subtype()
+                || Some(self.0.subtype()) == mt.suffix())
+    }
+}
+
+/// Predicate to match media type listed in `Accept` header
+///
+/// The most specific matching media range is used, i.e. `text/html` over
+/// `text/*` over `*/*`. Request matches if quality value of the range is
+/// greater than zero. Requests without `Accept` header do not match.
+///
+/// ```rust
+/// use ntex::web::{self, guard, App, HttpResponse};
+///
+/// fn main() {
+///     App::new().service(
+///         web::resource("/index")
+///             .route(
+///                 web::get()
+///                     .guard(guard::Accept("application/json"))
+///                     .to(|| async { HttpResponse::Ok().json(&"index") }),
+///             )
+///             .route(
+///                 web::get()
+///                     .guard(guard::Accept("text/html"))
+///                     .to(|| async { HttpResponse::Ok().body("<p>index</p>") }),
+///             ),
+///     );
+/// }
+/// ```
+pub fn Accept(mime: &'static str) -> AcceptGuard {
+    AcceptGuard(mime.parse().unwrap())
+}
+
+#[doc(hidden)]
+pub struct AcceptGuard(mime::Mime);
+
+impl Guard for AcceptGuard {
+    fn check(&self, req: &RequestHead) -> bool {
+        // (specificity, quality) of the most specific matching range
+        let mut matched: Option<(u8, f32)> = None;
+
+        for hdr in req.headers.get_all(header::ACCEPT) {
+            let val = match hdr.to_str() {
+                Ok(val) => val,
+                Err(_) => continue,
+            };
+            for range in val.split(',') {
+                let range = match range.trim().parse::<mime::Mime>() {
+                    Ok(range) => range,
+                    Err(_) => continue,
+                };
+                let specificity = if range.type_() == mime::STAR {
+                    0
+                } else if range.type_() != self.0.type_() {
+                    continue;
+                } else if range.subtype() == mime::STAR {
+                    1
+                } else if range.subtype() == self.0.subtype() {
+                    2
+                } else {
+                    continue;
+                };
+                let q = range
+                    .get_param("q")
+                    .and_then(|q| q.as_str().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                if matched.map(|(s, _)| specificity > s).unwrap_or(true) {
+                    matched = Some((specificity, q));
+                }
+            }
+        }
+        matched.map(|(_, q)| q > 0.0).unwrap_or(false)
+    }
+}
+
 /// Return predicate that matches if request contains specified Host name.
 ///
 /// ```rust
@@ -342,6 +563,40 @@ mod tests {
         assert!(!pred.check(req.head()));
     }
 
+    #[test]
+    fn test_content_type() {
+        let ct = |val: &'static str| {
+            TestRequest::with_header(header::CONTENT_TYPE, val).to_http_request()
+        };
+
+        let pred = ContentType::json();
+        assert!(pred.check(ct("application/json").head()));
+        assert!(pred.check(ct("application/json; charset=utf-8").head()));
+        assert!(pred.check(ct("Application/JSON").head()));
+        assert!(pred.check(ct("application/vnd.api+json").head()));
+        assert!(!pred.check(ct("application/jsonp").head()));
+        assert!(!pred.check(ct("text/json").head()));
+        assert!(!pred.check(ct("application/x-www-form-urlencoded").head()));
+
+        let pred = ContentType::form();
+        assert!(pred.check(ct("application/x-www-form-urlencoded").head()));
+        assert!(pred.check(ct("application/x-www-form-urlencoded; charset=utf-8").head()));
+        assert!(!pred.check(ct("multipart/form-data; boundary=x").head()));
+
+        let pred = ContentType(mime::TEXT_STAR);
+        assert!(pred.check(ct("text/plain").head()));
+        assert!(pred.check(ct("text/html; charset=utf-8").head()));
+        assert!(!pred.check(ct("application/json").head()));
+
+        let pred = ContentType(mime::STAR_STAR);
+        assert!(pred.check(ct("image/png").head()));
+
+        // missing or invalid content type
+        assert!(!pred.check(TestRequest::default().to_http_request().head()));
+        assert!(!pred.check(ct("invalid").head()));
+        assert!(!ContentType::json().check(ct("").head()));
+    }
+
     #[test]
     fn test_host() {
         let req = TestRequest::default()
@@ -479,6 +734,95 @@ mod tests {
         assert!(!Trace().check(req.head()));
     }
 
+    #[test]
+    fn test_version() {
+        let req = TestRequest::default()
+            .version(Version::HTTP_2)
+            .to_http_request();
+
+        assert!(Version(Version::HTTP_2).check(req.head()));
+        assert!(!Version(Version::HTTP_11).check(req.head()));
+        assert!(!Version(Version::HTTP_2)
+            .check(TestRequest::default().to_http_request().head()));
+    }
+
+    #[test]
+    fn test_remote_addr() {
+        let guard = || RemoteAddr("10.0.0.0/8".parse().unwrap());
+
+        let req = TestRequest::default()
+            .peer_addr("10.1.2.3:8080".parse().unwrap())
+            .to_http_request();
+        assert!(guard().check(req.head()));
+        assert!(guard().forwarded().check(req.head()));
+
+        let req = TestRequest::default()
+            .peer_addr("192.168.0.1:8080".parse().unwrap())
+            .to_http_request();
+        assert!(!guard().check(req.head()));
+
+        // no peer address
+        let req = TestRequest::default().to_http_request();
+        assert!(!guard().check(req.head()));
+
+        // forwarded address is used only if connection info is resolved
+        let req = TestRequest::default()
+            .peer_addr("192.168.0.1:8080".parse().unwrap())
+            .header("x-forwarded-for", "10.0.0.1")
+            .to_http_request();
+        assert!(!guard().forwarded().check(req.head()));
+        assert_eq!(req.connection_info().remote(), Some("10.0.0.1"));
+        assert!(guard().forwarded().check(req.head()));
+        assert!(!guard().check(req.head()));
+
+        let req = TestRequest::default()
+            .header(header::FORWARDED, "for=\"[fd00::1]:4711\"")
+            .to_http_request();
+        let _ = req.connection_info();
+        assert!(RemoteAddr("fd00::/8".parse().unwrap())
+            .forwarded()
+            .check(req.head()));
+    }
+
+    #[test]
+    fn test_accept() {
+        let req = |accept: &'static str| {
+            TestRequest::with_header(header::ACCEPT, accept).to_http_request()
+        };
+        let json = Accept("application/json");
+        let html = Accept("text/html");
+
+        let r = req("application/json");
+        assert!(json.check(r.head()));
+        assert!(!html.check(r.head()));
+
+        let r = req("text/html, application/xhtml+xml, application/json;q=0.9");
+        assert!(json.check(r.head()));
+        assert!(html.check(r.head()));
+
+        // wildcards
+        let r = req("text/*;q=0.5");
+        assert!(html.check(r.head()));
+        assert!(!json.check(r.head()));
+        let r = req("*/*");
+        assert!(html.check(r.head()));
+        assert!(json.check(r.head()));
+
+        // explicit rejections
+        let r = req("*/*, application/json;q=0");
+        assert!(!json.check(r.head()));
+        assert!(html.check(r.head()));
+        let r = req("text/*;q=0, text/html;q=0.1");
+        assert!(html.check(r.head()));
+        assert!(!Accept("text/plain").check(r.head()));
+        let r = req("application/json;q=0.0");
+        assert!(!json.check(r.head()));
+
+        // no or invalid header
+        assert!(!json.check(TestRequest::default().to_http_request().head()));
+        assert!(!json.check(req("invalid").head()));
+    }
+
     #[test]
     fn test_preds() {
         let r = TestRequest::default()
@@ -494,4 +838,20 @@ mod tests {
         assert!(Any(Get()).or(Trace()).check(r.head()));
         assert!(!Any(Get()).or(Get()).check(r.head()));
     }
+
+    #[test]
+    fn test_fn_guard() {
+        let req = TestRequest::with_header(header::CONTENT_TYPE, "text/plain")
+            .method(Method::POST)
+            .to_http_request();
+
+        let pred = fn_guard(|head| head.headers().contains_key(header::CONTENT_TYPE));
+        assert!(pred.check(req.head()));
+        assert!(!fn_guard(|head| head.method == Method::GET).check(req.head()));
+
+        // closures are guards as well and compose with other guards
+        let pred = |head: &RequestHead| head.uri.path() == "/";
+        assert!(All(Post()).and(pred).check(req.head()));
+        assert!(!Any(Get()).or(Not(pred)).check(req.head()));
+    }
 }
@@ -1,9 +1,10 @@
-use std::{cell::Ref, cell::RefCell, cell::RefMut, fmt, net, rc::Rc};
+use std::{cell::Ref, cell::RefCell, cell::RefMut, fmt, io, net, rc::Rc};
 
 use crate::http::{
-    HeaderMap, HttpMessage, Message, Method, Payload, RequestHead, Uri, Version,
+    ClientDisconnect, HeaderMap, HttpMessage, Message, Method, Payload, RequestHead, Uri,
+    Version,
 };
-use crate::io::{types, IoRef};
+use crate::io::IoRef;
 use crate::router::Path;
 use crate::util::{Extensions, Ready};
 
@@ -23,6 +24,8 @@ pub(crate) struct HttpRequestInner {
     pub(crate) path: Path<Uri>,
     pub(crate) payload: Payload,
     pub(crate) app_state: AppState,
+    pub(crate) pattern: Option<Rc<str>>,
+    pub(crate) name: Option<Rc<str>>,
     rmap: Rc<ResourceMap>,
     pool: &'static HttpRequestPool,
 }
@@ -44,6 +47,8 @@ impl HttpRequest {
             app_state,
             rmap,
             pool,
+            pattern: None,
+            name: None,
         }))
     }
 }
@@ -92,6 +97,18 @@ impl HttpRequest {
         self.head().uri.path()
     }
 
+    /// The target path and query string of this Request.
+    ///
+    /// E.g., /index.html?id=10
+    #[inline]
+    pub fn path_and_query(&self) -> &str {
+        self.head()
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or_else(|| self.path())
+    }
+
     /// The query string in the URL.
     ///
     /// E.g., id=10
@@ -116,9 +133,39 @@ impl HttpRequest {
     /// ntex http server, then peer address would be address of this proxy.
     #[inline]
     pub fn peer_addr(&self) -> Option<net::SocketAddr> {
-        self.io()
-            .map(|io| io.query::<types::PeerAddr>().get().map(|addr| addr.0))
-            .unwrap_or(None)
+        self.head().peer_addr()
+    }
+
+    /// Get future that resolves when client disconnects
+    ///
+    /// Future resolves when underlying connection get closed or, for http/2,
+    /// when request's stream get reset. Disconnect is detected while handler
+    /// is still processing request, so long running handler could abort work.
+    ///
+    /// ```rust
+    /// use ntex::util::{select, Either};
+    /// use ntex::web::{HttpRequest, HttpResponse};
+    ///
+    /// async fn index(req: HttpRequest) -> HttpResponse {
+    ///     let work = async { /* long running computation */ };
+    ///     match select(req.on_disconnect(), work).await {
+    ///         Either::Left(_) => HttpResponse::InternalServerError().finish(),
+    ///         Either::Right(_) => HttpResponse::Ok().finish(),
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn on_disconnect(&self) -> ClientDisconnect {
+        self.head().on_disconnect()
+    }
+
+    /// Send `103 Early Hints` informational response
+    ///
+    /// Hints must be sent before final response. HTTP/1.1 and HTTP/2
+    /// requests are supported.
+    #[inline]
+    pub fn send_early_hints(&self, headers: &HeaderMap) -> io::Result<()> {
+        self.head().send_early_hints(headers)
     }
 
     /// Get a reference to the Path parameters.
@@ -132,6 +179,24 @@ impl HttpRequest {
         &self.0.path
     }
 
+    /// Get pattern of the matched resource.
+    ///
+    /// Pattern is relative to enclosing scope. Returns `None` if request
+    /// is not routed to a resource yet.
+    #[inline]
+    pub fn match_pattern(&self) -> Option<&str> {
+        self.0.pattern.as_deref()
+    }
+
+    /// Get name of the matched resource.
+    ///
+    /// Returns `None` if request is not routed to a resource yet
+    /// or resource does not have name.
+    #[inline]
+    pub fn match_name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
     #[inline]
     pub(crate) fn match_info_mut(&mut self) -> &mut Path<Uri> {
         &mut Rc::get_mut(&mut self.0).unwrap().path
@@ -149,6 +214,21 @@ impl HttpRequest {
         self.head().extensions_mut()
     }
 
+    /// Get a reference to a per-connection data of type `T`
+    ///
+    /// Per-connection data is shared between all requests dispatched
+    /// on the same connection.
+    #[inline]
+    pub fn conn_data<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        self.head().conn_data()
+    }
+
+    /// Mutable reference to a per-connection data container
+    #[inline]
+    pub fn conn_data_mut(&self) -> Option<RefMut<'_, Extensions>> {
+        self.head().conn_data_mut()
+    }
+
     #[cfg(feature = "url")]
     /// Generate url for named resource
     ///
@@ -208,6 +288,28 @@ impl HttpRequest {
         ConnectionInfo::get(self.head(), self.app_config())
     }
 
+    /// Absolute url of the request.
+    ///
+    /// Url is constructed from connection info scheme and host and
+    /// request's path and query. If connection info host is not
+    /// a valid uri authority, server host name is used instead.
+    ///
+    /// E.g., https://www.rust-lang.org/index.html?id=10
+    pub fn full_url(&self) -> Uri {
+        let info = self.connection_info();
+        [info.host(), self.app_config().host()]
+            .iter()
+            .find_map(|host| {
+                Uri::builder()
+                    .scheme(info.scheme())
+                    .authority(*host)
+                    .path_and_query(self.path_and_query())
+                    .build()
+                    .ok()
+            })
+            .unwrap_or_else(|| self.uri().clone())
+    }
+
     /// App config
     #[inline]
     pub fn app_config(&self) -> &AppConfig {
@@ -392,6 +494,46 @@ mod tests {
         assert_eq!(req.query_string(), "id=test");
     }
 
+    #[test]
+    fn test_request_path_and_query() {
+        let req = TestRequest::with_uri("/path?id=test").to_http_request();
+        assert_eq!(req.path_and_query(), "/path?id=test");
+
+        let req = TestRequest::with_uri("/path").to_http_request();
+        assert_eq!(req.path_and_query(), "/path");
+
+        let req =
+            TestRequest::with_uri("http://example.com/path?id=test").to_http_request();
+        assert_eq!(req.path_and_query(), "/path?id=test");
+    }
+
+    #[test]
+    fn test_request_full_url() {
+        let req = TestRequest::with_uri("/path?id=test")
+            .header(header::HOST, "www.rust-lang.org")
+            .to_http_request();
+        assert_eq!(req.full_url(), "http://www.rust-lang.org/path?id=test");
+
+        let req = TestRequest::with_uri("/path")
+            .header(header::HOST, "www.rust-lang.org:8080")
+            .header("x-forwarded-proto", "https")
+            .to_http_request();
+        assert_eq!(req.full_url(), "https://www.rust-lang.org:8080/path");
+
+        let req =
+            TestRequest::with_uri("https://example.com/path?id=test").to_http_request();
+        assert_eq!(req.full_url(), "https://example.com/path?id=test");
+
+        // fallback to server host name
+        let req = TestRequest::with_uri("/path").to_http_request();
+        assert_eq!(req.full_url(), "http://localhost:8080/path");
+
+        let req = TestRequest::with_uri("/path")
+            .header(header::HOST, "invalid host")
+            .to_http_request();
+        assert_eq!(req.full_url(), "http://localhost:8080/path");
+    }
+
     #[cfg(feature = "url")]
     #[test]
     fn test_url_for() {
@@ -2,12 +2,29 @@ use std::cell::Ref;
 
 use crate::http::header::{self, HeaderName};
 use crate::http::RequestHead;
-use crate::web::config::AppConfig;
+use crate::web::{config::AppConfig, ipnet::parse_ip};
 
 const X_FORWARDED_FOR: &[u8] = b"x-forwarded-for";
 const X_FORWARDED_HOST: &[u8] = b"x-forwarded-host";
 const X_FORWARDED_PROTO: &[u8] = b"x-forwarded-proto";
 
+/// Precedence of `Forwarded` header versus `X-Forwarded-*` headers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ForwardedPrecedence {
+    /// `Forwarded` header takes precedence
+    Forwarded,
+    /// `X-Forwarded-For`, `X-Forwarded-Host` and `X-Forwarded-Proto`
+    /// headers take precedence
+    XForwarded,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ForwardedPrecedence {
+    fn default() -> Self {
+        ForwardedPrecedence::Forwarded
+    }
+}
+
 /// `HttpRequest` connection information
 #[derive(Debug, Clone, Default)]
 pub struct ConnectionInfo {
@@ -28,106 +45,121 @@ impl ConnectionInfo {
 
     #[allow(clippy::cognitive_complexity)]
     fn new(req: &RequestHead, cfg: &AppConfig) -> ConnectionInfo {
-        let mut host = None;
-        let mut scheme = None;
-        let mut remote = None;
         let mut peer = None;
+        let fwd = Forwarded::parse(req);
+        let x_fwd = |name: &[u8]| {
+            req.headers
+                .get(HeaderName::from_lowercase(name).unwrap())
+                .and_then(|h| h.to_str().ok())
+                .and_then(|h| h.split(',').next())
+                .map(|v| v.trim().to_owned())
+        };
+        let x_fwd_for: Vec<_> = req
+            .headers
+            .get_all(HeaderName::from_lowercase(X_FORWARDED_FOR).unwrap())
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_owned())
+            .collect();
 
-        // load forwarded header
-        for hdr in req.headers.get_all(&header::FORWARDED) {
-            if let Ok(val) = hdr.to_str() {
-                for pair in val.split(';') {
-                    for el in pair.split(',') {
-                        let mut items = el.trim().splitn(2, '=');
-                        if let Some(name) = items.next() {
-                            if let Some(val) = items.next() {
-                                match &name.to_lowercase() as &str {
-                                    "for" => {
-                                        if remote.is_none() {
-                                            remote = Some(val.trim());
-                                        }
-                                    }
-                                    "proto" => {
-                                        if scheme.is_none() {
-                                            scheme = Some(val.trim());
-                                        }
-                                    }
-                                    "host" => {
-                                        if host.is_none() {
-                                            host = Some(val.trim());
-                                        }
-                                    }
-                                    _ => (),
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        let trusted = cfg.trusted_proxies();
+        let (mut host, mut scheme, remote) = if trusted.is_empty() {
+            let (host, scheme, chain) = match cfg.forwarded_precedence() {
+                ForwardedPrecedence::Forwarded => (
+                    fwd.host.or_else(|| x_fwd(X_FORWARDED_HOST)),
+                    fwd.proto.or_else(|| x_fwd(X_FORWARDED_PROTO)),
+                    if fwd.remote.is_empty() {
+                        x_fwd_for
+                    } else {
+                        fwd.remote
+                    },
+                ),
+                ForwardedPrecedence::XForwarded => (
+                    x_fwd(X_FORWARDED_HOST).or(fwd.host),
+                    x_fwd(X_FORWARDED_PROTO).or(fwd.proto),
+                    if x_fwd_for.is_empty() {
+                        fwd.remote
+                    } else {
+                        x_fwd_for
+                    },
+                ),
+            };
+            (host, scheme, chain.into_iter().next())
+        } else if req
+            .peer_addr()
+            .map(|addr| trusted.iter().any(|net| net.contains(addr.ip())))
+            .unwrap_or(false)
+        {
+            // trusted proxy appends to one of the header families, the other
+            // one could be sent by the client. values from both families are
+            // used only if they agree with each other
+            let untrusted = |chain: Vec<String>| {
+                // closest to the server address which is not a trusted proxy
+                let idx = chain
+                    .iter()
+                    .rposition(|val| {
+                        !parse_ip(val)
+                            .map(|ip| trusted.iter().any(|net| net.contains(ip)))
+                            .unwrap_or(false)
+                    })
+                    .unwrap_or(0);
+                chain.into_iter().nth(idx)
+            };
+            let prefer_fwd = cfg.forwarded_precedence() == ForwardedPrecedence::Forwarded;
+            (
+                agree(fwd.host, x_fwd(X_FORWARDED_HOST), prefer_fwd, |a, b| {
+                    a.eq_ignore_ascii_case(b)
+                }),
+                agree(fwd.proto, x_fwd(X_FORWARDED_PROTO), prefer_fwd, |a, b| {
+                    a.eq_ignore_ascii_case(b)
+                }),
+                agree(
+                    untrusted(fwd.remote),
+                    untrusted(x_fwd_for),
+                    prefer_fwd,
+                    |a, b| match (parse_ip(a), parse_ip(b)) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => a == b,
+                    },
+                ),
+            )
+        } else {
+            // forwarding headers from untrusted peers are ignored
+            (None, None, None)
+        };
+        if remote.is_none() {
+            // get peeraddr from socketaddr
+            peer = req.peer_addr().map(|addr| format!("{}", addr));
         }
 
         // scheme
         if scheme.is_none() {
-            if let Some(h) = req
-                .headers
-                .get(&HeaderName::from_lowercase(X_FORWARDED_PROTO).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    scheme = h.split(',').next().map(|v| v.trim());
-                }
-            }
-            if scheme.is_none() {
-                scheme = req.uri.scheme().map(|a| a.as_str());
-                if scheme.is_none() && cfg.secure() {
-                    scheme = Some("https")
-                }
+            scheme = req.uri.scheme().map(|a| a.as_str().to_owned());
+            if scheme.is_none() && cfg.secure() {
+                scheme = Some("https".to_owned())
             }
         }
 
         // host
         if host.is_none() {
-            if let Some(h) = req
-                .headers
-                .get(&HeaderName::from_lowercase(X_FORWARDED_HOST).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    host = h.split(',').next().map(|v| v.trim());
-                }
+            if let Some(h) = req.headers.get(&header::HOST) {
+                host = h.to_str().ok().map(|h| h.to_owned());
             }
             if host.is_none() {
-                if let Some(h) = req.headers.get(&header::HOST) {
-                    host = h.to_str().ok();
-                }
+                host = req.uri.authority().map(|a| a.as_str().to_owned());
                 if host.is_none() {
-                    host = req.uri.authority().map(|a| a.as_str());
-                    if host.is_none() {
-                        host = Some(cfg.host());
-                    }
+                    host = Some(cfg.host().to_owned());
                 }
             }
         }
 
-        // remote addr
-        if remote.is_none() {
-            if let Some(h) = req
-                .headers
-                .get(&HeaderName::from_lowercase(X_FORWARDED_FOR).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    remote = h.split(',').next().map(|v| v.trim());
-                }
-            }
-            if remote.is_none() {
-                // get peeraddr from socketaddr
-                peer = req.peer_addr().map(|addr| format!("{}", addr));
-            }
-        }
-
         ConnectionInfo {
             peer,
-            scheme: scheme.unwrap_or("http").to_owned(),
-            host: host.unwrap_or("localhost").to_owned(),
-            remote: remote.map(|s| s.to_owned()),
+            remote,
+            scheme: scheme.unwrap_or_else(|| "http".to_owned()),
+            host: host.unwrap_or_else(|| "localhost".to_owned()),
         }
     }
 
@@ -138,6 +170,10 @@ impl ConnectionInfo {
     /// - Forwarded
     /// - X-Forwarded-Proto
     /// - Uri
+    ///
+    /// Order of `Forwarded` and `X-Forwarded-*` headers could be changed
+    /// with `HttpServer::forwarded_precedence()`. Forwarding headers are
+    /// subject to trusted proxies check, see [`ConnectionInfo::remote()`].
     #[inline]
     pub fn scheme(&self) -> &str {
         &self.scheme
@@ -152,6 +188,9 @@ impl ConnectionInfo {
     /// - Host
     /// - Uri
     /// - Server hostname
+    ///
+    /// Forwarding headers are subject to trusted proxies check,
+    /// see [`ConnectionInfo::remote()`].
     pub fn host(&self) -> &str {
         &self.host
     }
@@ -164,9 +203,18 @@ impl ConnectionInfo {
     /// - X-Forwarded-For
     /// - peer name of opened socket
     ///
+    /// If trusted proxies are configured with `HttpServer::trusted_proxies()`,
+    /// forwarding headers are used only if peer address belongs to a trusted
+    /// network. In that case the last address in the forwarding chain
+    /// that is not a trusted proxy is used. If both `Forwarded` and
+    /// `X-Forwarded-For` chains are present, they must resolve to the same
+    /// address, otherwise peer address is used. The same rules apply
+    /// to the scheme and the host.
+    ///
     /// # Security
     /// Do not use this function for security purposes, unless you can ensure the Forwarded and
-    /// X-Forwarded-For headers cannot be spoofed by the client. If you want the client's socket
+    /// X-Forwarded-For headers cannot be spoofed by the client, for example by configuring
+    /// trusted proxies. If you want the client's socket
     /// address explicitly, use
     /// [`HttpRequest::peer_addr()`](../web/struct.HttpRequest.html#method.peer_addr) instead.
     #[inline]
@@ -181,6 +229,143 @@ impl ConnectionInfo {
     }
 }
 
+/// Value reported by both `Forwarded` and `X-Forwarded-*` headers.
+///
+/// If only one header family reports the value it is used, conflicting
+/// values are discarded.
+fn agree<F>(
+    fwd: Option<String>,
+    x_fwd: Option<String>,
+    prefer_fwd: bool,
+    eq: F,
+) -> Option<String>
+where
+    F: Fn(&str, &str) -> bool,
+{
+    match (fwd, x_fwd) {
+        (Some(fwd), Some(x_fwd)) => {
+            if !eq(&fwd, &x_fwd) {
+                None
+            } else if prefer_fwd {
+                Some(fwd)
+            } else {
+                Some(x_fwd)
+            }
+        }
+        (fwd, x_fwd) => fwd.or(x_fwd),
+    }
+}
+
+/// Parsed `Forwarded` header, RFC 7239
+///
+/// First occurrence of `proto` and `host` parameters is used, all `for`
+/// parameters are collected. Malformed elements are skipped.
+#[derive(Default)]
+struct Forwarded {
+    remote: Vec<String>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+impl Forwarded {
+    fn parse(req: &RequestHead) -> Self {
+        let mut fwd = Forwarded::default();
+
+        for hdr in req.headers.get_all(&header::FORWARDED) {
+            let val = if let Ok(val) = hdr.to_str() {
+                val
+            } else {
+                continue;
+            };
+            'elements: for el in split_quoted(val, ',') {
+                let mut pairs = Vec::new();
+                for pair in split_quoted(el, ';') {
+                    let pair = pair.trim();
+                    if pair.is_empty() {
+                        continue;
+                    }
+                    let mut items = pair.splitn(2, '=');
+                    let name = items.next().unwrap_or("").trim();
+                    match items.next().and_then(|v| unquote(v.trim())) {
+                        Some(value) if !name.is_empty() => pairs.push((name, value)),
+                        _ => continue 'elements,
+                    }
+                }
+
+                for (name, value) in pairs {
+                    if name.eq_ignore_ascii_case("for") {
+                        fwd.remote.extend(node_addr(value));
+                    } else if name.eq_ignore_ascii_case("proto") {
+                        if fwd.proto.is_none() {
+                            fwd.proto = Some(value);
+                        }
+                    } else if name.eq_ignore_ascii_case("host") && fwd.host.is_none() {
+                        fwd.host = Some(value);
+                    }
+                }
+            }
+        }
+        fwd
+    }
+}
+
+/// Split string by separator, separators inside of quoted strings are ignored
+fn split_quoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (idx, ch) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && ch == '\\' {
+            escaped = true;
+        } else if ch == '"' {
+            quoted = !quoted;
+        } else if !quoted && ch == sep {
+            parts.push(&s[start..idx]);
+            start = idx + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Parse token or quoted string value
+fn unquote(s: &str) -> Option<String> {
+    if let Some(s) = s.strip_prefix('"') {
+        let s = s.strip_suffix('"')?;
+        let mut res = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => res.push(chars.next()?),
+                '"' => return None,
+                _ => res.push(ch),
+            }
+        }
+        Some(res)
+    } else if !s.is_empty() && !s.contains(|c: char| c == '"' || c.is_whitespace()) {
+        Some(s.to_owned())
+    } else {
+        None
+    }
+}
+
+/// Node address of `for` parameter.
+///
+/// Brackets are removed from IPv6 address without port,
+/// `unknown` and obfuscated identifiers are ignored.
+fn node_addr(s: String) -> Option<String> {
+    if s.eq_ignore_ascii_case("unknown") || s.starts_with('_') {
+        None
+    } else if let Some(ip) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        Some(ip.to_owned())
+    } else {
+        Some(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,4 +418,188 @@ mod tests {
         let info = req.connection_info();
         assert_eq!(info.scheme(), "https");
     }
+
+    #[test]
+    fn test_forwarded_rfc7239() {
+        let req = TestRequest::default()
+            .header(
+                header::FORWARDED,
+                "for=\"[2001:db8:cafe::17]:4711\";proto=https, for=192.0.2.43",
+            )
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.scheme(), "https");
+        assert_eq!(info.remote(), Some("[2001:db8:cafe::17]:4711"));
+
+        let req = TestRequest::default()
+            .header(header::FORWARDED, "For=\"[2001:db8:cafe::17]\"")
+            .to_http_request();
+        assert_eq!(req.connection_info().remote(), Some("2001:db8:cafe::17"));
+
+        // multiple elements and headers, first occurrence wins
+        let req = TestRequest::default()
+            .header(
+                header::FORWARDED,
+                "for=unknown, for=192.0.2.60;host=\"a.org\"",
+            )
+            .header(
+                header::FORWARDED,
+                "for=198.51.100.17;host=b.org;proto=https",
+            )
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.remote(), Some("192.0.2.60"));
+        assert_eq!(info.host(), "a.org");
+        assert_eq!(info.scheme(), "https");
+
+        // malformed elements are skipped
+        let req = TestRequest::default()
+            .header(
+                header::FORWARDED,
+                "for;proto=ftp, proto=a b;for=192.0.2.1, host=\"a,b.org\";for=192.0.2.2",
+            )
+            .to_http_request();
+        let info = req.connection_info();
+        assert_eq!(info.remote(), Some("192.0.2.2"));
+        assert_eq!(info.host(), "a,b.org");
+        assert_eq!(info.scheme(), "http");
+    }
+
+    #[test]
+    fn test_forwarded_precedence() {
+        let req = TestRequest::default()
+            .header(header::FORWARDED, "for=192.0.2.60;proto=https;host=a.org")
+            .header(X_FORWARDED_FOR, "192.0.2.43")
+            .header(X_FORWARDED_HOST, "b.org")
+            .to_http_request();
+
+        let cfg = AppConfig::default();
+        assert_eq!(cfg.forwarded_precedence(), ForwardedPrecedence::Forwarded);
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("192.0.2.60"));
+        assert_eq!(info.host(), "a.org");
+        assert_eq!(info.scheme(), "https");
+
+        let cfg =
+            AppConfig::default().set_forwarded_precedence(ForwardedPrecedence::XForwarded);
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("192.0.2.43"));
+        assert_eq!(info.host(), "b.org");
+        assert_eq!(info.scheme(), "https");
+    }
+
+    #[test]
+    fn test_trusted_proxies() {
+        let cfg = AppConfig::default().set_trusted_proxies(vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "::1".parse().unwrap(),
+        ]);
+
+        // untrusted peer
+        let req = TestRequest::default()
+            .peer_addr("192.0.2.1:8080".parse().unwrap())
+            .header(X_FORWARDED_FOR, "203.0.113.7")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("192.0.2.1:8080"));
+
+        let req = TestRequest::default()
+            .peer_addr("192.0.2.1:8080".parse().unwrap())
+            .header(header::FORWARDED, "for=203.0.113.7")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("192.0.2.1:8080"));
+
+        // trusted peer
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(X_FORWARDED_FOR, "203.0.113.7")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("203.0.113.7"));
+
+        // trusted proxies in chain are skipped, spoofed entries are ignored
+        let req = TestRequest::default()
+            .peer_addr("[::1]:8080".parse().unwrap())
+            .header(X_FORWARDED_FOR, "198.51.100.1, 203.0.113.7, 10.1.1.1")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("203.0.113.7"));
+
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(
+                header::FORWARDED,
+                "for=198.51.100.1, for=\"[2001:db8::1]:4711\", for=10.1.1.1",
+            )
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("[2001:db8::1]:4711"));
+
+        // all addresses are trusted
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(X_FORWARDED_FOR, "10.0.0.3, 10.0.0.2")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("10.0.0.3"));
+
+        // client sent `Forwarded` header, trusted proxy appends `X-Forwarded-For`
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(header::FORWARDED, "for=1.2.3.4;proto=https;host=evil.org")
+            .header(X_FORWARDED_FOR, "203.0.113.7")
+            .header(X_FORWARDED_PROTO, "http")
+            .header(X_FORWARDED_HOST, "example.org")
+            .header(header::HOST, "10.0.0.2")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("10.0.0.1:8080"));
+        assert_eq!(info.scheme(), "http");
+        assert_eq!(info.host(), "10.0.0.2");
+
+        // both header families agree
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(
+                header::FORWARDED,
+                "for=\"203.0.113.7:4711\";host=example.org",
+            )
+            .header(X_FORWARDED_FOR, "203.0.113.7")
+            .header(X_FORWARDED_HOST, "Example.org")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.remote(), Some("203.0.113.7:4711"));
+        assert_eq!(info.host(), "example.org");
+
+        // scheme and host from untrusted peer are ignored
+        let req = TestRequest::default()
+            .peer_addr("192.0.2.1:8080".parse().unwrap())
+            .header(header::FORWARDED, "proto=https;host=evil.org")
+            .header(X_FORWARDED_PROTO, "https")
+            .header(X_FORWARDED_HOST, "evil.org")
+            .header(header::HOST, "example.org")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.scheme(), "http");
+        assert_eq!(info.host(), "example.org");
+
+        // trusted peer
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:8080".parse().unwrap())
+            .header(X_FORWARDED_PROTO, "https")
+            .header(X_FORWARDED_HOST, "example.org")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info.scheme(), "https");
+        assert_eq!(info.host(), "example.org");
+
+        // no trusted proxies configured
+        let req = TestRequest::default()
+            .peer_addr("192.0.2.1:8080".parse().unwrap())
+            .header(X_FORWARDED_FOR, "203.0.113.7, 192.0.2.1")
+            .to_http_request();
+        let info = ConnectionInfo::new(req.head(), &AppConfig::default());
+        assert_eq!(info.remote(), Some("203.0.113.7"));
+    }
 }
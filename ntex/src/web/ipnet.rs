@@ -0,0 +1,167 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{fmt, str::FromStr};
+
+/// IP network in CIDR notation, like `10.0.0.0/8` or `fd00::/8`
+///
+/// ```rust
+/// use ntex::web::IpNetwork;
+///
+/// let net: IpNetwork = "10.0.0.0/8".parse().unwrap();
+/// assert!(net.contains("10.1.2.3".parse().unwrap()));
+/// assert!(!net.contains("192.168.0.1".parse().unwrap()));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+/// Invalid IP network
+#[derive(thiserror::Error, Copy, Clone, PartialEq, Eq, Debug)]
+#[error("Invalid IP network")]
+pub struct InvalidIpNetwork;
+
+impl IpNetwork {
+    /// Create network from address and prefix length.
+    ///
+    /// Host bits of the address are ignored.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, InvalidIpNetwork> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            Err(InvalidIpNetwork)
+        } else {
+            Ok(IpNetwork { addr, prefix })
+        }
+    }
+
+    #[inline]
+    /// Network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    #[inline]
+    /// Network prefix length
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Check if network contains the address.
+    ///
+    /// IPv4-mapped IPv6 addresses are matched against IPv4 networks.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, to_canonical(addr)) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn to_canonical(addr: IpAddr) -> IpAddr {
+    if let IpAddr::V6(v6) = addr {
+        if let [0, 0, 0, 0, 0, 0xffff, hi, lo] = v6.segments() {
+            return IpAddr::V4(Ipv4Addr::from(((hi as u32) << 16) | lo as u32));
+        }
+    }
+    addr
+}
+
+/// Parse ip address, with optional port and brackets
+pub(crate) fn parse_ip(s: &str) -> Option<IpAddr> {
+    let s = s.trim_matches('"');
+    s.parse::<IpAddr>()
+        .ok()
+        .or_else(|| s.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| {
+            s.strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.parse().ok())
+        })
+}
+
+impl FromStr for IpNetwork {
+    type Err = InvalidIpNetwork;
+
+    /// Parse network in CIDR notation, address without prefix
+    /// is parsed as a single host network.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts
+            .next()
+            .and_then(|addr| addr.parse::<IpAddr>().ok())
+            .ok_or(InvalidIpNetwork)?;
+        let prefix = match parts.next() {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| InvalidIpNetwork)?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+        IpNetwork::new(addr, prefix)
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> Self {
+        let prefix = if addr.is_ipv4() { 32 } else { 128 };
+        IpNetwork { addr, prefix }
+    }
+}
+
+impl From<Ipv4Addr> for IpNetwork {
+    fn from(addr: Ipv4Addr) -> Self {
+        IpAddr::V4(addr).into()
+    }
+}
+
+impl From<Ipv6Addr> for IpNetwork {
+    fn from(addr: Ipv6Addr) -> Self {
+        IpAddr::V6(addr).into()
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_network() {
+        let net: IpNetwork = "10.0.0.0/8".parse().unwrap();
+        assert_eq!(net.prefix(), 8);
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+        assert!(net.contains("10.255.0.1".parse().unwrap()));
+        assert!(net.contains("::ffff:10.0.0.1".parse().unwrap()));
+        assert!(!net.contains("11.0.0.1".parse().unwrap()));
+        assert!(!net.contains("::1".parse().unwrap()));
+
+        let net: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(net.contains("1.2.3.4".parse().unwrap()));
+
+        let net: IpNetwork = "192.168.1.10".parse().unwrap();
+        assert_eq!(net.prefix(), 32);
+        assert!(net.contains("192.168.1.10".parse().unwrap()));
+        assert!(!net.contains("192.168.1.11".parse().unwrap()));
+
+        let net: IpNetwork = "fd00::/8".parse().unwrap();
+        assert!(net.contains("fd12::1".parse().unwrap()));
+        assert!(!net.contains("fe80::1".parse().unwrap()));
+        assert!(!net.contains("10.0.0.1".parse().unwrap()));
+        assert_eq!(IpNetwork::from(Ipv6Addr::LOCALHOST).prefix(), 128);
+
+        assert_eq!("10.0.0.0/33".parse::<IpNetwork>(), Err(InvalidIpNetwork));
+        assert_eq!("10.0.0/8".parse::<IpNetwork>(), Err(InvalidIpNetwork));
+        assert_eq!("::/129".parse::<IpNetwork>(), Err(InvalidIpNetwork));
+        assert_eq!("::/".parse::<IpNetwork>(), Err(InvalidIpNetwork));
+    }
+}
@@ -0,0 +1,201 @@
+//! Adapter for using service combinators as web middlewares
+use std::task::{Context, Poll};
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use crate::service::{Service, Transform};
+use crate::web::{ErrorRenderer, WebRequest, WebResponse};
+
+/// `Middleware` adapter for generic service combinators.
+///
+/// Combinators like `Timeout` or `InFlightReject` wrap service errors into
+/// their own error types. Adapter converts such errors back to web error
+/// container, so combinator could be used as regular web middleware.
+/// With default error renderer `TimeoutError::Timeout` is rendered as
+/// `504 Gateway Timeout` and `InFlightError::Overflow` as
+/// `503 Service Unavailable`.
+///
+/// ```rust
+/// use ntex::util::{inflight::InFlightReject, timeout::Timeout};
+/// use ntex::web::{self, middleware::Adapter, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(Adapter::new(Timeout::new(ntex::time::Seconds(5))))
+///         .wrap(Adapter::new(InFlightReject::new(100)))
+///         .route("/", web::get().to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Adapter<T> {
+    inner: T,
+}
+
+impl<T> Adapter<T> {
+    /// Construct adapter for service combinator
+    pub fn new(inner: T) -> Self {
+        Adapter { inner }
+    }
+}
+
+impl<S, T> Transform<S> for Adapter<T>
+where
+    T: Transform<S>,
+{
+    type Service = AdapterMiddleware<T::Service>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        AdapterMiddleware {
+            service: self.inner.new_transform(service),
+        }
+    }
+}
+
+/// Adapter middleware
+pub struct AdapterMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for AdapterMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse>,
+    Err: ErrorRenderer,
+    Err::Container: From<S::Error>,
+{
+    type Response = WebResponse;
+    type Error = Err::Container;
+    type Future = AdapterResponse<S, Err>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx).map_err(From::from)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    #[inline]
+    fn call(&self, req: WebRequest<Err>) -> Self::Future {
+        AdapterResponse {
+            fut: self.service.call(req),
+            _t: PhantomData,
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[doc(hidden)]
+    pub struct AdapterResponse<S: Service<WebRequest<Err>>, Err> {
+        #[pin]
+        fut: S::Future,
+        _t: PhantomData<Err>,
+    }
+}
+
+impl<S, Err> Future for AdapterResponse<S, Err>
+where
+    S: Service<WebRequest<Err>>,
+    Err: ErrorRenderer,
+    Err::Container: From<S::Error>,
+{
+    type Output = Result<S::Response, Err::Container>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().fut.poll(cx).map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::service::{apply, fn_service, ServiceFactory};
+    use crate::time::{sleep, Millis};
+    use crate::util::{inflight::InFlightReject, join, timeout::Timeout};
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, App, DefaultError, Error, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_timeout() {
+        let srv = init_service(
+            App::new()
+                .wrap(Adapter::new(Timeout::new(Millis(50))))
+                .route(
+                    "/",
+                    web::get().to(|req: web::HttpRequest| async move {
+                        if req.query_string() == "slow" {
+                            sleep(Millis(500)).await;
+                        }
+                        HttpResponse::Ok()
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/?slow").to_request();
+        let err = srv.call(req).await.err().unwrap();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(err.to_string(), "Service call timeout");
+    }
+
+    #[crate::rt_test]
+    async fn test_inflight() {
+        let srv = init_service(
+            App::new().service(
+                web::service("/").finish(
+                    apply(
+                        InFlightReject::new(1),
+                        fn_service(|req: WebRequest<DefaultError>| async move {
+                            sleep(Millis(50)).await;
+                            Ok::<_, Error>(req.into_response(HttpResponse::Ok().finish()))
+                        }),
+                    )
+                    .map_err(Error::from),
+                ),
+            ),
+        )
+        .await;
+
+        let (r1, r2) = join(
+            srv.call(TestRequest::with_uri("/").to_request()),
+            srv.call(TestRequest::with_uri("/").to_request()),
+        )
+        .await;
+        assert_eq!(r1.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            r2.err().unwrap().as_response_error().status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[crate::rt_test]
+    async fn test_adapter_service() {
+        let srv = Adapter::new(Timeout::new(Millis(50))).new_transform(fn_service(
+            |req: WebRequest<DefaultError>| async move {
+                sleep(Millis(500)).await;
+                Ok::<_, Error>(req.into_response(HttpResponse::Ok().finish()))
+            },
+        ));
+        let err = srv
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+    }
+}
@@ -5,13 +5,14 @@ use std::{cmp, future::Future, marker, pin::Pin, str::FromStr};
 use crate::http::encoding::Encoder;
 use crate::http::header::{ContentEncoding, ACCEPT_ENCODING};
 use crate::service::{Service, Transform};
-use crate::web::{BodyEncoding, ErrorRenderer, WebRequest, WebResponse};
+use crate::web::{util::Enc, ErrorRenderer, WebRequest, WebResponse};
 
 #[derive(Debug, Clone)]
 /// `Middleware` for compressing response body.
 ///
 /// Use `BodyEncoding` trait for overriding response compression.
-/// To disable compression set encoding to `ContentEncoding::Identity` value.
+/// To disable compression use `BodyEncoding::no_compress()` or set encoding
+/// to `ContentEncoding::Identity` value.
 ///
 /// ```rust
 /// use ntex::web::{self, middleware, App, HttpResponse};
@@ -120,9 +121,12 @@ where
         let this = self.project();
 
         match this.fut.poll(cx)? {
-            Poll::Ready(resp) => {
-                let enc = if let Some(enc) = resp.response().get_encoding() {
-                    enc
+            Poll::Ready(mut resp) => {
+                // encoding override is consumed by middleware
+                let enc = if let Some(enc) =
+                    resp.response_mut().extensions_mut().remove::<Enc>()
+                {
+                    enc.0
                 } else {
                     *this.encoding
                 };
@@ -0,0 +1,301 @@
+//! Middleware for limiting number of concurrently processed requests
+use std::task::{Context, Poll};
+use std::{cell::Cell, error::Error, future::Future, pin::Pin, rc::Rc};
+
+use crate::channel::condition::Condition;
+use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
+use crate::http::header::{HeaderValue, RETRY_AFTER};
+use crate::service::{Service, Transform};
+use crate::time::Seconds;
+use crate::util::Bytes;
+use crate::web::{HttpResponse, WebRequest, WebResponse};
+
+/// `Middleware` for limiting number of concurrently processed requests.
+///
+/// Requests above the limit wait until one of the in-flight requests
+/// completes. With `reject()` mode excess requests get `503 Service Unavailable`
+/// response with `Retry-After` header instead.
+///
+/// Permit is held until response body is fully sent, not just until
+/// response head is produced.
+///
+/// Limit is tracked per middleware instance, middleware constructed within
+/// `App` factory limits concurrency for each worker separately.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .service(
+///             web::resource("/export")
+///                 .wrap(middleware::ConcurrencyLimit::new(2))
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///         )
+///         .service(
+///             web::resource("/index.html")
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///         );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    limit: usize,
+    reject: bool,
+    retry_after: Seconds,
+    count: Cell<usize>,
+    waiters: Condition,
+}
+
+impl ConcurrencyLimit {
+    /// Construct `ConcurrencyLimit` middleware with the specified
+    /// number of concurrent requests.
+    pub fn new(limit: usize) -> Self {
+        ConcurrencyLimit {
+            inner: Rc::new(Inner {
+                limit,
+                reject: false,
+                retry_after: Seconds(1),
+                count: Cell::new(0),
+                waiters: Condition::new(),
+            }),
+        }
+    }
+
+    /// Respond with `503 Service Unavailable` instead of waiting for a permit.
+    pub fn reject(mut self) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .reject = true;
+        self
+    }
+
+    /// Set value of `Retry-After` header for rejected requests.
+    ///
+    /// By default it is set to 1 second.
+    pub fn retry_after(mut self, secs: Seconds) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .retry_after = secs;
+        self
+    }
+}
+
+impl Inner {
+    fn try_acquire(self: &Rc<Self>) -> Option<Permit> {
+        let count = self.count.get();
+        if count < self.limit {
+            self.count.set(count + 1);
+            Some(Permit(self.clone()))
+        } else {
+            None
+        }
+    }
+
+    async fn acquire(self: &Rc<Self>) -> Permit {
+        loop {
+            if let Some(permit) = self.try_acquire() {
+                return permit;
+            }
+            self.waiters.wait().ready().await;
+        }
+    }
+}
+
+struct Permit(Rc<Inner>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.count.set(self.0.count.get() - 1);
+        self.0.waiters.notify();
+    }
+}
+
+impl<S> Transform<S> for ConcurrencyLimit {
+    type Service = ConcurrencyLimitMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        ConcurrencyLimitMiddleware {
+            service: Rc::new(service),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+pub struct ConcurrencyLimitMiddleware<S> {
+    service: Rc<S>,
+    inner: Rc<Inner>,
+}
+
+impl<S, E> Service<WebRequest<E>> for ConcurrencyLimitMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse> + 'static,
+    E: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        let inner = self.inner.clone();
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let permit = if inner.reject {
+                if let Some(permit) = inner.try_acquire() {
+                    permit
+                } else {
+                    log::trace!("Concurrency limit exceeded, reject request");
+                    let res = HttpResponse::ServiceUnavailable()
+                        .header(RETRY_AFTER, HeaderValue::from(inner.retry_after.seconds()))
+                        .finish();
+                    return Ok(req.into_response(res));
+                }
+            } else {
+                inner.acquire().await
+            };
+
+            let res = service.call(req).await?;
+            Ok(res.map_body(move |_, body| {
+                ResponseBody::Other(Body::from_message(PermitBody {
+                    body,
+                    _permit: permit,
+                }))
+            }))
+        })
+    }
+}
+
+struct PermitBody {
+    body: ResponseBody<Body>,
+    _permit: Permit,
+}
+
+impl MessageBody for PermitBody {
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        self.body.poll_next_chunk(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::time::{sleep, Millis};
+    use crate::util::{join, lazy};
+    use crate::web::test::{self, call_service, init_service, TestRequest};
+    use crate::web::{self, App};
+
+    #[crate::rt_test]
+    async fn test_serialize() {
+        let active = Rc::new(Cell::new(0));
+        let max_active = Rc::new(Cell::new(0));
+        let (active2, max_active2) = (active.clone(), max_active.clone());
+
+        let srv = init_service(
+            App::new().service(web::resource("/export").wrap(ConcurrencyLimit::new(1)).to(
+                move || {
+                    let (active, max_active) = (active2.clone(), max_active2.clone());
+                    async move {
+                        active.set(active.get() + 1);
+                        max_active.set(std::cmp::max(active.get(), max_active.get()));
+                        sleep(Millis(50)).await;
+                        active.set(active.get() - 1);
+                        HttpResponse::Ok().body("done")
+                    }
+                },
+            )),
+        )
+        .await;
+
+        let (res1, res2) = join(
+            async {
+                let res = srv
+                    .call(TestRequest::with_uri("/export").to_request())
+                    .await;
+                test::read_body(res.unwrap()).await
+            },
+            async {
+                let res = srv
+                    .call(TestRequest::with_uri("/export").to_request())
+                    .await;
+                test::read_body(res.unwrap()).await
+            },
+        )
+        .await;
+        assert_eq!(res1, Bytes::from_static(b"done"));
+        assert_eq!(res2, Bytes::from_static(b"done"));
+        assert_eq!(max_active.get(), 1);
+        assert_eq!(active.get(), 0);
+    }
+
+    #[crate::rt_test]
+    async fn test_permit_held_by_body() {
+        let srv = init_service(
+            App::new().service(
+                web::resource("/export")
+                    .wrap(ConcurrencyLimit::new(1))
+                    .to(|| async { HttpResponse::Ok().body("done") }),
+            ),
+        )
+        .await;
+
+        let res1 = call_service(&srv, TestRequest::with_uri("/export").to_request()).await;
+        let mut fut = Box::pin(srv.call(TestRequest::with_uri("/export").to_request()));
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+
+        assert_eq!(test::read_body(res1).await, Bytes::from_static(b"done"));
+        let res2 = fut.await.unwrap();
+        assert_eq!(res2.status(), StatusCode::OK);
+    }
+
+    #[crate::rt_test]
+    async fn test_reject() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/export")
+                        .wrap(ConcurrencyLimit::new(1).reject().retry_after(Seconds(5)))
+                        .to(|| async { HttpResponse::Ok().body("done") }),
+                )
+                .service(web::resource("/").to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let res1 = call_service(&srv, TestRequest::with_uri("/export").to_request()).await;
+        assert_eq!(res1.status(), StatusCode::OK);
+
+        let res2 = call_service(&srv, TestRequest::with_uri("/export").to_request()).await;
+        assert_eq!(res2.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res2.headers().get(RETRY_AFTER).unwrap(), "5");
+
+        // other resources are not limited
+        let res = call_service(&srv, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        drop(res1);
+        let res3 = call_service(&srv, TestRequest::with_uri("/export").to_request()).await;
+        assert_eq!(res3.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,295 @@
+//! Error responses logging middleware
+use std::task::{Context, Poll};
+use std::{error::Error, future::Future, pin::Pin, rc::Rc};
+
+use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
+use crate::http::header::HeaderName;
+use crate::http::{Method, StatusCode};
+use crate::service::{Service, Transform};
+use crate::util::{Bytes, BytesMut};
+use crate::web::{HttpRequest, WebRequest, WebResponse};
+
+/// Max size of logged response body
+const MAX_BODY: usize = 1024;
+
+/// Request id.
+///
+/// `ErrorLogger` uses request id from request extensions, if extensions
+/// do not contain `RequestId`, value of request id header is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// `Middleware` for logging error responses.
+///
+/// Unlike [`Logger`](struct.Logger.html), middleware logs only error
+/// responses. Responses with `5xx` status codes are logged at `ERROR` level,
+/// responses with `4xx` status codes are logged at `DEBUG` level. Log record
+/// contains request method, path, response status, request id and response
+/// body truncated to 1 KB. Record is logged when response body is sent.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::ErrorLogger::default())
+///         .route("/", web::get().to(|| async { HttpResponse::InternalServerError() }));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ErrorLogger {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    request_id: HeaderName,
+}
+
+impl ErrorLogger {
+    /// Create `ErrorLogger` middleware.
+    pub fn new() -> ErrorLogger {
+        ErrorLogger {
+            inner: Rc::new(Inner {
+                request_id: HeaderName::from_static("x-request-id"),
+            }),
+        }
+    }
+
+    /// Set request id header name.
+    ///
+    /// By default `x-request-id` header is used.
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .request_id = name;
+        self
+    }
+}
+
+impl Default for ErrorLogger {
+    fn default() -> Self {
+        ErrorLogger::new()
+    }
+}
+
+impl<S> Transform<S> for ErrorLogger {
+    type Service = ErrorLoggerMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        ErrorLoggerMiddleware {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Error logger middleware
+pub struct ErrorLoggerMiddleware<S> {
+    inner: Rc<Inner>,
+    service: S,
+}
+
+impl<S, E> Service<WebRequest<E>> for ErrorLoggerMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Future: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        let inner = self.inner.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let status = res.status();
+            if !status.is_client_error() && !status.is_server_error() {
+                return Ok(res);
+            }
+
+            let req = res.request();
+            let request_id = request_id(req, &inner.request_id);
+            let method = req.method().clone();
+            let path = req.path().to_string();
+
+            Ok(res.map_body(move |_, body| {
+                ResponseBody::Other(Body::from_message(ErrorLog {
+                    body,
+                    method,
+                    path,
+                    status,
+                    request_id,
+                    buf: BytesMut::new(),
+                }))
+            }))
+        })
+    }
+}
+
+fn request_id(req: &HttpRequest, header: &HeaderName) -> Option<String> {
+    req.extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .or_else(|| {
+            req.headers()
+                .get(header)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string())
+        })
+}
+
+struct ErrorLog {
+    body: ResponseBody<Body>,
+    method: Method,
+    path: String,
+    status: StatusCode,
+    request_id: Option<String>,
+    buf: BytesMut,
+}
+
+impl ErrorLog {
+    fn message(&self) -> String {
+        format!(
+            "{} {} {} request-id: {} body: {:?}",
+            self.method,
+            self.path,
+            self.status.as_u16(),
+            self.request_id.as_deref().unwrap_or("-"),
+            String::from_utf8_lossy(&self.buf)
+        )
+    }
+}
+
+impl Drop for ErrorLog {
+    fn drop(&mut self) {
+        if self.status.is_server_error() {
+            log::error!("{}", self.message());
+        } else {
+            log::debug!("{}", self.message());
+        }
+    }
+}
+
+impl MessageBody for ErrorLog {
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        match self.body.poll_next_chunk(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                let len = (MAX_BODY - self.buf.len()).min(chunk.len());
+                self.buf.extend_from_slice(&chunk[..len]);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            val => val,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::body::BodyStream;
+    use crate::util::poll_fn;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_error_logger() {
+        let srv = init_service(
+            App::new()
+                .wrap(ErrorLogger::new())
+                .route(
+                    "/error",
+                    web::get()
+                        .to(|| async { HttpResponse::InternalServerError().body("err") }),
+                )
+                .route(
+                    "/",
+                    web::get().to(|| async { HttpResponse::Ok().body("ok") }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/error")
+            .header("x-request-id", "req-1")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(read_body(resp).await, "err");
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, "ok");
+
+        let req = TestRequest::with_uri("/unknown").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_request_id() {
+        let header = HeaderName::from_static("x-request-id");
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(request_id(&req, &header), None);
+
+        let req = TestRequest::default()
+            .header("x-request-id", "req-1")
+            .to_http_request();
+        assert_eq!(request_id(&req, &header).as_deref(), Some("req-1"));
+
+        // extensions take precedence over header
+        req.extensions_mut().insert(RequestId("req-2".to_string()));
+        assert_eq!(request_id(&req, &header).as_deref(), Some("req-2"));
+    }
+
+    #[crate::rt_test]
+    async fn test_error_log() {
+        let body = Body::from_message(BodyStream::new(futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; 1000])),
+            Ok(Bytes::from(vec![b'b'; 100])),
+        ])));
+        let mut log = ErrorLog {
+            body: ResponseBody::Other(body),
+            method: Method::POST,
+            path: "/test".to_string(),
+            status: StatusCode::BAD_GATEWAY,
+            request_id: Some("req-1".to_string()),
+            buf: BytesMut::new(),
+        };
+        let mut size = 0;
+        while let Some(chunk) = poll_fn(|cx| log.poll_next_chunk(cx)).await {
+            size += chunk.unwrap().len();
+        }
+        assert_eq!(size, 1100);
+        assert_eq!(log.buf.len(), MAX_BODY);
+        assert_eq!(
+            log.message(),
+            format!(
+                "POST /test 502 request-id: req-1 body: {:?}",
+                format!("{}{}", "a".repeat(1000), "b".repeat(24))
+            )
+        );
+
+        log.request_id = None;
+        log.buf.clear();
+        assert_eq!(log.message(), "POST /test 502 request-id: - body: \"\"");
+    }
+}
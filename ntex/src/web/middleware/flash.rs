@@ -0,0 +1,349 @@
+//! Middleware for one-time flash messages
+use std::task::{Context, Poll};
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use coo_kie::{Cookie, CookieJar, Key, SameSite};
+
+use crate::http::header::{HeaderValue, SET_COOKIE};
+use crate::http::HttpMessage;
+use crate::service::{Service, Transform};
+use crate::web::{HttpRequest, WebRequest, WebResponse};
+
+/// `Middleware` for one-time flash messages.
+///
+/// Flash message is stored in a signed cookie. Message could be set with
+/// [`FlashMessage::set()`](struct.FlashMessage.html#method.set) and it is
+/// available for the next request via
+/// [`FlashMessage::get()`](struct.FlashMessage.html#method.get).
+/// Reading message removes flash cookie. Middleware requires `flash` feature.
+///
+/// ```rust
+/// use ntex::http::header;
+/// use ntex::web::{self, middleware, App, HttpRequest, HttpResponse};
+/// use ntex::web::middleware::FlashMessage;
+///
+/// async fn submit(req: HttpRequest) -> HttpResponse {
+///     FlashMessage::set(&req, "Saved!");
+///     HttpResponse::SeeOther().header(header::LOCATION, "/").finish()
+/// }
+///
+/// async fn index(req: HttpRequest) -> String {
+///     FlashMessage::get(&req).unwrap_or_default()
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::Flash::new(&[0; 64]))
+///         .route("/", web::get().to(index))
+///         .route("/submit", web::post().to(submit));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Flash {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    key: Key,
+    name: String,
+    path: String,
+    secure: bool,
+}
+
+impl Flash {
+    /// Construct `Flash` middleware.
+    ///
+    /// Key is used for signing flash cookie.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is less than 64 bytes in length.
+    pub fn new(key: &[u8]) -> Self {
+        Flash {
+            inner: Rc::new(Inner {
+                key: Key::from(key),
+                name: "_flash".to_string(),
+                path: "/".to_string(),
+                secure: false,
+            }),
+        }
+    }
+
+    /// Set flash cookie name, by default `_flash` is used.
+    pub fn name(mut self, name: &str) -> Self {
+        self.inner_mut().name = name.to_string();
+        self
+    }
+
+    /// Set flash cookie path, by default `/` is used.
+    pub fn path(mut self, path: &str) -> Self {
+        self.inner_mut().path = path.to_string();
+        self
+    }
+
+    /// Set `Secure` attribute of flash cookie.
+    ///
+    /// By default it is disabled.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.inner_mut().secure = secure;
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner).expect("Multiple copies exist")
+    }
+}
+
+impl Inner {
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        Cookie::build(self.name.clone(), value)
+            .path(self.path.clone())
+            .secure(self.secure)
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .finish()
+    }
+
+    fn load(&self, req: &WebRequest<impl Sized>) -> Option<String> {
+        let mut jar = CookieJar::new();
+        jar.add_original(req.cookie(&self.name)?);
+        jar.signed(&self.key)
+            .get(&self.name)
+            .map(|c| c.value().to_string())
+    }
+}
+
+/// Flash message helper
+pub struct FlashMessage;
+
+impl FlashMessage {
+    /// Set flash message for the next request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `Flash` middleware is not installed.
+    pub fn set<T: Into<String>>(req: &HttpRequest, msg: T) {
+        let extensions = req.extensions();
+        let state = extensions
+            .get::<FlashState>()
+            .expect("Flash middleware is not installed");
+        state.0.borrow_mut().outgoing = Some(msg.into());
+    }
+
+    /// Get flash message set by the previous request.
+    ///
+    /// Message is removed, flash cookie get deleted.
+    pub fn get(req: &HttpRequest) -> Option<String> {
+        let extensions = req.extensions();
+        let mut state = extensions.get::<FlashState>()?.0.borrow_mut();
+        state.consumed = true;
+        state.incoming.take()
+    }
+}
+
+#[derive(Clone)]
+struct FlashState(Rc<RefCell<State>>);
+
+struct State {
+    present: bool,
+    consumed: bool,
+    incoming: Option<String>,
+    outgoing: Option<String>,
+}
+
+impl<S> Transform<S> for Flash {
+    type Service = FlashMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        FlashMiddleware {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+pub struct FlashMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, E> Service<WebRequest<E>> for FlashMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Future: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        let inner = self.inner.clone();
+        let state = FlashState(Rc::new(RefCell::new(State {
+            present: req.cookie(&inner.name).is_some(),
+            consumed: false,
+            incoming: inner.load(&req),
+            outgoing: None,
+        })));
+        req.extensions_mut().insert(state.clone());
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let state = state.0.borrow();
+            let cookie = if let Some(ref msg) = state.outgoing {
+                let mut jar = CookieJar::new();
+                jar.signed_mut(&inner.key).add(inner.cookie(msg.clone()));
+                jar.get(&inner.name).cloned()
+            } else if state.present && state.consumed {
+                let mut cookie = inner.cookie(String::new());
+                cookie.make_removal();
+                Some(cookie)
+            } else {
+                None
+            };
+
+            if let Some(cookie) = cookie {
+                if let Ok(val) = HeaderValue::from_str(&cookie.encoded().to_string()) {
+                    res.headers_mut().append(SET_COOKIE, val);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{self, COOKIE};
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    fn flash_cookie(res: &WebResponse) -> Option<Cookie<'static>> {
+        res.headers()
+            .get(SET_COOKIE)
+            .map(|v| Cookie::parse_encoded(v.to_str().unwrap().to_string()).unwrap())
+    }
+
+    #[crate::rt_test]
+    async fn test_flash() {
+        let srv = init_service(
+            App::new()
+                .wrap(Flash::new(&[1; 64]))
+                .route(
+                    "/set",
+                    web::post().to(|req: HttpRequest| async move {
+                        FlashMessage::set(&req, "Saved; ok!");
+                        HttpResponse::SeeOther()
+                            .header(header::LOCATION, "/get")
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/get",
+                    web::get().to(|req: HttpRequest| async move {
+                        FlashMessage::get(&req).unwrap_or_else(|| "none".to_string())
+                    }),
+                ),
+        )
+        .await;
+
+        // set flash message
+        let req = TestRequest::with_uri("/set")
+            .method(crate::http::Method::POST)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        let cookie = flash_cookie(&res).unwrap();
+        assert_eq!(cookie.name(), "_flash");
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_ne!(cookie.value(), "Saved; ok!");
+
+        // read flash message, cookie get removed
+        let req = TestRequest::with_uri("/get")
+            .header(COOKIE, cookie.encoded().stripped().to_string())
+            .to_request();
+        let res = call_service(&srv, req).await;
+        let removal = flash_cookie(&res).unwrap();
+        assert_eq!(removal.name(), "_flash");
+        assert_eq!(removal.value(), "");
+        assert_eq!(removal.max_age(), Some(time::Duration::ZERO));
+        assert_eq!(read_body(res).await, "Saved; ok!");
+
+        // no flash message
+        let req = TestRequest::with_uri("/get").to_request();
+        let res = call_service(&srv, req).await;
+        assert!(flash_cookie(&res).is_none());
+        assert_eq!(read_body(res).await, "none");
+
+        // tampered cookie
+        let value = cookie.value().replace("ok", "ko");
+        let req = TestRequest::with_uri("/get")
+            .header(
+                COOKIE,
+                Cookie::new("_flash", value)
+                    .encoded()
+                    .stripped()
+                    .to_string(),
+            )
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(flash_cookie(&res).unwrap().value(), "");
+        assert_eq!(read_body(res).await, "none");
+
+        // cookie signed with different key
+        let srv = init_service(App::new().wrap(Flash::new(&[2; 64])).route(
+            "/get",
+            web::get().to(|req: HttpRequest| async move {
+                FlashMessage::get(&req).unwrap_or_else(|| "none".to_string())
+            }),
+        ))
+        .await;
+        let req = TestRequest::with_uri("/get")
+            .header(COOKIE, cookie.encoded().stripped().to_string())
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(read_body(res).await, "none");
+    }
+
+    #[crate::rt_test]
+    async fn test_flash_not_read() {
+        let srv = init_service(
+            App::new()
+                .wrap(Flash::new(&[1; 64]).name("msg").path("/app").secure(true))
+                .route(
+                    "/set",
+                    web::get().to(|req: HttpRequest| async move {
+                        FlashMessage::set(&req, "test");
+                        HttpResponse::Ok()
+                    }),
+                )
+                .route("/index", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let res = call_service(&srv, TestRequest::with_uri("/set").to_request()).await;
+        let cookie = flash_cookie(&res).unwrap();
+        assert_eq!(cookie.name(), "msg");
+        assert_eq!(cookie.path(), Some("/app"));
+        assert_eq!(cookie.secure(), Some(true));
+
+        // message is not consumed, cookie stays
+        let req = TestRequest::with_uri("/index")
+            .header(COOKIE, cookie.encoded().stripped().to_string())
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert!(flash_cookie(&res).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+//! Middleware for applying other middleware to a subset of request methods
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use crate::http::Method;
+use crate::service::{Service, Transform};
+use crate::util::Either;
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` that applies inner middleware only to requests with
+/// specified methods.
+///
+/// Requests with other methods are passed to the wrapped service directly.
+///
+/// ```rust
+/// use ntex::http::Method;
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::MethodFilter::new(
+///             middleware::DefaultHeaders::new().header("X-Mutation", "1"),
+///             [Method::POST, Method::PUT, Method::DELETE],
+///         ))
+///         .service(
+///             web::resource("/test")
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///                 .route(web::post().to(|| async { HttpResponse::Ok() }))
+///         );
+/// }
+/// ```
+pub struct MethodFilter<T> {
+    transform: T,
+    methods: Rc<Vec<Method>>,
+}
+
+impl<T> MethodFilter<T> {
+    /// Construct `MethodFilter` middleware for the specified middleware
+    /// and set of methods.
+    pub fn new<I>(transform: T, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        MethodFilter {
+            transform,
+            methods: Rc::new(methods.into_iter().collect()),
+        }
+    }
+}
+
+impl<T: Clone> Clone for MethodFilter<T> {
+    fn clone(&self) -> Self {
+        MethodFilter {
+            transform: self.transform.clone(),
+            methods: self.methods.clone(),
+        }
+    }
+}
+
+impl<S, T> Transform<S> for MethodFilter<T>
+where
+    T: Transform<Rc<S>>,
+{
+    type Service = MethodFilterMiddleware<S, T::Service>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        let service = Rc::new(service);
+        MethodFilterMiddleware {
+            filtered: self.transform.new_transform(service.clone()),
+            service,
+            methods: self.methods.clone(),
+        }
+    }
+}
+
+/// Method filter middleware
+pub struct MethodFilterMiddleware<S, M> {
+    service: Rc<S>,
+    filtered: M,
+    methods: Rc<Vec<Method>>,
+}
+
+impl<S, M, E> Service<WebRequest<E>> for MethodFilterMiddleware<S, M>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    M: Service<WebRequest<E>, Response = WebResponse, Error = S::Error>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Either<M::Future, S::Future>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let ready1 = self.filtered.poll_ready(cx)?.is_ready();
+        let ready2 = self.service.poll_ready(cx)?.is_ready();
+        if ready1 && ready2 {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        let ready1 = self.filtered.poll_shutdown(cx, is_error).is_ready();
+        let ready2 = self.service.poll_shutdown(cx, is_error).is_ready();
+        if ready1 && ready2 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    #[inline]
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        if self.methods.contains(req.method()) {
+            Either::Left(self.filtered.call(req))
+        } else {
+            Either::Right(self.service.call(req))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{HeaderValue, CONTENT_TYPE};
+    use crate::util::lazy;
+    use crate::web::middleware::DefaultHeaders;
+    use crate::web::test::{call_service, init_service, ok_service, TestRequest};
+    use crate::web::{self, App, DefaultError, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_method_filter() {
+        let srv = init_service(
+            App::new()
+                .wrap(MethodFilter::new(
+                    DefaultHeaders::new().header(CONTENT_TYPE, "0001"),
+                    [Method::POST, Method::PUT],
+                ))
+                .service(
+                    web::resource("/")
+                        .route(web::get().to(|| async { HttpResponse::Ok() }))
+                        .route(web::post().to(|| async { HttpResponse::Ok() }))
+                        .route(web::put().to(|| async { HttpResponse::Ok() })),
+                ),
+        )
+        .await;
+
+        for method in [Method::POST, Method::PUT] {
+            let req = TestRequest::with_uri("/").method(method).to_request();
+            let resp = call_service(&srv, req).await;
+            assert_eq!(
+                resp.headers().get(CONTENT_TYPE).unwrap(),
+                HeaderValue::from_static("0001")
+            );
+        }
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(resp.status().is_success());
+        assert!(resp.headers().get(CONTENT_TYPE).is_none());
+    }
+
+    #[crate::rt_test]
+    async fn test_readiness() {
+        let mw = MethodFilter::new(DefaultHeaders::new(), [Method::POST])
+            .new_transform(ok_service::<DefaultError>());
+        assert!(lazy(|cx| mw.poll_ready(cx).is_ready()).await);
+        assert!(lazy(|cx| mw.poll_shutdown(cx, true).is_ready()).await);
+    }
+}
@@ -8,5 +8,39 @@ pub use self::compress::Compress;
 mod logger;
 pub use self::logger::Logger;
 
+mod structuredlogger;
+pub use self::structuredlogger::{AccessLogEntry, AccessLogSink, StructuredLogger};
+
+mod errorlogger;
+pub use self::errorlogger::{ErrorLogger, RequestId};
+
+#[cfg(feature = "tracing")]
+mod tracinglogger;
+#[cfg(feature = "tracing")]
+pub use self::tracinglogger::TracingLogger;
+
 mod defaultheaders;
 pub use self::defaultheaders::DefaultHeaders;
+
+mod adapter;
+pub use self::adapter::{Adapter, AdapterMiddleware};
+
+mod panichandler;
+pub use self::panichandler::PanicHandler;
+
+mod concurrencylimit;
+pub use self::concurrencylimit::ConcurrencyLimit;
+
+mod securityheaders;
+pub use self::securityheaders::SecurityHeaders;
+
+mod methodfilter;
+pub use self::methodfilter::MethodFilter;
+
+mod rewrite;
+pub use self::rewrite::{OriginalUri, Rewrite};
+
+#[cfg(feature = "flash")]
+mod flash;
+#[cfg(feature = "flash")]
+pub use self::flash::{Flash, FlashMessage};
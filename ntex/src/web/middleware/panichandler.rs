@@ -0,0 +1,167 @@
+//! Middleware for handling panics in request handlers
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::task::{Context, Poll};
+use std::{any::Any, future::Future, pin::Pin};
+
+use crate::service::{Service, Transform};
+use crate::util::{Either, Ready};
+use crate::web::error::{ErrorRenderer, PanicError};
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for catching panics in request handlers.
+///
+/// Panic during request processing is converted to `PanicError` error,
+/// with default error renderer client receives `500 Internal Server Error`
+/// response. Panic message is logged at `ERROR` level, backtrace is printed
+/// by panic hook if `RUST_BACKTRACE` is set.
+///
+/// Service that panicked is not restarted, middleware only guarantees
+/// that request gets response.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::PanicHandler::new())
+///         .route("/", web::get().to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PanicHandler;
+
+impl PanicHandler {
+    /// Construct `PanicHandler` middleware.
+    pub fn new() -> Self {
+        PanicHandler
+    }
+}
+
+impl<S> Transform<S> for PanicHandler {
+    type Service = PanicHandlerMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        PanicHandlerMiddleware { service }
+    }
+}
+
+/// Panic handler middleware
+pub struct PanicHandlerMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for PanicHandlerMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = Err::Container>,
+    Err: ErrorRenderer,
+    Err::Container: From<PanicError>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Either<CatchUnwind<S::Future>, Ready<WebResponse, S::Error>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<Err>) -> Self::Future {
+        match catch_unwind(AssertUnwindSafe(|| self.service.call(req))) {
+            Ok(fut) => Either::Left(CatchUnwind { fut }),
+            Err(panic) => Either::Right(Ready::Err(panic_error(panic).into())),
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[doc(hidden)]
+    pub struct CatchUnwind<F> {
+        #[pin]
+        fut: F,
+    }
+}
+
+impl<F, E> Future for CatchUnwind<F>
+where
+    F: Future<Output = Result<WebResponse, E>>,
+    E: From<PanicError>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = self.project().fut;
+        match catch_unwind(AssertUnwindSafe(|| fut.poll(cx))) {
+            Ok(res) => res,
+            Err(panic) => Poll::Ready(Err(panic_error(panic).into())),
+        }
+    }
+}
+
+fn panic_error(panic: Box<dyn Any + Send>) -> PanicError {
+    let message = if let Some(msg) = panic.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    };
+    log::error!("Request handler panicked: {}", message);
+    PanicError::new(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_panic_handler() {
+        let srv = init_service(
+            App::new()
+                .wrap(PanicHandler::new())
+                .route(
+                    "/panic",
+                    web::get().to(|| async {
+                        if true {
+                            panic!("handler panic");
+                        }
+                        HttpResponse::Ok()
+                    }),
+                )
+                .route("/", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/panic").to_request();
+        let err = srv.call(req).await.err().unwrap();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(err.to_string(), "Internal server error");
+
+        // service is still usable
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let err = catch_unwind(|| panic!("static message")).unwrap_err();
+        assert_eq!(panic_error(err).message(), "static message");
+
+        let err = catch_unwind(|| panic!("formatted {}", 1)).unwrap_err();
+        assert_eq!(panic_error(err).message(), "formatted 1");
+
+        let err = catch_unwind(|| std::panic::panic_any(1)).unwrap_err();
+        assert_eq!(panic_error(err).message(), "Box<dyn Any>");
+    }
+}
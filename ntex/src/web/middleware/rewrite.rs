@@ -0,0 +1,245 @@
+//! Middleware for rewriting request path before routing
+use std::task::{Context, Poll};
+use std::{convert::TryFrom, rc::Rc};
+
+use crate::http::uri::{PathAndQuery, Uri};
+use crate::http::RequestHead;
+use crate::router::{Path, ResourceDef, Router};
+use crate::service::{Service, Transform};
+use crate::util::HashMap;
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for rewriting request path.
+///
+/// Path is rewritten before request routing, so middleware must be
+/// registered with `App::wrap()`. Rules are checked in registration order,
+/// the first matching rule is applied. Pattern rule uses the same syntax as
+/// resource definitions, matched segments could be used in the replacement.
+/// Query string is preserved. Original uri is available in
+/// request extensions as `OriginalUri`.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(
+///             middleware::Rewrite::new()
+///                 .rule("/v2/users/{id}", "/users/{id}")
+///                 .rule("/old/{tail}*", "/{tail}")
+///         )
+///         .service(
+///             web::resource("/users/{id}")
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///         );
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Rewrite {
+    rules: Rc<Vec<Rule>>,
+}
+
+enum Rule {
+    Pattern(Router<()>, ResourceDef),
+    Fn(Box<dyn Fn(&RequestHead) -> Option<String>>),
+}
+
+/// Original request uri, before path rewrite
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OriginalUri(pub Uri);
+
+impl Rewrite {
+    /// Construct `Rewrite` middleware without rules.
+    pub fn new() -> Self {
+        Rewrite::default()
+    }
+
+    /// Add pattern rule.
+    ///
+    /// Path that matches `pattern` is replaced with `replacement`,
+    /// `{name}` in replacement is substituted with matched segment.
+    pub fn rule(mut self, pattern: &str, replacement: &str) -> Self {
+        let mut router = Router::build();
+        router.path(pattern, ());
+        Rc::get_mut(&mut self.rules)
+            .expect("Multiple copies exist")
+            .push(Rule::Pattern(
+                router.finish(),
+                ResourceDef::new(replacement),
+            ));
+        self
+    }
+
+    /// Add rule function.
+    ///
+    /// Function returns new path or `None` if path must not be rewritten.
+    pub fn rule_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestHead) -> Option<String> + 'static,
+    {
+        Rc::get_mut(&mut self.rules)
+            .expect("Multiple copies exist")
+            .push(Rule::Fn(Box::new(f)));
+        self
+    }
+
+    fn rewrite(&self, head: &RequestHead) -> Option<String> {
+        for rule in self.rules.iter() {
+            match rule {
+                Rule::Pattern(router, replacement) => {
+                    let mut path = Path::new(head.uri.clone());
+                    if router.recognize(&mut path).is_some() {
+                        let segments: HashMap<_, _> = path.iter().collect();
+                        let mut new_path = String::new();
+                        if replacement.resource_path_named(&mut new_path, &segments) {
+                            return Some(new_path);
+                        }
+                        log::error!(
+                            "Cannot build rewrite path {:?} for {:?}",
+                            replacement.pattern(),
+                            head.uri.path()
+                        );
+                    }
+                }
+                Rule::Fn(f) => {
+                    if let Some(new_path) = f(head) {
+                        return Some(new_path);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<S> Transform<S> for Rewrite {
+    type Service = RewriteMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        RewriteMiddleware {
+            service,
+            rewrite: self.clone(),
+        }
+    }
+}
+
+/// Path rewrite middleware
+pub struct RewriteMiddleware<S> {
+    service: S,
+    rewrite: Rewrite,
+}
+
+impl<S, E> Service<WebRequest<E>> for RewriteMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, mut req: WebRequest<E>) -> Self::Future {
+        if let Some(path) = self.rewrite.rewrite(req.head()) {
+            let uri = req.uri().clone();
+            let rooted = path.starts_with('/');
+            let path_and_query = if let Some(query) = uri.query() {
+                PathAndQuery::try_from(format!("{}?{}", path, query))
+            } else {
+                PathAndQuery::try_from(path)
+            };
+
+            let mut parts = uri.clone().into_parts();
+            parts.path_and_query = path_and_query.ok();
+            match Uri::from_parts(parts) {
+                Ok(new_uri) if rooted => {
+                    log::trace!("Rewrite {:?} to {:?}", uri, new_uri);
+                    if !req.extensions().contains::<OriginalUri>() {
+                        req.extensions_mut().insert(OriginalUri(uri));
+                    }
+                    req.match_info_mut().set(new_uri.clone());
+                    req.head_mut().uri = new_uri;
+                }
+                _ => log::error!("Cannot rewrite {:?}, invalid path", uri),
+            }
+        }
+        self.service.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpRequest, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_rewrite() {
+        let srv = init_service(
+            App::new()
+                .wrap(
+                    Rewrite::new()
+                        .rule("/v2/users/{id}", "/users/{id}")
+                        .rule("/old/{tail}*", "/{tail}")
+                        .rule_fn(|head| {
+                            if head.uri.path().starts_with("/legacy/") {
+                                Some(head.uri.path().replacen("/legacy/", "/", 1))
+                            } else {
+                                None
+                            }
+                        }),
+                )
+                .service(
+                    web::resource("/users/{id}").to(|req: HttpRequest| async move {
+                        let orig = req
+                            .extensions()
+                            .get::<OriginalUri>()
+                            .map(|u| u.0.to_string())
+                            .unwrap_or_default();
+                        HttpResponse::Ok().body(format!(
+                            "{} {} {:?} {}",
+                            req.match_info().query("id"),
+                            req.path(),
+                            req.uri().query(),
+                            orig
+                        ))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/v2/users/42?a=b").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(body, "42 /users/42 Some(\"a=b\") /v2/users/42?a=b");
+
+        let req = TestRequest::with_uri("/legacy/users/7").to_request();
+        let resp = call_service(&srv, req).await;
+        let body = read_body(resp).await;
+        assert_eq!(body, "7 /users/7 None /legacy/users/7");
+
+        let req = TestRequest::with_uri("/old/users/5").to_request();
+        let resp = call_service(&srv, req).await;
+        let body = read_body(resp).await;
+        assert_eq!(body, "5 /users/5 None /old/users/5");
+
+        let req = TestRequest::with_uri("/users/1").to_request();
+        let resp = call_service(&srv, req).await;
+        let body = read_body(resp).await;
+        assert_eq!(body, "1 /users/1 None ");
+
+        let req = TestRequest::with_uri("/v2/items/1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
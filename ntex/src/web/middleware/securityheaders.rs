@@ -0,0 +1,299 @@
+//! Middleware for setting common security related response headers
+use std::task::{Context, Poll};
+use std::{future::Future, pin::Pin, rc::Rc};
+
+use crate::http::header::{self, HeaderName, HeaderValue};
+use crate::service::{Service, Transform};
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for setting common security related response headers.
+///
+/// By default following headers are set:
+///
+/// * `X-Content-Type-Options: nosniff`
+/// * `X-Frame-Options: DENY`
+/// * `Referrer-Policy: strict-origin-when-cross-origin`
+/// * `Strict-Transport-Security: max-age=31536000; includeSubDomains`, only
+///   for requests with `https` scheme, scheme is checked via `ConnectionInfo::scheme()`
+///
+/// `Content-Security-Policy` header is not set unless configured explicitly.
+/// Every header could be overridden or disabled. This middleware does not
+/// set header if response headers already contains it.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(
+///             middleware::SecurityHeaders::new()
+///                 .frame_options(Some("SAMEORIGIN"))
+///                 .content_security_policy("default-src 'self'")
+///         )
+///         .service(
+///             web::resource("/test")
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///         );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    content_type_options: bool,
+    frame_options: Option<HeaderValue>,
+    referrer_policy: Option<HeaderValue>,
+    hsts: Option<HeaderValue>,
+    csp: Option<HeaderValue>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        SecurityHeaders {
+            inner: Rc::new(Inner {
+                content_type_options: true,
+                frame_options: Some(HeaderValue::from_static("DENY")),
+                referrer_policy: Some(HeaderValue::from_static(
+                    "strict-origin-when-cross-origin",
+                )),
+                hsts: Some(HeaderValue::from_static(
+                    "max-age=31536000; includeSubDomains",
+                )),
+                csp: None,
+            }),
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// Construct `SecurityHeaders` middleware with default set of headers.
+    pub fn new() -> SecurityHeaders {
+        SecurityHeaders::default()
+    }
+
+    /// Enable or disable `X-Content-Type-Options: nosniff` header.
+    ///
+    /// By default it is enabled.
+    pub fn content_type_options(mut self, enabled: bool) -> Self {
+        self.inner_mut().content_type_options = enabled;
+        self
+    }
+
+    /// Set `X-Frame-Options` header value, `None` disables header.
+    ///
+    /// By default it is set to `DENY`.
+    pub fn frame_options(mut self, value: Option<&str>) -> Self {
+        self.inner_mut().frame_options = value.map(header_value);
+        self
+    }
+
+    /// Set `Referrer-Policy` header value, `None` disables header.
+    ///
+    /// By default it is set to `strict-origin-when-cross-origin`.
+    pub fn referrer_policy(mut self, value: Option<&str>) -> Self {
+        self.inner_mut().referrer_policy = value.map(header_value);
+        self
+    }
+
+    /// Set `Strict-Transport-Security` header value, `None` disables header.
+    ///
+    /// Header is emitted only for requests over `https`.
+    /// By default it is set to `max-age=31536000; includeSubDomains`.
+    pub fn hsts(mut self, value: Option<&str>) -> Self {
+        self.inner_mut().hsts = value.map(header_value);
+        self
+    }
+
+    /// Set `Content-Security-Policy` header value.
+    pub fn content_security_policy(mut self, value: &str) -> Self {
+        self.inner_mut().csp = Some(header_value(value));
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner).expect("Multiple copies exist")
+    }
+}
+
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("Cannot create header value")
+}
+
+impl<S> Transform<S> for SecurityHeaders {
+    type Service = SecurityHeadersMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        SecurityHeadersMiddleware {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+pub struct SecurityHeadersMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, E> Service<WebRequest<E>> for SecurityHeadersMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Future: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        let inner = self.inner.clone();
+        let secure = inner.hsts.is_some() && req.connection_info().scheme() == "https";
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let headers = res.headers_mut();
+            let mut set = |name: HeaderName, value: &HeaderValue| {
+                if !headers.contains_key(&name) {
+                    headers.insert(name, value.clone());
+                }
+            };
+
+            if inner.content_type_options {
+                set(
+                    header::X_CONTENT_TYPE_OPTIONS,
+                    &HeaderValue::from_static("nosniff"),
+                );
+            }
+            if let Some(ref value) = inner.frame_options {
+                set(header::X_FRAME_OPTIONS, value);
+            }
+            if let Some(ref value) = inner.referrer_policy {
+                set(header::REFERRER_POLICY, value);
+            }
+            if let Some(ref value) = inner.csp {
+                set(header::CONTENT_SECURITY_POLICY, value);
+            }
+            if secure {
+                if let Some(ref value) = inner.hsts {
+                    set(header::STRICT_TRANSPORT_SECURITY, value);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{
+        CONTENT_SECURITY_POLICY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+        X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+    };
+    use crate::service::IntoService;
+    use crate::util::lazy;
+    use crate::web::test::{ok_service, TestRequest};
+    use crate::web::{DefaultError, Error, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_default_headers() {
+        let mw = SecurityHeaders::new().new_transform(ok_service());
+
+        assert!(lazy(|cx| mw.poll_ready(cx).is_ready()).await);
+        assert!(lazy(|cx| mw.poll_shutdown(cx, true).is_ready()).await);
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get(X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+        assert_eq!(resp.headers().get(X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(
+            resp.headers().get(REFERRER_POLICY).unwrap(),
+            "strict-origin-when-cross-origin"
+        );
+        assert!(!resp.headers().contains_key(CONTENT_SECURITY_POLICY));
+        assert!(!resp.headers().contains_key(STRICT_TRANSPORT_SECURITY));
+    }
+
+    #[crate::rt_test]
+    async fn test_hsts() {
+        let mw = SecurityHeaders::new().new_transform(ok_service());
+
+        let req = TestRequest::with_uri("https://www.rust-lang.org/").to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=31536000; includeSubDomains"
+        );
+
+        let req = TestRequest::with_header("x-forwarded-proto", "https").to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(resp.headers().contains_key(STRICT_TRANSPORT_SECURITY));
+
+        let req = TestRequest::with_uri("http://www.rust-lang.org/").to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(STRICT_TRANSPORT_SECURITY));
+
+        let mw = SecurityHeaders::new()
+            .hsts(None)
+            .new_transform(ok_service());
+        let req = TestRequest::with_uri("https://www.rust-lang.org/").to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(STRICT_TRANSPORT_SECURITY));
+    }
+
+    #[crate::rt_test]
+    async fn test_overrides() {
+        let srv = |req: WebRequest<DefaultError>| async move {
+            Ok::<_, Error>(
+                req.into_response(
+                    HttpResponse::Ok()
+                        .header(X_FRAME_OPTIONS, "ALLOW-FROM https://example.com")
+                        .finish(),
+                ),
+            )
+        };
+        let mw = SecurityHeaders::new()
+            .content_type_options(false)
+            .referrer_policy(Some("no-referrer"))
+            .content_security_policy("default-src 'self'")
+            .new_transform(srv.into_service());
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(X_CONTENT_TYPE_OPTIONS));
+        assert_eq!(
+            resp.headers().get(X_FRAME_OPTIONS).unwrap(),
+            "ALLOW-FROM https://example.com"
+        );
+        assert_eq!(resp.headers().get(REFERRER_POLICY).unwrap(), "no-referrer");
+        assert_eq!(
+            resp.headers().get(CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'"
+        );
+
+        let mw = SecurityHeaders::new()
+            .frame_options(None)
+            .referrer_policy(None)
+            .new_transform(ok_service());
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(X_FRAME_OPTIONS));
+        assert!(!resp.headers().contains_key(REFERRER_POLICY));
+    }
+}
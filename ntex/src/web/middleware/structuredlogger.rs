@@ -0,0 +1,326 @@
+//! Structured request logging middleware
+use std::task::{Context, Poll};
+use std::{error::Error, future::Future, pin::Pin, rc::Rc, time::Duration, time::Instant};
+
+use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
+use crate::http::header::HeaderName;
+use crate::http::{Method, StatusCode};
+use crate::service::{Service, Transform};
+use crate::util::{Bytes, HashSet};
+use crate::web::{WebRequest, WebResponse};
+
+/// Access log entry
+///
+/// Entry is passed to [`AccessLogSink`](trait.AccessLogSink.html) after
+/// response body is sent.
+#[derive(Clone, Debug)]
+pub struct AccessLogEntry {
+    /// Request method
+    pub method: Method,
+    /// Request path
+    pub path: String,
+    /// Matched resource pattern, pattern is relative to enclosing scope
+    pub pattern: Option<String>,
+    /// Response status code
+    pub status: StatusCode,
+    /// Size of response body in bytes
+    pub size: usize,
+    /// Time taken to serve the request
+    pub duration: Duration,
+    /// Remote IP-address (IP-address of proxy if using reverse proxy)
+    pub remote_addr: Option<String>,
+    /// Request id, value of request id header
+    pub request_id: Option<String>,
+}
+
+/// Receiver of access log entries
+pub trait AccessLogSink {
+    /// Record access log entry
+    fn record(&self, entry: &AccessLogEntry);
+}
+
+impl<F> AccessLogSink for F
+where
+    F: Fn(&AccessLogEntry),
+{
+    fn record(&self, entry: &AccessLogEntry) {
+        (self)(entry)
+    }
+}
+
+/// `Middleware` for structured access logging.
+///
+/// Unlike [`Logger`](struct.Logger.html), middleware does not format log
+/// lines, it passes typed [`AccessLogEntry`](struct.AccessLogEntry.html) to
+/// the provided [`AccessLogSink`](trait.AccessLogSink.html). Entry is recorded
+/// when response body is sent.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+/// use ntex::web::middleware::AccessLogEntry;
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::StructuredLogger::new(|entry: &AccessLogEntry| {
+///             println!(
+///                 r#"{{"method":"{}","path":"{}","status":{},"ms":{}}}"#,
+///                 entry.method,
+///                 entry.path,
+///                 entry.status.as_u16(),
+///                 entry.duration.as_millis()
+///             );
+///         }))
+///         .route("/", web::get().to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct StructuredLogger {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    sink: Box<dyn AccessLogSink>,
+    exclude: HashSet<String>,
+    request_id: HeaderName,
+}
+
+impl StructuredLogger {
+    /// Create `StructuredLogger` middleware with the specified sink.
+    pub fn new<T: AccessLogSink + 'static>(sink: T) -> StructuredLogger {
+        StructuredLogger {
+            inner: Rc::new(Inner {
+                sink: Box::new(sink),
+                exclude: HashSet::default(),
+                request_id: HeaderName::from_static("x-request-id"),
+            }),
+        }
+    }
+
+    /// Ignore and do not log access info for specified path.
+    pub fn exclude<T: Into<String>>(mut self, path: T) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .exclude
+            .insert(path.into());
+        self
+    }
+
+    /// Set request id header name.
+    ///
+    /// By default `x-request-id` header is used.
+    pub fn request_id_header(mut self, name: HeaderName) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .request_id = name;
+        self
+    }
+}
+
+impl<S> Transform<S> for StructuredLogger {
+    type Service = StructuredLoggerMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        StructuredLoggerMiddleware {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Structured logger middleware
+pub struct StructuredLoggerMiddleware<S> {
+    inner: Rc<Inner>,
+    service: S,
+}
+
+impl<S, E> Service<WebRequest<E>> for StructuredLoggerMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Future: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        if self.inner.exclude.contains(req.path()) {
+            let fut = self.service.call(req);
+            return Box::pin(fut);
+        }
+
+        let start = Instant::now();
+        let inner = self.inner.clone();
+        let mut entry = AccessLogEntry {
+            method: req.method().clone(),
+            path: req.path().to_string(),
+            pattern: None,
+            status: StatusCode::OK,
+            size: 0,
+            duration: Duration::default(),
+            remote_addr: req.connection_info().remote().map(|s| s.to_string()),
+            request_id: req
+                .headers()
+                .get(&inner.request_id)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string()),
+        };
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            entry.status = res.status();
+            entry.pattern = res.request().match_pattern().map(|s| s.to_string());
+
+            Ok(res.map_body(move |_, body| {
+                ResponseBody::Other(Body::from_message(StreamLog {
+                    body,
+                    inner,
+                    start,
+                    entry,
+                }))
+            }))
+        })
+    }
+}
+
+struct StreamLog {
+    body: ResponseBody<Body>,
+    inner: Rc<Inner>,
+    start: Instant,
+    entry: AccessLogEntry,
+}
+
+impl Drop for StreamLog {
+    fn drop(&mut self) {
+        self.entry.duration = self.start.elapsed();
+        self.inner.sink.record(&self.entry);
+    }
+}
+
+impl MessageBody for StreamLog {
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        match self.body.poll_next_chunk(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.entry.size += chunk.len();
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            val => val,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[derive(Clone, Default)]
+    struct MockSink(Rc<RefCell<Vec<AccessLogEntry>>>);
+
+    impl AccessLogSink for MockSink {
+        fn record(&self, entry: &AccessLogEntry) {
+            self.0.borrow_mut().push(entry.clone());
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_structured_logger() {
+        let sink = MockSink::default();
+        let srv = init_service(
+            App::new()
+                .wrap(StructuredLogger::new(sink.clone()).exclude("/health"))
+                .service(
+                    web::scope("/api").service(
+                        web::resource("/user/{id}")
+                            .route(web::get().to(|| async { "user info" }))
+                            .route(web::post().to(|| async { HttpResponse::Created() })),
+                    ),
+                )
+                .route("/health", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/api/user/1")
+            .header("x-request-id", "req-1")
+            .header("x-forwarded-for", "10.0.0.1")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        // entry is recorded when response body is sent
+        assert!(sink.0.borrow().is_empty());
+        assert_eq!(read_body(resp).await, "user info");
+
+        let req = TestRequest::with_uri("/api/user/2")
+            .method(Method::POST)
+            .to_request();
+        drop(call_service(&srv, req).await);
+
+        let req = TestRequest::with_uri("/health").to_request();
+        drop(call_service(&srv, req).await);
+
+        let req = TestRequest::with_uri("/unknown").to_request();
+        drop(call_service(&srv, req).await);
+
+        let entries = sink.0.borrow();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].method, Method::GET);
+        assert_eq!(entries[0].path, "/api/user/1");
+        assert_eq!(entries[0].pattern.as_deref(), Some("/user/{id}"));
+        assert_eq!(entries[0].status, StatusCode::OK);
+        assert_eq!(entries[0].size, 9);
+        assert_eq!(entries[0].remote_addr.as_deref(), Some("10.0.0.1"));
+        assert_eq!(entries[0].request_id.as_deref(), Some("req-1"));
+
+        assert_eq!(entries[1].method, Method::POST);
+        assert_eq!(entries[1].path, "/api/user/2");
+        assert_eq!(entries[1].status, StatusCode::CREATED);
+        assert_eq!(entries[1].size, 0);
+        assert_eq!(entries[1].request_id, None);
+
+        assert_eq!(entries[2].path, "/unknown");
+        assert_eq!(entries[2].pattern, None);
+        assert_eq!(entries[2].status, StatusCode::NOT_FOUND);
+    }
+
+    #[crate::rt_test]
+    async fn test_request_id_header() {
+        let sink = MockSink::default();
+        let srv = init_service(
+            App::new()
+                .wrap(
+                    StructuredLogger::new(sink.clone())
+                        .request_id_header(HeaderName::from_static("x-trace-id")),
+                )
+                .route("/", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/")
+            .header("x-trace-id", "trace")
+            .header("x-request-id", "req")
+            .to_request();
+        drop(call_service(&srv, req).await);
+        assert_eq!(sink.0.borrow()[0].request_id.as_deref(), Some("trace"));
+        assert_eq!(sink.0.borrow()[0].pattern.as_deref(), Some("/"));
+    }
+}
@@ -0,0 +1,312 @@
+//! Request tracing middleware
+use std::task::{Context, Poll};
+use std::{future::Future, pin::Pin, thread, time::Instant};
+
+use tracing_pkg::{field::Empty, Instrument, Span};
+
+use crate::service::{Service, Transform};
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for tracing requests with `tracing` crate.
+///
+/// Middleware opens `http_request` span for each request and enters it for
+/// the duration of the request handling. Span has following fields:
+///
+/// * `http.method` - request method
+/// * `http.target` - request path
+/// * `http.route` - matched resource pattern, pattern is relative to
+///   enclosing scope
+/// * `http.status_code` - response status code
+/// * `latency_ms` - time spent on handling request, in milliseconds
+///
+/// Route, status code and latency are recorded before span get closed.
+/// If inner service returns error or panics, status code is recorded
+/// as `500`.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::TracingLogger::default())
+///         .service(
+///             web::resource("/test")
+///                 .route(web::get().to(|| async { HttpResponse::Ok() }))
+///         );
+/// }
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct TracingLogger;
+
+impl TracingLogger {
+    /// Construct `TracingLogger` middleware.
+    pub fn new() -> TracingLogger {
+        TracingLogger
+    }
+}
+
+impl<S> Transform<S> for TracingLogger {
+    type Service = TracingLoggerMiddleware<S>;
+
+    fn new_transform(&self, service: S) -> Self::Service {
+        TracingLoggerMiddleware { service }
+    }
+}
+
+/// Tracing logger middleware
+pub struct TracingLoggerMiddleware<S> {
+    service: S,
+}
+
+impl<S, E> Service<WebRequest<E>> for TracingLoggerMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Future: 'static,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>, is_error: bool) -> Poll<()> {
+        self.service.poll_shutdown(cx, is_error)
+    }
+
+    fn call(&self, req: WebRequest<E>) -> Self::Future {
+        let span = tracing_pkg::info_span!(
+            "http_request",
+            http.method = %req.method(),
+            http.target = %req.path(),
+            http.route = Empty,
+            http.status_code = Empty,
+            latency_ms = Empty,
+        );
+        let mut guard = SpanGuard {
+            span: span.clone(),
+            start: Instant::now(),
+            completed: false,
+        };
+        let fut = span.in_scope(|| self.service.call(req));
+
+        Box::pin(async move {
+            let res = fut.instrument(guard.span.clone()).await;
+            match res {
+                Ok(ref res) => {
+                    if let Some(pattern) = res.request().match_pattern() {
+                        guard.span.record("http.route", pattern);
+                    }
+                    guard.complete(res.status().as_u16());
+                }
+                Err(_) => guard.complete(500),
+            }
+            res
+        })
+    }
+}
+
+/// Records status code and latency when request handling is done
+struct SpanGuard {
+    span: Span,
+    start: Instant,
+    completed: bool,
+}
+
+impl SpanGuard {
+    fn complete(&mut self, status: u16) {
+        self.completed = true;
+        self.span.record("http.status_code", status);
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if !self.completed && thread::panicking() {
+            self.span.record("http.status_code", 500);
+        }
+        self.span
+            .record("latency_ms", self.start.elapsed().as_millis() as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::{atomic::AtomicU64, atomic::Ordering, Arc, Mutex};
+
+    use tracing_core::span::{Attributes, Current, Id, Record};
+    use tracing_pkg::field::{Field, Visit};
+    use tracing_pkg::{subscriber::Subscriber, Event, Metadata};
+
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::service::IntoService;
+    use crate::util::lazy;
+    use crate::web::test::{init_service, ok_service, TestRequest};
+    use crate::web::{self, App, DefaultError, HttpResponse};
+
+    type Fields = HashMap<String, String>;
+
+    /// Subscriber that collects fields of all spans
+    #[derive(Clone, Default)]
+    struct TestSubscriber {
+        next_id: Arc<AtomicU64>,
+        spans: Arc<Mutex<HashMap<u64, (&'static Metadata<'static>, Fields)>>>,
+        stack: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl TestSubscriber {
+        fn fields(&self, name: &str) -> Vec<Fields> {
+            let mut spans: Vec<_> = self.spans.lock().unwrap().drain().collect();
+            spans.sort_by_key(|(id, _)| *id);
+            spans
+                .into_iter()
+                .filter(|(_, (meta, _))| meta.name() == name)
+                .map(|(_, (_, fields))| fields)
+                .collect()
+        }
+    }
+
+    struct Visitor<'a>(&'a mut Fields);
+
+    impl<'a> Visit for Visitor<'a> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl Subscriber for TestSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut fields = Fields::new();
+            attrs.record(&mut Visitor(&mut fields));
+            self.spans
+                .lock()
+                .unwrap()
+                .insert(id, (attrs.metadata(), fields));
+            Id::from_u64(id)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            if let Some((_, fields)) = self.spans.lock().unwrap().get_mut(&span.into_u64())
+            {
+                values.record(&mut Visitor(fields));
+            }
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            let spans = self.spans.lock().unwrap();
+            match self.stack.lock().unwrap().last() {
+                Some(id) => Current::new(Id::from_u64(*id), spans[id].0),
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_tracing_logger() {
+        let subscriber = TestSubscriber::default();
+        let _guard = tracing_pkg::subscriber::set_default(subscriber.clone());
+
+        let srv = init_service(
+            App::new().wrap(TracingLogger::new()).service(
+                web::scope("/api")
+                    .service(
+                        web::resource("/user/{id}")
+                            .to(|| async { HttpResponse::Created() }),
+                    )
+                    .service(web::resource("/error").to(|| async {
+                        Err::<HttpResponse, _>(web::error::ErrorBadRequest("error"))
+                    })),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/api/user/1").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = TestRequest::with_uri("/api/error").method(crate::http::Method::POST);
+        let resp = srv.call(req.to_request()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let fields = subscriber.fields("http_request");
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0]["http.method"], "GET");
+        assert_eq!(fields[0]["http.target"], "/api/user/1");
+        assert_eq!(fields[0]["http.route"], "/user/{id}");
+        assert_eq!(fields[0]["http.status_code"], "201");
+        assert!(fields[0].contains_key("latency_ms"));
+        assert_eq!(fields[1]["http.method"], "POST");
+        assert_eq!(fields[1]["http.route"], "/error");
+        assert_eq!(fields[1]["http.status_code"], "400");
+        assert!(fields[1].contains_key("latency_ms"));
+    }
+
+    #[crate::rt_test]
+    async fn test_tracing_logger_errors() {
+        let subscriber = TestSubscriber::default();
+        let _guard = tracing_pkg::subscriber::set_default(subscriber.clone());
+
+        let mw = TracingLogger::new().new_transform(ok_service::<DefaultError>());
+        assert!(lazy(|cx| mw.poll_ready(cx).is_ready()).await);
+        assert!(lazy(|cx| mw.poll_shutdown(cx, true).is_ready()).await);
+
+        let srv = |_: WebRequest<DefaultError>| async { Err::<WebResponse, _>(()) };
+        let mw = TracingLogger::new().new_transform(srv.into_service());
+        let req = TestRequest::with_uri("/err").to_srv_request();
+        assert!(mw.call(req).await.is_err());
+
+        let srv = |_: WebRequest<DefaultError>| async {
+            if true {
+                panic!("handler panic");
+            }
+            Ok::<_, ()>(TestRequest::default().to_srv_response(HttpResponse::Ok().finish()))
+        };
+        let mw = TracingLogger::new().new_transform(srv.into_service());
+        let mut fut = mw.call(TestRequest::with_uri("/panic").to_srv_request());
+        let res = lazy(|cx| {
+            catch_unwind(AssertUnwindSafe(|| {
+                let _ = Pin::new(&mut fut).poll(cx);
+            }))
+        })
+        .await;
+        assert!(res.is_err());
+        drop(fut);
+
+        let fields = subscriber.fields("http_request");
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0]["http.target"], "/err");
+        assert_eq!(fields[0]["http.status_code"], "500");
+        assert!(!fields[0].contains_key("http.route"));
+        assert_eq!(fields[1]["http.target"], "/panic");
+        assert_eq!(fields[1]["http.status_code"], "500");
+        assert!(fields[1].contains_key("latency_ms"));
+    }
+}
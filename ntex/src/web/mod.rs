@@ -61,18 +61,24 @@
 //! * `compress` - enables content encoding compression support
 //! * `openssl` - enables ssl support via `openssl` crate
 //! * `rustls` - enables ssl support via `rustls` crate
+//! * `openapi` - enables OpenAPI specification generation
 
 mod app;
 mod app_service;
+pub mod blocking;
 mod config;
 pub mod error;
 mod error_default;
 mod extract;
+pub mod fs;
 pub mod guard;
 mod handler;
 mod httprequest;
 mod info;
+mod ipnet;
 pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 mod request;
 mod resource;
 mod responder;
@@ -88,6 +94,7 @@ mod util;
 pub mod ws;
 
 // re-export proc macro
+pub use ntex_macros::rt_main as main;
 pub use ntex_macros::web_connect as connect;
 pub use ntex_macros::web_delete as delete;
 pub use ntex_macros::web_get as get;
@@ -96,6 +103,7 @@ pub use ntex_macros::web_options as options;
 pub use ntex_macros::web_patch as patch;
 pub use ntex_macros::web_post as post;
 pub use ntex_macros::web_put as put;
+pub use ntex_macros::web_route as route;
 pub use ntex_macros::web_trace as trace;
 
 pub use crate::http::Response as HttpResponse;
@@ -104,11 +112,12 @@ pub use crate::http::ResponseBuilder as HttpResponseBuilder;
 pub use self::app::App;
 pub use self::config::ServiceConfig;
 pub use self::error::{
-    DefaultError, Error, ErrorContainer, ErrorRenderer, WebResponseError,
+    DefaultError, Error, ErrorContainer, ErrorRenderer, WebError, WebResponseError,
 };
 pub use self::extract::FromRequest;
 pub use self::handler::Handler;
 pub use self::httprequest::HttpRequest;
+pub use self::ipnet::{InvalidIpNetwork, IpNetwork};
 pub use self::request::WebRequest;
 pub use self::resource::Resource;
 pub use self::responder::Responder;
@@ -127,10 +136,12 @@ pub mod dev {
 
     use super::Handler;
     pub use crate::web::config::AppConfig;
-    pub use crate::web::info::ConnectionInfo;
+    pub use crate::web::info::{ConnectionInfo, ForwardedPrecedence};
     pub use crate::web::rmap::ResourceMap;
     pub use crate::web::route::IntoRoutes;
-    pub use crate::web::service::{WebServiceAdapter, WebServiceConfig, WebServiceFactory};
+    pub use crate::web::service::{
+        RouteEntry, RouteRegistry, WebServiceAdapter, WebServiceConfig, WebServiceFactory,
+    };
 
     pub(crate) fn insert_slesh(mut patterns: Vec<String>) -> Vec<String> {
         for path in &mut patterns {
@@ -0,0 +1,411 @@
+//! OpenAPI 3.0 specification of the application routes.
+//!
+//! Handlers annotated with `#[ntex::openapi(...)]` attribute attach
+//! [`Operation`] metadata to their resources. Once application is
+//! constructed, operations of all registered routes are stored in a
+//! per-worker registry, [`spec()`] assembles OpenAPI document from it.
+//!
+//! ```rust
+//! use ntex::web::{self, openapi, types::Json, App, HttpResponse};
+//!
+//! #[derive(serde::Serialize)]
+//! struct Item {
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! impl openapi::Schema for Item {
+//!     fn schema() -> serde_json::Value {
+//!         serde_json::json!({
+//!             "type": "object",
+//!             "properties": {
+//!                 "id": u64::schema(),
+//!                 "name": String::schema(),
+//!             }
+//!         })
+//!     }
+//! }
+//!
+//! #[ntex::openapi(summary = "Get item", tag = "items", response = Json<Item>)]
+//! #[web::get("/items/{id}")]
+//! async fn get_item(id: web::types::Path<u64>) -> Json<Item> {
+//!     Json(Item { id: id.into_inner(), name: "item".to_string() })
+//! }
+//!
+//! #[web::get("/openapi.json")]
+//! async fn spec() -> HttpResponse {
+//!     HttpResponse::Ok().json(&openapi::spec())
+//! }
+//!
+//! fn main() {
+//!     let app = App::new().service((get_item, spec));
+//! }
+//! ```
+use std::{cell::RefCell, collections::BTreeMap};
+
+use serde_json::{json, Map, Value};
+
+use super::dev::RouteRegistry;
+use super::types::{Form, Json};
+use crate::http::Method;
+
+thread_local! {
+    static REGISTRY: RefCell<BTreeMap<String, BTreeMap<String, Value>>> =
+        RefCell::new(BTreeMap::new());
+}
+
+/// Type description for OpenAPI specification
+pub trait Schema {
+    /// Schema object of the type
+    fn schema() -> Value;
+
+    /// Media type of request or response body
+    fn media_type() -> &'static str {
+        "application/json"
+    }
+}
+
+/// Route metadata for OpenAPI specification.
+///
+/// Usually it is created by `#[ntex::openapi(...)]` attribute, but could be
+/// attached to a resource with `Resource::metadata()` as well.
+#[derive(Debug, Clone, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    tags: Vec<String>,
+    request_body: Option<Body>,
+    response: Option<Body>,
+}
+
+#[derive(Debug, Clone)]
+struct Body {
+    media_type: &'static str,
+    schema: fn() -> Value,
+}
+
+impl Body {
+    fn new<T: Schema>() -> Self {
+        Body {
+            media_type: T::media_type(),
+            schema: T::schema,
+        }
+    }
+
+    fn content(&self) -> Value {
+        json!({ (self.media_type): { "schema": (self.schema)() } })
+    }
+}
+
+impl Operation {
+    /// Create empty operation
+    pub fn new() -> Self {
+        Operation::default()
+    }
+
+    /// Set operation summary
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    /// Add operation tag
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Set type of request body
+    pub fn request_body<T: Schema>(mut self) -> Self {
+        self.request_body = Some(Body::new::<T>());
+        self
+    }
+
+    /// Set type of successful response body
+    pub fn response<T: Schema>(mut self) -> Self {
+        self.response = Some(Body::new::<T>());
+        self
+    }
+
+    fn to_value(&self, params: &[String]) -> Value {
+        let mut op = Map::new();
+        if let Some(ref summary) = self.summary {
+            op.insert("summary".into(), json!(summary));
+        }
+        if !self.tags.is_empty() {
+            op.insert("tags".into(), json!(self.tags));
+        }
+        if !params.is_empty() {
+            let params: Vec<_> = params
+                .iter()
+                .map(|name| {
+                    json!({
+                        "name": name,
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    })
+                })
+                .collect();
+            op.insert("parameters".into(), Value::Array(params));
+        }
+        if let Some(ref body) = self.request_body {
+            op.insert(
+                "requestBody".into(),
+                json!({ "required": true, "content": body.content() }),
+            );
+        }
+        let mut response = json!({ "description": "Successful response" });
+        if let Some(ref body) = self.response {
+            response["content"] = body.content();
+        }
+        op.insert("responses".into(), json!({ "200": response }));
+        Value::Object(op)
+    }
+}
+
+/// Store operations of registered routes.
+///
+/// Called for every constructed application, operations of the same
+/// path and method replace previously stored ones.
+pub(super) fn register(routes: &RouteRegistry) {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        for route in routes.iter() {
+            if let Some(op) = route.metadata::<Operation>() {
+                let (path, params) = convert_pattern(&route.pattern);
+                let item = registry.entry(path).or_default();
+                for method in route_methods(&route.methods) {
+                    item.insert(method, op.to_value(&params));
+                }
+            }
+        }
+    })
+}
+
+/// Assemble OpenAPI 3.0 specification of the registered routes.
+///
+/// Registry is populated when application is constructed, each worker
+/// has its own registry. `info` object contains placeholder values,
+/// update it on returned value if needed.
+pub fn spec() -> Value {
+    let paths: Map<String, Value> = REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .iter()
+            .map(|(path, ops)| {
+                let ops: Map<_, _> =
+                    ops.iter().map(|(m, op)| (m.clone(), op.clone())).collect();
+                (path.clone(), Value::Object(ops))
+            })
+            .collect()
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "", "version": "" },
+        "paths": paths,
+    })
+}
+
+/// Lowercase method names, route without methods accepts any method
+fn route_methods(methods: &[Method]) -> Vec<String> {
+    if methods.is_empty() {
+        [
+            "get", "put", "post", "delete", "options", "head", "patch", "trace",
+        ]
+        .iter()
+        .map(|m| m.to_string())
+        .collect()
+    } else {
+        methods
+            .iter()
+            .map(|m| m.as_str().to_ascii_lowercase())
+            .collect()
+    }
+}
+
+/// Convert path pattern to OpenAPI path template, `/items/{id:\d+}`
+/// becomes `/items/{id}`. Returns path and names of parameters.
+fn convert_pattern(pattern: &str) -> (String, Vec<String>) {
+    let mut path = String::with_capacity(pattern.len());
+    let mut params = Vec::new();
+    let mut depth = 0;
+    let mut name = String::new();
+    let mut in_name = false;
+
+    for ch in pattern.chars() {
+        match ch {
+            '{' => {
+                if depth == 0 {
+                    in_name = true;
+                    name.clear();
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    path.push('{');
+                    path.push_str(&name);
+                    path.push('}');
+                    params.push(name.clone());
+                }
+            }
+            ':' if depth == 1 => in_name = false,
+            _ if depth > 0 => {
+                if in_name {
+                    name.push(ch);
+                }
+            }
+            _ => path.push(ch),
+        }
+    }
+    (path, params)
+}
+
+macro_rules! schema_impl {
+    ($tp:expr, $($t:ty),*) => {
+        $(impl Schema for $t {
+            fn schema() -> Value {
+                json!({ "type": $tp })
+            }
+        })*
+    };
+}
+
+schema_impl!("integer", i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+schema_impl!("number", f32, f64);
+schema_impl!("boolean", bool);
+schema_impl!("string", String, &'static str);
+
+impl Schema for () {
+    fn schema() -> Value {
+        json!({})
+    }
+}
+
+impl Schema for Value {
+    fn schema() -> Value {
+        json!({})
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        let mut schema = T::schema();
+        if let Value::Object(ref mut map) = schema {
+            map.insert("nullable".into(), Value::Bool(true));
+        }
+        schema
+    }
+
+    fn media_type() -> &'static str {
+        T::media_type()
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: Schema> Schema for std::collections::HashMap<String, T> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::schema() })
+    }
+}
+
+impl<T: Schema> Schema for Json<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for Form<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn media_type() -> &'static str {
+        "application/x-www-form-urlencoded"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::{self, test, App, HttpResponse};
+
+    #[test]
+    fn test_convert_pattern() {
+        assert_eq!(convert_pattern("/items"), ("/items".to_string(), vec![]));
+        assert_eq!(
+            convert_pattern("/items/{id}/{sub:\\d{2,4}}"),
+            (
+                "/items/{id}/{sub}".to_string(),
+                vec!["id".to_string(), "sub".to_string()]
+            )
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_spec() {
+        let _ = test::init_service(
+            App::new()
+                .service(
+                    web::scope("/api").service(
+                        web::resource("/items/{id:[0-9]+}")
+                            .metadata(
+                                Operation::new()
+                                    .summary("Update item")
+                                    .tag("items")
+                                    .request_body::<Form<Vec<String>>>()
+                                    .response::<Json<Option<u64>>>(),
+                            )
+                            .route(web::put().to(|| async { HttpResponse::Ok() })),
+                    ),
+                )
+                .service(
+                    web::resource("/other")
+                        .route(web::get().to(|| async { HttpResponse::Ok() })),
+                ),
+        )
+        .await;
+
+        let spec = spec();
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert!(spec["paths"].get("/other").is_none());
+        assert_eq!(
+            spec["paths"]["/api/items/{id}"]["put"],
+            json!({
+                "summary": "Update item",
+                "tags": ["items"],
+                "parameters": [{
+                    "name": "id",
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }],
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "application/x-www-form-urlencoded": {
+                            "schema": { "type": "array", "items": { "type": "string" } }
+                        }
+                    }
+                },
+                "responses": {
+                    "200": {
+                        "description": "Successful response",
+                        "content": {
+                            "application/json": {
+                                "schema": { "type": "integer", "nullable": true }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+}
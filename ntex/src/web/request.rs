@@ -1,9 +1,10 @@
 use std::{cell::Ref, cell::RefMut, fmt, marker::PhantomData, net, rc::Rc};
 
 use crate::http::{
-    header, HeaderMap, HttpMessage, Method, Payload, RequestHead, Response, Uri, Version,
+    header, ClientDisconnect, HeaderMap, HttpMessage, Method, Payload, RequestHead,
+    Response, Uri, Version,
 };
-use crate::io::{types, IoRef};
+use crate::io::IoRef;
 use crate::router::{Path, Resource};
 use crate::util::Extensions;
 
@@ -140,6 +141,18 @@ impl<Err> WebRequest<Err> {
         self.head().uri.path()
     }
 
+    /// The target path and query string of this Request.
+    ///
+    /// E.g., /index.html?id=10
+    #[inline]
+    pub fn path_and_query(&self) -> &str {
+        self.head()
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or_else(|| self.path())
+    }
+
     /// The query string in the URL.
     ///
     /// E.g., id=10
@@ -160,11 +173,16 @@ impl<Err> WebRequest<Err> {
     /// To get client connection information `ConnectionInfo` should be used.
     #[inline]
     pub fn peer_addr(&self) -> Option<net::SocketAddr> {
-        self.head()
-            .io
-            .as_ref()
-            .map(|io| io.query::<types::PeerAddr>().get().map(|addr| addr.0))
-            .unwrap_or(None)
+        self.head().peer_addr()
+    }
+
+    /// Get future that resolves when client disconnects
+    ///
+    /// Future resolves when underlying connection get closed or, for http/2,
+    /// when request's stream get reset.
+    #[inline]
+    pub fn on_disconnect(&self) -> ClientDisconnect {
+        self.head().on_disconnect()
     }
 
     /// Get *ConnectionInfo* for the current request.
@@ -173,6 +191,14 @@ impl<Err> WebRequest<Err> {
         ConnectionInfo::get(self.head(), self.app_config())
     }
 
+    /// Absolute url of the request.
+    ///
+    /// See [`HttpRequest::full_url()`](struct.HttpRequest.html#method.full_url)
+    #[inline]
+    pub fn full_url(&self) -> Uri {
+        self.req.full_url()
+    }
+
     /// Get a reference to the Path parameters.
     ///
     /// Params is a container for url parameters.
@@ -184,6 +210,32 @@ impl<Err> WebRequest<Err> {
         self.req.match_info()
     }
 
+    #[inline]
+    /// Get pattern of the matched resource.
+    ///
+    /// Pattern is relative to enclosing scope. Returns `None` if request
+    /// is not routed to a resource yet.
+    pub fn match_pattern(&self) -> Option<&str> {
+        self.req.match_pattern()
+    }
+
+    #[inline]
+    /// Get name of the matched resource.
+    ///
+    /// Returns `None` if request is not routed to a resource yet
+    /// or resource does not have name.
+    pub fn match_name(&self) -> Option<&str> {
+        self.req.match_name()
+    }
+
+    #[inline]
+    pub(crate) fn set_match_pattern(&mut self, pattern: Rc<str>, name: Option<Rc<str>>) {
+        if let Some(inner) = Rc::get_mut(&mut (self.req).0) {
+            inner.pattern = Some(pattern);
+            inner.name = name;
+        }
+    }
+
     #[inline]
     /// Get a mutable reference to the Path parameters.
     pub fn match_info_mut(&mut self) -> &mut Path<Uri> {
@@ -223,6 +275,38 @@ impl<Err> WebRequest<Err> {
         Rc::get_mut(&mut (self.req).0).unwrap().payload = payload;
     }
 
+    /// Replace request payload with result of the provided function.
+    ///
+    /// Function receives current payload, this allows middlewares to wrap
+    /// existing payload, for example with decoding stream.
+    ///
+    /// ```rust
+    /// use futures_util::StreamExt;
+    /// use ntex::{http::Payload, util::Bytes};
+    /// use ntex::web::{DefaultError, WebRequest};
+    ///
+    /// fn uppercase(req: &mut WebRequest<DefaultError>) {
+    ///     req.map_payload(|pl| {
+    ///         Payload::from_stream(
+    ///             pl.map(|res| res.map(|chunk| Bytes::from(chunk.to_ascii_uppercase()))),
+    ///         )
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if request is not unique, for example if `HttpRequest`
+    /// instance was cloned from this request and is still alive.
+    pub fn map_payload<F>(&mut self, f: F)
+    where
+        F: FnOnce(Payload) -> Payload,
+    {
+        let inner = Rc::get_mut(&mut (self.req).0)
+            .expect("Cannot map payload, request has multiple references");
+        inner.payload = f(inner.payload.take());
+    }
+
     #[doc(hidden)]
     /// Set new app state container
     pub(super) fn set_state_container(&mut self, state: AppState) {
@@ -240,6 +324,21 @@ impl<Err> WebRequest<Err> {
     pub fn extensions_mut(&self) -> RefMut<'_, Extensions> {
         self.req.extensions_mut()
     }
+
+    /// Get a reference to a per-connection data of type `T`
+    ///
+    /// Per-connection data is shared between all requests dispatched
+    /// on the same connection.
+    #[inline]
+    pub fn conn_data<T: 'static>(&self) -> Option<Ref<'_, T>> {
+        self.head().conn_data()
+    }
+
+    /// Mutable reference to a per-connection data container
+    #[inline]
+    pub fn conn_data_mut(&self) -> Option<RefMut<'_, Extensions>> {
+        self.head().conn_data_mut()
+    }
 }
 
 impl<Err> Resource<Uri> for WebRequest<Err> {
@@ -301,7 +400,10 @@ impl<Err: ErrorRenderer> fmt::Debug for WebRequest<Err> {
 
 #[cfg(test)]
 mod tests {
-    use crate::http::{self, header, HttpMessage};
+    use futures_util::StreamExt;
+
+    use crate::http::{self, header, HttpMessage, Payload};
+    use crate::util::{Bytes, BytesMut};
     use crate::web::test::TestRequest;
     use crate::web::HttpResponse;
 
@@ -335,4 +437,23 @@ mod tests {
         req.message_extensions_mut().remove::<String>();
         assert!(!req.extensions().contains::<String>());
     }
+
+    #[crate::rt_test]
+    async fn test_map_payload() {
+        let mut req = TestRequest::default()
+            .set_payload("hello world")
+            .to_srv_request();
+        req.map_payload(|pl| {
+            Payload::from_stream(
+                pl.map(|res| res.map(|chunk| Bytes::from(chunk.to_ascii_uppercase()))),
+            )
+        });
+
+        let mut pl = req.take_payload();
+        let mut body = BytesMut::new();
+        while let Some(chunk) = pl.recv().await {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(body.freeze(), Bytes::from_static(b"HELLO WORLD"));
+    }
 }
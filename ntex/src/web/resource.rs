@@ -2,14 +2,15 @@ use std::{
     cell::RefCell, fmt, future::Future, pin::Pin, rc::Rc, task::Context, task::Poll,
 };
 
-use crate::http::Response;
+use crate::http::header::{self, HeaderValue};
+use crate::http::{RequestHead, Response};
 use crate::router::{IntoPattern, ResourceDef};
 use crate::service::boxed::{self, BoxService, BoxServiceFactory};
 use crate::service::{pipeline_factory, PipelineFactory};
 use crate::service::{Identity, IntoServiceFactory, Service, ServiceFactory, Transform};
 use crate::util::{Either, Extensions, Ready};
 
-use super::dev::{insert_slesh, WebServiceConfig, WebServiceFactory};
+use super::dev::{insert_slesh, RouteEntry, WebServiceConfig, WebServiceFactory};
 use super::error::ErrorRenderer;
 use super::extract::FromRequest;
 use super::handler::Handler;
@@ -44,8 +45,11 @@ type HttpNewService<Err: ErrorRenderer> =
 /// }
 /// ```
 ///
-/// If no matching route could be found, *405* response code get returned.
-/// Default behavior could be overriden with `default_resource()` method.
+/// If no matching route could be found, *405* response code get returned,
+/// `Allow` header contains methods of routes that accept the request.
+/// If request is rejected by non-method guards of all routes, for example
+/// by header guards, router continues matching next resources.
+/// Default behavior could be overriden with `default_service()` method.
 pub struct Resource<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     middleware: M,
     filter: PipelineFactory<T, WebRequest<Err>>,
@@ -53,8 +57,10 @@ pub struct Resource<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     name: Option<String>,
     routes: Vec<Route<Err>>,
     state: Option<Extensions>,
+    metadata: Extensions,
     guards: Vec<Box<dyn Guard>>,
     default: Rc<RefCell<Option<Rc<HttpNewService<Err>>>>>,
+    fallthrough: bool,
 }
 
 impl<Err: ErrorRenderer> Resource<Err> {
@@ -64,10 +70,12 @@ impl<Err: ErrorRenderer> Resource<Err> {
             rdef: path.patterns(),
             name: None,
             state: None,
+            metadata: Extensions::new(),
             middleware: Identity,
             filter: pipeline_factory(Filter::new()),
             guards: Vec::new(),
             default: Rc::new(RefCell::new(None)),
+            fallthrough: true,
         }
     }
 }
@@ -84,12 +92,38 @@ where
 {
     /// Set resource name.
     ///
-    /// Name is used for url generation.
+    /// Name is used for url generation, name of the matched resource
+    /// is available via `HttpRequest::match_name()`.
     pub fn name(mut self, name: &str) -> Self {
         self.name = Some(name.to_string());
         self
     }
 
+    /// Attach metadata to the resource.
+    ///
+    /// Metadata is not used for request handling, it is available
+    /// for introspection via `RouteRegistry`, for example for api
+    /// specification generation. Only one value of each type is stored,
+    /// route metadata overrides resource metadata of the same type.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, App, HttpResponse};
+    ///
+    /// struct Summary(&'static str);
+    ///
+    /// fn main() {
+    ///     let app = App::new().service(
+    ///         web::resource("/users")
+    ///             .metadata(Summary("List users"))
+    ///             .route(web::get().to(|| async { HttpResponse::Ok() })),
+    ///     );
+    /// }
+    /// ```
+    pub fn metadata<D: 'static>(mut self, data: D) -> Self {
+        self.metadata.insert(data);
+        self
+    }
+
     /// Add match guard to a resource.
     ///
     /// ```rust
@@ -262,9 +296,11 @@ where
             rdef: self.rdef,
             name: self.name,
             state: self.state,
+            metadata: self.metadata,
             guards: self.guards,
             routes: self.routes,
             default: self.default,
+            fallthrough: self.fallthrough,
         }
     }
 
@@ -282,12 +318,42 @@ where
             rdef: self.rdef,
             name: self.name,
             state: self.state,
+            metadata: self.metadata,
             guards: self.guards,
             routes: self.routes,
             default: self.default,
+            fallthrough: self.fallthrough,
         }
     }
 
+    /// Enable or disable fall-through for requests rejected by route guards.
+    ///
+    /// By default, if all routes reject request with non-method guards,
+    /// for example header guards, resource does not handle request and router
+    /// continues matching next resources. If fall-through is disabled
+    /// resource always responds with *405* response. Resources with default
+    /// service never fall through.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, guard, App, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new().service(
+    ///         web::resource("/")
+    ///             .guard_fallthrough(false)
+    ///             .route(
+    ///                 web::get()
+    ///                     .guard(guard::Header("x-version", "2"))
+    ///                     .to(|| async { HttpResponse::Ok() }),
+    ///             ),
+    ///     );
+    /// }
+    /// ```
+    pub fn guard_fallthrough(mut self, enabled: bool) -> Self {
+        self.fallthrough = enabled;
+        self
+    }
+
     /// Default service to be used if no matching route could be found.
     /// By default *405* response get returned. Resource does not use
     /// default handler from `App` or `Scope`.
@@ -321,6 +387,13 @@ where
     Err: ErrorRenderer,
 {
     fn register(mut self, config: &mut WebServiceConfig<Err>) {
+        if self.fallthrough
+            && self.default.borrow().is_none()
+            && self.routes.iter().any(|route| !route.guards().is_empty())
+        {
+            let guards = self.routes.iter().map(|route| route.guards()).collect();
+            self.guards.push(Box::new(RoutesGuard(guards)));
+        }
         let guards = if self.guards.is_empty() {
             None
         } else {
@@ -335,6 +408,29 @@ where
             *rdef.name_mut() = name.clone();
         }
 
+        let mut methods = Vec::new();
+        let mut any_method = false;
+        let mut metadata = std::mem::take(&mut self.metadata);
+        for route in &mut self.routes {
+            any_method |= route.methods().is_empty();
+            for m in route.methods() {
+                if !methods.contains(m) {
+                    methods.push(m.clone());
+                }
+            }
+            metadata.extend(route.take_metadata());
+        }
+        if any_method {
+            methods.clear();
+        }
+        config.add_route(RouteEntry {
+            pattern: rdef.pattern().to_string(),
+            name: self.name.clone(),
+            methods,
+            scope: String::new(),
+            metadata: Rc::new(metadata),
+        });
+
         let state = self.state.take().map(|state| {
             AppState::new(
                 state,
@@ -347,6 +443,8 @@ where
             state,
             routes: self.routes,
             default: self.default,
+            pattern: Rc::from(rdef.pattern()),
+            name: self.name.as_deref().map(Rc::from),
         };
 
         config.register_service(
@@ -383,6 +481,8 @@ where
     ) -> ResourceServiceFactory<Err, M, PipelineFactory<T, WebRequest<Err>>> {
         let router_factory = ResourceRouterFactory {
             state: None,
+            pattern: Rc::from(self.rdef.last().map(|s| s.as_str()).unwrap_or("")),
+            name: self.name.as_deref().map(Rc::from),
             routes: self.routes,
             default: self.default,
         };
@@ -500,10 +600,23 @@ where
     }
 }
 
+/// Matches if any of routes accepts request, method guards are not checked
+struct RoutesGuard(Vec<Rc<Vec<Box<dyn Guard>>>>);
+
+impl Guard for RoutesGuard {
+    fn check(&self, req: &RequestHead) -> bool {
+        self.0
+            .iter()
+            .any(|guards| guards.iter().all(|guard| guard.check(req)))
+    }
+}
+
 struct ResourceRouterFactory<Err: ErrorRenderer> {
     routes: Vec<Route<Err>>,
     default: Rc<RefCell<Option<Rc<HttpNewService<Err>>>>>,
     state: Option<AppState>,
+    pattern: Rc<str>,
+    name: Option<Rc<str>>,
 }
 
 impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFactory<Err> {
@@ -517,6 +630,8 @@ impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFacto
         let state = self.state.clone();
         let routes = self.routes.iter().map(|route| route.service()).collect();
         let default_fut = self.default.borrow().as_ref().map(|f| f.new_service(()));
+        let pattern = self.pattern.clone();
+        let name = self.name.clone();
 
         Box::pin(async move {
             let default = if let Some(fut) = default_fut {
@@ -529,6 +644,8 @@ impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFacto
                 state,
                 routes,
                 default,
+                pattern,
+                name,
             })
         })
     }
@@ -538,6 +655,8 @@ struct ResourceRouter<Err: ErrorRenderer> {
     state: Option<AppState>,
     routes: Vec<RouteService<Err>>,
     default: Option<HttpService<Err>>,
+    pattern: Rc<str>,
+    name: Option<Rc<str>>,
 }
 
 impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
@@ -554,6 +673,8 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
     }
 
     fn call(&self, mut req: WebRequest<Err>) -> Self::Future {
+        req.set_match_pattern(self.pattern.clone(), self.name.clone());
+
         for route in self.routes.iter() {
             if route.check(&mut req) {
                 if let Some(ref state) = self.state {
@@ -565,8 +686,26 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
         if let Some(ref default) = self.default {
             Either::Right(default.call(req))
         } else {
+            // methods of routes that accept request
+            let mut allowed: Vec<&str> = Vec::new();
+            for route in self.routes.iter() {
+                if route.check_guards(req.head()) {
+                    for m in route.methods() {
+                        if !allowed.contains(&m.as_str()) {
+                            allowed.push(m.as_str());
+                        }
+                    }
+                }
+            }
+
+            let mut res = Response::MethodNotAllowed();
+            if !allowed.is_empty() {
+                if let Ok(val) = HeaderValue::from_str(&allowed.join(", ")) {
+                    res.header(header::ALLOW, val);
+                }
+            }
             Either::Left(Ready::Ok(WebResponse::new(
-                Response::MethodNotAllowed().finish(),
+                res.finish(),
                 req.into_parts().0,
             )))
         }
@@ -579,9 +718,10 @@ mod tests {
     use crate::http::{Method, StatusCode};
     use crate::time::{sleep, Millis};
     use crate::web::middleware::DefaultHeaders;
-    use crate::web::test::{call_service, init_service, TestRequest};
-    use crate::web::{self, guard, request::WebRequest, App, DefaultError, HttpResponse};
-    use crate::{service::fn_service, util::Ready};
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, guard, request::WebRequest, App, DefaultError};
+    use crate::web::{HttpRequest, HttpResponse};
+    use crate::{service::fn_service, util::Bytes, util::Ready};
 
     #[crate::rt_test]
     async fn test_filter() {
@@ -653,6 +793,29 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[crate::rt_test]
+    async fn test_match_name() {
+        let srv = init_service(
+            App::new()
+                .service(web::resource("/named/{id}").name("named").to(
+                    |req: HttpRequest| async move {
+                        HttpResponse::Ok().body(format!("{:?}", req.match_name()))
+                    },
+                ))
+                .service(web::resource("/unnamed").to(|req: HttpRequest| async move {
+                    HttpResponse::Ok().body(format!("{:?}", req.match_name()))
+                })),
+        )
+        .await;
+        let req = TestRequest::with_uri("/named/1").to_request();
+        let body = read_body(call_service(&srv, req).await).await;
+        assert_eq!(body, Bytes::from_static(b"Some(\"named\")"));
+
+        let req = TestRequest::with_uri("/unnamed").to_request();
+        let body = read_body(call_service(&srv, req).await).await;
+        assert_eq!(body, Bytes::from_static(b"None"));
+    }
+
     #[crate::rt_test]
     async fn test_default_resource() {
         let srv = init_service(
@@ -739,6 +902,114 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::NO_CONTENT);
     }
 
+    #[crate::rt_test]
+    async fn test_method_not_allowed() {
+        let srv = init_service(
+            App::new().service(
+                web::resource("/test")
+                    .route(web::get().to(|| async { HttpResponse::Ok() }))
+                    .route(web::post().to(|| async { HttpResponse::Ok() }))
+                    .route(web::get().to(|| async { HttpResponse::Ok() })),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::DELETE)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, POST");
+    }
+
+    #[crate::rt_test]
+    async fn test_route_guards_fallthrough() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/test")
+                        .route(
+                            web::get()
+                                .guard(guard::Header("x-version", "2"))
+                                .to(|| async { HttpResponse::Ok() }),
+                        )
+                        .route(
+                            web::put()
+                                .guard(guard::Header("x-version", "2"))
+                                .to(|| async { HttpResponse::Ok() }),
+                        ),
+                )
+                .service(
+                    web::resource("/test")
+                        .route(web::get().to(|| async { HttpResponse::Created() })),
+                )
+                .service(
+                    web::resource("/header").route(
+                        web::get()
+                            .guard(guard::Header("x-version", "2"))
+                            .to(|| async { HttpResponse::Ok() }),
+                    ),
+                ),
+        )
+        .await;
+
+        // method mismatch
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .header("x-version", "2")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, PUT");
+
+        let req = TestRequest::with_uri("/test")
+            .header("x-version", "2")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // header guard rejects, next resource is matched
+        let req = TestRequest::with_uri("/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET");
+
+        // no other resources
+        let req = TestRequest::with_uri("/header").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[crate::rt_test]
+    async fn test_route_guards_no_fallthrough() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/test").guard_fallthrough(false).route(
+                        web::get()
+                            .guard(guard::Header("x-version", "2"))
+                            .to(|| async { HttpResponse::Ok() }),
+                    ),
+                )
+                .service(
+                    web::resource("/test")
+                        .route(web::get().to(|| async { HttpResponse::Created() })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert!(resp.headers().get(header::ALLOW).is_none());
+    }
+
     #[crate::rt_test]
     async fn test_state() {
         let srv = init_service(
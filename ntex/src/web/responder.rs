@@ -417,7 +417,7 @@ where
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
-    use crate::http::body::{Body, ResponseBody};
+    use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
     use crate::http::header::{HeaderValue, CONTENT_TYPE};
     use crate::http::{Response as HttpResponse, StatusCode};
     use crate::web::test::{init_service, TestRequest};
@@ -542,6 +542,22 @@ pub(crate) mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[crate::rt_test]
+    async fn test_static_responder() {
+        static TEXT: &str = "static text";
+        static DATA: &[u8] = b"static data";
+        let req = TestRequest::default().to_http_request();
+
+        // static data is not copied
+        let resp: HttpResponse = responder(TEXT).respond_to(&req).await;
+        assert_eq!(resp.body().get_ref().as_ptr(), TEXT.as_ptr());
+        assert_eq!(resp.body().size(), BodySize::Sized(11));
+
+        let resp: HttpResponse = responder(DATA).respond_to(&req).await;
+        assert_eq!(resp.body().get_ref().as_ptr(), DATA.as_ptr());
+        assert_eq!(resp.body().size(), BodySize::Sized(11));
+    }
+
     #[crate::rt_test]
     async fn test_result_responder() {
         let req = TestRequest::default().to_http_request();
@@ -1,7 +1,15 @@
-use std::fmt;
+use std::{error::Error, fmt, future::Future, mem, pin::Pin, task::Context, task::Poll};
 
-use crate::http::body::{Body, MessageBody, ResponseBody};
+#[cfg(feature = "cookie")]
+use coo_kie::{Cookie, CookieJar};
+use serde::Serialize;
+
+use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
+#[cfg(feature = "cookie")]
+use crate::http::error::HttpError;
+use crate::http::header::{HeaderValue, CONTENT_TYPE};
 use crate::http::{HeaderMap, Response, ResponseHead, StatusCode};
+use crate::util::Bytes;
 
 use super::error::{ErrorContainer, ErrorRenderer};
 use super::httprequest::HttpRequest;
@@ -53,6 +61,15 @@ impl WebResponse {
         WebResponse::new(response, self.request)
     }
 
+    /// Replace response with the result of closure, request is preserved
+    #[inline]
+    pub fn map_into_response<F>(self, f: F) -> WebResponse
+    where
+        F: FnOnce(Response<Body>) -> Response<Body>,
+    {
+        WebResponse::new(f(self.response), self.request)
+    }
+
     /// Get reference to original request
     #[inline]
     pub fn request(&self) -> &HttpRequest {
@@ -89,6 +106,43 @@ impl WebResponse {
         self.response.headers_mut()
     }
 
+    #[cfg(feature = "cookie")]
+    /// Set `Set-Cookie` headers for the difference between two cookie jars.
+    ///
+    /// Existing `Set-Cookie` headers for cookies from both jars are removed,
+    /// then headers are added only for cookies that are added or changed
+    /// in `updated` jar. Removal cookies are added for cookies that exist
+    /// in `original` jar only.
+    pub fn diff_cookies(
+        &mut self,
+        original: &CookieJar,
+        updated: &CookieJar,
+    ) -> Result<(), HttpError> {
+        for cookie in original.iter().chain(updated.iter()) {
+            self.response.del_cookie(cookie.name());
+        }
+
+        for cookie in updated.iter() {
+            if original.get(cookie.name()) != Some(cookie) {
+                self.response.add_cookie(cookie)?;
+            }
+        }
+        for cookie in original.iter() {
+            if updated.get(cookie.name()).is_none() {
+                let mut removal = Cookie::named(cookie.name().to_string());
+                if let Some(path) = cookie.path() {
+                    removal.set_path(path.to_string());
+                }
+                if let Some(domain) = cookie.domain() {
+                    removal.set_domain(domain.to_string());
+                }
+                removal.make_removal();
+                self.response.add_cookie(&removal)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Execute closure and in case of error convert it to response.
     pub fn checked_expr<Err, F, E>(mut self, f: F) -> Self
     where
@@ -104,6 +158,59 @@ impl WebResponse {
         }
     }
 
+    /// Set json body.
+    ///
+    /// Response body is replaced with serialized value and `Content-Type`
+    /// header is set to `application/json`, status and other headers are
+    /// not changed.
+    pub fn set_json<T: Serialize>(&mut self, value: &T) -> Result<(), serde_json::Error> {
+        let body = serde_json::to_string(value)?;
+
+        let res = mem::replace(&mut self.response, Response::new(StatusCode::OK));
+        self.response = res.map_body(|head, _| {
+            head.headers
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            ResponseBody::Other(Body::from(body))
+        });
+        Ok(())
+    }
+
+    /// Execute async closure and in case of error convert it to response.
+    ///
+    /// ```rust
+    /// use ntex::http::header::{HeaderName, HeaderValue};
+    /// use ntex::web::{DefaultError, Error, WebResponse};
+    ///
+    /// async fn lookup_value() -> Result<HeaderValue, Error> {
+    ///     Ok(HeaderValue::from_static("value"))
+    /// }
+    ///
+    /// async fn add_header(res: WebResponse) -> WebResponse {
+    ///     res.async_checked_expr::<DefaultError, _, _>(|res| {
+    ///         Box::pin(async move {
+    ///             let val = lookup_value().await?;
+    ///             res.headers_mut().insert(HeaderName::from_static("x-value"), val);
+    ///             Ok::<_, Error>(())
+    ///         })
+    ///     })
+    ///     .await
+    /// }
+    /// ```
+    pub async fn async_checked_expr<Err, F, E>(mut self, f: F) -> Self
+    where
+        F: for<'a> FnOnce(
+            &'a mut Self,
+        ) -> Pin<Box<dyn Future<Output = Result<(), E>> + 'a>>,
+        E: Into<Err::Container>,
+        Err: ErrorRenderer,
+    {
+        if let Err(err) = f(&mut self).await {
+            WebResponse::from_err::<Err, E>(err, self.request)
+        } else {
+            self
+        }
+    }
+
     /// Extract response body
     pub fn take_body(&mut self) -> ResponseBody<Body> {
         self.response.take_body()
@@ -121,6 +228,60 @@ impl WebResponse {
             request: self.request,
         }
     }
+
+    /// Register a callback that runs after response body is sent.
+    ///
+    /// Callback runs once, after the last chunk of the body is passed to
+    /// the connection, or after body stream fails. If response get dropped
+    /// before body is completed, for example peer disconnects, callback runs
+    /// on drop. Use `rt::spawn()` inside of the callback to run async cleanup.
+    pub fn on_body_complete<F>(self, f: F) -> WebResponse
+    where
+        F: FnOnce() + 'static,
+    {
+        self.map_body(move |_, body| {
+            ResponseBody::Other(Body::from_message(CompleteBody { body, f: Some(f) }))
+        })
+    }
+}
+
+struct CompleteBody<F: FnOnce()> {
+    body: ResponseBody<Body>,
+    f: Option<F>,
+}
+
+impl<F: FnOnce()> CompleteBody<F> {
+    fn complete(&mut self) {
+        if let Some(f) = self.f.take() {
+            f()
+        }
+    }
+}
+
+impl<F: FnOnce()> Drop for CompleteBody<F> {
+    fn drop(&mut self) {
+        self.complete()
+    }
+}
+
+impl<F: FnOnce() + 'static> MessageBody for CompleteBody<F> {
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        match self.body.poll_next_chunk(cx) {
+            Poll::Ready(Some(Ok(chunk))) => Poll::Ready(Some(Ok(chunk))),
+            Poll::Pending => Poll::Pending,
+            val => {
+                self.complete();
+                val
+            }
+        }
+    }
 }
 
 impl From<WebResponse> for Response<Body> {
@@ -153,7 +314,11 @@ impl fmt::Debug for WebResponse {
 
 #[cfg(test)]
 mod tests {
-    use crate::http::{self, StatusCode};
+    use std::{cell::Cell, io, rc::Rc};
+
+    use crate::http::body::{BodySize, MessageBody};
+    use crate::http::{self, header::CONTENT_TYPE, StatusCode};
+    use crate::util::{poll_fn, Bytes};
     use crate::web::test::TestRequest;
     use crate::web::{DefaultError, HttpResponse};
 
@@ -176,4 +341,163 @@ mod tests {
         });
         assert_eq!(res.response().status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
+
+    #[crate::rt_test]
+    async fn test_async_checked_expr() {
+        use crate::http::body::{Body, ResponseBody};
+        use crate::http::header::{HeaderName, HeaderValue};
+        use crate::web::error::{ErrorBadRequest, InternalError};
+        use crate::web::WebResponse;
+
+        async fn checksum(body: Bytes) -> Result<String, InternalError<&'static str>> {
+            crate::time::sleep(crate::time::Millis(1)).await;
+            if body.is_empty() {
+                Err(ErrorBadRequest("empty body"))
+            } else {
+                Ok(format!("{:x}", body.iter().map(|b| *b as u32).sum::<u32>()))
+            }
+        }
+
+        // response post-processing, like in middleware
+        async fn add_checksum(res: WebResponse) -> WebResponse {
+            res.async_checked_expr::<DefaultError, _, _>(|res| {
+                Box::pin(async move {
+                    let body = match res.response().body() {
+                        ResponseBody::Body(Body::Bytes(b))
+                        | ResponseBody::Other(Body::Bytes(b)) => b.clone(),
+                        _ => Bytes::new(),
+                    };
+                    let sum = checksum(body).await?;
+                    res.headers_mut().insert(
+                        HeaderName::from_static("x-checksum"),
+                        HeaderValue::from_str(&sum).unwrap(),
+                    );
+                    Ok::<_, InternalError<&'static str>>(())
+                })
+            })
+            .await
+        }
+
+        let res = TestRequest::default().to_srv_response(HttpResponse::Ok().body("abc"));
+        let res = add_checksum(res).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("x-checksum").unwrap(), "126");
+
+        let res = TestRequest::default().to_srv_response(HttpResponse::Ok().finish());
+        let res = add_checksum(res).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(!res.headers().contains_key("x-checksum"));
+
+        let res = TestRequest::default()
+            .to_srv_response(HttpResponse::Ok().finish())
+            .map_into_response(|_| HttpResponse::Created().finish());
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+
+    #[crate::rt_test]
+    async fn test_set_json() {
+        let mut res = TestRequest::default().to_srv_response(
+            HttpResponse::NotFound()
+                .header("x-test", "1")
+                .content_type("text/plain")
+                .body("not found"),
+        );
+        res.set_json(&serde_json::json!({"error": "not found"}))
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers().get("x-test").unwrap(), "1");
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+        let body = crate::web::test::read_body(res).await;
+        assert_eq!(body, Bytes::from_static(b"{\"error\":\"not found\"}"));
+
+        // serialization error
+        let mut res =
+            TestRequest::default().to_srv_response(HttpResponse::NotFound().finish());
+        let mut map = std::collections::HashMap::new();
+        map.insert(vec![1], 1);
+        assert!(res.set_json(&map).is_err());
+        assert!(res.headers().get(CONTENT_TYPE).is_none());
+    }
+
+    #[crate::rt_test]
+    async fn test_on_body_complete() {
+        let (tx, rx) = crate::channel::mpsc::channel::<Result<Bytes, io::Error>>();
+        let done = Rc::new(Cell::new(false));
+        let done2 = done.clone();
+
+        let mut res = TestRequest::default()
+            .to_srv_response(HttpResponse::Ok().streaming(rx))
+            .on_body_complete(move || done2.set(true));
+        assert_eq!(res.response().body().size(), BodySize::Stream);
+
+        let mut body = res.take_body();
+        tx.send(Ok(Bytes::from_static(b"chunk"))).unwrap();
+        let chunk = poll_fn(|cx| body.poll_next_chunk(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), Bytes::from_static(b"chunk"));
+        assert!(!done.get());
+
+        drop(tx);
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+        assert!(done.get());
+
+        // body is dropped before completion
+        let done = Rc::new(Cell::new(false));
+        let done2 = done.clone();
+        let mut res = TestRequest::default()
+            .to_srv_response(HttpResponse::Ok().body("body"))
+            .on_body_complete(move || done2.set(true));
+        assert_eq!(res.response().body().size(), BodySize::Sized(4));
+
+        let body = res.take_body();
+        assert!(!done.get());
+        drop(body);
+        assert!(done.get());
+    }
+
+    #[cfg(feature = "cookie")]
+    #[test]
+    fn test_diff_cookies() {
+        use coo_kie::{Cookie, CookieJar};
+
+        let mut original = CookieJar::new();
+        original.add_original(Cookie::new("same", "1"));
+        original.add_original(Cookie::new("changed", "1"));
+        original.add_original(Cookie::build("deleted", "1").path("/app").finish());
+
+        let mut updated = original.clone();
+        updated.add(Cookie::new("changed", "2"));
+        updated.add(Cookie::new("added", "1"));
+        updated.remove(Cookie::named("deleted"));
+
+        let mut res = TestRequest::default().to_srv_response(
+            HttpResponse::Ok()
+                .cookie(Cookie::new("same", "1"))
+                .cookie(Cookie::new("other", "1"))
+                .finish(),
+        );
+        res.diff_cookies(&original, &updated).unwrap();
+
+        let mut cookies: Vec<_> = res
+            .response()
+            .cookies()
+            .map(|c| (c.name().to_string(), c.value().to_string()))
+            .collect();
+        cookies.sort();
+        assert_eq!(
+            cookies,
+            vec![
+                ("added".to_string(), "1".to_string()),
+                ("changed".to_string(), "2".to_string()),
+                ("deleted".to_string(), "".to_string()),
+                ("other".to_string(), "1".to_string()),
+            ]
+        );
+        let deleted = res
+            .response()
+            .cookies()
+            .find(|c| c.name() == "deleted")
+            .unwrap();
+        assert_eq!(deleted.path(), Some("/app"));
+        assert_eq!(deleted.max_age(), Some(coo_kie::time::Duration::ZERO));
+    }
 }
@@ -1,6 +1,8 @@
 use std::{future::Future, mem, pin::Pin, rc::Rc, task::Context, task::Poll};
 
-use crate::{http::Method, service::Service, service::ServiceFactory, util::Ready};
+use crate::http::{Method, RequestHead};
+use crate::service::{Service, ServiceFactory};
+use crate::util::{Extensions, Ready};
 
 use super::error::ErrorRenderer;
 use super::error_default::DefaultError;
@@ -20,6 +22,7 @@ pub struct Route<Err: ErrorRenderer = DefaultError> {
     handler: Box<dyn HandlerFn<Err>>,
     methods: Vec<Method>,
     guards: Rc<Vec<Box<dyn Guard>>>,
+    metadata: Extensions,
 }
 
 impl<Err: ErrorRenderer> Route<Err> {
@@ -29,6 +32,7 @@ impl<Err: ErrorRenderer> Route<Err> {
             handler: Box::new(HandlerWrapper::new(|| async { HttpResponse::NotFound() })),
             methods: Vec::new(),
             guards: Rc::new(Vec::new()),
+            metadata: Extensions::new(),
         }
     }
 
@@ -42,6 +46,20 @@ impl<Err: ErrorRenderer> Route<Err> {
         mem::take(Rc::get_mut(&mut self.guards).unwrap())
     }
 
+    /// Route methods
+    pub(super) fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    pub(super) fn take_metadata(&mut self) -> Extensions {
+        mem::take(&mut self.metadata)
+    }
+
+    /// Route guards, not including method guards
+    pub(super) fn guards(&self) -> Rc<Vec<Box<dyn Guard>>> {
+        self.guards.clone()
+    }
+
     pub(super) fn service(&self) -> RouteService<Err> {
         RouteService {
             handler: self.handler.clone_handler(),
@@ -74,13 +92,16 @@ impl<Err: ErrorRenderer> RouteService<Err> {
         if !self.methods.is_empty() && !self.methods.contains(&req.head().method) {
             return false;
         }
+        self.check_guards(req.head())
+    }
 
-        for f in self.guards.iter() {
-            if !f.check(req.head()) {
-                return false;
-            }
-        }
-        true
+    /// Check route guards, except method guards
+    pub(super) fn check_guards(&self, head: &RequestHead) -> bool {
+        self.guards.iter().all(|f| f.check(head))
+    }
+
+    pub(super) fn methods(&self) -> &[Method] {
+        &self.methods
     }
 }
 
@@ -137,6 +158,16 @@ impl<Err: ErrorRenderer> Route<Err> {
         self
     }
 
+    /// Attach metadata to the route.
+    ///
+    /// Metadata is not used for request handling, it is merged into
+    /// resource metadata and is available via `RouteRegistry`.
+    /// Only one value of each type is stored.
+    pub fn metadata<T: 'static>(mut self, data: T) -> Self {
+        self.metadata.insert(data);
+        self
+    }
+
     /// Set handler function, use request extractors for parameters.
     ///
     /// ```rust
@@ -269,7 +300,7 @@ array_routes!(12, a, b, c, d, e, f, g, h, i, j, k, l);
 
 #[cfg(test)]
 mod tests {
-    use crate::http::{Method, StatusCode};
+    use crate::http::{Method, RequestHead, StatusCode};
     use crate::time::{sleep, Millis};
     use crate::util::Bytes;
     use crate::web::test::{call_service, init_service, read_body, TestRequest};
@@ -346,4 +377,28 @@ mod tests {
         let body = read_body(resp).await;
         assert_eq!(body, Bytes::from_static(b"{\"name\":\"test\"}"));
     }
+
+    #[crate::rt_test]
+    async fn test_route_closure_guard() {
+        let srv = init_service(
+            App::new().service(
+                web::resource("/test").route(
+                    web::route()
+                        .guard(|head: &RequestHead| head.method == Method::PATCH)
+                        .to(|| async { HttpResponse::Ok() }),
+                ),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::PATCH)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -39,6 +39,10 @@ type BoxResponse<Err: ErrorRenderer> =
 ///
 /// You can get variable path segments from `HttpRequest::match_info()`.
 /// `Path` extractor also is able to extract scope level variable segments.
+/// Segments of all enclosing scopes and of the resource are merged,
+/// in order from outer scope to the resource. `HttpRequest::url_for()`
+/// for resources inside of scope expects values for all these segments
+/// in the same order.
 ///
 /// ```rust
 /// use ntex::web::{self, App, HttpResponse};
@@ -58,6 +62,37 @@ type BoxResponse<Err: ErrorRenderer> =
 ///  * /{project_id}/path2 - `GET` requests
 ///  * /{project_id}/path3 - `HEAD` requests
 ///
+/// Scope could be used for mounting self-contained sub-application,
+/// for example defined in different crate:
+///  * scope state shadows state of the same type from parent scope or
+///    application, other state types are looked up in parent
+///  * scope default service handles unmatched paths under scope prefix,
+///    if it is not set parent's default service is used
+///  * scope middlewares apply only to scope services, including default service,
+///    parent middlewares wrap scope responses
+///  * resource names are shared by the whole application, `url_for()` could
+///    resolve names from any scope
+///
+/// Error renderer is a type parameter of the application, it is shared
+/// by all scopes.
+///
+/// ```rust
+/// use ntex::web::{self, dev::WebServiceFactory, middleware, App, DefaultError, HttpResponse};
+///
+/// fn users_app() -> impl WebServiceFactory<DefaultError> {
+///     web::scope("/users")
+///         .state(String::from("users"))
+///         .wrap(middleware::DefaultHeaders::new().header("X-Users", "1"))
+///         .service(web::resource("/{id}").name("user").to(|| async { HttpResponse::Ok() }))
+///         .default_service(web::to(|| async { HttpResponse::NotFound() }))
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::Logger::default())
+///         .service(users_app());
+/// }
+/// ```
 pub struct Scope<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     middleware: M,
     filter: PipelineFactory<T, WebRequest<Err>>,
@@ -393,6 +428,12 @@ where
             .for_each(|mut srv| srv.register(&mut cfg));
 
         let slesh = self.rdef.iter().any(|s| s.ends_with('/'));
+        let prefix = ResourceDef::root_prefix(self.rdef.clone());
+        for mut route in cfg.take_routes() {
+            route.pattern = format!("{}{}", prefix.pattern(), route.pattern);
+            route.scope = format!("{}{}", prefix.pattern(), route.scope);
+            config.add_route(route);
+        }
         let mut rmap = ResourceMap::new(ResourceDef::root_prefix(self.rdef.clone()));
 
         // external resources
@@ -1293,4 +1334,133 @@ mod tests {
             Bytes::from_static(b"http://localhost:8080/a/b/c/12345")
         );
     }
+
+    #[crate::rt_test]
+    async fn test_nested_scope_params() {
+        #[derive(serde::Deserialize)]
+        struct Params {
+            tenant: String,
+            project: u32,
+            id: String,
+        }
+
+        let srv =
+            init_service(App::new().service(web::scope("/tenants/{tenant}").service(
+                web::scope("/projects/{project}").service(
+                    web::resource("/items/{id}").name("item").to(
+                        |req: HttpRequest,
+                         path: web::types::Path<(String, u32, String)>,
+                         params: web::types::Path<Params>| async move {
+                            assert_eq!(req.match_info().len(), 3);
+                            assert_eq!(params.tenant, path.0);
+                            assert_eq!(params.project, path.1);
+                            assert_eq!(params.id, path.2);
+                            let url = req.url_for("item", &["t2", "7", "i2"]).unwrap();
+                            assert!(req.url_for("item", &["t2", "7"]).is_err());
+                            HttpResponse::Ok().body(format!(
+                                "{} {} {} {}",
+                                &req.match_info()["tenant"],
+                                &req.match_info()["project"],
+                                &req.match_info()["id"],
+                                url
+                            ))
+                        },
+                    ),
+                ),
+            )))
+            .await;
+
+        let req = TestRequest::with_uri("/tenants/t1/projects/5/items/i1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(
+                b"t1 5 i1 http://localhost:8080/tenants/t2/projects/7/items/i2"
+            )
+        );
+
+        let req = TestRequest::with_uri("/tenants/t1/projects/x/items/i1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[crate::rt_test]
+    async fn test_mounted_scope() {
+        use crate::web::dev::WebServiceFactory;
+        use crate::web::middleware::{AccessLogEntry, StructuredLogger};
+        use std::{cell::RefCell, rc::Rc};
+
+        fn child() -> impl WebServiceFactory<DefaultError> {
+            web::scope("/child")
+                .state(10usize)
+                .wrap(DefaultHeaders::new().header("x-child", "1"))
+                .route(
+                    "/t",
+                    web::get().to(
+                        |st: web::types::State<usize>, st2: web::types::State<u16>| {
+                            let body = format!("{} {}", *st, *st2);
+                            async move { HttpResponse::Ok().body(body) }
+                        },
+                    ),
+                )
+                .default_service(|r: WebRequest<DefaultError>| async move {
+                    Ok(r.into_response(HttpResponse::NotFound().body("child")))
+                })
+        }
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log2 = log.clone();
+        let srv = init_service(
+            App::new()
+                .state(1usize)
+                .state(2u16)
+                .wrap(StructuredLogger::new(move |e: &AccessLogEntry| {
+                    log2.borrow_mut().push((e.path.clone(), e.status))
+                }))
+                .service(child())
+                .route(
+                    "/t",
+                    web::get().to(|st: web::types::State<usize>| {
+                        let body = format!("{}", *st);
+                        async move { HttpResponse::Ok().body(body) }
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/child/t").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key("x-child"));
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"10 2"));
+
+        let req = TestRequest::with_uri("/t").to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(!resp.headers().contains_key("x-child"));
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::with_uri("/child/unknown").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(resp.headers().contains_key("x-child"));
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"child"));
+
+        let req = TestRequest::with_uri("/unknown").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(!resp.headers().contains_key("x-child"));
+        assert_eq!(read_body(resp).await, Bytes::new());
+
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                ("/child/t".to_string(), StatusCode::OK),
+                ("/t".to_string(), StatusCode::OK),
+                ("/child/unknown".to_string(), StatusCode::NOT_FOUND),
+                ("/unknown".to_string(), StatusCode::NOT_FOUND),
+            ]
+        );
+    }
 }
@@ -6,13 +6,18 @@ use tls_openssl::ssl::{AlpnError, SslAcceptor, SslAcceptorBuilder};
 use tls_rustls::ServerConfig as RustlsServerConfig;
 
 use crate::http::{
-    body::MessageBody, HttpService, KeepAlive, Request, Response, ResponseError,
+    body::MessageBody, header::HeaderValue, HttpService, KeepAlive, Request, Response,
+    ResponseError,
 };
-use crate::server::{Server, ServerBuilder};
+use crate::server::{Server, ServerBuilder, SignalPolicy};
 use crate::service::{map_config, IntoServiceFactory, ServiceFactory};
-use crate::{time::Seconds, util::PoolId};
+use crate::time::{Millis, Seconds};
+use crate::{io::timer, util::PoolId};
 
+use super::blocking;
 use super::config::AppConfig;
+use super::info::ForwardedPrecedence;
+use super::ipnet::IpNetwork;
 
 struct Config {
     host: Option<String>,
@@ -20,7 +25,15 @@ struct Config {
     client_timeout: Seconds,
     client_disconnect: Seconds,
     handshake_timeout: Seconds,
+    forwarded: ForwardedPrecedence,
+    trusted_proxies: Vec<IpNetwork>,
     pool: PoolId,
+    blocking_threads: usize,
+    blocking_queue: Option<usize>,
+    timer_resolution: Millis,
+    default_content_type: Option<HeaderValue>,
+    h2_ka_interval: Seconds,
+    h2_ka_timeout: Seconds,
 }
 
 /// An HTTP Server.
@@ -77,7 +90,15 @@ where
                 client_timeout: Seconds(5),
                 client_disconnect: Seconds(5),
                 handshake_timeout: Seconds(5),
+                forwarded: ForwardedPrecedence::default(),
+                trusted_proxies: Vec::new(),
                 pool: PoolId::P0,
+                blocking_threads: 0,
+                blocking_queue: None,
+                timer_resolution: Millis(500),
+                default_content_type: None,
+                h2_ka_interval: Seconds::ZERO,
+                h2_ka_timeout: Seconds(20),
             })),
             backlog: 1024,
             builder: ServerBuilder::default(),
@@ -178,6 +199,25 @@ where
         self
     }
 
+    /// Set keep-alive ping interval for http/2 connections.
+    ///
+    /// Server sends `PING` frame every `interval` and closes connection
+    /// if peer does not acknowledge ping within keep-alive timeout.
+    ///
+    /// By default custom interval is disabled.
+    pub fn h2_keep_alive_interval(self, val: Seconds) -> Self {
+        self.config.lock().unwrap().h2_ka_interval = val;
+        self
+    }
+
+    /// Set keep-alive ping timeout for http/2 connections.
+    ///
+    /// By default keep-alive timeout is set to 20 seconds.
+    pub fn h2_keep_alive_timeout(self, val: Seconds) -> Self {
+        self.config.lock().unwrap().h2_ka_timeout = val;
+        self
+    }
+
     /// Set server host name.
     ///
     /// Host name is used by application router as a hostname for url generation.
@@ -190,6 +230,80 @@ where
         self
     }
 
+    /// Set precedence of `Forwarded` header versus `X-Forwarded-*` headers.
+    ///
+    /// Check [ConnectionInfo](./dev/struct.ConnectionInfo.html)
+    /// documentation for more information.
+    ///
+    /// By default `Forwarded` header takes precedence.
+    pub fn forwarded_precedence(self, val: ForwardedPrecedence) -> Self {
+        self.config.lock().unwrap().forwarded = val;
+        self
+    }
+
+    /// Set trusted proxy networks.
+    ///
+    /// If trusted proxies are set, forwarding headers are used for remote
+    /// address resolution only if request's peer address belongs to one of
+    /// the networks, otherwise peer address is used. Addresses of trusted
+    /// proxies are skipped in the forwarding chain. Scheme and host from
+    /// forwarding headers are used only for trusted peers as well.
+    ///
+    /// Check [ConnectionInfo](./dev/struct.ConnectionInfo.html#method.remote)
+    /// documentation for more information.
+    ///
+    /// By default all peers are trusted.
+    pub fn trusted_proxies<T>(self, nets: T) -> Self
+    where
+        T: IntoIterator<Item = IpNetwork>,
+    {
+        self.config.lock().unwrap().trusted_proxies = nets.into_iter().collect();
+        self
+    }
+
+    /// Set max number of concurrently executing blocking operations per worker.
+    ///
+    /// Limit applies to `web::block()` calls and static files reads,
+    /// operations above limit wait in a queue. By default number is not limited.
+    pub fn blocking_threads(self, num: usize) -> Self {
+        self.config.lock().unwrap().blocking_threads = num;
+        self
+    }
+
+    /// Set max number of blocking operations waiting for execution per worker.
+    ///
+    /// If queue is full, `web::block()` returns `BlockingError::Overloaded`
+    /// error. By default queue is not limited.
+    pub fn blocking_queue(self, num: usize) -> Self {
+        self.config.lock().unwrap().blocking_queue = Some(num);
+        self
+    }
+
+    /// Set timer resolution for keep-alive and client timeouts.
+    ///
+    /// Keep-alive, client and http/2 idle timeouts of all connections of the
+    /// worker are checked once per tick of shared timer, timeouts could fire
+    /// up to `resolution` later. Lower resolution is cheaper for large number
+    /// of idle connections. By default resolution is 500 millis.
+    pub fn timer_resolution<T: Into<Millis>>(self, resolution: T) -> Self {
+        self.config.lock().unwrap().timer_resolution = resolution.into();
+        self
+    }
+
+    /// Set default `Content-Type` header value.
+    ///
+    /// Header is added to responses with non-empty body that do not contain
+    /// `Content-Type` header. By default content type is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn default_content_type(self, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("Invalid content type value");
+        self.config.lock().unwrap().default_content_type = Some(value);
+        self
+    }
+
     /// Stop ntex runtime when server get dropped.
     ///
     /// By default "stop runtime" is disabled.
@@ -206,6 +320,36 @@ where
         self
     }
 
+    /// Set process signals handling policy.
+    ///
+    /// By default `SIGTERM` stops server gracefully, `SIGINT` and `SIGQUIT`
+    /// stop server immediately, `SIGHUP` is ignored.
+    ///
+    /// ```rust,no_run
+    /// use ntex::rt::Signal;
+    /// use ntex::server::{SignalAction, SignalPolicy};
+    /// use ntex::web::{self, App, HttpResponse, HttpServer};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     HttpServer::new(|| {
+    ///         App::new().route("/", web::get().to(|| async { HttpResponse::Ok() }))
+    ///     })
+    ///     .signals(
+    ///         SignalPolicy::new()
+    ///             .on(Signal::Int, SignalAction::Graceful)
+    ///             .on(Signal::Hup, SignalAction::callback(|_| log::info!("reload"))),
+    ///     )
+    ///     .bind("127.0.0.1:8080")?
+    ///     .run()
+    ///     .await
+    /// }
+    /// ```
+    pub fn signals(mut self, policy: SignalPolicy) -> Self {
+        self.builder = self.builder.signals(policy);
+        self
+    }
+
     /// Timeout for graceful workers shutdown.
     ///
     /// After receiving a stop signal, workers have this much time to finish
@@ -243,13 +387,21 @@ where
                         false,
                         addr,
                         c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
+                    )
+                    .set_forwarded_precedence(c.forwarded)
+                    .set_trusted_proxies(c.trusted_proxies.clone());
                     r.memory_pool(c.pool);
+                    blocking::set_running_limit(c.blocking_threads);
+                    blocking::set_queue_limit(c.blocking_queue);
+                    timer::set_resolution(c.timer_resolution);
 
                     HttpService::build()
                         .keep_alive(c.keep_alive)
                         .client_timeout(c.client_timeout)
+                        .set_default_content_type(c.default_content_type.clone())
                         .disconnect_timeout(c.client_disconnect)
+                        .h2_keep_alive_interval(c.h2_ka_interval)
+                        .h2_keep_alive_timeout(c.h2_ka_timeout)
                         .finish(map_config(factory(), move |_| cfg.clone()))
                 })?;
         Ok(self)
@@ -285,13 +437,21 @@ where
                         true,
                         addr,
                         c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
+                    )
+                    .set_forwarded_precedence(c.forwarded)
+                    .set_trusted_proxies(c.trusted_proxies.clone());
                     r.memory_pool(c.pool);
+                    blocking::set_running_limit(c.blocking_threads);
+                    blocking::set_queue_limit(c.blocking_queue);
+                    timer::set_resolution(c.timer_resolution);
 
                     HttpService::build()
                         .keep_alive(c.keep_alive)
                         .client_timeout(c.client_timeout)
+                        .set_default_content_type(c.default_content_type.clone())
                         .disconnect_timeout(c.client_disconnect)
+                        .h2_keep_alive_interval(c.h2_ka_interval)
+                        .h2_keep_alive_timeout(c.h2_ka_timeout)
                         .ssl_handshake_timeout(c.handshake_timeout)
                         .finish(map_config(factory(), move |_| cfg.clone()))
                         .openssl(acceptor.clone())
@@ -330,13 +490,21 @@ where
                     true,
                     addr,
                     c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                );
+                )
+                .set_forwarded_precedence(c.forwarded)
+                .set_trusted_proxies(c.trusted_proxies.clone());
                 r.memory_pool(c.pool);
+                blocking::set_running_limit(c.blocking_threads);
+                blocking::set_queue_limit(c.blocking_queue);
+                timer::set_resolution(c.timer_resolution);
 
                 HttpService::build()
                     .keep_alive(c.keep_alive)
                     .client_timeout(c.client_timeout)
+                    .set_default_content_type(c.default_content_type.clone())
                     .disconnect_timeout(c.client_disconnect)
+                    .h2_keep_alive_interval(c.h2_ka_interval)
+                    .h2_keep_alive_timeout(c.h2_ka_timeout)
                     .ssl_handshake_timeout(c.handshake_timeout)
                     .finish(map_config(factory(), move |_| cfg.clone()))
                     .rustls(config.clone())
@@ -442,12 +610,20 @@ where
                 false,
                 socket_addr,
                 c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
-            );
+            )
+            .set_forwarded_precedence(c.forwarded)
+            .set_trusted_proxies(c.trusted_proxies.clone());
             r.memory_pool(c.pool);
+            blocking::set_running_limit(c.blocking_threads);
+            blocking::set_queue_limit(c.blocking_queue);
+            timer::set_resolution(c.timer_resolution);
 
             HttpService::build()
                 .keep_alive(c.keep_alive)
                 .client_timeout(c.client_timeout)
+                .set_default_content_type(c.default_content_type.clone())
+                .h2_keep_alive_interval(c.h2_ka_interval)
+                .h2_keep_alive_timeout(c.h2_ka_timeout)
                 .finish(map_config(factory(), move |_| config.clone()))
         })?;
         Ok(self)
@@ -475,12 +651,20 @@ where
                     false,
                     socket_addr,
                     c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
-                );
+                )
+                .set_forwarded_precedence(c.forwarded)
+                .set_trusted_proxies(c.trusted_proxies.clone());
                 r.memory_pool(c.pool);
+                blocking::set_running_limit(c.blocking_threads);
+                blocking::set_queue_limit(c.blocking_queue);
+                timer::set_resolution(c.timer_resolution);
 
                 HttpService::build()
                     .keep_alive(c.keep_alive)
                     .client_timeout(c.client_timeout)
+                    .set_default_content_type(c.default_content_type.clone())
+                    .h2_keep_alive_interval(c.h2_ka_interval)
+                    .h2_keep_alive_timeout(c.h2_ka_timeout)
                     .finish(map_config(factory(), move |_| config.clone()))
             },
         )?;
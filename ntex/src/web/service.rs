@@ -1,5 +1,6 @@
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
+use crate::http::Method;
 use crate::router::{IntoPattern, ResourceDef};
 use crate::service::{boxed, IntoServiceFactory, ServiceFactory};
 use crate::util::Extensions;
@@ -73,6 +74,10 @@ impl AppState {
         &self.0.config
     }
 
+    pub(crate) fn downgrade(&self) -> WeakAppState {
+        WeakAppState(Rc::downgrade(&self.0))
+    }
+
     pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
         let result = self.0.ext.get::<T>();
         if result.is_some() {
@@ -95,6 +100,15 @@ impl AppState {
     }
 }
 
+#[derive(Debug, Clone)]
+pub(crate) struct WeakAppState(Weak<AppStateInner>);
+
+impl WeakAppState {
+    pub(crate) fn upgrade(&self) -> Option<AppState> {
+        self.0.upgrade().map(AppState)
+    }
+}
+
 /// Application service configuration
 pub struct WebServiceConfig<Err: ErrorRenderer> {
     state: AppState,
@@ -106,6 +120,75 @@ pub struct WebServiceConfig<Err: ErrorRenderer> {
         Option<Guards>,
         Option<Rc<ResourceMap>>,
     )>,
+    routes: Vec<RouteEntry>,
+}
+
+/// Registered route description
+#[derive(Debug, Clone)]
+pub struct RouteEntry {
+    /// Path pattern, including scope prefix
+    pub pattern: String,
+    /// Resource name
+    pub name: Option<String>,
+    /// Route methods, empty if any method is accepted
+    pub methods: Vec<Method>,
+    /// Scope prefix, empty for application level routes
+    pub scope: String,
+    pub(super) metadata: Rc<Extensions>,
+}
+
+impl RouteEntry {
+    /// Get reference to resource metadata of type `T`
+    pub fn metadata<T: 'static>(&self) -> Option<&T> {
+        self.metadata.get::<T>()
+    }
+
+    /// Resource metadata container
+    pub fn metadata_ext(&self) -> &Extensions {
+        &self.metadata
+    }
+}
+
+/// Metadata is not compared
+impl PartialEq for RouteEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.name == other.name
+            && self.methods == other.methods
+            && self.scope == other.scope
+    }
+}
+
+impl Eq for RouteEntry {}
+
+/// Registry of application routes.
+///
+/// Registry is available for `App::finish_hook()` callbacks,
+/// after all services get registered.
+#[derive(Debug, Default)]
+pub struct RouteRegistry {
+    routes: Vec<RouteEntry>,
+}
+
+impl RouteRegistry {
+    pub(super) fn new(routes: Vec<RouteEntry>) -> Self {
+        RouteRegistry { routes }
+    }
+
+    /// Registered routes, in registration order
+    pub fn routes(&self) -> &[RouteEntry] {
+        &self.routes
+    }
+
+    /// Iterate over registered routes
+    pub fn iter(&self) -> impl Iterator<Item = &RouteEntry> {
+        self.routes.iter()
+    }
+
+    /// Find route by resource name
+    pub fn get(&self, name: &str) -> Option<&RouteEntry> {
+        self.routes.iter().find(|r| r.name.as_deref() == Some(name))
+    }
 }
 
 impl<Err: ErrorRenderer> WebServiceConfig<Err> {
@@ -116,6 +199,7 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             default,
             root: true,
             services: Vec::new(),
+            routes: Vec::new(),
         }
     }
 
@@ -144,10 +228,26 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             state: state.unwrap_or_else(|| self.state.clone()),
             default: self.default.clone(),
             services: Vec::new(),
+            routes: Vec::new(),
             root: false,
         }
     }
 
+    /// Routes registered so far.
+    ///
+    /// Routes of nested scopes are added after scope registration completes.
+    pub fn routes(&self) -> &[RouteEntry] {
+        &self.routes
+    }
+
+    pub(super) fn add_route(&mut self, route: RouteEntry) {
+        self.routes.push(route);
+    }
+
+    pub(super) fn take_routes(&mut self) -> Vec<RouteEntry> {
+        std::mem::take(&mut self.routes)
+    }
+
     /// Service configuration
     pub fn config(&self) -> &AppConfig {
         self.state.config()
@@ -294,6 +394,13 @@ where
         if let Some(ref name) = self.name {
             *rdef.name_mut() = name.clone();
         }
+        config.add_route(RouteEntry {
+            pattern: rdef.pattern().to_string(),
+            name: self.name,
+            methods: Vec::new(),
+            scope: String::new(),
+            metadata: Rc::new(Extensions::new()),
+        });
         config.register_service(rdef, guards, self.srv, None)
     }
 }
@@ -505,4 +612,61 @@ mod tests {
         assert!(s.contains("WebResponse"));
         assert!(s.contains("x-test"));
     }
+
+    #[crate::rt_test]
+    async fn test_routes() {
+        use std::cell::RefCell;
+
+        struct Routes(Rc<RefCell<Vec<RouteEntry>>>);
+
+        impl<Err: ErrorRenderer> WebServiceFactory<Err> for Routes {
+            fn register(self, config: &mut WebServiceConfig<Err>) {
+                *self.0.borrow_mut() = config.routes().to_vec();
+            }
+        }
+
+        let routes = Rc::new(RefCell::new(Vec::new()));
+        let _ = init_service(
+            App::new()
+                .service(
+                    web::resource("/index")
+                        .name("index")
+                        .route(web::get().to(|| async { HttpResponse::Ok() }))
+                        .route(web::head().to(|| async { HttpResponse::Ok() })),
+                )
+                .service(
+                    web::scope("/api").service(
+                        web::scope("/v1")
+                            .route("/users/{id}", web::post().to(|| async { "" }))
+                            .service(web::service("/raw").finish(
+                                |req: WebRequest<_>| async move {
+                                    Ok(req.into_response(HttpResponse::Ok()))
+                                },
+                            )),
+                    ),
+                )
+                .route("/any", web::to(|| async { HttpResponse::Ok() }))
+                .service(Routes(routes.clone())),
+        )
+        .await;
+
+        let entry = |pattern: &str, name: Option<&str>, methods: &[Method], scope: &str| {
+            RouteEntry {
+                pattern: pattern.to_string(),
+                name: name.map(|s| s.to_string()),
+                methods: methods.to_vec(),
+                scope: scope.to_string(),
+                metadata: Rc::new(Extensions::new()),
+            }
+        };
+        assert_eq!(
+            *routes.borrow(),
+            vec![
+                entry("/index", Some("index"), &[Method::GET, Method::HEAD], ""),
+                entry("/api/v1/users/{id}", None, &[Method::POST], "/api/v1"),
+                entry("/api/v1/raw", None, &[], "/api/v1"),
+                entry("/any", None, &[], ""),
+            ]
+        );
+    }
 }
@@ -1,6 +1,7 @@
 //! Various helpers for ntex applications to use during testing.
 use std::{
-    convert::TryFrom, error::Error, fmt, net, net::SocketAddr, rc::Rc, sync::mpsc, thread,
+    convert::TryFrom, error::Error, fmt, io, net, net::SocketAddr, rc::Rc, sync::mpsc,
+    thread,
 };
 
 #[cfg(feature = "cookie")]
@@ -9,10 +10,10 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::http::body::MessageBody;
-use crate::http::client::{Client, ClientRequest, ClientResponse, Connector};
+use crate::http::client::{Client, ClientRequest, ClientResponse};
 use crate::http::error::{HttpError, PayloadError, ResponseError};
 use crate::http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
-use crate::http::test::TestRequest as HttpTestRequest;
+use crate::http::test::{self, TestRequest as HttpTestRequest};
 use crate::http::{HttpService, Method, Payload, Request, StatusCode, Uri, Version};
 use crate::router::{Path, ResourceDef};
 use crate::service::{
@@ -177,7 +178,7 @@ where
 ///         .to_request();
 ///
 ///     let resp = test::call_service(&mut app, req).await;
-///     let result = test::read_body(resp);
+///     let result = test::read_body(resp).await;
 ///     assert_eq!(result, Bytes::from_static(b"welcome!"));
 /// }
 /// ```
@@ -243,9 +244,62 @@ where
     T: DeserializeOwned,
 {
     let body = read_response::<S>(app, req).await;
+    json_body(&body, "read_response_json")
+}
 
-    serde_json::from_slice(&body)
-        .unwrap_or_else(|_| panic!("read_response_json failed during deserialization"))
+/// Helper function that returns a deserialized response body of a WebResponse.
+///
+/// Response body is drained completely, streaming responses are supported.
+///
+/// # Panics
+///
+/// Panics if response body is not valid json for type `T`, panic message
+/// contains deserialization error and response body.
+///
+/// ```rust
+/// use ntex::web::{self, test, App};
+/// use serde::{Serialize, Deserialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// pub struct Person {
+///     id: String,
+///     name: String
+/// }
+///
+/// #[ntex::test]
+/// async fn test_get_person() {
+///     let app = test::init_service(
+///         App::new().service(
+///             web::resource("/people/{id}").route(web::get().to(
+///                 |id: web::types::Path<String>| async move {
+///                     web::types::Json(Person { id: id.into_inner(), name: "User".into() })
+///                 },
+///             )),
+///         ),
+///     ).await;
+///
+///     let req = test::TestRequest::with_uri("/people/1").to_request();
+///     let resp = test::call_service(&app, req).await;
+///     assert!(resp.status().is_success());
+///
+///     let person: Person = test::read_json(resp).await;
+///     assert_eq!(person.id, "1");
+/// }
+/// ```
+pub async fn read_json<T: DeserializeOwned>(res: WebResponse) -> T {
+    let body = read_body(res).await;
+    json_body(&body, "read_json")
+}
+
+fn json_body<T: DeserializeOwned>(body: &[u8], name: &str) -> T {
+    serde_json::from_slice(body).unwrap_or_else(|e| {
+        panic!(
+            "{} failed during deserialization: {}, body: {:?}",
+            name,
+            e,
+            String::from_utf8_lossy(body)
+        )
+    })
 }
 
 /// Helper method for extractors testing
@@ -303,7 +357,6 @@ pub struct TestRequest {
     rmap: ResourceMap,
     config: AppConfig,
     path: Path<Uri>,
-    peer_addr: Option<SocketAddr>,
     app_state: Extensions,
 }
 
@@ -314,7 +367,6 @@ impl Default for TestRequest {
             rmap: ResourceMap::new(ResourceDef::new("")),
             config: AppConfig::default(),
             path: Path::new(Uri::default()),
-            peer_addr: None,
             app_state: Extensions::new(),
         }
     }
@@ -405,8 +457,11 @@ impl TestRequest {
     }
 
     /// Set peer addr
+    ///
+    /// Address is available via `HttpRequest::peer_addr()` and
+    /// `ConnectionInfo` of the request.
     pub fn peer_addr(mut self, addr: SocketAddr) -> Self {
-        self.peer_addr = Some(addr);
+        self.req.peer_addr(addr);
         self
     }
 
@@ -439,6 +494,9 @@ impl TestRequest {
 
     /// Set application data. This is equivalent of `App::data()` method
     /// for testing purpose.
+    ///
+    /// Value is available via `HttpRequest::app_state()` and `State<T>`
+    /// extractor without constructing an application.
     pub fn state<T: 'static>(mut self, data: T) -> Self {
         self.app_state.insert(data);
         self
@@ -698,43 +756,10 @@ where
 
     let (system, server, addr) = rx.recv().unwrap();
 
-    let client = {
-        let connector = {
-            #[cfg(feature = "openssl")]
-            {
-                use tls_openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
-
-                let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
-                builder.set_verify(SslVerifyMode::NONE);
-                let _ = builder
-                    .set_alpn_protos(b"\x02h2\x08http/1.1")
-                    .map_err(|e| log::error!("Cannot set alpn protocol: {:?}", e));
-                Connector::default()
-                    .lifetime(Seconds::ZERO)
-                    .keep_alive(Seconds(30))
-                    .timeout(Millis(30_000))
-                    .disconnect_timeout(Millis(5_000))
-                    .openssl(builder.build())
-                    .finish()
-            }
-            #[cfg(not(feature = "openssl"))]
-            {
-                Connector::default()
-                    .lifetime(Seconds::ZERO)
-                    .timeout(Millis(30_000))
-                    .finish()
-            }
-        };
-
-        Client::build()
-            .connector(connector)
-            .timeout(Seconds(30))
-            .finish()
-    };
-
     TestServer {
         addr,
-        client,
+        client: test::client(),
+        keep_alive: true,
         system,
         ssl,
         server,
@@ -837,6 +862,7 @@ pub struct TestServer {
     client: Client,
     system: crate::rt::System,
     ssl: bool,
+    keep_alive: bool,
     server: Server,
 }
 
@@ -857,44 +883,77 @@ impl TestServer {
         }
     }
 
+    /// Configure connection reuse for test client.
+    ///
+    /// By default connections are kept alive and reused. If keep-alive
+    /// is disabled, requests are sent with `Connection: close` header.
+    pub fn client_keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = enabled;
+        self
+    }
+
+    /// Open raw tcp connection to the server.
+    ///
+    /// Read and write timeouts are set to 5 seconds.
+    pub fn connect_tcp(&self) -> io::Result<net::TcpStream> {
+        test::connect_tcp(self.addr)
+    }
+
+    /// Send hand-crafted bytes over new connection and read raw response.
+    ///
+    /// Reads until the connection is closed by the server or
+    /// no data is received within `timeout`. Use `http::test::read_raw()`
+    /// for reading from connection opened with `connect_tcp()`.
+    pub fn send_raw(&self, data: &[u8], timeout: Millis) -> io::Result<Bytes> {
+        test::send_raw(self.addr, data, timeout)
+    }
+
     /// Create `GET` request
     pub fn get<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.get(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.get(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `POST` request
     pub fn post<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.post(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.post(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `HEAD` request
     pub fn head<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.head(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.head(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `PUT` request
     pub fn put<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.put(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.put(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `PATCH` request
     pub fn patch<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.patch(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.patch(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `DELETE` request
     pub fn delete<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.delete(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.delete(self.url(path.as_ref()).as_str()))
     }
 
     /// Create `OPTIONS` request
     pub fn options<S: AsRef<str>>(&self, path: S) -> ClientRequest {
-        self.client.options(self.url(path.as_ref()).as_str())
+        self.prepare(self.client.options(self.url(path.as_ref()).as_str()))
     }
 
     /// Connect to test http server
     pub fn request<S: AsRef<str>>(&self, method: Method, path: S) -> ClientRequest {
-        self.client.request(method, path.as_ref())
+        self.prepare(self.client.request(method, path.as_ref()))
+    }
+
+    fn prepare(&self, req: ClientRequest) -> ClientRequest {
+        if self.keep_alive {
+            req
+        } else {
+            req.force_close()
+        }
     }
 
     /// Load response's body
@@ -985,15 +1044,51 @@ mod tests {
             .to_http_request();
         assert!(req.headers().contains_key(header::CONTENT_TYPE));
         assert!(req.headers().contains_key(header::DATE));
-        // assert_eq!(req.peer_addr(), Some("127.0.0.1:8081".parse().unwrap()));
+        assert_eq!(req.peer_addr(), Some("127.0.0.1:8081".parse().unwrap()));
+        assert_eq!(req.connection_info().remote(), Some("127.0.0.1:8081"));
         assert_eq!(&req.match_info()["test"], "123");
         assert_eq!(req.version(), Version::HTTP_2);
         let data = req.app_state::<u64>().unwrap();
         assert_eq!(*data, 20);
 
+        let req = TestRequest::default()
+            .peer_addr("127.0.0.1:8081".parse().unwrap())
+            .to_srv_request();
+        assert_eq!(req.peer_addr(), Some("127.0.0.1:8081".parse().unwrap()));
+
         assert_eq!(format!("{:?}", StreamType::Tcp), "StreamType::Tcp");
     }
 
+    #[crate::rt_test]
+    async fn test_state_stub() {
+        use crate::web::{types::State, FromRequest};
+
+        trait Pool {
+            fn query(&self) -> &'static str;
+        }
+
+        struct MemoryPool;
+
+        impl Pool for MemoryPool {
+            fn query(&self) -> &'static str {
+                "in-memory"
+            }
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .state::<Rc<dyn Pool>>(Rc::new(MemoryPool))
+            .to_http_parts();
+        let pool =
+            <State<Rc<dyn Pool>> as FromRequest<DefaultError>>::from_request(&req, &mut pl)
+                .await
+                .unwrap();
+        assert_eq!(pool.query(), "in-memory");
+
+        let req = TestRequest::default().state(10u32).to_srv_request();
+        assert_eq!(*req.app_state::<u32>().unwrap(), 10);
+        assert!(req.app_state::<u64>().is_none());
+    }
+
     #[crate::rt_test]
     async fn test_request_methods() {
         let app = init_service(
@@ -1075,6 +1170,57 @@ mod tests {
         assert_eq!(&result.id, "12345");
     }
 
+    #[crate::rt_test]
+    async fn test_read_json() {
+        let app = init_service(
+            App::new()
+                .route(
+                    "/people/{id}",
+                    web::get().to(|id: web::types::Path<String>| async move {
+                        web::types::Json(Person {
+                            id: id.into_inner(),
+                            name: "User name".to_string(),
+                        })
+                    }),
+                )
+                .route(
+                    "/stream",
+                    web::get().to(|| async {
+                        let chunks = vec![r#"{"id":"1","#, r#""name":"#, r#""Streamed"}"#];
+                        HttpResponse::Ok().streaming(futures_util::stream::iter(
+                            chunks.into_iter().map(|c| {
+                                Ok::<_, Infallible>(Bytes::from_static(c.as_bytes()))
+                            }),
+                        ))
+                    }),
+                ),
+        )
+        .await;
+
+        let resp =
+            call_service(&app, TestRequest::with_uri("/people/12").to_request()).await;
+        let result: Person = read_json(resp).await;
+        assert_eq!(&result.id, "12");
+        assert_eq!(&result.name, "User name");
+
+        // chunked body
+        let resp = call_service(&app, TestRequest::with_uri("/stream").to_request()).await;
+        let result: Person = read_json(resp).await;
+        assert_eq!(&result.id, "1");
+        assert_eq!(&result.name, "Streamed");
+    }
+
+    #[crate::rt_test]
+    #[should_panic(expected = "read_json failed during deserialization")]
+    async fn test_read_json_error() {
+        let app =
+            init_service(App::new().route("/", web::get().to(|| async { "not a json" })))
+                .await;
+
+        let resp = call_service(&app, TestRequest::default().to_request()).await;
+        let _: Person = read_json(resp).await;
+    }
+
     #[crate::rt_test]
     async fn test_request_response_form() {
         let app = init_service(App::new().service(web::resource("/people").route(
@@ -1168,6 +1314,45 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[crate::rt_test]
+    async fn test_raw_connection() {
+        use std::io::Write;
+
+        let srv = server(|| {
+            App::new().service(web::resource("/").to(|req: HttpRequest| async move {
+                HttpResponse::Ok().body(req.peer_addr().unwrap().port().to_string())
+            }))
+        });
+
+        let srv = srv.client_keep_alive(false);
+        let mut resp = srv.get("/").send().await.unwrap();
+        let port1 = resp.body().await.unwrap();
+        let mut resp = srv.get("/").send().await.unwrap();
+        let port2 = resp.body().await.unwrap();
+        assert_ne!(port1, port2);
+
+        let srv = srv.client_keep_alive(true);
+        let mut resp = srv.get("/").send().await.unwrap();
+        let port1 = resp.body().await.unwrap();
+        let mut resp = srv.get("/").send().await.unwrap();
+        let port2 = resp.body().await.unwrap();
+        assert_eq!(port1, port2);
+
+        let data = srv
+            .send_raw(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n", Millis(1000))
+            .unwrap();
+        assert!(data.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+        // pipelined requests
+        let mut stream = srv.connect_tcp().unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let data = test::read_raw(&mut stream, Millis(250)).unwrap();
+        let data = String::from_utf8_lossy(&data);
+        assert_eq!(data.matches("HTTP/1.1 200 OK").count(), 2);
+    }
+
     #[crate::rt_test]
     async fn test_test_methods() {
         let srv = server(|| {
@@ -0,0 +1,579 @@
+//! Cbor extractor/responder
+use std::{fmt, future::Future, ops, pin::Pin, sync::Arc, task::Context, task::Poll};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "compress")]
+use crate::http::encoding::Decoder;
+use crate::http::header::CONTENT_LENGTH;
+use crate::http::{HttpMessage, Payload, Response, StatusCode};
+use crate::util::{stream_recv, BytesMut};
+use crate::web::error::{CborError, CborPayloadError, ErrorRenderer};
+use crate::web::error::{InternalError, WebResponseError};
+use crate::web::responder::{Ready, Responder};
+use crate::web::{FromRequest, HttpRequest, HttpResponse};
+
+use super::payload::{BodyMemoryLimiter, BodyMemoryPermit};
+
+/// Cbor helper
+///
+/// Cbor can be used for two different purpose. First is for CBOR response
+/// generation and second is for extracting typed information from request's
+/// payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Deserialize` trait from *serde*. Both definite and
+/// indefinite length items are supported.
+///
+/// [**CborConfig**](struct.CborConfig.html) allows to configure extraction
+/// process.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// deserialize `Info` from request's body
+/// async fn index(info: web::types::Cbor<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///        web::resource("/index.html").route(
+///            web::post().to(index))
+///     );
+/// }
+/// ```
+///
+/// The `Cbor` type allows you to respond with CBOR encoded data: simply
+/// return a value of type Cbor<T> where T is the type of a structure
+/// to serialize. The type `T` must implement the `Serialize` trait from *serde*.
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(serde::Serialize)]
+/// struct MyObj {
+///     name: String,
+/// }
+///
+/// fn index(req: web::HttpRequest) -> Result<web::types::Cbor<MyObj>, std::io::Error> {
+///     Ok(web::types::Cbor(MyObj {
+///         name: req.match_info().get("name").unwrap().to_string(),
+///     }))
+/// }
+/// # fn main() {}
+/// ```
+pub struct Cbor<T>(pub T);
+
+impl<T> Cbor<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Cbor<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Cbor<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> fmt::Debug for Cbor<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Cbor").field(&self.0).finish()
+    }
+}
+
+impl<T> fmt::Display for Cbor<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T: Serialize, Err: ErrorRenderer> Responder<Err> for Cbor<T>
+where
+    Err::Container: From<CborError>,
+{
+    type Error = CborError;
+    type Future = Ready<Response>;
+
+    fn respond_to(self, req: &HttpRequest) -> Self::Future {
+        let mut body = Vec::new();
+        if let Err(e) = ciborium::ser::into_writer(&self.0, &mut body) {
+            return e.error_response(req).into();
+        }
+
+        Response::build(StatusCode::OK)
+            .content_type("application/cbor")
+            .body(body)
+            .into()
+    }
+}
+
+/// Cbor extractor. Allow to extract typed information from request's
+/// payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Deserialize` trait from *serde*.
+///
+/// Extraction errors are rendered with application's error renderer,
+/// or with [`CborConfig::error_handler()`](struct.CborConfig.html#method.error_handler)
+/// if it is configured.
+impl<T, Err: ErrorRenderer> FromRequest<Err> for Cbor<T>
+where
+    T: DeserializeOwned + 'static,
+    CborPayloadError: WebResponseError<Err>,
+{
+    type Error = InternalError<CborPayloadError, Err>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req2 = req.clone();
+        let (limit, ctype, err_handler) = req
+            .app_state::<CborConfig>()
+            .map(|c| (c.limit, c.content_type.clone(), c.err_handler.clone()))
+            .unwrap_or((32768, None, None));
+
+        let fut = CborBody::new(req, payload, ctype).limit(limit);
+        Box::pin(async move {
+            match fut.await {
+                Err(e) => {
+                    log::debug!(
+                        "Failed to deserialize Cbor from payload. \
+                         Request path: {}",
+                        req2.path()
+                    );
+                    let res = if let Some(err_handler) = err_handler {
+                        (*err_handler)(&e, &req2)
+                    } else {
+                        e.error_response(&req2)
+                    };
+                    Err(InternalError::from_response(e, res))
+                }
+                Ok(data) => Ok(Cbor(data)),
+            }
+        })
+    }
+}
+
+/// Cbor extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, App, FromRequest, HttpResponse};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// deserialize `Info` from request's body, max payload size is 4kb
+/// async fn index(info: web::types::Cbor<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             .state(
+///                 // change cbor extractor configuration
+///                 web::types::CborConfig::default()
+///                    .limit(4096)
+///                    .error_handler(|err, req| {  // <- create custom error response
+///                        HttpResponse::Conflict().body(err.to_string())
+///                    })
+///             )
+///             .route(web::post().to(index))
+///     );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CborConfig {
+    limit: usize,
+    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    err_handler: Option<Arc<ErrorHandler>>,
+}
+
+type ErrorHandler = dyn Fn(&CborPayloadError, &HttpRequest) -> HttpResponse + Send + Sync;
+
+impl CborConfig {
+    /// Change max size of payload. By default max size is 32Kb
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set predicate for allowed content types
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Set custom error handler
+    ///
+    /// Handler creates response for extraction errors, by default
+    /// error is rendered with application's error renderer.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&CborPayloadError, &HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+}
+
+impl Default for CborConfig {
+    fn default() -> Self {
+        CborConfig {
+            limit: 32768,
+            content_type: None,
+            err_handler: None,
+        }
+    }
+}
+
+/// Request's payload cbor parser, it resolves to a deserialized `T` value.
+///
+/// Returns error:
+///
+/// * content type is not `application/cbor`
+///   (unless specified in [`CborConfig`](struct.CborConfig.html))
+/// * content length is greater than 256k
+struct CborBody<U> {
+    limit: usize,
+    permit: Option<BodyMemoryPermit>,
+    length: Option<usize>,
+    #[cfg(feature = "compress")]
+    stream: Option<Decoder<Payload>>,
+    #[cfg(not(feature = "compress"))]
+    stream: Option<Payload>,
+    err: Option<CborPayloadError>,
+    fut: Option<Pin<Box<dyn Future<Output = Result<U, CborPayloadError>>>>>,
+}
+
+impl<U> CborBody<U>
+where
+    U: DeserializeOwned + 'static,
+{
+    /// Create `CborBody` for request.
+    fn new(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        ctype: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    ) -> Self {
+        // check content-type
+        let cbor = if let Ok(Some(mime)) = req.mime_type() {
+            (mime.type_() == mime::APPLICATION && mime.subtype() == "cbor")
+                || mime.suffix().map(|s| s.as_str()) == Some("cbor")
+                || ctype.as_ref().map_or(false, |predicate| predicate(mime))
+        } else {
+            false
+        };
+
+        if !cbor {
+            return CborBody {
+                limit: 262_144,
+                permit: None,
+                length: None,
+                stream: None,
+                fut: None,
+                err: Some(CborPayloadError::ContentType),
+            };
+        }
+
+        let len = req
+            .headers()
+            .get(&CONTENT_LENGTH)
+            .and_then(|l| l.to_str().ok())
+            .and_then(|s| s.parse::<usize>().ok());
+
+        #[cfg(feature = "compress")]
+        let payload = Decoder::from_headers(payload.take(), req.headers());
+        #[cfg(not(feature = "compress"))]
+        let payload = payload.take();
+
+        CborBody {
+            limit: 262_144,
+            permit: BodyMemoryLimiter::permit(req),
+            length: len,
+            stream: Some(payload),
+            fut: None,
+            err: None,
+        }
+    }
+
+    /// Change max size of payload. By default max size is 256Kb
+    fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl<U> Future for CborBody<U>
+where
+    U: DeserializeOwned + 'static,
+{
+    type Output = Result<U, CborPayloadError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(ref mut fut) = self.fut {
+            return Pin::new(fut).poll(cx);
+        }
+
+        if let Some(err) = self.err.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        let limit = self.limit;
+        if let Some(len) = self.length.take() {
+            if len > limit {
+                return Poll::Ready(Err(CborPayloadError::Overflow));
+            }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
+        }
+        let permit = self.permit.take();
+        let mut stream = self.stream.take().unwrap();
+
+        self.fut = Some(Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(item) = stream_recv(&mut stream).await {
+                let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
+                if (body.len() + chunk.len()) > limit {
+                    return Err(CborPayloadError::Overflow);
+                } else {
+                    body.extend_from_slice(&chunk);
+                }
+            }
+            Ok(ciborium::de::from_reader::<U, _>(&body[..])?)
+        }));
+
+        self.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::http::{error::ResponseError, header};
+    use crate::util::Bytes;
+    use crate::web::test::{from_request, respond_to, TestRequest};
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct MyObject {
+        name: String,
+    }
+
+    fn cbor_body() -> Bytes {
+        let mut body = Vec::new();
+        ciborium::ser::into_writer(
+            &MyObject {
+                name: "test".to_string(),
+            },
+            &mut body,
+        )
+        .unwrap();
+        Bytes::from(body)
+    }
+
+    fn cbor_request(body: Bytes) -> TestRequest {
+        TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/cbor"),
+            )
+            .set_payload(body)
+    }
+
+    #[test]
+    fn test_cbor() {
+        let mut c = Cbor(MyObject {
+            name: "test2".to_string(),
+        });
+        assert_eq!(c.name, "test2");
+        c.name = "test".to_string();
+        assert_eq!(c.name, "test");
+        assert!(format!("{:?}", c).contains("Cbor"));
+        assert_eq!(format!("{}", Cbor("test")), "test");
+    }
+
+    #[crate::rt_test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let c = Cbor(MyObject {
+            name: "test".to_string(),
+        });
+        let resp = respond_to(c, &req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            header::HeaderValue::from_static("application/cbor")
+        );
+        assert_eq!(resp.body().get_ref(), &cbor_body()[..]);
+    }
+
+    #[crate::rt_test]
+    async fn test_extract() {
+        let (req, mut pl) = cbor_request(cbor_body()).to_http_parts();
+        let s = from_request::<Cbor<MyObject>>(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            s.into_inner(),
+            MyObject {
+                name: "test".to_string()
+            }
+        );
+
+        // limit is enforced
+        let (req, mut pl) = cbor_request(cbor_body())
+            .state(CborConfig::default().limit(4))
+            .to_http_parts();
+        let err = from_request::<Cbor<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert!(format!("{}", err).contains("Cbor payload size is bigger than allowed"));
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // malformed payload
+        let (req, mut pl) =
+            cbor_request(Bytes::from_static(b"\xa1\x64name")).to_http_parts();
+        let err = from_request::<Cbor<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert!(format!("{}", err).contains("Cbor deserialize error"));
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_extract_indefinite_length() {
+        // {_ "name": (_ "te", "st"), "data": (_ h'0102', h'03')}
+        let body = Bytes::from_static(
+            b"\xbf\x64name\x7f\x62te\x62st\xff\x64data\x5f\x42\x01\x02\x41\x03\xff\xff",
+        );
+        let (req, mut pl) = cbor_request(body).to_http_parts();
+        let s =
+            from_request::<Cbor<HashMap<String, ciborium::value::Value>>>(&req, &mut pl)
+                .await
+                .unwrap();
+        assert_eq!(s["name"], ciborium::value::Value::Text("test".to_string()));
+        assert_eq!(s["data"], ciborium::value::Value::Bytes(vec![1, 2, 3]));
+
+        // indefinite length map is not terminated
+        let body = Bytes::from_static(b"\xbf\x64name\x64test");
+        let (req, mut pl) = cbor_request(body).to_http_parts();
+        let err = from_request::<Cbor<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_error_handler() {
+        let (req, mut pl) = cbor_request(Bytes::from_static(b"\xff"))
+            .state(CborConfig::default().error_handler(|err, _| {
+                assert!(matches!(err, CborPayloadError::Deserialize(_)));
+                HttpResponse::Conflict().finish()
+            }))
+            .to_http_parts();
+        let err = from_request::<Cbor<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_cbor_body() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let res = CborBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(res.err().unwrap(), CborPayloadError::ContentType));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            )
+            .to_http_parts();
+        let res = CborBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(res.err().unwrap(), CborPayloadError::ContentType));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/cbor"),
+            )
+            .header(
+                header::CONTENT_LENGTH,
+                header::HeaderValue::from_static("10000"),
+            )
+            .to_http_parts();
+        let res = CborBody::<MyObject>::new(&req, &mut pl, None)
+            .limit(100)
+            .await;
+        assert!(matches!(res.err().unwrap(), CborPayloadError::Overflow));
+
+        let (req, mut pl) = TestRequest::with_header(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/octet-stream"),
+        )
+        .set_payload(cbor_body())
+        .to_http_parts();
+        let res = CborBody::<MyObject>::new(
+            &req,
+            &mut pl,
+            Some(Arc::new(|mime: mime::Mime| {
+                mime == mime::APPLICATION_OCTET_STREAM
+            })),
+        )
+        .await;
+        assert!(res.is_ok());
+    }
+}
@@ -0,0 +1,219 @@
+//! Extractor defined by async function
+use std::{fmt, future::Future, ops, pin::Pin, rc::Rc};
+
+use crate::http::Payload;
+use crate::web::error::{Error, ErrorRenderer, StateExtractorError};
+use crate::web::extract::FromRequest;
+use crate::web::httprequest::HttpRequest;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, Error>>>>;
+
+/// Extractor defined by async function.
+///
+/// Custom extractor could be defined without implementing `FromRequest` trait.
+/// Extraction function is registered with `Extract::new()` as an application
+/// (or scope/resource) state, then `Extract<T>` could be used as
+/// a handler argument. If extraction function for `T` is not registered,
+/// using `Extract<T>` extractor would cause *Internal Server Error* response.
+///
+/// ```rust
+/// use ntex::web::{self, error, types::Extract, App, HttpRequest};
+///
+/// struct CurrentUser {
+///     name: String,
+/// }
+///
+/// async fn current_user(req: HttpRequest) -> Result<CurrentUser, web::Error> {
+///     match req.headers().get("x-user").and_then(|v| v.to_str().ok()) {
+///         Some(name) => Ok(CurrentUser { name: name.to_string() }),
+///         None => Err(error::ErrorUnauthorized("unauthorized").into()),
+///     }
+/// }
+///
+/// async fn index(user: Extract<CurrentUser>) -> String {
+///     format!("Welcome {}!", user.name)
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .state(Extract::new(current_user))
+///         .route("/", web::get().to(index));
+/// }
+/// ```
+pub struct Extract<T>(pub T);
+
+impl<T: 'static> Extract<T> {
+    /// Create extraction function for `T`.
+    ///
+    /// Result must be registered with `App::state()`, `Scope::state()`
+    /// or `Resource::state()` methods.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<F, R, E>(f: F) -> ExtractFn<T>
+    where
+        F: Fn(HttpRequest) -> R + 'static,
+        R: Future<Output = Result<T, E>> + 'static,
+        E: Into<Error>,
+    {
+        ExtractFn(Rc::new(move |req| {
+            let fut = f(req);
+            Box::pin(async move { fut.await.map_err(Into::into) })
+        }))
+    }
+}
+
+impl<T> Extract<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Extract<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Extract<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Extract<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Extract").field(&self.0).finish()
+    }
+}
+
+/// Extraction function for `Extract<T>` extractor
+pub struct ExtractFn<T>(Rc<dyn Fn(HttpRequest) -> BoxFuture<T>>);
+
+impl<T> Clone for ExtractFn<T> {
+    fn clone(&self) -> Self {
+        ExtractFn(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for ExtractFn<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractFn").finish()
+    }
+}
+
+impl<T: 'static, Err: ErrorRenderer> FromRequest<Err> for Extract<T> {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(f) = req.app_state::<ExtractFn<T>>() {
+            let fut = (f.0)(req.clone());
+            Box::pin(async move { fut.await.map(Extract) })
+        } else {
+            log::debug!(
+                "Failed to construct Extract extractor, function is not registered. \
+                 Request path: {:?}",
+                req.path()
+            );
+            Box::pin(async { Err(StateExtractorError::NotConfigured.into()) })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, error, App, HttpResponse};
+
+    #[derive(Debug)]
+    struct CurrentUser {
+        name: String,
+    }
+
+    async fn current_user(req: HttpRequest) -> Result<CurrentUser, Error> {
+        match req.headers().get("x-user").and_then(|v| v.to_str().ok()) {
+            Some(name) => Ok(CurrentUser {
+                name: name.to_string(),
+            }),
+            None => Err(error::ErrorUnauthorized("unauthorized").into()),
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_extract() {
+        let srv = init_service(
+            App::new()
+                .state(Extract::new(current_user))
+                .route(
+                    "/",
+                    web::get().to(|user: Extract<CurrentUser>| async move {
+                        format!("Welcome {}!", user.name)
+                    }),
+                )
+                .route(
+                    "/opt",
+                    web::get().to(|user: Option<Extract<CurrentUser>>| async move {
+                        match user {
+                            Some(user) => user.into_inner().name,
+                            None => "anonymous".to_string(),
+                        }
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/")
+            .header("x-user", "bob")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, "Welcome bob!");
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = TestRequest::with_uri("/opt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "anonymous");
+    }
+
+    #[crate::rt_test]
+    async fn test_extract_scope() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::scope("/admin")
+                        .state(Extract::new(|_: HttpRequest| async {
+                            Ok::<_, Error>(CurrentUser {
+                                name: "admin".to_string(),
+                            })
+                        }))
+                        .route(
+                            "/",
+                            web::get().to(|user: Extract<CurrentUser>| async move {
+                                HttpResponse::Ok().body(user.0.name)
+                            }),
+                        ),
+                )
+                .route(
+                    "/",
+                    web::get().to(|_: Extract<CurrentUser>| async { HttpResponse::Ok() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/admin/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "admin");
+
+        // extraction function is not registered
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
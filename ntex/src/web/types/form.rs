@@ -2,10 +2,13 @@
 use std::{fmt, future::Future, ops, pin::Pin, task::Context, task::Poll};
 
 use encoding_rs::{Encoding, UTF_8};
-use serde::{de::DeserializeOwned, Serialize};
+use percent_encoding::percent_decode;
+use serde::de::{self, value, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::Serialize;
 
 #[cfg(feature = "compress")]
 use crate::http::encoding::Decoder;
+use crate::http::error::ContentTypeError;
 use crate::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use crate::http::{HttpMessage, Payload, Response, StatusCode};
 use crate::util::{stream_recv, BytesMut};
@@ -13,6 +16,8 @@ use crate::web::error::{ErrorRenderer, UrlencodedError, WebResponseError};
 use crate::web::responder::{Ready, Responder};
 use crate::web::{FromRequest, HttpRequest};
 
+use super::payload::{BodyMemoryLimiter, BodyMemoryPermit};
+
 /// Form data helper (`application/x-www-form-urlencoded`)
 ///
 /// Can be use to extract url-encoded data from the request body,
@@ -105,15 +110,23 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-        let limit = req
+        let req2 = req.clone();
+        let (limit, nested) = req
             .app_state::<FormConfig>()
-            .map(|c| c.limit)
-            .unwrap_or(16384);
+            .map(|c| (c.limit, c.nested))
+            .unwrap_or((16384, false));
 
-        let fut = UrlEncoded::new(req, payload).limit(limit);
+        let fut = UrlEncoded::new(req, payload).limit(limit).nested(nested);
         Box::pin(async move {
             match fut.await {
-                Err(e) => Err(e),
+                Err(e) => {
+                    log::debug!(
+                        "Failed to deserialize Form from payload. \
+                         Request path: {}",
+                        req2.path()
+                    );
+                    Err(e)
+                }
                 Ok(item) => Ok(Form(item)),
             }
         })
@@ -182,6 +195,7 @@ where
 #[derive(Clone, Debug)]
 pub struct FormConfig {
     limit: usize,
+    nested: bool,
 }
 
 impl FormConfig {
@@ -190,11 +204,24 @@ impl FormConfig {
         self.limit = limit;
         self
     }
+
+    /// Parse bracketed keys into nested structures.
+    ///
+    /// With nested mode enabled `user[name]=x` is deserialized as field `name`
+    /// of struct field `user`, and repeated keys, like `tags[]=a&tags[]=b`,
+    /// are deserialized as sequence. By default nested mode is disabled.
+    pub fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
 }
 
 impl Default for FormConfig {
     fn default() -> Self {
-        FormConfig { limit: 16384 }
+        FormConfig {
+            limit: 16384,
+            nested: false,
+        }
     }
 }
 
@@ -207,6 +234,7 @@ impl Default for FormConfig {
 /// Returns error:
 ///
 /// * content type is not `application/x-www-form-urlencoded`
+/// * content type charset is not supported
 /// * content-length is greater than 32k
 ///
 struct UrlEncoded<U> {
@@ -215,8 +243,10 @@ struct UrlEncoded<U> {
     #[cfg(not(feature = "compress"))]
     stream: Option<Payload>,
     limit: usize,
+    permit: Option<BodyMemoryPermit>,
     length: Option<usize>,
     encoding: &'static Encoding,
+    nested: bool,
     err: Option<UrlencodedError>,
     fut: Option<Pin<Box<dyn Future<Output = Result<U, UrlencodedError>>>>>,
 }
@@ -230,6 +260,9 @@ impl<U> UrlEncoded<U> {
         }
         let encoding = match req.encoding() {
             Ok(enc) => enc,
+            Err(ContentTypeError::UnknownEncoding) => {
+                return Self::err(UrlencodedError::Charset)
+            }
             Err(_) => return Self::err(UrlencodedError::ContentType),
         };
 
@@ -255,7 +288,9 @@ impl<U> UrlEncoded<U> {
             encoding,
             stream: Some(payload),
             limit: 32_768,
+            permit: BodyMemoryLimiter::permit(req),
             length: len,
+            nested: false,
             fut: None,
             err: None,
         }
@@ -265,10 +300,12 @@ impl<U> UrlEncoded<U> {
         UrlEncoded {
             stream: None,
             limit: 32_768,
+            permit: None,
             fut: None,
             err: Some(e),
             length: None,
             encoding: UTF_8,
+            nested: false,
         }
     }
 
@@ -277,6 +314,12 @@ impl<U> UrlEncoded<U> {
         self.limit = limit;
         self
     }
+
+    /// Parse bracketed keys into nested structures
+    fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
 }
 
 impl<U> Future for UrlEncoded<U>
@@ -300,10 +343,17 @@ where
             if len > limit {
                 return Poll::Ready(Err(UrlencodedError::Overflow { size: len, limit }));
             }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
         }
 
         // future
         let encoding = self.encoding;
+        let nested = self.nested;
+        let permit = self.permit.take();
         let mut stream = self.stream.take().unwrap();
 
         self.fut = Some(Box::pin(async move {
@@ -311,6 +361,9 @@ where
 
             while let Some(item) = stream_recv(&mut stream).await {
                 let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
                 if (body.len() + chunk.len()) > limit {
                     return Err(UrlencodedError::Overflow {
                         size: body.len() + chunk.len(),
@@ -321,13 +374,15 @@ where
                 }
             }
 
-            if encoding == UTF_8 {
+            if nested {
+                let pairs = parse_pairs(&body, encoding)?;
+                U::deserialize(Node::from_pairs(pairs)?).map_err(|_| UrlencodedError::Parse)
+            } else if encoding == UTF_8 {
                 serde_urlencoded::from_bytes::<U>(&body).map_err(|_| UrlencodedError::Parse)
             } else {
-                let body = encoding
-                    .decode_without_bom_handling_and_without_replacement(&body)
-                    .map(|s| s.into_owned())
-                    .ok_or(UrlencodedError::Parse)?;
+                // percent-decoded values are in request charset, re-encode as utf-8
+                let body = serde_urlencoded::to_string(parse_pairs(&body, encoding)?)
+                    .map_err(|_| UrlencodedError::Parse)?;
                 serde_urlencoded::from_str::<U>(&body).map_err(|_| UrlencodedError::Parse)
             }
         }));
@@ -335,6 +390,240 @@ where
     }
 }
 
+/// Split urlencoded body to percent-decoded key-value pairs
+fn parse_pairs(
+    body: &[u8],
+    encoding: &'static Encoding,
+) -> Result<Vec<(String, String)>, UrlencodedError> {
+    let decode = |s: &[u8]| {
+        let s: Vec<u8> = s
+            .iter()
+            .map(|b| if *b == b'+' { b' ' } else { *b })
+            .collect();
+        let s: Vec<u8> = percent_decode(&s).collect();
+        encoding
+            .decode_without_bom_handling_and_without_replacement(&s)
+            .map(|s| s.into_owned())
+            .ok_or(UrlencodedError::Parse)
+    };
+
+    body.split(|b| *b == b'&')
+        .filter(|s| !s.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, |b| *b == b'=');
+            let key = decode(parts.next().unwrap_or_default())?;
+            let value = decode(parts.next().unwrap_or_default())?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Nested form value
+#[derive(Debug)]
+enum Node {
+    Leaf(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Build nested value from key-value pairs with bracketed keys
+    fn from_pairs(pairs: Vec<(String, String)>) -> Result<Node, UrlencodedError> {
+        let mut map = Vec::new();
+        for (key, value) in pairs {
+            let path = split_key(&key);
+            Node::insert_map(&mut map, path[0], &path[1..], value)?;
+        }
+        Ok(Node::Map(map))
+    }
+
+    fn insert_map(
+        map: &mut Vec<(String, Node)>,
+        key: &str,
+        path: &[&str],
+        value: String,
+    ) -> Result<(), UrlencodedError> {
+        let node = if let Some(idx) = map.iter().position(|(k, _)| k == key) {
+            &mut map[idx].1
+        } else if path.is_empty() {
+            map.push((key.to_string(), Node::Leaf(value)));
+            return Ok(());
+        } else {
+            let node = if path[0].is_empty() {
+                Node::Seq(Vec::new())
+            } else {
+                Node::Map(Vec::new())
+            };
+            map.push((key.to_string(), node));
+            &mut map.last_mut().unwrap().1
+        };
+
+        // repeated key turns value into sequence
+        if matches!(node, Node::Leaf(_)) && path.first().map_or(true, |s| s.is_empty()) {
+            let leaf = std::mem::replace(node, Node::Seq(Vec::new()));
+            if let Node::Seq(ref mut seq) = node {
+                seq.push(leaf);
+            }
+        }
+
+        match (path.split_first(), node) {
+            (None, Node::Seq(seq)) => {
+                seq.push(Node::Leaf(value));
+                Ok(())
+            }
+            (Some((&"", path)), Node::Seq(seq)) => Node::insert_seq(seq, path, value),
+            (Some((key, path)), Node::Map(map)) if !key.is_empty() => {
+                Node::insert_map(map, key, path, value)
+            }
+            _ => Err(UrlencodedError::Parse),
+        }
+    }
+
+    fn insert_seq(
+        seq: &mut Vec<Node>,
+        path: &[&str],
+        value: String,
+    ) -> Result<(), UrlencodedError> {
+        match path.first() {
+            None => seq.push(Node::Leaf(value)),
+            Some(&"") => {
+                let mut inner = Vec::new();
+                Node::insert_seq(&mut inner, &path[1..], value)?;
+                seq.push(Node::Seq(inner));
+            }
+            Some(key) => {
+                // `items[][name]=a&items[][age]=1` fills the same item
+                // until key repeats
+                if let Some(Node::Map(map)) = seq.last_mut() {
+                    if !map.iter().any(|(k, _)| k == key) {
+                        return Node::insert_map(map, key, &path[1..], value);
+                    }
+                }
+                let mut map = Vec::new();
+                Node::insert_map(&mut map, key, &path[1..], value)?;
+                seq.push(Node::Map(map));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split `a[b][]` key to `["a", "b", ""]` segments
+fn split_key(key: &str) -> Vec<&str> {
+    if let Some(pos) = key.find('[') {
+        if pos > 0 && key.ends_with(']') {
+            let mut path = vec![&key[..pos]];
+            let rest = &key[pos + 1..key.len() - 1];
+            if rest.split("][").all(|s| !s.contains(['[', ']'])) {
+                path.extend(rest.split("]["));
+                return path;
+            }
+        }
+    }
+    vec![key]
+}
+
+impl<'de> IntoDeserializer<'de, value::Error> for Node {
+    type Deserializer = Node;
+
+    fn into_deserializer(self) -> Node {
+        self
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self {
+                    Node::Leaf(s) => visitor.$visit(s.parse().map_err(de::Error::custom)?),
+                    node => node.deserialize_any(visitor),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+    type Error = value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Node::Leaf(s) => visitor.visit_string(s),
+            Node::Seq(seq) => {
+                visitor.visit_seq(value::SeqDeserializer::new(seq.into_iter()))
+            }
+            Node::Map(map) => {
+                visitor.visit_map(value::MapDeserializer::new(map.into_iter()))
+            }
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            // single value for sequence
+            Node::Leaf(_) => {
+                visitor.visit_seq(value::SeqDeserializer::new(std::iter::once(self)))
+            }
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Node::Leaf(s) => visitor.visit_enum(s.into_deserializer()),
+            _ => Err(de::Error::custom("expected enum variant")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
@@ -343,6 +632,7 @@ mod tests {
     use crate::http::header::{HeaderValue, CONTENT_TYPE};
     use crate::util::Bytes;
     use crate::web::test::{from_request, respond_to, TestRequest};
+    use crate::web::DefaultError;
 
     #[derive(Deserialize, Serialize, Debug, PartialEq, thiserror::Error)]
     #[error("Info({hello})")]
@@ -485,6 +775,139 @@ mod tests {
         );
     }
 
+    #[crate::rt_test]
+    async fn test_urlencoded_charset() {
+        // "name=José Müller" in latin-1
+        let (req, mut pl) = TestRequest::with_header(
+            CONTENT_TYPE,
+            "application/x-www-form-urlencoded; charset=ISO-8859-1",
+        )
+        .set_payload(Bytes::from_static(b"hello=Jos%E9+M\xfcller&counter=1"))
+        .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await.unwrap();
+        assert_eq!(info.hello, "José Müller");
+
+        let (req, mut pl) = TestRequest::with_header(
+            CONTENT_TYPE,
+            "application/x-www-form-urlencoded; charset=unknown",
+        )
+        .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+        .to_http_parts();
+        let err = from_request::<Form<Info>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, UrlencodedError::Charset));
+        assert_eq!(
+            WebResponseError::<DefaultError>::status_code(&err),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: u8,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Nested {
+        user: User,
+        tags: Vec<String>,
+        ids: Vec<u32>,
+        active: Option<bool>,
+    }
+
+    #[crate::rt_test]
+    async fn test_nested() {
+        let body =
+            "user[name]=J%C3%BCrgen+K&user[age]=42&tags[]=a&tags[]=b%26c&ids=1&ids=2";
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from(body))
+                .state(FormConfig::default().nested(true))
+                .to_http_parts();
+        let Form(s) = from_request::<Form<Nested>>(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            s,
+            Nested {
+                user: User {
+                    name: "Jürgen K".to_string(),
+                    age: 42
+                },
+                tags: vec!["a".to_string(), "b&c".to_string()],
+                ids: vec![1, 2],
+                active: None,
+            }
+        );
+
+        // single value for sequence, flat structs work in nested mode
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from_static(
+                    b"tags[]=a&ids=7&user[name]=x&user[age]=1&active=true",
+                ))
+                .state(FormConfig::default().nested(true))
+                .to_http_parts();
+        let Form(s) = from_request::<Form<Nested>>(&req, &mut pl).await.unwrap();
+        assert_eq!(s.tags, vec!["a".to_string()]);
+        assert_eq!(s.ids, vec![7]);
+        assert_eq!(s.active, Some(true));
+
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+                .state(FormConfig::default().nested(true))
+                .to_http_parts();
+        let Form(s) = from_request::<Form<Info>>(&req, &mut pl).await.unwrap();
+        assert_eq!(s.counter, 123);
+
+        // sequence of structs
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Users {
+            users: Vec<User>,
+        }
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from_static(
+                    b"users[][name]=a&users[][age]=1&users[][name]=b&users[][age]=2",
+                ))
+                .state(FormConfig::default().nested(true))
+                .to_http_parts();
+        let Form(s) = from_request::<Form<Users>>(&req, &mut pl).await.unwrap();
+        assert_eq!(s.users.len(), 2);
+        assert_eq!(s.users[1].name, "b");
+
+        // conflicting keys
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from_static(b"user=a&user[name]=b"))
+                .state(FormConfig::default().nested(true))
+                .to_http_parts();
+        let res = from_request::<Form<Nested>>(&req, &mut pl).await;
+        assert!(matches!(res.err().unwrap(), UrlencodedError::Parse));
+
+        for body in [
+            &b"a=1&a[b]=2"[..],
+            b"a[b]=1&a[b][c]=2",
+            b"a[]=1&a[b]=2",
+            b"a[b][]=1&a[b][c]=2",
+        ] {
+            assert!(
+                Node::from_pairs(parse_pairs(body, UTF_8).unwrap()).is_err(),
+                "{:?}",
+                std::str::from_utf8(body)
+            );
+        }
+
+        // nested mode is disabled by default
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .set_payload(Bytes::from_static(b"user[name]=a&user[age]=1"))
+                .to_http_parts();
+        assert!(from_request::<Form<Nested>>(&req, &mut pl).await.is_err());
+    }
+
     #[crate::rt_test]
     async fn test_responder() {
         let req = TestRequest::default().to_http_request();
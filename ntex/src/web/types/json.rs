@@ -1,17 +1,23 @@
 //! Json extractor/responder
-use std::{fmt, future::Future, ops, pin::Pin, sync::Arc, task::Context, task::Poll};
+use std::task::{Context, Poll};
+use std::{fmt, future::Future, marker::PhantomData, ops, pin::Pin, sync::Arc};
 
-use serde::{de::DeserializeOwned, Serialize};
+use serde::de::{DeserializeOwned, DeserializeSeed};
+use serde::Serialize;
 
 #[cfg(feature = "compress")]
 use crate::http::encoding::Decoder;
 use crate::http::header::CONTENT_LENGTH;
 use crate::http::{HttpMessage, Payload, Response, StatusCode};
 use crate::util::{stream_recv, BytesMut};
-use crate::web::error::{ErrorRenderer, JsonError, JsonPayloadError, WebResponseError};
+use crate::web::error::{
+    ErrorRenderer, InternalError, JsonError, JsonPayloadError, WebResponseError,
+};
 use crate::web::responder::{Ready, Responder};
 use crate::web::{FromRequest, HttpRequest};
 
+use super::payload::{BodyMemoryLimiter, BodyMemoryPermit};
+
 /// Json helper
 ///
 /// Json can be used for two different purpose. First is for json response
@@ -118,7 +124,12 @@ where
     fn respond_to(self, req: &HttpRequest) -> Self::Future {
         let body = match serde_json::to_string(&self.0) {
             Ok(body) => body,
-            Err(e) => return e.error_response(req).into(),
+            Err(e) => {
+                // serialization failure is a server error
+                return InternalError::<_, Err>::new(e, StatusCode::INTERNAL_SERVER_ERROR)
+                    .error_response(req)
+                    .into();
+            }
         };
 
         Response::build(StatusCode::OK)
@@ -224,8 +235,8 @@ where
 /// ```
 #[derive(Clone)]
 pub struct JsonConfig {
-    limit: usize,
-    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    pub(super) limit: usize,
+    pub(super) content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
 }
 
 impl JsonConfig {
@@ -261,8 +272,10 @@ impl Default for JsonConfig {
 /// * content type is not `application/json`
 ///   (unless specified in [`JsonConfig`](struct.JsonConfig.html))
 /// * content length is greater than 256k
-struct JsonBody<U> {
+pub(super) struct JsonBody<U, S = PhantomData<U>> {
+    seed: Option<S>,
     limit: usize,
+    permit: Option<BodyMemoryPermit>,
     length: Option<usize>,
     #[cfg(feature = "compress")]
     stream: Option<Decoder<Payload>>,
@@ -281,6 +294,22 @@ where
         req: &HttpRequest,
         payload: &mut Payload,
         ctype: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    ) -> Self {
+        JsonBody::with_seed(req, payload, ctype, PhantomData)
+    }
+}
+
+impl<U, S> JsonBody<U, S>
+where
+    U: 'static,
+    S: for<'de> DeserializeSeed<'de, Value = U> + 'static,
+{
+    /// Create `JsonBody` for request, body is deserialized with `seed`.
+    pub(super) fn with_seed(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        ctype: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+        seed: S,
     ) -> Self {
         // check content-type
         let json = if let Ok(Some(mime)) = req.mime_type() {
@@ -293,7 +322,9 @@ where
 
         if !json {
             return JsonBody {
+                seed: None,
                 limit: 262_144,
+                permit: None,
                 length: None,
                 stream: None,
                 fut: None,
@@ -313,7 +344,9 @@ where
         let payload = payload.take();
 
         JsonBody {
+            seed: Some(seed),
             limit: 262_144,
+            permit: BodyMemoryLimiter::permit(req),
             length: len,
             stream: Some(payload),
             fut: None,
@@ -322,15 +355,18 @@ where
     }
 
     /// Change max size of payload. By default max size is 256Kb
-    fn limit(mut self, limit: usize) -> Self {
+    pub(super) fn limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
 }
 
-impl<U> Future for JsonBody<U>
+impl<U, S> Unpin for JsonBody<U, S> {}
+
+impl<U, S> Future for JsonBody<U, S>
 where
-    U: DeserializeOwned + 'static,
+    U: 'static,
+    S: for<'de> DeserializeSeed<'de, Value = U> + 'static,
 {
     type Output = Result<U, JsonPayloadError>;
 
@@ -348,7 +384,14 @@ where
             if len > limit {
                 return Poll::Ready(Err(JsonPayloadError::Overflow));
             }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
         }
+        let permit = self.permit.take();
+        let seed = self.seed.take().unwrap();
         let mut stream = self.stream.take().unwrap();
 
         self.fut = Some(Box::pin(async move {
@@ -356,13 +399,19 @@ where
 
             while let Some(item) = stream_recv(&mut stream).await {
                 let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
                 if (body.len() + chunk.len()) > limit {
                     return Err(JsonPayloadError::Overflow);
                 } else {
                     body.extend_from_slice(&chunk);
                 }
             }
-            Ok(serde_json::from_slice::<U>(&body)?)
+            let mut de = serde_json::Deserializer::from_slice(&body);
+            let value = seed.deserialize(&mut de)?;
+            de.end()?;
+            Ok(value)
         }));
 
         self.poll(cx)
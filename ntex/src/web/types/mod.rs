@@ -1,18 +1,34 @@
 //! Extractor types
 
+#[cfg(feature = "cbor")]
+mod cbor;
+mod extract;
 pub(in crate::web) mod form;
 pub(in crate::web) mod json;
+#[cfg(feature = "msgpack")]
+mod msgpack;
 mod path;
 pub(in crate::web) mod payload;
+#[cfg(feature = "protobuf")]
+mod protobuf;
 mod query;
 pub(in crate::web) mod state;
+mod versioned;
 
+#[cfg(feature = "cbor")]
+pub use self::cbor::{Cbor, CborConfig};
+pub use self::extract::{Extract, ExtractFn};
 pub use self::form::{Form, FormConfig};
 pub use self::json::{Json, JsonConfig};
+#[cfg(feature = "msgpack")]
+pub use self::msgpack::{MsgPack, MsgPackConfig};
 pub use self::path::Path;
-pub use self::payload::{Payload, PayloadConfig};
-pub use self::query::Query;
-pub use self::state::State;
+pub use self::payload::{BodyMemoryLimiter, Payload, PayloadConfig};
+#[cfg(feature = "protobuf")]
+pub use self::protobuf::{ProtoBuf, ProtoBufConfig};
+pub use self::query::{Query, QueryConfig};
+pub use self::state::{LazyState, State, WeakState};
+pub use self::versioned::{FromVersion, Versioned};
 
 #[deprecated]
 #[doc(hidden)]
@@ -0,0 +1,593 @@
+//! MsgPack extractor/responder
+use std::{fmt, future::Future, ops, pin::Pin, sync::Arc, task::Context, task::Poll};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "compress")]
+use crate::http::encoding::Decoder;
+use crate::http::header::CONTENT_LENGTH;
+use crate::http::{HttpMessage, Payload, Response, StatusCode};
+use crate::util::{stream_recv, BytesMut};
+use crate::web::error::{ErrorRenderer, MsgPackError, MsgPackPayloadError};
+use crate::web::error::{InternalError, WebResponseError};
+use crate::web::responder::{Ready, Responder};
+use crate::web::{FromRequest, HttpRequest, HttpResponse};
+
+use super::payload::{BodyMemoryLimiter, BodyMemoryPermit};
+
+/// MsgPack helper
+///
+/// MsgPack can be used for two different purpose. First is for MessagePack
+/// response generation and second is for extracting typed information from
+/// request's payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Deserialize` trait from *serde*.
+///
+/// [**MsgPackConfig**](struct.MsgPackConfig.html) allows to configure extraction
+/// process.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// deserialize `Info` from request's body
+/// async fn index(info: web::types::MsgPack<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///        web::resource("/index.html").route(
+///            web::post().to(index))
+///     );
+/// }
+/// ```
+///
+/// The `MsgPack` type allows you to respond with MessagePack encoded data:
+/// simply return a value of type MsgPack<T> where T is the type of a structure
+/// to serialize. The type `T` must implement the `Serialize` trait from *serde*.
+/// Structs are serialized as maps, field names are preserved.
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(serde::Serialize)]
+/// struct MyObj {
+///     name: String,
+/// }
+///
+/// fn index(req: web::HttpRequest) -> Result<web::types::MsgPack<MyObj>, std::io::Error> {
+///     Ok(web::types::MsgPack(MyObj {
+///         name: req.match_info().get("name").unwrap().to_string(),
+///     }))
+/// }
+/// # fn main() {}
+/// ```
+pub struct MsgPack<T>(pub T);
+
+impl<T> MsgPack<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for MsgPack<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for MsgPack<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> fmt::Debug for MsgPack<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MsgPack").field(&self.0).finish()
+    }
+}
+
+impl<T> fmt::Display for MsgPack<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T: Serialize, Err: ErrorRenderer> Responder<Err> for MsgPack<T>
+where
+    Err::Container: From<MsgPackError>,
+{
+    type Error = MsgPackError;
+    type Future = Ready<Response>;
+
+    fn respond_to(self, req: &HttpRequest) -> Self::Future {
+        let body = match rmp_serde::to_vec_named(&self.0) {
+            Ok(body) => body,
+            Err(e) => return e.error_response(req).into(),
+        };
+
+        Response::build(StatusCode::OK)
+            .content_type("application/msgpack")
+            .body(body)
+            .into()
+    }
+}
+
+/// MsgPack extractor. Allow to extract typed information from request's
+/// payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Deserialize` trait from *serde*.
+///
+/// [**MsgPackConfig**](struct.MsgPackConfig.html) allows to configure extraction
+/// process.
+///
+/// Extraction errors are rendered with application's error renderer,
+/// or with [`MsgPackConfig::error_handler()`](struct.MsgPackConfig.html#method.error_handler)
+/// if it is configured.
+impl<T, Err: ErrorRenderer> FromRequest<Err> for MsgPack<T>
+where
+    T: DeserializeOwned + 'static,
+    MsgPackPayloadError: WebResponseError<Err>,
+{
+    type Error = InternalError<MsgPackPayloadError, Err>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req2 = req.clone();
+        let (limit, ctype, err_handler) = req
+            .app_state::<MsgPackConfig>()
+            .map(|c| (c.limit, c.content_type.clone(), c.err_handler.clone()))
+            .unwrap_or((32768, None, None));
+
+        let fut = MsgPackBody::new(req, payload, ctype).limit(limit);
+        Box::pin(async move {
+            match fut.await {
+                Err(e) => {
+                    log::debug!(
+                        "Failed to deserialize MsgPack from payload. \
+                         Request path: {}",
+                        req2.path()
+                    );
+                    let res = if let Some(err_handler) = err_handler {
+                        (*err_handler)(&e, &req2)
+                    } else {
+                        e.error_response(&req2)
+                    };
+                    Err(InternalError::from_response(e, res))
+                }
+                Ok(data) => Ok(MsgPack(data)),
+            }
+        })
+    }
+}
+
+/// MsgPack extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, App, FromRequest, HttpResponse};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// deserialize `Info` from request's body, max payload size is 4kb
+/// async fn index(info: web::types::MsgPack<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             .state(
+///                 // change msgpack extractor configuration
+///                 web::types::MsgPackConfig::default()
+///                    .limit(4096)
+///                    .content_type(|mime| {  // <- accept application/octet-stream
+///                        mime == mime::APPLICATION_OCTET_STREAM
+///                    })
+///                    .error_handler(|err, req| {  // <- create custom error response
+///                        HttpResponse::Conflict().body(err.to_string())
+///                    })
+///             )
+///             .route(web::post().to(index))
+///     );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MsgPackConfig {
+    limit: usize,
+    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    err_handler: Option<Arc<ErrorHandler>>,
+}
+
+type ErrorHandler =
+    dyn Fn(&MsgPackPayloadError, &HttpRequest) -> HttpResponse + Send + Sync;
+
+impl MsgPackConfig {
+    /// Change max size of payload. By default max size is 32Kb
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set predicate for allowed content types
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Set custom error handler
+    ///
+    /// Handler creates response for extraction errors, by default
+    /// error is rendered with application's error renderer.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&MsgPackPayloadError, &HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.err_handler = Some(Arc::new(f));
+        self
+    }
+}
+
+impl Default for MsgPackConfig {
+    fn default() -> Self {
+        MsgPackConfig {
+            limit: 32768,
+            content_type: None,
+            err_handler: None,
+        }
+    }
+}
+
+/// Request's payload msgpack parser, it resolves to a deserialized `T` value.
+///
+/// Returns error:
+///
+/// * content type is not `application/msgpack` or `application/x-msgpack`
+///   (unless specified in [`MsgPackConfig`](struct.MsgPackConfig.html))
+/// * content length is greater than 256k
+struct MsgPackBody<U> {
+    limit: usize,
+    permit: Option<BodyMemoryPermit>,
+    length: Option<usize>,
+    #[cfg(feature = "compress")]
+    stream: Option<Decoder<Payload>>,
+    #[cfg(not(feature = "compress"))]
+    stream: Option<Payload>,
+    err: Option<MsgPackPayloadError>,
+    fut: Option<Pin<Box<dyn Future<Output = Result<U, MsgPackPayloadError>>>>>,
+}
+
+impl<U> MsgPackBody<U>
+where
+    U: DeserializeOwned + 'static,
+{
+    /// Create `MsgPackBody` for request.
+    fn new(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        ctype: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    ) -> Self {
+        // check content-type
+        let msgpack = if let Ok(Some(mime)) = req.mime_type() {
+            (mime.type_() == mime::APPLICATION
+                && (mime.subtype() == "msgpack" || mime.subtype() == "x-msgpack"))
+                || ctype.as_ref().map_or(false, |predicate| predicate(mime))
+        } else {
+            false
+        };
+
+        if !msgpack {
+            return MsgPackBody {
+                limit: 262_144,
+                permit: None,
+                length: None,
+                stream: None,
+                fut: None,
+                err: Some(MsgPackPayloadError::ContentType),
+            };
+        }
+
+        let len = req
+            .headers()
+            .get(&CONTENT_LENGTH)
+            .and_then(|l| l.to_str().ok())
+            .and_then(|s| s.parse::<usize>().ok());
+
+        #[cfg(feature = "compress")]
+        let payload = Decoder::from_headers(payload.take(), req.headers());
+        #[cfg(not(feature = "compress"))]
+        let payload = payload.take();
+
+        MsgPackBody {
+            limit: 262_144,
+            permit: BodyMemoryLimiter::permit(req),
+            length: len,
+            stream: Some(payload),
+            fut: None,
+            err: None,
+        }
+    }
+
+    /// Change max size of payload. By default max size is 256Kb
+    fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl<U> Future for MsgPackBody<U>
+where
+    U: DeserializeOwned + 'static,
+{
+    type Output = Result<U, MsgPackPayloadError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(ref mut fut) = self.fut {
+            return Pin::new(fut).poll(cx);
+        }
+
+        if let Some(err) = self.err.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        let limit = self.limit;
+        if let Some(len) = self.length.take() {
+            if len > limit {
+                return Poll::Ready(Err(MsgPackPayloadError::Overflow));
+            }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
+        }
+        let permit = self.permit.take();
+        let mut stream = self.stream.take().unwrap();
+
+        self.fut = Some(Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(item) = stream_recv(&mut stream).await {
+                let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
+                if (body.len() + chunk.len()) > limit {
+                    return Err(MsgPackPayloadError::Overflow);
+                } else {
+                    body.extend_from_slice(&chunk);
+                }
+            }
+            Ok(rmp_serde::from_slice::<U>(&body)?)
+        }));
+
+        self.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{error::ResponseError, header};
+    use crate::util::Bytes;
+    use crate::web::test::{from_request, respond_to, TestRequest};
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct MyObject {
+        name: String,
+    }
+
+    fn msgpack_body() -> Bytes {
+        Bytes::from(
+            rmp_serde::to_vec_named(&MyObject {
+                name: "test".to_string(),
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_msgpack() {
+        let mut m = MsgPack(MyObject {
+            name: "test2".to_string(),
+        });
+        assert_eq!(m.name, "test2");
+        m.name = "test".to_string();
+        assert_eq!(m.name, "test");
+        assert!(format!("{:?}", m).contains("MsgPack"));
+        assert_eq!(format!("{}", MsgPack("test")), "test");
+    }
+
+    #[crate::rt_test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let m = MsgPack(MyObject {
+            name: "test".to_string(),
+        });
+        let resp = respond_to(m, &req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            header::HeaderValue::from_static("application/msgpack")
+        );
+        assert_eq!(resp.body().get_ref(), &msgpack_body()[..]);
+    }
+
+    #[crate::rt_test]
+    async fn test_extract() {
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/msgpack"),
+            )
+            .set_payload(msgpack_body())
+            .to_http_parts();
+
+        let s = from_request::<MsgPack<MyObject>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            s.into_inner(),
+            MyObject {
+                name: "test".to_string()
+            }
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/x-msgpack"),
+            )
+            .set_payload(msgpack_body())
+            .to_http_parts();
+        let s = from_request::<MsgPack<MyObject>>(&req, &mut pl).await;
+        assert_eq!(s.unwrap().name, "test");
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/msgpack"),
+            )
+            .set_payload(msgpack_body())
+            .state(MsgPackConfig::default().limit(4))
+            .to_http_parts();
+
+        let s = from_request::<MsgPack<MyObject>>(&req, &mut pl).await;
+        assert!(format!("{}", s.err().unwrap())
+            .contains("MsgPack payload size is bigger than allowed"));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/msgpack"),
+            )
+            .set_payload(Bytes::from_static(b"\xc1\xc1"))
+            .to_http_parts();
+        let err = from_request::<MsgPack<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert!(format!("{}", err).contains("MsgPack deserialize error"));
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_error_handler() {
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/msgpack"),
+            )
+            .set_payload(Bytes::from_static(b"\xc1\xc1"))
+            .state(MsgPackConfig::default().error_handler(|err, _| {
+                assert!(matches!(err, MsgPackPayloadError::Deserialize(_)));
+                HttpResponse::Conflict().finish()
+            }))
+            .to_http_parts();
+        let err = from_request::<MsgPack<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            ResponseError::error_response(&err).status(),
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_msgpack_body() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let res = MsgPackBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(
+            res.err().unwrap(),
+            MsgPackPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            )
+            .to_http_parts();
+        let res = MsgPackBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(
+            res.err().unwrap(),
+            MsgPackPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/msgpack"),
+            )
+            .header(
+                header::CONTENT_LENGTH,
+                header::HeaderValue::from_static("10000"),
+            )
+            .to_http_parts();
+        let res = MsgPackBody::<MyObject>::new(&req, &mut pl, None)
+            .limit(100)
+            .await;
+        assert!(matches!(res.err().unwrap(), MsgPackPayloadError::Overflow));
+    }
+
+    #[crate::rt_test]
+    async fn test_with_msgpack_and_custom_content_type() {
+        let (req, mut pl) = TestRequest::with_header(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/octet-stream"),
+        )
+        .set_payload(msgpack_body())
+        .state(
+            MsgPackConfig::default()
+                .content_type(|mime: mime::Mime| mime == mime::APPLICATION_OCTET_STREAM),
+        )
+        .to_http_parts();
+
+        let s = from_request::<MsgPack<MyObject>>(&req, &mut pl).await;
+        assert!(s.is_ok());
+
+        let (req, mut pl) = TestRequest::with_header(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/html"),
+        )
+        .set_payload(msgpack_body())
+        .state(
+            MsgPackConfig::default()
+                .content_type(|mime: mime::Mime| mime == mime::APPLICATION_OCTET_STREAM),
+        )
+        .to_http_parts();
+
+        let s = from_request::<MsgPack<MyObject>>(&req, &mut pl).await;
+        assert!(s.is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Payload/Bytes/String extractors
-use std::{future::Future, pin::Pin, str, task::Context, task::Poll};
+use std::sync::{atomic::AtomicUsize, atomic::Ordering, Arc};
+use std::{cell::Cell, future::Future, pin::Pin, rc::Rc, str, task::Context, task::Poll};
 
 use encoding_rs::UTF_8;
 use mime::Mime;
@@ -110,8 +111,69 @@ impl<Err: ErrorRenderer> FromRequest<Err> for Payload {
     type Future = Ready<Payload, Self::Error>;
 
     #[inline]
-    fn from_request(_: &HttpRequest, payload: &mut crate::http::Payload) -> Self::Future {
-        Ready::Ok(Payload(payload.take()))
+    fn from_request(req: &HttpRequest, payload: &mut crate::http::Payload) -> Self::Future {
+        let payload = payload.take();
+        match BodyMemoryLimiter::permit(req) {
+            Some(permit) => {
+                let length = req
+                    .headers()
+                    .get(&header::CONTENT_LENGTH)
+                    .and_then(|l| l.to_str().ok())
+                    .and_then(|l| l.parse::<usize>().ok());
+                Ready::Ok(Payload(crate::http::Payload::from_stream(LimitedPayload {
+                    payload,
+                    permit,
+                    length,
+                    size: 0,
+                    failed: false,
+                })))
+            }
+            None => Ready::Ok(Payload(payload)),
+        }
+    }
+}
+
+/// Payload stream that reserves received data in memory limiter
+///
+/// Known content length is reserved before first read.
+struct LimitedPayload {
+    payload: crate::http::Payload,
+    permit: BodyMemoryPermit,
+    length: Option<usize>,
+    size: usize,
+    failed: bool,
+}
+
+impl Stream for LimitedPayload {
+    type Item = Result<Bytes, error::PayloadError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if self.failed {
+            return Poll::Ready(None);
+        }
+        if let Some(len) = self.length.take() {
+            if let Err(e) = self.permit.reserve(len) {
+                self.failed = true;
+                return Poll::Ready(Some(Err(e)));
+            }
+        }
+
+        match self.payload.poll_recv(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                let size = self.size + chunk.len();
+                if let Err(e) = self.permit.reserve(size) {
+                    self.failed = true;
+                    Poll::Ready(Some(Err(e)))
+                } else {
+                    self.size = size;
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+            }
+            res => res,
+        }
     }
 }
 
@@ -300,6 +362,118 @@ impl Default for PayloadConfig {
     }
 }
 
+/// Global memory budget for buffered request bodies.
+///
+/// Body extractors (`Bytes`, `String`, `Json`, `Form`, etc) reserve memory
+/// from the limiter before buffering request's payload. If the budget is
+/// exhausted, extractor fails with `PayloadError::MemoryLimit` error and
+/// default error renderer responds with *503 Service Unavailable*.
+/// `Payload` stream extractor reserves memory for received chunks, stream
+/// yields `PayloadError::MemoryLimit` error if the budget is exhausted.
+/// Reserved memory is released when request is dropped.
+///
+/// Limiter is a backpressure mechanism for all concurrent requests,
+/// per-request size limits are configured by extractor's config.
+/// Limiter must be created once and cloned to every application instance,
+/// so the budget is shared between workers.
+///
+/// ```rust
+/// use ntex::web::{self, types::BodyMemoryLimiter, App, HttpServer};
+///
+/// async fn index(body: String) -> String {
+///     body
+/// }
+///
+/// fn main() {
+///     // 64Mb for all buffered bodies
+///     let limiter = BodyMemoryLimiter::new(64 * 1024 * 1024);
+///
+///     HttpServer::new(move || {
+///         App::new()
+///             .state(limiter.clone())
+///             .route("/", web::post().to(index))
+///     });
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BodyMemoryLimiter(Arc<LimiterInner>);
+
+#[derive(Debug)]
+struct LimiterInner {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl BodyMemoryLimiter {
+    /// Create limiter with memory budget in bytes
+    pub fn new(limit: usize) -> Self {
+        BodyMemoryLimiter(Arc::new(LimiterInner {
+            limit,
+            used: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Memory budget in bytes
+    pub fn limit(&self) -> usize {
+        self.0.limit
+    }
+
+    /// Currently reserved memory in bytes
+    pub fn used(&self) -> usize {
+        self.0.used.load(Ordering::Acquire)
+    }
+
+    /// Create permit for request, if limiter is configured for application
+    pub(super) fn permit(req: &HttpRequest) -> Option<BodyMemoryPermit> {
+        let limiter = req.app_state::<BodyMemoryLimiter>()?;
+        let permit = BodyMemoryPermit(Rc::new(PermitInner {
+            limiter: limiter.clone(),
+            size: Cell::new(0),
+        }));
+        // reserved memory is released with request
+        req.extensions_mut().insert(permit.clone());
+        Some(permit)
+    }
+}
+
+/// Memory reserved by request
+#[derive(Clone)]
+pub(super) struct BodyMemoryPermit(Rc<PermitInner>);
+
+struct PermitInner {
+    limiter: BodyMemoryLimiter,
+    size: Cell<usize>,
+}
+
+impl BodyMemoryPermit {
+    /// Grow reservation up to `size` bytes
+    pub(super) fn reserve(&self, size: usize) -> Result<(), error::PayloadError> {
+        let current = self.0.size.get();
+        if size > current {
+            let inner = &(self.0.limiter).0;
+            let additional = size - current;
+            inner
+                .used
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                    used.checked_add(additional)
+                        .filter(|total| *total <= inner.limit)
+                })
+                .map_err(|_| error::PayloadError::MemoryLimit)?;
+            self.0.size.set(size);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PermitInner {
+    fn drop(&mut self) {
+        (self.limiter)
+            .0
+            .used
+            .fetch_sub(self.size.get(), Ordering::AcqRel);
+    }
+}
+
 /// Future that resolves to a complete http message body.
 ///
 /// Load http message body.
@@ -309,6 +483,7 @@ impl Default for PayloadConfig {
 /// method to change upper limit.
 struct HttpMessageBody {
     limit: usize,
+    permit: Option<BodyMemoryPermit>,
     length: Option<usize>,
     #[cfg(feature = "compress")]
     stream: Option<crate::http::encoding::Decoder<crate::http::Payload>>,
@@ -349,6 +524,7 @@ impl HttpMessageBody {
         HttpMessageBody {
             stream,
             limit: 262_144,
+            permit: BodyMemoryLimiter::permit(req),
             length: len,
             fut: None,
             err: None,
@@ -365,6 +541,7 @@ impl HttpMessageBody {
         HttpMessageBody {
             stream: None,
             limit: 262_144,
+            permit: None,
             fut: None,
             err: Some(e),
             length: None,
@@ -388,16 +565,25 @@ impl Future for HttpMessageBody {
             if len > self.limit {
                 return Poll::Ready(Err(PayloadError::from(error::PayloadError::Overflow)));
             }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
         }
 
         // future
         let limit = self.limit;
+        let permit = self.permit.take();
         let mut stream = self.stream.take().unwrap();
         self.fut = Some(Box::pin(async move {
             let mut body = BytesMut::with_capacity(8192);
 
             while let Some(item) = stream_recv(&mut stream).await {
                 let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
                 if body.len() + chunk.len() > limit {
                     return Err(PayloadError::from(error::PayloadError::Overflow));
                 } else {
@@ -435,6 +621,43 @@ mod tests {
         assert!(cfg.check_mimetype(&req).is_ok());
     }
 
+    #[crate::rt_test]
+    async fn test_body_memory_limiter() {
+        let limiter = BodyMemoryLimiter::new(16);
+        assert_eq!(limiter.limit(), 16);
+
+        let (req, mut pl) = TestRequest::with_header(header::CONTENT_LENGTH, "11")
+            .state(limiter.clone())
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+        let s = from_request::<Bytes>(&req, &mut pl).await.unwrap();
+        assert_eq!(s, Bytes::from_static(b"hello=world"));
+        assert_eq!(limiter.used(), 11);
+
+        // budget is exhausted
+        let (req2, mut pl) = TestRequest::default()
+            .state(limiter.clone())
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+        let res = from_request::<Bytes>(&req2, &mut pl).await;
+        assert!(matches!(
+            res,
+            Err(PayloadError::Payload(error::PayloadError::MemoryLimit))
+        ));
+        drop(req2);
+        assert_eq!(limiter.used(), 11);
+
+        // memory is released with request
+        drop(req);
+        assert_eq!(limiter.used(), 0);
+        let (req, mut pl) = TestRequest::default()
+            .state(limiter.clone())
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+        let s = from_request::<String>(&req, &mut pl).await.unwrap();
+        assert_eq!(s, "hello=world");
+    }
+
     #[crate::rt_test]
     async fn test_payload() {
         let (req, mut pl) = TestRequest::with_header(header::CONTENT_LENGTH, "11")
@@ -0,0 +1,568 @@
+//! ProtoBuf extractor/responder
+use std::{fmt, future::Future, ops, pin::Pin, sync::Arc, task::Context, task::Poll};
+
+use prost::Message;
+
+#[cfg(feature = "compress")]
+use crate::http::encoding::Decoder;
+use crate::http::header::CONTENT_LENGTH;
+use crate::http::{HttpMessage, Payload, Response, StatusCode};
+use crate::util::{stream_recv, BytesMut};
+use crate::web::error::{ErrorRenderer, ProtoBufPayloadError};
+use crate::web::responder::{Ready, Responder};
+use crate::web::{FromRequest, HttpRequest};
+
+use super::payload::{BodyMemoryLimiter, BodyMemoryPermit};
+
+/// ProtoBuf helper
+///
+/// ProtoBuf can be used for two different purpose. First is for protobuf
+/// response generation and second is for extracting typed information from
+/// request's payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Message` and `Default` traits from *prost*. Types generated
+/// by `prost-build` implement both.
+///
+/// [**ProtoBufConfig**](struct.ProtoBufConfig.html) allows to configure extraction
+/// process.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct Info {
+///     #[prost(string, tag = "1")]
+///     username: String,
+/// }
+///
+/// /// decode `Info` from request's body
+/// async fn index(info: web::types::ProtoBuf<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///        web::resource("/index.html").route(
+///            web::post().to(index))
+///     );
+/// }
+/// ```
+///
+/// The `ProtoBuf` type allows you to respond with protobuf encoded data:
+/// simply return a value of type ProtoBuf<T> where T is the type of a message
+/// to encode.
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct MyObj {
+///     #[prost(string, tag = "1")]
+///     name: String,
+/// }
+///
+/// fn index(req: web::HttpRequest) -> web::types::ProtoBuf<MyObj> {
+///     web::types::ProtoBuf(MyObj {
+///         name: req.match_info().get("name").unwrap().to_string(),
+///     })
+/// }
+/// # fn main() {}
+/// ```
+pub struct ProtoBuf<T>(pub T);
+
+impl<T> ProtoBuf<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for ProtoBuf<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for ProtoBuf<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> fmt::Debug for ProtoBuf<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProtoBuf").field(&self.0).finish()
+    }
+}
+
+impl<T> fmt::Display for ProtoBuf<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T: Message, Err: ErrorRenderer> Responder<Err> for ProtoBuf<T> {
+    type Error = Err::Container;
+    type Future = Ready<Response>;
+
+    fn respond_to(self, _: &HttpRequest) -> Self::Future {
+        Response::build(StatusCode::OK)
+            .content_type("application/protobuf")
+            .body(self.0.encode_to_vec())
+            .into()
+    }
+}
+
+/// ProtoBuf extractor. Allow to extract typed information from request's
+/// payload.
+///
+/// To extract typed information from request's body, the type `T` must
+/// implement the `Message` and `Default` traits from *prost*.
+///
+/// [**ProtoBufConfig**](struct.ProtoBufConfig.html) allows to configure extraction
+/// process.
+impl<T, Err: ErrorRenderer> FromRequest<Err> for ProtoBuf<T>
+where
+    T: Message + Default + 'static,
+{
+    type Error = ProtoBufPayloadError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req2 = req.clone();
+        let (limit, ctype) = req
+            .app_state::<ProtoBufConfig>()
+            .map(|c| (c.limit, c.content_type.clone()))
+            .unwrap_or((32768, None));
+
+        let fut = ProtoBufBody::new(req, payload, ctype).limit(limit);
+        Box::pin(async move {
+            match fut.await {
+                Err(e) => {
+                    log::debug!(
+                        "Failed to decode ProtoBuf from payload. \
+                         Request path: {}",
+                        req2.path()
+                    );
+                    Err(e)
+                }
+                Ok(data) => Ok(ProtoBuf(data)),
+            }
+        })
+    }
+}
+
+/// ProtoBuf extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, App, FromRequest, HttpResponse};
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct Info {
+///     #[prost(string, tag = "1")]
+///     username: String,
+/// }
+///
+/// /// decode `Info` from request's body, max payload size is 4kb
+/// async fn index(info: web::types::ProtoBuf<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             .state(
+///                 // change protobuf extractor configuration
+///                 web::types::ProtoBufConfig::default()
+///                    .limit(4096)
+///                    .content_type(|mime| {  // <- accept application/octet-stream
+///                        mime == mime::APPLICATION_OCTET_STREAM
+///                    })
+///             )
+///             .route(web::post().to(index))
+///     );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ProtoBufConfig {
+    limit: usize,
+    debug: bool,
+    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+}
+
+impl ProtoBufConfig {
+    /// Change max size of payload. By default max size is 32Kb
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set predicate for allowed content types
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Include decode error details into error response.
+    ///
+    /// By default decode error details are omitted from response.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub(in crate::web) fn is_debug(&self) -> bool {
+        self.debug
+    }
+}
+
+impl Default for ProtoBufConfig {
+    fn default() -> Self {
+        ProtoBufConfig {
+            limit: 32768,
+            debug: false,
+            content_type: None,
+        }
+    }
+}
+
+/// Request's payload protobuf parser, it resolves to a decoded `T` value.
+///
+/// Returns error:
+///
+/// * content type is not `application/protobuf` or `application/x-protobuf`
+///   (unless specified in [`ProtoBufConfig`](struct.ProtoBufConfig.html))
+/// * content length is greater than 256k
+struct ProtoBufBody<U> {
+    limit: usize,
+    permit: Option<BodyMemoryPermit>,
+    length: Option<usize>,
+    #[cfg(feature = "compress")]
+    stream: Option<Decoder<Payload>>,
+    #[cfg(not(feature = "compress"))]
+    stream: Option<Payload>,
+    err: Option<ProtoBufPayloadError>,
+    fut: Option<Pin<Box<dyn Future<Output = Result<U, ProtoBufPayloadError>>>>>,
+}
+
+impl<U> ProtoBufBody<U>
+where
+    U: Message + Default + 'static,
+{
+    /// Create `ProtoBufBody` for request.
+    fn new(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        ctype: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    ) -> Self {
+        // check content-type
+        let protobuf = if let Ok(Some(mime)) = req.mime_type() {
+            (mime.type_() == mime::APPLICATION
+                && (mime.subtype() == "protobuf" || mime.subtype() == "x-protobuf"))
+                || ctype.as_ref().map_or(false, |predicate| predicate(mime))
+        } else {
+            false
+        };
+
+        if !protobuf {
+            return ProtoBufBody {
+                limit: 262_144,
+                permit: None,
+                length: None,
+                stream: None,
+                fut: None,
+                err: Some(ProtoBufPayloadError::ContentType),
+            };
+        }
+
+        let len = req
+            .headers()
+            .get(&CONTENT_LENGTH)
+            .and_then(|l| l.to_str().ok())
+            .and_then(|s| s.parse::<usize>().ok());
+
+        #[cfg(feature = "compress")]
+        let payload = Decoder::from_headers(payload.take(), req.headers());
+        #[cfg(not(feature = "compress"))]
+        let payload = payload.take();
+
+        ProtoBufBody {
+            limit: 262_144,
+            permit: BodyMemoryLimiter::permit(req),
+            length: len,
+            stream: Some(payload),
+            fut: None,
+            err: None,
+        }
+    }
+
+    /// Change max size of payload. By default max size is 256Kb
+    fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl<U> Future for ProtoBufBody<U>
+where
+    U: Message + Default + 'static,
+{
+    type Output = Result<U, ProtoBufPayloadError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(ref mut fut) = self.fut {
+            return Pin::new(fut).poll(cx);
+        }
+
+        if let Some(err) = self.err.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        let limit = self.limit;
+        if let Some(len) = self.length.take() {
+            if len > limit {
+                return Poll::Ready(Err(ProtoBufPayloadError::Overflow));
+            }
+            if let Some(ref permit) = self.permit {
+                if let Err(e) = permit.reserve(len) {
+                    return Poll::Ready(Err(e.into()));
+                }
+            }
+        }
+        let permit = self.permit.take();
+        let mut stream = self.stream.take().unwrap();
+
+        self.fut = Some(Box::pin(async move {
+            let mut body = BytesMut::with_capacity(8192);
+
+            while let Some(item) = stream_recv(&mut stream).await {
+                let chunk = item?;
+                if let Some(ref permit) = permit {
+                    permit.reserve(body.len() + chunk.len())?;
+                }
+                if (body.len() + chunk.len()) > limit {
+                    return Err(ProtoBufPayloadError::Overflow);
+                } else {
+                    body.extend_from_slice(&chunk);
+                }
+            }
+            Ok(U::decode(&body[..])?)
+        }));
+
+        self.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header;
+    use crate::util::Bytes;
+    use crate::web::test::{from_request, respond_to, TestRequest};
+    use crate::web::{DefaultError, WebResponseError};
+
+    #[derive(Clone, PartialEq, Message)]
+    struct MyObject {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(uint32, repeated, tag = "2")]
+        ids: Vec<u32>,
+    }
+
+    fn protobuf_body() -> Bytes {
+        Bytes::from(
+            MyObject {
+                name: "test".to_string(),
+                ids: vec![1, 2],
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_protobuf() {
+        let mut m = ProtoBuf(MyObject {
+            name: "test2".to_string(),
+            ids: Vec::new(),
+        });
+        assert_eq!(m.name, "test2");
+        m.name = "test".to_string();
+        assert_eq!(m.name, "test");
+        assert!(format!("{:?}", m).contains("ProtoBuf"));
+        assert_eq!(format!("{}", ProtoBuf("test")), "test");
+    }
+
+    #[crate::rt_test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let m = ProtoBuf(MyObject {
+            name: "test".to_string(),
+            ids: vec![1, 2],
+        });
+        let resp = respond_to(m, &req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            header::HeaderValue::from_static("application/protobuf")
+        );
+        assert_eq!(resp.body().get_ref(), &protobuf_body()[..]);
+    }
+
+    #[crate::rt_test]
+    async fn test_extract() {
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/protobuf"),
+            )
+            .set_payload(protobuf_body())
+            .to_http_parts();
+
+        let s = from_request::<ProtoBuf<MyObject>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            s.into_inner(),
+            MyObject {
+                name: "test".to_string(),
+                ids: vec![1, 2],
+            }
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/x-protobuf"),
+            )
+            .set_payload(protobuf_body())
+            .to_http_parts();
+        let s = from_request::<ProtoBuf<MyObject>>(&req, &mut pl).await;
+        assert_eq!(s.unwrap().name, "test");
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/protobuf"),
+            )
+            .set_payload(protobuf_body())
+            .state(ProtoBufConfig::default().limit(4))
+            .to_http_parts();
+
+        let s = from_request::<ProtoBuf<MyObject>>(&req, &mut pl).await;
+        assert!(format!("{}", s.err().unwrap())
+            .contains("ProtoBuf payload size is bigger than allowed"));
+    }
+
+    #[crate::rt_test]
+    async fn test_decode_error() {
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/protobuf"),
+            )
+            .set_payload(Bytes::from_static(b"\x0a\x10test"))
+            .to_http_parts();
+        let err = from_request::<ProtoBuf<MyObject>>(&req, &mut pl)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, ProtoBufPayloadError::Deserialize(_)));
+
+        let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(resp.body().get_ref(), b"ProtoBuf deserialize error");
+
+        let req = TestRequest::default()
+            .state(ProtoBufConfig::default().debug(true))
+            .to_http_request();
+        let resp = WebResponseError::<DefaultError>::error_response(&err, &req);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = resp.body().get_ref();
+        assert!(body.len() > "ProtoBuf deserialize error: ".len());
+        assert!(body.starts_with(b"ProtoBuf deserialize error: "));
+    }
+
+    #[crate::rt_test]
+    async fn test_protobuf_body() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let res = ProtoBufBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(
+            res.err().unwrap(),
+            ProtoBufPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            )
+            .to_http_parts();
+        let res = ProtoBufBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(
+            res.err().unwrap(),
+            ProtoBufPayloadError::ContentType
+        ));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/protobuf"),
+            )
+            .header(
+                header::CONTENT_LENGTH,
+                header::HeaderValue::from_static("10000"),
+            )
+            .to_http_parts();
+        let res = ProtoBufBody::<MyObject>::new(&req, &mut pl, None)
+            .limit(100)
+            .await;
+        assert!(matches!(res.err().unwrap(), ProtoBufPayloadError::Overflow));
+    }
+
+    #[crate::rt_test]
+    async fn test_with_protobuf_and_custom_content_type() {
+        let (req, mut pl) = TestRequest::with_header(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/octet-stream"),
+        )
+        .set_payload(protobuf_body())
+        .state(
+            ProtoBufConfig::default()
+                .content_type(|mime: mime::Mime| mime == mime::APPLICATION_OCTET_STREAM),
+        )
+        .to_http_parts();
+
+        let s = from_request::<ProtoBuf<MyObject>>(&req, &mut pl).await;
+        assert!(s.is_ok());
+
+        let (req, mut pl) = TestRequest::with_header(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/html"),
+        )
+        .set_payload(protobuf_body())
+        .state(
+            ProtoBufConfig::default()
+                .content_type(|mime: mime::Mime| mime == mime::APPLICATION_OCTET_STREAM),
+        )
+        .to_http_parts();
+
+        let s = from_request::<ProtoBuf<MyObject>>(&req, &mut pl).await;
+        assert!(s.is_err());
+    }
+}
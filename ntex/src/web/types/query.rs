@@ -132,7 +132,24 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        serde_urlencoded::from_str::<T>(req.query_string())
+        let query = req.query_string();
+        if let Some(max) = req.app_state::<QueryConfig>().and_then(|c| c.max_params) {
+            // stop counting as soon as limit is exceeded
+            let count = query
+                .split('&')
+                .filter(|s| !s.is_empty())
+                .take(max + 1)
+                .count();
+            if count > max {
+                log::debug!(
+                    "Query parameters limit is exceeded. Request path: {:?}",
+                    req.path()
+                );
+                return Ready::Err(QueryPayloadError::TooManyParams(max));
+            }
+        }
+
+        serde_urlencoded::from_str::<T>(query)
             .map(|val| Ready::Ok(Query(val)))
             .unwrap_or_else(move |e| {
                 let e = QueryPayloadError::Deserialize(e);
@@ -147,10 +164,51 @@ where
     }
 }
 
+/// Query extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, App};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// async fn index(info: web::types::Query<Info>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             // reject queries with more than 16 parameters
+///             .state(web::types::QueryConfig::default().max_params(16))
+///             .route(web::get().to(index))
+///     );
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct QueryConfig {
+    max_params: Option<usize>,
+}
+
+impl QueryConfig {
+    /// Set max number of query parameters.
+    ///
+    /// Requests with more parameters are rejected with `400 Bad Request`
+    /// response. By default number of parameters is not limited.
+    pub fn max_params(mut self, max: usize) -> Self {
+        self.max_params = Some(max);
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::web::test::{from_request, TestRequest};
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, from_request, init_service, TestRequest};
+    use crate::web::{self, App, HttpResponse};
 
     #[derive(serde::Deserialize, Debug, thiserror::Error)]
     #[error("Id({id})")]
@@ -192,4 +250,33 @@ mod tests {
         let s = s.into_inner();
         assert_eq!(s.id, "test1");
     }
+
+    #[crate::rt_test]
+    async fn test_max_params() {
+        let srv = init_service(
+            App::new()
+                .state(QueryConfig::default().max_params(2))
+                .route(
+                    "/",
+                    web::get()
+                        .to(|_: Query<Vec<(String, String)>>| async { HttpResponse::Ok() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/?a=1&&b=2&").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/?a=1&b=2&c=3").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = TestRequest::with_uri("/?a=1&b=2&c=3")
+            .state(QueryConfig::default().max_params(2))
+            .to_http_request();
+        let res =
+            from_request::<Query<Vec<(String, String)>>>(&req, &mut Payload::None).await;
+        assert!(matches!(res, Err(QueryPayloadError::TooManyParams(2))));
+    }
 }
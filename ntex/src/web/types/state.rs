@@ -1,9 +1,11 @@
-use std::{marker::PhantomData, ops::Deref};
+use std::{cell::Cell, cell::RefCell, fmt, future::Future, marker::PhantomData};
+use std::{ops::Deref, pin::Pin, rc::Rc};
 
+use crate::channel::condition::Condition;
 use crate::web::error::{ErrorRenderer, StateExtractorError};
 use crate::web::extract::FromRequest;
 use crate::web::httprequest::HttpRequest;
-use crate::web::service::AppState;
+use crate::web::service::{AppState, WeakAppState};
 use crate::{http::Payload, util::Ready};
 
 /// Application state.
@@ -60,6 +62,14 @@ impl<T: 'static> State<T> {
     pub fn get_ref(&self) -> &T {
         self.0.get::<T>().expect("Unexpected state")
     }
+
+    /// Create weak reference to application state.
+    ///
+    /// Weak reference does not keep application state alive, it could
+    /// be used by background tasks.
+    pub fn downgrade(&self) -> WeakState<T> {
+        WeakState(self.0.downgrade(), PhantomData)
+    }
 }
 
 impl<T: 'static> Deref for State<T> {
@@ -76,6 +86,42 @@ impl<T> Clone for State<T> {
     }
 }
 
+/// Weak reference to application state.
+///
+/// ```rust
+/// use std::cell::Cell;
+/// use ntex::{rt, time, web};
+///
+/// async fn index(st: web::types::State<Cell<usize>>) -> &'static str {
+///     let weak = st.downgrade();
+///     rt::spawn(async move {
+///         time::sleep(time::Seconds(1)).await;
+///         // application could be stopped at this point
+///         if let Some(st) = weak.upgrade() {
+///             st.set(st.get() + 1);
+///         }
+///     });
+///     "ok"
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WeakState<T>(WeakAppState, PhantomData<T>);
+
+impl<T: 'static> WeakState<T> {
+    /// Attempt to upgrade weak reference to `State<T>`.
+    ///
+    /// Returns `None` if application state has been dropped.
+    pub fn upgrade(&self) -> Option<State<T>> {
+        self.0.upgrade().map(|st| State(st, PhantomData))
+    }
+}
+
+impl<T> Clone for WeakState<T> {
+    fn clone(&self) -> WeakState<T> {
+        WeakState(self.0.clone(), PhantomData)
+    }
+}
+
 impl<T: 'static, E: ErrorRenderer> FromRequest<E> for State<T> {
     type Error = StateExtractorError;
     type Future = Ready<Self, Self::Error>;
@@ -95,6 +141,180 @@ impl<T: 'static, E: ErrorRenderer> FromRequest<E> for State<T> {
     }
 }
 
+type InitFuture<T> = Pin<Box<dyn Future<Output = Result<T, ()>>>>;
+
+/// Lazily initialized application state.
+///
+/// State is constructed with async initializer on first access. Application
+/// is constructed for each worker thread, so initializer runs once per worker
+/// on the worker's runtime. Requests that arrive while state is being
+/// initialized wait for initialization to complete.
+///
+/// If initializer fails, error is logged and `LazyState<T>` extractor
+/// returns *Internal Server Error* response. Initialization is retried
+/// on next access.
+///
+/// ```rust
+/// use ntex::web::{self, types::LazyState, App, HttpResponse};
+///
+/// struct DbConnection;
+///
+/// async fn connect() -> Result<DbConnection, std::io::Error> {
+///     Ok(DbConnection)
+/// }
+///
+/// async fn index(db: LazyState<DbConnection>) -> HttpResponse {
+///     let _conn: &DbConnection = &db;
+///     HttpResponse::Ok().finish()
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .state(LazyState::new(connect))
+///         .route("/", web::get().to(index));
+/// }
+/// ```
+pub struct LazyState<T> {
+    inner: Rc<LazyInner<T>>,
+    value: Option<Rc<T>>,
+}
+
+struct LazyInner<T> {
+    value: RefCell<Option<Rc<T>>>,
+    initializing: Cell<bool>,
+    ready: Condition,
+    init: Box<dyn Fn() -> InitFuture<T>>,
+}
+
+impl<T: 'static> LazyState<T> {
+    /// Create lazy state with async initializer.
+    pub fn new<F, Out, E>(init: F) -> Self
+    where
+        F: Fn() -> Out + 'static,
+        Out: Future<Output = Result<T, E>> + 'static,
+        E: fmt::Debug,
+    {
+        LazyState {
+            inner: Rc::new(LazyInner {
+                value: RefCell::new(None),
+                initializing: Cell::new(false),
+                ready: Condition::new(),
+                init: Box::new(move || {
+                    let fut = init();
+                    Box::pin(async move {
+                        fut.await.map_err(|e| {
+                            log::error!("Cannot initialize lazy state: {:?}", e)
+                        })
+                    })
+                }),
+            }),
+            value: None,
+        }
+    }
+
+    /// Check if state is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.inner.value.borrow().is_some()
+    }
+
+    /// Get reference to inner state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if state is not constructed by `LazyState<T>` extractor.
+    pub fn get_ref(&self) -> &T {
+        self.value.as_ref().expect("LazyState is not initialized")
+    }
+
+    async fn get(self) -> Result<Self, StateExtractorError> {
+        loop {
+            if let Some(value) = self.inner.value.borrow().clone() {
+                return Ok(LazyState {
+                    inner: self.inner.clone(),
+                    value: Some(value),
+                });
+            }
+
+            if self.inner.initializing.get() {
+                self.inner.ready.wait().await;
+                continue;
+            }
+
+            let guard = InitGuard(&self.inner);
+            guard.0.initializing.set(true);
+            let result = (guard.0.init)().await;
+            drop(guard);
+
+            return match result {
+                Ok(value) => {
+                    let value = Rc::new(value);
+                    *self.inner.value.borrow_mut() = Some(value.clone());
+                    Ok(LazyState {
+                        inner: self.inner.clone(),
+                        value: Some(value),
+                    })
+                }
+                Err(_) => Err(StateExtractorError::InitFailed),
+            };
+        }
+    }
+}
+
+/// Resets initialization flag and wakes up waiters,
+/// even if initialization future is dropped.
+struct InitGuard<'a, T>(&'a LazyInner<T>);
+
+impl<'a, T> Drop for InitGuard<'a, T> {
+    fn drop(&mut self) {
+        self.0.initializing.set(false);
+        self.0.ready.notify();
+    }
+}
+
+impl<T: 'static> Deref for LazyState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get_ref()
+    }
+}
+
+impl<T> Clone for LazyState<T> {
+    fn clone(&self) -> LazyState<T> {
+        LazyState {
+            inner: self.inner.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LazyState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyState")
+            .field("initialized", &self.inner.value.borrow().is_some())
+            .finish()
+    }
+}
+
+impl<T: 'static, E: ErrorRenderer> FromRequest<E> for LazyState<T> {
+    type Error = StateExtractorError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(st) = req.app_state::<LazyState<T>>() {
+            Box::pin(st.clone().get())
+        } else {
+            log::debug!(
+                "Failed to construct App-level LazyState extractor. \
+                 Request path: {:?}",
+                req.path()
+            );
+            Box::pin(async { Err(StateExtractorError::NotConfigured) })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{atomic::AtomicUsize, atomic::Ordering, Arc};
@@ -102,6 +322,8 @@ mod tests {
     use super::*;
     use crate::http::StatusCode;
     use crate::service::Service;
+    use crate::time::{sleep, Millis};
+    use crate::util::join_all;
     use crate::web::test::{self, init_service, TestRequest};
     use crate::web::{self, App, HttpResponse};
 
@@ -200,6 +422,112 @@ mod tests {
         assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[crate::rt_test]
+    async fn test_weak_state() {
+        let weak = Rc::new(RefCell::new(None));
+        let weak2 = weak.clone();
+        let srv = init_service(App::new().state(10usize).service(web::resource("/").to(
+            move |st: State<usize>| {
+                *weak2.borrow_mut() = Some(st.downgrade());
+                async { HttpResponse::Ok() }
+            },
+        )))
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        drop(resp);
+
+        let weak: WeakState<usize> = weak.borrow_mut().take().unwrap();
+        assert_eq!(*weak.clone().upgrade().unwrap(), 10);
+
+        // app is dropped
+        drop(srv);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[crate::rt_test]
+    async fn test_lazy_state() {
+        let counter = Rc::new(Cell::new(0usize));
+        let counter2 = counter.clone();
+        let srv = init_service(
+            App::new()
+                .state(LazyState::new(move || {
+                    let counter = counter2.clone();
+                    async move {
+                        sleep(Millis(50)).await;
+                        counter.set(counter.get() + 1);
+                        Ok::<_, ()>(counter.get() * 10)
+                    }
+                }))
+                .service(web::resource("/").to(|st: LazyState<usize>| async move {
+                    assert!(st.is_initialized());
+                    assert_eq!(*st, 10);
+                    HttpResponse::Ok()
+                })),
+        )
+        .await;
+
+        // concurrent first accesses wait for single initialization
+        let results =
+            join_all((0..3).map(|_| srv.call(TestRequest::default().to_request()))).await;
+        for res in results {
+            assert_eq!(res.unwrap().status(), StatusCode::OK);
+        }
+        assert_eq!(counter.get(), 1);
+
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(counter.get(), 1);
+
+        // not configured
+        let srv = init_service(App::new().service(
+            web::resource("/").to(|_: LazyState<usize>| async { HttpResponse::Ok() }),
+        ))
+        .await;
+        let req = TestRequest::default().to_request();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[crate::rt_test]
+    async fn test_lazy_state_init_error() {
+        let attempts = Rc::new(Cell::new(0usize));
+        let attempts2 = attempts.clone();
+        let srv = init_service(
+            App::new()
+                .state(LazyState::new(move || {
+                    let attempts = attempts2.clone();
+                    async move {
+                        attempts.set(attempts.get() + 1);
+                        if attempts.get() == 1 {
+                            Err("connection refused")
+                        } else {
+                            Ok(attempts.get())
+                        }
+                    }
+                }))
+                .service(
+                    web::resource("/")
+                        .to(|st: LazyState<usize>| async move { st.to_string() }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // initialization is retried
+        let req = TestRequest::default().to_request();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(test::read_body(res).await, "2");
+        assert_eq!(attempts.get(), 2);
+    }
+
     #[crate::rt_test]
     async fn test_override_state() {
         let srv = init_service(App::new().state(1usize).service(
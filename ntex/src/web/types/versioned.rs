@@ -0,0 +1,253 @@
+//! Versioned json extractor
+use std::{fmt, future::Future, ops, pin::Pin};
+
+use serde::de::DeserializeSeed;
+
+use crate::http::{header::HeaderValue, Payload};
+use crate::web::error::{ErrorRenderer, JsonPayloadError};
+use crate::web::{FromRequest, HttpRequest};
+
+use super::json::{JsonBody, JsonConfig};
+
+/// Type that could be deserialized from json payload depending on
+/// request's version header.
+///
+/// Header value is passed to `FromVersion::seed()`, resulting seed is
+/// used for payload deserialization.
+pub trait FromVersion: Sized + 'static {
+    /// Name of the version header, default is `x-api-version`
+    const HEADER: &'static str = "x-api-version";
+
+    /// Deserialize seed
+    type Seed: for<'de> DeserializeSeed<'de, Value = Self> + 'static;
+
+    /// Create seed for version header value.
+    ///
+    /// `version` is `None` if request does not contain version header.
+    fn seed(version: Option<&HeaderValue>) -> Self::Seed;
+}
+
+/// Extract typed information from request's json payload, payload shape
+/// depends on version header.
+///
+/// Payload limits and content types are configured with `JsonConfig`.
+///
+/// ```rust
+/// use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+/// use ntex::http::header::HeaderValue;
+/// use ntex::web::{self, types::{FromVersion, Versioned}, App};
+///
+/// #[derive(serde::Deserialize)]
+/// struct UserV1 { name: String }
+///
+/// #[derive(serde::Deserialize)]
+/// struct UserV2 { first_name: String, last_name: String }
+///
+/// enum User {
+///     V1(UserV1),
+///     V2(UserV2),
+/// }
+///
+/// enum UserSeed { V1, V2 }
+///
+/// impl<'de> DeserializeSeed<'de> for UserSeed {
+///     type Value = User;
+///
+///     fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<User, D::Error> {
+///         match self {
+///             UserSeed::V1 => UserV1::deserialize(d).map(User::V1),
+///             UserSeed::V2 => UserV2::deserialize(d).map(User::V2),
+///         }
+///     }
+/// }
+///
+/// impl FromVersion for User {
+///     type Seed = UserSeed;
+///
+///     fn seed(version: Option<&HeaderValue>) -> UserSeed {
+///         match version.map(|v| v.as_bytes()) {
+///             Some(b"2") => UserSeed::V2,
+///             _ => UserSeed::V1,
+///         }
+///     }
+/// }
+///
+/// async fn index(user: Versioned<User>) -> String {
+///     match user.into_inner() {
+///         User::V1(u) => format!("Welcome {}!", u.name),
+///         User::V2(u) => format!("Welcome {} {}!", u.first_name, u.last_name),
+///     }
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html").route(web::post().to(index)));
+/// }
+/// ```
+pub struct Versioned<T>(pub T);
+
+impl<T> Versioned<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Versioned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Versioned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Versioned: {:?}", self.0)
+    }
+}
+
+impl<T, Err: ErrorRenderer> FromRequest<Err> for Versioned<T>
+where
+    T: FromVersion,
+{
+    type Error = JsonPayloadError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req2 = req.clone();
+        let (limit, ctype) = req
+            .app_state::<JsonConfig>()
+            .map(|c| (c.limit, c.content_type.clone()))
+            .unwrap_or((32768, None));
+
+        let seed = T::seed(req.headers().get(T::HEADER));
+        let fut = JsonBody::with_seed(req, payload, ctype, seed).limit(limit);
+        Box::pin(async move {
+            match fut.await {
+                Err(e) => {
+                    log::debug!(
+                        "Failed to deserialize versioned Json from payload. \
+                         Request path: {}",
+                        req2.path()
+                    );
+                    Err(e)
+                }
+                Ok(data) => Ok(Versioned(data)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::{Deserialize, Deserializer};
+
+    use super::*;
+    use crate::http::header;
+    use crate::util::Bytes;
+    use crate::web::test::{from_request, TestRequest};
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct UserV1 {
+        name: String,
+    }
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct UserV2 {
+        first_name: String,
+        last_name: String,
+    }
+
+    #[derive(PartialEq, Debug)]
+    enum User {
+        V1(UserV1),
+        V2(UserV2),
+    }
+
+    enum UserSeed {
+        V1,
+        V2,
+    }
+
+    impl<'de> DeserializeSeed<'de> for UserSeed {
+        type Value = User;
+
+        fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<User, D::Error> {
+            match self {
+                UserSeed::V1 => UserV1::deserialize(d).map(User::V1),
+                UserSeed::V2 => UserV2::deserialize(d).map(User::V2),
+            }
+        }
+    }
+
+    impl FromVersion for User {
+        type Seed = UserSeed;
+
+        fn seed(version: Option<&HeaderValue>) -> UserSeed {
+            match version.map(|v| v.as_bytes()) {
+                Some(b"2") => UserSeed::V2,
+                _ => UserSeed::V1,
+            }
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_versioned() {
+        let (req, mut pl) = TestRequest::default()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload(Bytes::from_static(b"{\"name\": \"test\"}"))
+            .to_http_parts();
+        let user = from_request::<Versioned<User>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            user.into_inner(),
+            User::V1(UserV1 {
+                name: "test".to_string()
+            })
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-api-version", "2")
+            .set_payload(Bytes::from_static(
+                b"{\"first_name\": \"a\", \"last_name\": \"b\"}",
+            ))
+            .to_http_parts();
+        let user = from_request::<Versioned<User>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            *user,
+            User::V2(UserV2 {
+                first_name: "a".to_string(),
+                last_name: "b".to_string()
+            })
+        );
+
+        // v1 body with v2 header
+        let (req, mut pl) = TestRequest::default()
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-api-version", "2")
+            .set_payload(Bytes::from_static(b"{\"name\": \"test\"}"))
+            .to_http_parts();
+        let res = from_request::<Versioned<User>>(&req, &mut pl).await;
+        assert!(matches!(res, Err(JsonPayloadError::Deserialize(_))));
+
+        let (req, mut pl) = TestRequest::default()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload(Bytes::from_static(b"{\"name\": \"test\"}"))
+            .state(JsonConfig::default().limit(10))
+            .to_http_parts();
+        let res = from_request::<Versioned<User>>(&req, &mut pl).await;
+        assert!(matches!(res, Err(JsonPayloadError::Overflow)));
+    }
+}
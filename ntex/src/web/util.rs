@@ -9,6 +9,7 @@ use crate::http::header::ContentEncoding;
 use crate::http::{Method, Request, Response};
 use crate::service::{IntoServiceFactory, ServiceFactory};
 
+use super::blocking;
 use super::config::AppConfig;
 use super::error::ErrorRenderer;
 use super::extract::FromRequest;
@@ -255,12 +256,20 @@ pub fn service<T: IntoPattern>(path: T) -> WebServiceAdapter {
 
 /// Execute blocking function on a thread pool, returns future that resolves
 /// to result of the function execution.
+///
+/// Number of concurrently executing functions could be limited, check
+/// [`blocking`](blocking/index.html) module. If limits are exceeded
+/// `BlockingError::Overloaded` error is returned.
 pub async fn block<F, I, E>(f: F) -> Result<I, BlockingError<E>>
 where
     F: FnOnce() -> Result<I, E> + Send + 'static,
     I: Send + 'static,
     E: Send + std::fmt::Debug + 'static,
 {
+    let _permit = blocking::acquire()
+        .await
+        .map_err(|_| BlockingError::Overloaded)?;
+
     match crate::rt::spawn_blocking(f).await {
         Ok(res) => res.map_err(BlockingError::Error),
         Err(_) => Err(BlockingError::Canceled),
@@ -295,7 +304,7 @@ where
     HttpServer::new(factory)
 }
 
-struct Enc(ContentEncoding);
+pub(super) struct Enc(pub(super) ContentEncoding);
 
 /// Helper trait that allows to set specific encoding for response.
 pub trait BodyEncoding {
@@ -304,6 +313,15 @@ pub trait BodyEncoding {
 
     /// Set content encoding
     fn encoding(&mut self, encoding: ContentEncoding) -> &mut Self;
+
+    /// Disable compression for response
+    ///
+    /// `Compress` middleware sends response as is, regardless of
+    /// `Accept-Encoding` request header. Useful for already compressed
+    /// or encrypted payloads.
+    fn no_compress(&mut self) -> &mut Self {
+        self.encoding(ContentEncoding::Identity)
+    }
 }
 
 impl BodyEncoding for HttpResponseBuilder {
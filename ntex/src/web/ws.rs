@@ -1,15 +1,16 @@
 //! WebSockets protocol support
-use std::fmt;
+use std::{fmt, future::Future, time};
 
-pub use crate::ws::{CloseCode, CloseReason, Frame, Message, WsSink};
+pub use crate::ws::{CloseCode, CloseReason, Frame, Message, WsSession, WsSink};
 
-use crate::http::{body::BodySize, h1, StatusCode};
+use crate::http::{body::BodySize, h1, header, RequestHead, StatusCode};
+use crate::io::{DispatchItem, IoBoxed};
 use crate::service::{
     apply_fn, fn_factory_with_config, IntoServiceFactory, Service, ServiceFactory,
 };
 use crate::web::{HttpRequest, HttpResponse};
-use crate::ws::{error::HandshakeError, error::WsError, handshake};
-use crate::{io::DispatchItem, rt, time::Seconds, util::Either, util::Ready, ws};
+use crate::ws::{error::HandshakeError, error::ProtocolError, error::WsError, handshake};
+use crate::{rt, time::Seconds, util::Either, util::Ready, ws};
 
 /// Do websocket handshake and start websockets service.
 pub async fn start<T, F, Err>(req: HttpRequest, factory: F) -> Result<HttpResponse, Err>
@@ -19,45 +20,7 @@ where
     F: IntoServiceFactory<T, Frame, WsSink>,
     Err: From<T::InitError> + From<HandshakeError>,
 {
-    let inner_factory = factory.into_factory().map_err(WsError::Service);
-
-    let factory = fn_factory_with_config(move |sink: WsSink| {
-        let fut = inner_factory.new_service(sink.clone());
-
-        async move {
-            let srv = fut.await?;
-            Ok::<_, T::InitError>(apply_fn(srv, move |req, srv| match req {
-                DispatchItem::Item(item) => {
-                    let s = if matches!(item, Frame::Close(_)) {
-                        Some(sink.clone())
-                    } else {
-                        None
-                    };
-                    let fut = srv.call(item);
-                    Either::Left(async move {
-                        let result = fut.await;
-                        if let Some(s) = s {
-                            rt::spawn(async move { s.io().close() });
-                        }
-                        result
-                    })
-                }
-                DispatchItem::WBackPressureEnabled
-                | DispatchItem::WBackPressureDisabled => Either::Right(Ready::Ok(None)),
-                DispatchItem::KeepAliveTimeout => {
-                    Either::Right(Ready::Err(WsError::KeepAlive))
-                }
-                DispatchItem::DecoderError(e) | DispatchItem::EncoderError(e) => {
-                    Either::Right(Ready::Err(WsError::Protocol(e)))
-                }
-                DispatchItem::Disconnect(e) => {
-                    Either::Right(Ready::Err(WsError::Disconnected(e)))
-                }
-            }))
-        }
-    });
-
-    start_with(req, factory).await
+    WsBuilder::new().start(req, factory).await
 }
 
 /// Do websocket handshake and start websockets service.
@@ -72,37 +35,345 @@ where
     F: IntoServiceFactory<T, DispatchItem<ws::Codec>, WsSink>,
     Err: From<T::InitError> + From<HandshakeError>,
 {
-    log::trace!("Start ws handshake verification for {:?}", req.path());
-
-    // ws handshake
-    let res = handshake(req.head())?.finish().into_parts().0;
-
-    // extract io
-    let item = req
-        .head()
-        .take_io()
-        .ok_or(HandshakeError::NoWebsocketUpgrade)?;
-    let io = item.0;
-    let codec = item.1;
-
-    io.encode(h1::Message::Item((res, BodySize::Empty)), &codec)
-        .map_err(|_| HandshakeError::NoWebsocketUpgrade)?;
-    log::trace!("Ws handshake verification completed for {:?}", req.path());
-
-    // create sink
-    let codec = ws::Codec::new();
-    let sink = WsSink::new(io.get_ref(), codec.clone());
-
-    // create ws service
-    let srv = factory.into_factory().new_service(sink).await?;
-
-    // start websockets service dispatcher
-    rt::spawn(async move {
-        let res = crate::io::Dispatcher::new(io, codec, srv)
-            .keepalive_timeout(Seconds::ZERO)
-            .await;
-        log::trace!("Ws handler is terminated: {:?}", res);
-    });
-
-    Ok(HttpResponse::new(StatusCode::OK))
+    WsBuilder::new().start_with(req, factory).await
+}
+
+/// Do websocket handshake and start websockets session.
+///
+/// `f` is called with session handle, session handle could be used for
+/// receiving frames and for sending messages. Connection is closed
+/// when all session handles are dropped.
+///
+/// ```rust
+/// use ntex::web::{self, ws, App, HttpRequest};
+///
+/// async fn index(req: HttpRequest) -> Result<web::HttpResponse, web::Error> {
+///     ws::start_session(req, |session: ws::WsSession| async move {
+///         while let Some(Ok(frame)) = session.recv().await {
+///             match frame {
+///                 ws::Frame::Text(text) => {
+///                     let text = String::from_utf8_lossy(&text).to_string();
+///                     if session.text(text).await.is_err() {
+///                         break;
+///                     }
+///                 }
+///                 ws::Frame::Close(_) => break,
+///                 _ => (),
+///             }
+///         }
+///     })
+///     .await
+/// }
+///
+/// fn main() {
+///     let app = App::new().route("/ws", web::get().to(index));
+/// }
+/// ```
+pub async fn start_session<F, R, Err>(req: HttpRequest, f: F) -> Result<HttpResponse, Err>
+where
+    F: FnOnce(WsSession) -> R,
+    R: Future<Output = ()> + 'static,
+    Err: From<HandshakeError>,
+{
+    WsBuilder::new().start_session(req, f).await
+}
+
+/// Websocket connection configuration.
+///
+/// ```rust
+/// use ntex::service::{fn_factory_with_config, fn_service};
+/// use ntex::web::{self, ws, App, HttpRequest};
+///
+/// async fn service(msg: ws::Frame) -> Result<Option<ws::Message>, web::Error> {
+///     Ok(None)
+/// }
+///
+/// async fn index(req: HttpRequest) -> Result<web::HttpResponse, web::Error> {
+///     ws::WsBuilder::new()
+///         .max_frame_size(16_384)
+///         .max_message_size(1_048_576)
+///         .protocols(vec!["graphql-ws"])
+///         .start(
+///             req,
+///             fn_factory_with_config(|_| async {
+///                 Ok::<_, web::Error>(fn_service(service))
+///             }),
+///         )
+///         .await
+/// }
+///
+/// fn main() {
+///     let app = App::new().route("/ws", web::get().to(index));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WsBuilder {
+    max_frame_size: usize,
+    max_message_size: usize,
+    accept_unmasked: bool,
+    utf8_policy: ws::Utf8Policy,
+    idle_timeout: Seconds,
+    max_write_buffer: usize,
+    protocols: Vec<String>,
+}
+
+impl Default for WsBuilder {
+    fn default() -> Self {
+        WsBuilder::new()
+    }
+}
+
+impl WsBuilder {
+    /// Create new websocket connection configuration
+    pub fn new() -> Self {
+        WsBuilder {
+            max_frame_size: 65_536,
+            max_message_size: usize::MAX,
+            accept_unmasked: false,
+            utf8_policy: ws::Utf8Policy::Fail,
+            idle_timeout: Seconds::ZERO,
+            max_write_buffer: 65_536,
+            protocols: Vec::new(),
+        }
+    }
+
+    /// Set max frame size
+    ///
+    /// By default max size is set to 64kb
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Set max message size
+    ///
+    /// Limits total size of fragmented message. By default size is not limited.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Accept unmasked frames from client.
+    ///
+    /// By default unmasked frames are rejected.
+    pub fn accept_unmasked_frames(mut self, accept: bool) -> Self {
+        self.accept_unmasked = accept;
+        self
+    }
+
+    /// Set utf-8 validation policy for text messages.
+    ///
+    /// By default connection is closed with `CloseCode::Invalid` code
+    /// if text message is not valid utf-8.
+    pub fn utf8_policy(mut self, policy: ws::Utf8Policy) -> Self {
+        self.utf8_policy = policy;
+        self
+    }
+
+    /// Set idle connection timeout
+    ///
+    /// Connection is closed with `CloseCode::Normal` if no frame is received
+    /// or sent within specified period of time. Any frame, including pings and
+    /// pongs, resets timer. Services started with `start_with()` receive
+    /// `DispatchItem::KeepAliveTimeout` instead.
+    ///
+    /// By default idle timeout is disabled.
+    pub fn idle_timeout(mut self, timeout: Seconds) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Set write buffer high-water mark for websocket sessions
+    ///
+    /// `WsSession` sending methods wait until write buffer is flushed
+    /// if its size exceeds this value. By default it is set to 64kb
+    pub fn max_write_buffer(mut self, size: usize) -> Self {
+        self.max_write_buffer = size;
+        self
+    }
+
+    /// Set supported websocket protocols
+    ///
+    /// First protocol from this list which is also requested by client
+    /// is selected and returned in `Sec-WebSocket-Protocol` response header.
+    pub fn protocols<U, V>(mut self, protos: U) -> Self
+    where
+        U: IntoIterator<Item = V>,
+        V: AsRef<str>,
+    {
+        self.protocols = protos.into_iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    fn codec(&self) -> ws::Codec {
+        ws::Codec::new()
+            .max_size(self.max_frame_size)
+            .max_message_size(self.max_message_size)
+            .accept_unmasked_frames(self.accept_unmasked)
+            .utf8_policy(self.utf8_policy)
+    }
+
+    fn select_protocol(&self, req: &RequestHead) -> Option<&str> {
+        let requested: Vec<_> = req
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .filter_map(|hdr| hdr.to_str().ok())
+            .flat_map(|hdr| hdr.split(','))
+            .map(|s| s.trim())
+            .collect();
+
+        self.protocols
+            .iter()
+            .find(|p| requested.contains(&p.as_str()))
+            .map(|p| p.as_str())
+    }
+
+    /// Do websocket handshake and start websockets service.
+    pub async fn start<T, F, Err>(
+        &self,
+        req: HttpRequest,
+        factory: F,
+    ) -> Result<HttpResponse, Err>
+    where
+        T: ServiceFactory<Frame, WsSink, Response = Option<Message>> + 'static,
+        T::Error: fmt::Debug,
+        F: IntoServiceFactory<T, Frame, WsSink>,
+        Err: From<T::InitError> + From<HandshakeError>,
+    {
+        let inner_factory = factory.into_factory().map_err(WsError::Service);
+        let idle_timeout = time::Duration::from(self.idle_timeout);
+
+        let factory = fn_factory_with_config(move |sink: WsSink| {
+            let fut = inner_factory.new_service(sink.clone());
+
+            async move {
+                let srv = fut.await?;
+                Ok::<_, T::InitError>(apply_fn(srv, move |req, srv| match req {
+                    DispatchItem::Item(item) => {
+                        let is_close = matches!(item, Frame::Close(_));
+                        let s = sink.clone();
+                        let fut = srv.call(item);
+                        Either::Left(async move {
+                            let result = fut.await;
+                            if is_close {
+                                rt::spawn(async move { s.io().close() });
+                            } else if matches!(result, Ok(Some(_)))
+                                && !idle_timeout.is_zero()
+                            {
+                                s.io().start_keepalive_timer(idle_timeout);
+                            }
+                            result
+                        })
+                    }
+                    DispatchItem::WBackPressureEnabled
+                    | DispatchItem::WBackPressureDisabled => Either::Right(Ready::Ok(None)),
+                    DispatchItem::KeepAliveTimeout => {
+                        log::trace!("Ws connection is idle, closing");
+                        Either::Right(Ready::Ok(Some(Message::Close(Some(
+                            CloseCode::Normal.into(),
+                        )))))
+                    }
+                    DispatchItem::DecoderError(ProtocolError::InvalidUtf8) => {
+                        log::trace!("Ws text message is not valid utf-8, closing");
+                        Either::Right(Ready::Ok(Some(Message::Close(Some(
+                            CloseCode::Invalid.into(),
+                        )))))
+                    }
+                    DispatchItem::DecoderError(e) | DispatchItem::EncoderError(e) => {
+                        Either::Right(Ready::Err(WsError::Protocol(e)))
+                    }
+                    DispatchItem::Disconnect(e) => {
+                        Either::Right(Ready::Err(WsError::Disconnected(e)))
+                    }
+                }))
+            }
+        });
+
+        self.start_with(req, factory).await
+    }
+
+    /// Do websocket handshake and start websockets service.
+    pub async fn start_with<T, F, Err>(
+        &self,
+        req: HttpRequest,
+        factory: F,
+    ) -> Result<HttpResponse, Err>
+    where
+        T: ServiceFactory<DispatchItem<ws::Codec>, WsSink, Response = Option<Message>>
+            + 'static,
+        T::Error: fmt::Debug,
+        F: IntoServiceFactory<T, DispatchItem<ws::Codec>, WsSink>,
+        Err: From<T::InitError> + From<HandshakeError>,
+    {
+        let io = self.handshake(&req)?;
+
+        // create sink
+        let codec = self.codec();
+        let sink = WsSink::with_idle_timeout(
+            io.get_ref(),
+            codec.clone(),
+            self.idle_timeout.into(),
+        );
+
+        // create ws service
+        let srv = factory.into_factory().new_service(sink).await?;
+
+        // start websockets service dispatcher
+        let disp =
+            crate::io::Dispatcher::new(io, codec, srv).keepalive_timeout(self.idle_timeout);
+        rt::spawn(async move {
+            let res = disp.await;
+            log::trace!("Ws handler is terminated: {:?}", res);
+        });
+
+        Ok(HttpResponse::new(StatusCode::OK))
+    }
+
+    /// Do websocket handshake and start websockets session.
+    pub async fn start_session<F, R, Err>(
+        &self,
+        req: HttpRequest,
+        f: F,
+    ) -> Result<HttpResponse, Err>
+    where
+        F: FnOnce(WsSession) -> R,
+        R: Future<Output = ()> + 'static,
+        Err: From<HandshakeError>,
+    {
+        let io = self.handshake(&req)?;
+        WsSession::start(
+            io,
+            self.codec(),
+            self.idle_timeout.into(),
+            self.max_write_buffer,
+            f,
+        );
+
+        Ok(HttpResponse::new(StatusCode::OK))
+    }
+
+    fn handshake(&self, req: &HttpRequest) -> Result<IoBoxed, HandshakeError> {
+        log::trace!("Start ws handshake verification for {:?}", req.path());
+
+        // ws handshake
+        let mut res = handshake(req.head())?;
+        if let Some(protocol) = self.select_protocol(req.head()) {
+            res.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        let res = res.finish().into_parts().0;
+
+        // extract io
+        let item = req
+            .head()
+            .take_io()
+            .ok_or(HandshakeError::NoWebsocketUpgrade)?;
+        let io = item.0;
+        let codec = item.1;
+
+        io.encode(h1::Message::Item((res, BodySize::Empty)), &codec)
+            .map_err(|_| HandshakeError::NoWebsocketUpgrade)?;
+        log::trace!("Ws handshake verification completed for {:?}", req.path());
+
+        Ok(io)
+    }
 }
@@ -1,4 +1,6 @@
-use std::cell::Cell;
+use std::{borrow::Cow, cell::Cell, cmp, str};
+
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::codec::{Decoder, Encoder};
 use crate::util::{ByteString, Bytes, BytesMut};
@@ -27,7 +29,7 @@ pub enum Message {
 /// WebSocket frame
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Frame {
-    /// Text frame, codec does not verify utf8 encoding
+    /// Text frame, utf8 encoding is handled according to codec's `Utf8Policy`
     Text(Bytes),
     /// Binary frame
     Binary(Bytes),
@@ -41,6 +43,56 @@ pub enum Frame {
     Close(Option<CloseReason>),
 }
 
+impl Message {
+    /// Create text message with json encoded value
+    pub fn json<T: Serialize>(val: &T) -> Result<Message, serde_json::Error> {
+        Ok(Message::Text(ByteString::from(serde_json::to_string(val)?)))
+    }
+
+    /// Create binary message
+    pub fn binary_from<T: AsRef<[u8]>>(data: T) -> Message {
+        Message::Binary(Bytes::copy_from_slice(data.as_ref()))
+    }
+
+    /// Deserialize json encoded payload of text or binary message
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        match self {
+            Message::Text(txt) => serde_json::from_str(txt),
+            Message::Binary(bin) => serde_json::from_slice(bin),
+            _ => Err(serde::de::Error::custom("Not a text or binary message")),
+        }
+    }
+}
+
+impl Frame {
+    /// Deserialize json encoded payload of text or binary frame
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        match self {
+            Frame::Text(data) | Frame::Binary(data) => serde_json::from_slice(data),
+            _ => Err(serde::de::Error::custom("Not a text or binary frame")),
+        }
+    }
+}
+
+/// Handling of text messages with invalid utf-8 encoding
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Fail with `ProtocolError::InvalidUtf8` error, connection is closed
+    /// with `CloseCode::Invalid` (1007) code
+    Fail,
+    /// Replace invalid sequences with `U+FFFD REPLACEMENT CHARACTER`
+    ConvertLossy,
+    /// Do not verify text messages
+    Passthrough,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Utf8Policy {
+    fn default() -> Self {
+        Utf8Policy::Fail
+    }
+}
+
 /// WebSocket continuation item
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Item {
@@ -55,6 +107,17 @@ pub enum Item {
 pub struct Codec {
     flags: Cell<Flags>,
     max_size: usize,
+    max_message_size: usize,
+    message_size: Cell<usize>,
+    utf8_policy: Utf8Policy,
+    utf8_tail: Cell<Utf8Tail>,
+}
+
+/// Incomplete utf-8 sequence at the end of text fragment
+#[derive(Debug, Default, Clone, Copy)]
+struct Utf8Tail {
+    buf: [u8; 4],
+    len: u8,
 }
 
 bitflags::bitflags! {
@@ -63,6 +126,8 @@ bitflags::bitflags! {
         const R_CONTINUATION = 0b0000_0010;
         const W_CONTINUATION = 0b0000_0100;
         const CLOSED         = 0b0000_1000;
+        const UNMASKED       = 0b0001_0000;
+        const R_TEXT         = 0b0010_0000;
     }
 }
 
@@ -71,6 +136,10 @@ impl Codec {
     pub fn new() -> Codec {
         Codec {
             max_size: 65_536,
+            max_message_size: usize::MAX,
+            message_size: Cell::new(0),
+            utf8_policy: Utf8Policy::Fail,
+            utf8_tail: Cell::new(Utf8Tail::default()),
             flags: Cell::new(Flags::SERVER),
         }
     }
@@ -83,6 +152,34 @@ impl Codec {
         self
     }
 
+    /// Set max message size
+    ///
+    /// Limits total size of fragmented message. By default size is not limited.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Set utf-8 validation policy for text messages.
+    ///
+    /// By default invalid text message is a protocol error.
+    pub fn utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.utf8_policy = policy;
+        self
+    }
+
+    /// Accept unmasked frames in server mode.
+    ///
+    /// By default unmasked frames from client are rejected.
+    pub fn accept_unmasked_frames(self, accept: bool) -> Self {
+        if accept {
+            self.insert_flags(Flags::UNMASKED);
+        } else {
+            self.remove_flags(Flags::UNMASKED);
+        }
+        self
+    }
+
     /// Set decoder to client mode.
     ///
     /// By default decoder works in server mode.
@@ -96,6 +193,107 @@ impl Codec {
         self.flags.get().contains(Flags::CLOSED)
     }
 
+    fn check_message_size(&self, size: usize, first: bool) -> Result<(), ProtocolError> {
+        let size = if first {
+            size
+        } else {
+            self.message_size.get().saturating_add(size)
+        };
+        if size > self.max_message_size {
+            Err(ProtocolError::Overflow)
+        } else {
+            self.message_size.set(size);
+            Ok(())
+        }
+    }
+
+    /// Handle utf-8 encoding of the unfragmented text message
+    fn text_payload(&self, payload: Bytes) -> Result<Bytes, ProtocolError> {
+        match self.utf8_policy {
+            Utf8Policy::Passthrough => Ok(payload),
+            Utf8Policy::Fail => {
+                if str::from_utf8(&payload).is_ok() {
+                    Ok(payload)
+                } else {
+                    Err(ProtocolError::InvalidUtf8)
+                }
+            }
+            Utf8Policy::ConvertLossy => Ok(convert_lossy(payload)),
+        }
+    }
+
+    /// Handle utf-8 encoding of the text message fragment
+    fn text_fragment(&self, payload: Bytes, fin: bool) -> Result<Bytes, ProtocolError> {
+        match self.utf8_policy {
+            Utf8Policy::Passthrough => Ok(payload),
+            Utf8Policy::Fail => {
+                self.check_utf8(&payload, fin)?;
+                Ok(payload)
+            }
+            Utf8Policy::ConvertLossy => {
+                let tail = self.utf8_tail.take();
+                let payload = if tail.len > 0 {
+                    let mut buf =
+                        BytesMut::with_capacity(tail.len as usize + payload.len());
+                    buf.extend_from_slice(&tail.buf[..tail.len as usize]);
+                    buf.extend_from_slice(&payload);
+                    buf.freeze()
+                } else {
+                    payload
+                };
+
+                // keep incomplete sequence until next fragment
+                let n = if fin { 0 } else { incomplete_tail(&payload) };
+                let split = payload.len() - n;
+                let mut tail = Utf8Tail::default();
+                tail.buf[..n].copy_from_slice(&payload[split..]);
+                tail.len = n as u8;
+                self.utf8_tail.set(tail);
+
+                Ok(convert_lossy(payload.slice(..split)))
+            }
+        }
+    }
+
+    /// Validate utf-8 encoding of the text message fragment.
+    ///
+    /// Multi-byte sequence could be split between fragments, incomplete
+    /// sequence at the end of fragment is kept until next fragment.
+    fn check_utf8(&self, data: &[u8], fin: bool) -> Result<(), ProtocolError> {
+        let mut tail = self.utf8_tail.get();
+        let mut data = data;
+
+        // complete sequence from previous fragment
+        if tail.len > 0 {
+            let len = tail.len as usize;
+            let n = cmp::min(utf8_width(tail.buf[0]) - len, data.len());
+            tail.buf[len..len + n].copy_from_slice(&data[..n]);
+            tail.len += n as u8;
+            data = &data[n..];
+
+            match str::from_utf8(&tail.buf[..tail.len as usize]) {
+                Ok(_) => tail.len = 0,
+                Err(e) if e.error_len().is_none() && !fin => {
+                    self.utf8_tail.set(tail);
+                    return Ok(());
+                }
+                Err(_) => return Err(ProtocolError::InvalidUtf8),
+            }
+        }
+
+        match str::from_utf8(data) {
+            Ok(_) => (),
+            Err(e) if e.error_len().is_none() && !fin => {
+                let rest = &data[e.valid_up_to()..];
+                tail.buf[..rest.len()].copy_from_slice(rest);
+                tail.len = rest.len() as u8;
+            }
+            Err(_) => return Err(ProtocolError::InvalidUtf8),
+        }
+        self.utf8_tail.set(tail);
+        Ok(())
+    }
+
     fn insert_flags(&self, f: Flags) {
         let mut flags = self.flags.get();
         flags.insert(f);
@@ -109,6 +307,39 @@ impl Codec {
     }
 }
 
+/// Length of utf-8 sequence for the leading byte
+fn utf8_width(b: u8) -> usize {
+    match b {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    }
+}
+
+/// Length of incomplete utf-8 sequence at the end of the data
+fn incomplete_tail(data: &[u8]) -> usize {
+    for n in 1..=cmp::min(3, data.len()) {
+        let pos = data.len() - n;
+        // leading byte of the last sequence
+        if data[pos] & 0xC0 != 0x80 {
+            return match str::from_utf8(&data[pos..]) {
+                Err(e) if e.error_len().is_none() => n,
+                _ => 0,
+            };
+        }
+    }
+    0
+}
+
+fn convert_lossy(data: Bytes) -> Bytes {
+    match String::from_utf8_lossy(&data) {
+        Cow::Borrowed(_) => None,
+        Cow::Owned(s) => Some(Bytes::from(s)),
+    }
+    .unwrap_or(data)
+}
+
 impl Default for Codec {
     fn default() -> Self {
         Self::new()
@@ -220,16 +451,33 @@ impl Decoder for Codec {
     type Error = ProtocolError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match Parser::parse(src, self.flags.get().contains(Flags::SERVER), self.max_size) {
+        let flags = self.flags.get();
+        match Parser::parse_frame(
+            src,
+            flags.contains(Flags::SERVER),
+            flags.contains(Flags::UNMASKED),
+            self.max_size,
+        ) {
             Ok(Some((finished, opcode, payload))) => {
+                // check message size
+                let size = payload.as_ref().map(|p| p.len()).unwrap_or(0);
+                match opcode {
+                    OpCode::Text | OpCode::Binary => self.check_message_size(size, true)?,
+                    OpCode::Continue => self.check_message_size(size, false)?,
+                    _ => (),
+                }
+
                 // handle continuation
                 if !finished {
                     match opcode {
                         OpCode::Continue => {
-                            if self.flags.get().contains(Flags::R_CONTINUATION) {
-                                Ok(Some(Frame::Continuation(Item::Continue(
-                                    payload.unwrap_or_else(Bytes::new),
-                                ))))
+                            let flags = self.flags.get();
+                            if flags.contains(Flags::R_CONTINUATION) {
+                                let mut payload = payload.unwrap_or_else(Bytes::new);
+                                if flags.contains(Flags::R_TEXT) {
+                                    payload = self.text_fragment(payload, false)?;
+                                }
+                                Ok(Some(Frame::Continuation(Item::Continue(payload))))
                             } else {
                                 Err(ProtocolError::ContinuationNotStarted)
                             }
@@ -246,10 +494,13 @@ impl Decoder for Codec {
                         }
                         OpCode::Text => {
                             if !self.flags.get().contains(Flags::R_CONTINUATION) {
-                                self.insert_flags(Flags::R_CONTINUATION);
-                                Ok(Some(Frame::Continuation(Item::FirstText(
+                                self.utf8_tail.set(Utf8Tail::default());
+                                let payload = self.text_fragment(
                                     payload.unwrap_or_else(Bytes::new),
-                                ))))
+                                    false,
+                                )?;
+                                self.insert_flags(Flags::R_CONTINUATION | Flags::R_TEXT);
+                                Ok(Some(Frame::Continuation(Item::FirstText(payload))))
                             } else {
                                 Err(ProtocolError::ContinuationStarted)
                             }
@@ -269,11 +520,14 @@ impl Decoder for Codec {
                 } else {
                     match opcode {
                         OpCode::Continue => {
-                            if self.flags.get().contains(Flags::R_CONTINUATION) {
-                                self.remove_flags(Flags::R_CONTINUATION);
-                                Ok(Some(Frame::Continuation(Item::Last(
-                                    payload.unwrap_or_else(Bytes::new),
-                                ))))
+                            let flags = self.flags.get();
+                            if flags.contains(Flags::R_CONTINUATION) {
+                                let mut payload = payload.unwrap_or_else(Bytes::new);
+                                if flags.contains(Flags::R_TEXT) {
+                                    payload = self.text_fragment(payload, true)?;
+                                }
+                                self.remove_flags(Flags::R_CONTINUATION | Flags::R_TEXT);
+                                Ok(Some(Frame::Continuation(Item::Last(payload))))
                             } else {
                                 Err(ProtocolError::ContinuationNotStarted)
                             }
@@ -297,7 +551,8 @@ impl Decoder for Codec {
                             Ok(Some(Frame::Binary(payload.unwrap_or_else(Bytes::new))))
                         }
                         OpCode::Text => {
-                            Ok(Some(Frame::Text(payload.unwrap_or_else(Bytes::new))))
+                            let payload = payload.unwrap_or_else(Bytes::new);
+                            Ok(Some(Frame::Text(self.text_payload(payload)?)))
                         }
                     }
                 }
@@ -307,3 +562,263 @@ impl Decoder for Codec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(codec: &Codec, msg: Message) -> BytesMut {
+        let mut buf = BytesMut::new();
+        codec.encode(msg, &mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_max_message_size() {
+        let client = Codec::new().client_mode();
+        let codec = Codec::new().max_size(16).max_message_size(24);
+
+        let mut buf = encode(&client, Message::Binary(Bytes::from_static(&[0; 16])));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = encode(&client, Message::Binary(Bytes::from_static(&[0; 17])));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+
+        let first = Item::FirstBinary(Bytes::from_static(&[0; 16]));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let last = Item::Last(Bytes::from_static(&[0; 16]));
+        let mut buf = encode(&client, Message::Continuation(last));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+
+        // new message resets size
+        let codec = Codec::new().max_size(16).max_message_size(24);
+        let first = Item::FirstText(Bytes::from_static(&[0; 12]));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let last = Item::Last(Bytes::from_static(&[0; 12]));
+        let mut buf = encode(&client, Message::Continuation(last));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let mut buf = encode(&client, Message::Text("0123456789".into()));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_utf8() {
+        let client = Codec::new().client_mode();
+        let codec = Codec::new();
+
+        let mut buf = encode(&client, Message::Text("привет".into()));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = encode(&client, Message::Binary(Bytes::from_static(b"\xff")));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::new();
+        Parser::write_message(&mut buf, &b"a\xffb"[..], OpCode::Text, true, true);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // multi-byte sequence split between fragments
+        let data = "a€b".as_bytes();
+        let codec = Codec::new();
+        let first = Item::FirstText(Bytes::copy_from_slice(&data[..2]));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let cont = Item::Continue(Bytes::copy_from_slice(&data[2..3]));
+        let mut buf = encode(&client, Message::Continuation(cont));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let last = Item::Last(Bytes::copy_from_slice(&data[3..]));
+        let mut buf = encode(&client, Message::Continuation(last));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        // incomplete sequence at the end of message
+        let first = Item::FirstText(Bytes::copy_from_slice(&data[..2]));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let last = Item::Last(Bytes::copy_from_slice(&data[2..3]));
+        let mut buf = encode(&client, Message::Continuation(last));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // invalid continuation of split sequence
+        let codec = Codec::new();
+        let client = Codec::new().client_mode();
+        let first = Item::FirstText(Bytes::from_static(b"\xe2"));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let cont = Item::Continue(Bytes::from_static(b"a"));
+        let mut buf = encode(&client, Message::Continuation(cont));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // binary continuation is not verified
+        let codec = Codec::new();
+        let client = Codec::new().client_mode();
+        let first = Item::FirstBinary(Bytes::from_static(b"\xe2"));
+        let mut buf = encode(&client, Message::Continuation(first));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let last = Item::Last(Bytes::from_static(b"\xff"));
+        let mut buf = encode(&client, Message::Continuation(last));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+
+    /// Decode text message fragmented into chunks
+    fn decode_fragments(codec: &Codec, chunks: &[&[u8]]) -> Result<Vec<u8>, ProtocolError> {
+        let client = Codec::new().client_mode();
+        let mut result = Vec::new();
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let data = Bytes::copy_from_slice(chunk);
+            let item = if idx == 0 {
+                Item::FirstText(data)
+            } else if idx == chunks.len() - 1 {
+                Item::Last(data)
+            } else {
+                Item::Continue(data)
+            };
+            let mut buf = encode(&client, Message::Continuation(item));
+            match codec.decode(&mut buf)?.unwrap() {
+                Frame::Continuation(Item::FirstText(data))
+                | Frame::Continuation(Item::Continue(data))
+                | Frame::Continuation(Item::Last(data)) => result.extend_from_slice(&data),
+                frame => panic!("Unexpected frame: {:?}", frame),
+            }
+        }
+        Ok(result)
+    }
+
+    #[test]
+    fn test_utf8_fragments() {
+        // codepoints split at every possible boundary
+        let text = "κόσμε-€-𐍈".as_bytes();
+        for i in 1..text.len() - 1 {
+            for j in i + 1..text.len() {
+                let chunks = [&text[..i], &text[i..j], &text[j..]];
+                let codec = Codec::new();
+                assert_eq!(decode_fragments(&codec, &chunks).unwrap(), text);
+                let codec = Codec::new().utf8_policy(Utf8Policy::ConvertLossy);
+                assert_eq!(decode_fragments(&codec, &chunks).unwrap(), text);
+            }
+        }
+
+        // invalid sequence split between fragments
+        let chunks: [&[u8]; 3] = [b"a\xf0\x90", b"\x80", b"\xff"];
+        let codec = Codec::new();
+        assert!(matches!(
+            decode_fragments(&codec, &chunks),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+        let codec = Codec::new().utf8_policy(Utf8Policy::ConvertLossy);
+        assert_eq!(
+            decode_fragments(&codec, &chunks).unwrap(),
+            "a\u{FFFD}\u{FFFD}".as_bytes()
+        );
+        let codec = Codec::new().utf8_policy(Utf8Policy::Passthrough);
+        assert_eq!(
+            decode_fragments(&codec, &chunks).unwrap(),
+            b"a\xf0\x90\x80\xff"
+        );
+    }
+
+    #[test]
+    fn test_utf8_policy() {
+        let frame = || {
+            let mut buf = BytesMut::new();
+            Parser::write_message(&mut buf, &b"a\xffb"[..], OpCode::Text, true, true);
+            buf
+        };
+
+        let codec = Codec::new().utf8_policy(Utf8Policy::Fail);
+        assert!(matches!(
+            codec.decode(&mut frame()),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        let codec = Codec::new().utf8_policy(Utf8Policy::ConvertLossy);
+        assert_eq!(
+            codec.decode(&mut frame()).unwrap().unwrap(),
+            Frame::Text(Bytes::from("a\u{FFFD}b"))
+        );
+
+        let codec = Codec::new().utf8_policy(Utf8Policy::Passthrough);
+        assert_eq!(
+            codec.decode(&mut frame()).unwrap().unwrap(),
+            Frame::Text(Bytes::from_static(b"a\xffb"))
+        );
+
+        // incomplete sequence at the end of fragmented message
+        let chunks: [&[u8]; 2] = [b"ab", b"c\xe2\x82"];
+        let codec = Codec::new().utf8_policy(Utf8Policy::ConvertLossy);
+        assert_eq!(
+            decode_fragments(&codec, &chunks).unwrap(),
+            "abc\u{FFFD}".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Msg {
+            id: u32,
+            text: String,
+        }
+
+        let val = Msg {
+            id: 1,
+            text: "hello".to_string(),
+        };
+        let msg = Message::json(&val).unwrap();
+        assert_eq!(msg, Message::Text(r#"{"id":1,"text":"hello"}"#.into()));
+        assert_eq!(msg.parse_json::<Msg>().unwrap(), val);
+
+        let msg = Message::binary_from(r#"{"id":1,"text":"hello"}"#);
+        assert!(matches!(msg, Message::Binary(_)));
+        assert_eq!(msg.parse_json::<Msg>().unwrap(), val);
+        assert!(Message::binary_from(vec![1, 2, 3])
+            .parse_json::<Msg>()
+            .is_err());
+        assert!(Message::Ping(Bytes::new()).parse_json::<Msg>().is_err());
+
+        // received frame
+        let client = Codec::new().client_mode();
+        let mut buf = encode(&client, Message::json(&val).unwrap());
+        let frame = Codec::new().decode(&mut buf).unwrap().unwrap();
+        assert_eq!(frame.parse_json::<Msg>().unwrap(), val);
+        assert!(Frame::Close(None).parse_json::<Msg>().is_err());
+    }
+
+    #[test]
+    fn test_unmasked_frames() {
+        let server = Codec::new();
+        let mut buf = encode(&server, Message::Text("text".into()));
+        assert!(matches!(
+            Codec::new().decode(&mut buf.clone()),
+            Err(ProtocolError::UnmaskedFrame)
+        ));
+
+        let codec = Codec::new().accept_unmasked_frames(true);
+        assert_eq!(
+            codec.decode(&mut buf).unwrap().unwrap(),
+            Frame::Text(Bytes::from_static(b"text"))
+        );
+
+        let client = Codec::new().client_mode();
+        let mut buf = encode(&client, Message::Text("text".into()));
+        assert_eq!(
+            codec.decode(&mut buf).unwrap().unwrap(),
+            Frame::Text(Bytes::from_static(b"text"))
+        );
+    }
+}
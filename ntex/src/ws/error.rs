@@ -7,7 +7,7 @@ use crate::http::error::{HttpError, ParseError, ResponseError};
 use crate::http::{header::HeaderValue, header::ALLOW, Response, StatusCode};
 use crate::{connect::ConnectError, util::Either};
 
-use super::OpCode;
+use super::{CloseCode, OpCode};
 
 /// Websocket service errors
 #[derive(Error, Debug)]
@@ -55,6 +55,19 @@ pub enum ProtocolError {
     /// Unknown continuation fragment
     #[error("Unknown continuation fragment {0}")]
     ContinuationFragment(OpCode),
+    /// Text message is not valid utf-8
+    #[error("Text message is not valid utf-8")]
+    InvalidUtf8,
+}
+
+impl ProtocolError {
+    /// Close code that should be sent to the peer
+    pub fn close_code(&self) -> CloseCode {
+        match self {
+            ProtocolError::InvalidUtf8 => CloseCode::Invalid,
+            _ => CloseCode::Protocol,
+        }
+    }
 }
 
 /// Websocket client error
@@ -15,6 +15,7 @@ impl Parser {
     fn parse_metadata(
         src: &[u8],
         server: bool,
+        unmasked: bool,
         max_size: usize,
     ) -> Result<Option<(usize, bool, OpCode, usize, Option<u32>)>, ProtocolError> {
         let chunk_len = src.len();
@@ -30,7 +31,7 @@ impl Parser {
 
         // check masking
         let masked = second & 0x80 != 0;
-        if !masked && server {
+        if !masked && server && !unmasked {
             return Err(ProtocolError::UnmaskedFrame);
         } else if masked && !server {
             return Err(ProtocolError::MaskedFrame);
@@ -72,7 +73,7 @@ impl Parser {
             return Err(ProtocolError::Overflow);
         }
 
-        let mask = if server {
+        let mask = if masked {
             if chunk_len < idx + 4 {
                 return Ok(None);
             }
@@ -92,10 +93,21 @@ impl Parser {
         src: &mut BytesMut,
         server: bool,
         max_size: usize,
+    ) -> Result<Option<(bool, OpCode, Option<Bytes>)>, ProtocolError> {
+        Parser::parse_frame(src, server, false, max_size)
+    }
+
+    /// Parse the input stream into a frame, optionally accept unmasked
+    /// frames in server mode.
+    pub(super) fn parse_frame(
+        src: &mut BytesMut,
+        server: bool,
+        unmasked: bool,
+        max_size: usize,
     ) -> Result<Option<(bool, OpCode, Option<Bytes>)>, ProtocolError> {
         // try to parse ws frame metadata
         let (idx, finished, opcode, length, mask) =
-            match Parser::parse_metadata(src, server, max_size)? {
+            match Parser::parse_metadata(src, server, unmasked, max_size)? {
                 None => return Ok(None),
                 Some(res) => res,
             };
@@ -0,0 +1,293 @@
+use std::task::{Context, Poll};
+use std::{cell::Cell, cell::RefCell, collections::VecDeque, fmt, future::Future};
+use std::{io, pin::Pin, rc::Rc, time::Duration};
+
+use crate::channel::condition::Condition;
+use crate::io::{IoBoxed, IoRef, RecvError};
+use crate::task::LocalWaker;
+use crate::util::{poll_fn, ByteString, Bytes, Stream};
+use crate::{rt, ws};
+
+use super::error::ProtocolError;
+use super::{CloseCode, CloseReason, Frame, Message};
+
+/// Max number of received but not yet consumed frames
+const INCOMING_SIZE: usize = 16;
+
+/// Websocket session.
+///
+/// Session handle could be cloned and moved to background tasks, all
+/// handles share the same connection. Sending methods wait until
+/// connection's write buffer is flushed if write buffer size exceeds
+/// configured high-water mark. Incoming frames are available via `recv()`
+/// method or via `Stream` implementation, incoming frames should be consumed
+/// by one task. Ping frames are answered automatically.
+///
+/// Connection is closed when all session handles are dropped.
+#[derive(Clone)]
+pub struct WsSession(Rc<Handle>);
+
+struct Handle(Rc<Inner>);
+
+struct Inner {
+    io: IoRef,
+    codec: ws::Codec,
+    idle_timeout: Duration,
+    max_buffer: usize,
+    eof: Cell<bool>,
+    flush: Cell<bool>,
+    incoming: RefCell<VecDeque<Result<Frame, ProtocolError>>>,
+    rx_task: LocalWaker,
+    driver_task: LocalWaker,
+    write_ready: Condition,
+}
+
+impl WsSession {
+    /// Start websocket session.
+    ///
+    /// Session driver and `f` future are spawned to current runtime.
+    pub(crate) fn start<F, R>(
+        io: IoBoxed,
+        codec: ws::Codec,
+        idle_timeout: Duration,
+        max_buffer: usize,
+        f: F,
+    ) where
+        F: FnOnce(WsSession) -> R,
+        R: Future<Output = ()> + 'static,
+    {
+        let inner = Rc::new(Inner {
+            codec,
+            idle_timeout,
+            max_buffer,
+            io: io.get_ref(),
+            eof: Cell::new(false),
+            flush: Cell::new(false),
+            incoming: RefCell::new(VecDeque::with_capacity(INCOMING_SIZE)),
+            rx_task: LocalWaker::new(),
+            driver_task: LocalWaker::new(),
+            write_ready: Condition::new(),
+        });
+        if idle_timeout.is_zero() {
+            inner.io.stop_keepalive_timer();
+        } else {
+            inner.io.start_keepalive_timer(idle_timeout);
+        }
+
+        rt::spawn(Driver {
+            io,
+            inner: inner.clone(),
+        });
+        rt::spawn(f(WsSession(Rc::new(Handle(inner)))));
+    }
+
+    /// Io reference
+    pub fn io(&self) -> &IoRef {
+        &self.0 .0.io
+    }
+
+    /// Send text message to the peer.
+    pub async fn text<T: Into<ByteString>>(&self, text: T) -> io::Result<()> {
+        self.send(Message::Text(text.into())).await
+    }
+
+    /// Send binary message to the peer.
+    pub async fn binary<T: Into<Bytes>>(&self, data: T) -> io::Result<()> {
+        self.send(Message::Binary(data.into())).await
+    }
+
+    /// Send ping message to the peer.
+    pub async fn ping<T: Into<Bytes>>(&self, data: T) -> io::Result<()> {
+        self.send(Message::Ping(data.into())).await
+    }
+
+    /// Send close message and close connection.
+    pub async fn close(&self, reason: Option<CloseReason>) -> io::Result<()> {
+        self.send(Message::Close(reason)).await
+    }
+
+    /// Send message to the peer.
+    ///
+    /// Waits until write buffer is flushed if its size exceeds high-water mark.
+    /// Returns error if connection is closed.
+    pub async fn send(&self, msg: Message) -> io::Result<()> {
+        let inner = &self.0 .0;
+
+        loop {
+            if inner.io.is_closed() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "Websocket connection is closed",
+                ));
+            }
+            if inner.io.with_write_buf(|buf| buf.len())? < inner.max_buffer {
+                break;
+            }
+
+            // wait until write buffer is flushed
+            let waiter = inner.write_ready.wait();
+            inner.flush.set(true);
+            inner.driver_task.wake();
+            waiter.await;
+        }
+
+        let is_close = matches!(msg, Message::Close(_));
+        inner
+            .io
+            .encode(msg, &inner.codec)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        if is_close {
+            inner.io.close();
+        } else if !inner.idle_timeout.is_zero() {
+            inner.io.start_keepalive_timer(inner.idle_timeout);
+        }
+        Ok(())
+    }
+
+    /// Receive next frame from the peer.
+    ///
+    /// Returns `None` if connection is closed.
+    pub async fn recv(&self) -> Option<Result<Frame, ProtocolError>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Poll for next frame from the peer.
+    pub fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame, ProtocolError>>> {
+        let inner = &self.0 .0;
+
+        if let Some(item) = inner.incoming.borrow_mut().pop_front() {
+            inner.driver_task.wake();
+            Poll::Ready(Some(item))
+        } else if inner.eof.get() {
+            Poll::Ready(None)
+        } else {
+            inner.rx_task.register(cx.waker());
+            Poll::Pending
+        }
+    }
+}
+
+impl Stream for WsSession {
+    type Item = Result<Frame, ProtocolError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv(cx)
+    }
+}
+
+impl fmt::Debug for WsSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsSession")
+            .field("max_buffer", &self.0 .0.max_buffer)
+            .field("closed", &self.0 .0.io.is_closed())
+            .finish()
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let inner = &self.0;
+        if !inner.io.is_closed() {
+            log::trace!("All ws session handles are dropped, closing connection");
+            if !inner.codec.is_closed() {
+                let _ = inner
+                    .io
+                    .encode(Message::Close(Some(CloseCode::Normal.into())), &inner.codec);
+            }
+            inner.io.close();
+        }
+    }
+}
+
+/// Reads incoming frames and wakes up writers
+struct Driver {
+    io: IoBoxed,
+    inner: Rc<Inner>,
+}
+
+impl Driver {
+    fn close(&self, reason: Option<CloseReason>) {
+        if !self.inner.codec.is_closed() {
+            let _ = self.io.encode(Message::Close(reason), &self.inner.codec);
+        }
+        self.io.close();
+    }
+
+    fn eof(&self) {
+        self.inner.eof.set(true);
+        self.inner.rx_task.wake();
+        self.io.close();
+    }
+}
+
+impl Future for Driver {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let inner = &this.inner;
+        inner.driver_task.register(cx.waker());
+
+        // wake up writers
+        if inner.flush.get() && this.io.poll_flush(cx, true).is_ready() {
+            inner.flush.set(false);
+            inner.write_ready.notify();
+        }
+
+        // read incoming frames
+        while !inner.eof.get() && inner.incoming.borrow().len() < INCOMING_SIZE {
+            match this.io.poll_recv(&inner.codec, cx) {
+                Poll::Ready(Ok(frame)) => {
+                    if !inner.idle_timeout.is_zero() {
+                        this.io.start_keepalive_timer(inner.idle_timeout);
+                    }
+                    match frame {
+                        Frame::Ping(ref data) => {
+                            let _ =
+                                this.io.encode(Message::Pong(data.clone()), &inner.codec);
+                        }
+                        Frame::Close(ref reason) => this.close(reason.clone()),
+                        _ => (),
+                    }
+                    inner.incoming.borrow_mut().push_back(Ok(frame));
+                    inner.rx_task.wake();
+                }
+                Poll::Ready(Err(RecvError::KeepAlive)) => {
+                    log::trace!("Ws session is idle, closing");
+                    this.close(Some(CloseCode::Normal.into()));
+                }
+                Poll::Ready(Err(RecvError::WriteBackpressure)) => {
+                    if this.io.poll_flush(cx, false).is_pending() {
+                        return Poll::Pending;
+                    }
+                }
+                Poll::Ready(Err(RecvError::Decoder(err))) => {
+                    log::trace!("Ws protocol error: {:?}", err);
+                    this.close(Some(err.close_code().into()));
+                    inner.incoming.borrow_mut().push_back(Err(err));
+                    this.eof();
+                }
+                Poll::Ready(Err(RecvError::Stop))
+                | Poll::Ready(Err(RecvError::PeerGone(_))) => {
+                    log::trace!("Ws session is terminated");
+                    this.eof();
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        if inner.eof.get() {
+            // wait for io shutdown, pending writers get error
+            let res = this.io.poll_shutdown(cx);
+            if res.is_ready() {
+                inner.write_ready.notify();
+            }
+            res.map(|_| ())
+        } else {
+            Poll::Pending
+        }
+    }
+}
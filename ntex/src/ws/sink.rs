@@ -1,4 +1,4 @@
-use std::{future::Future, rc::Rc};
+use std::{future::Future, rc::Rc, time::Duration};
 
 use crate::io::{IoRef, OnDisconnect};
 use crate::ws;
@@ -9,11 +9,26 @@ pub struct WsSink(Rc<WsSinkInner>);
 struct WsSinkInner {
     io: IoRef,
     codec: ws::Codec,
+    idle_timeout: Duration,
 }
 
 impl WsSink {
     pub(crate) fn new(io: IoRef, codec: ws::Codec) -> Self {
-        Self(Rc::new(WsSinkInner { io, codec }))
+        Self::with_idle_timeout(io, codec, Duration::ZERO)
+    }
+
+    /// Create sink that restarts connection's keep-alive timer
+    /// on every sent message.
+    pub(crate) fn with_idle_timeout(
+        io: IoRef,
+        codec: ws::Codec,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self(Rc::new(WsSinkInner {
+            io,
+            codec,
+            idle_timeout,
+        }))
     }
 
     /// Io reference
@@ -37,6 +52,8 @@ impl WsSink {
             inner.io.encode(item, &inner.codec)?;
             if close {
                 inner.io.close();
+            } else if !inner.idle_timeout.is_zero() {
+                inner.io.start_keepalive_timer(inner.idle_timeout);
             }
             Ok(())
         }
@@ -1,13 +1,15 @@
 use std::collections::HashMap;
-use std::io::{Read, Write};
+use std::fs;
+use std::io::{self, Read, Write};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use brotli2::write::BrotliEncoder;
 use coo_kie::Cookie;
 use flate2::{read::GzDecoder, write::GzEncoder, write::ZlibEncoder, Compression};
-use futures_util::stream::once;
+use futures_util::stream::{iter, once};
 use rand::Rng;
+use sha1::{Digest, Sha1};
 
 use ntex::http::client::error::{JsonPayloadError, SendRequestError};
 use ntex::http::client::{Client, Connector};
@@ -236,6 +238,180 @@ async fn test_connection_reuse() {
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
 
+#[ntex::test]
+async fn test_connection_lifetime() {
+    let num = Arc::new(AtomicUsize::new(0));
+    let num2 = num.clone();
+
+    let srv = test_server(move || {
+        let num2 = num2.clone();
+        pipeline_factory(move |io| {
+            num2.fetch_add(1, Ordering::Relaxed);
+            Ready::Ok(io)
+        })
+        .and_then(HttpService::new(map_config(
+            App::new().service(
+                web::resource("/").route(web::to(|| async { HttpResponse::Ok() })),
+            ),
+            |_| AppConfig::default(),
+        )))
+    });
+
+    let client = Client::build()
+        .connector(Connector::default().lifetime(Seconds(0)).finish())
+        .timeout(Seconds(10))
+        .finish();
+
+    // every request opens new connection
+    for _ in 0..3 {
+        let response = client.get(srv.url("/")).send().await.unwrap();
+        assert!(response.status().is_success());
+        assert!(!response.timings().is_reused());
+    }
+    assert_eq!(num.load(Ordering::Relaxed), 3);
+}
+
+#[ntex::test]
+async fn test_response_timings() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(|| async {
+            sleep(Millis(100)).await;
+            HttpResponse::Ok()
+        })))
+    });
+
+    let client = Client::build().timeout(Seconds(10)).finish();
+
+    // new connection
+    let response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let timings = response.timings();
+    assert!(!timings.is_reused());
+    assert!(timings.connect() > std::time::Duration::ZERO);
+    assert!(timings.ttfb() >= std::time::Duration::from_millis(100));
+    assert_eq!(timings.total(), timings.connect() + timings.ttfb());
+
+    // reused connection
+    let response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let timings = response.timings();
+    assert!(timings.is_reused());
+    assert!(timings.ttfb() >= std::time::Duration::from_millis(100));
+}
+
+#[ntex::test]
+async fn test_response_save_to_file() {
+    let chunks: Vec<Bytes> = (0..16u8)
+        .map(|i| Bytes::from(vec![i; 16 * 1024 + i as usize]))
+        .collect();
+    let data: Vec<u8> = chunks.iter().flat_map(|c| c.iter().copied()).collect();
+
+    let srv = test::server(move || {
+        let chunks = chunks.clone();
+        App::new()
+            .service(web::resource("/").route(web::to(move || {
+                let chunks = chunks.clone();
+                async move {
+                    HttpResponse::Ok()
+                        .streaming(iter(chunks.into_iter().map(Ok::<_, io::Error>)))
+                }
+            })))
+            .service(web::resource("/error").route(web::to(|| async {
+                HttpResponse::Ok()
+                    .header(header::CONTENT_ENCODING, "gzip")
+                    .streaming(iter(vec![
+                        Ok::<_, io::Error>(Bytes::from_static(b"not")),
+                        Ok(Bytes::from_static(b"gzip")),
+                    ]))
+            })))
+            .service(web::resource("/pending").route(web::to(|| async {
+                HttpResponse::Ok().streaming(futures_util::StreamExt::chain(
+                    iter(vec![Ok::<_, io::Error>(Bytes::from_static(b"data"))]),
+                    futures_util::stream::pending(),
+                ))
+            })))
+    });
+
+    let dir = std::env::temp_dir().join("ntex-client-save-to-file");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("download.bin");
+
+    // copy to in-memory writer
+    #[cfg(feature = "futures-io")]
+    {
+        let mut response = srv.get("/").send().await.unwrap();
+        let mut buf = Vec::new();
+        let size = response.copy_to(&mut buf).await.unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(buf, data);
+    }
+
+    // save to file
+    let mut response = srv.get("/").send().await.unwrap();
+    let size = response.save_to_file(&path).await.unwrap();
+    assert_eq!(size, data.len() as u64);
+    assert_eq!(Sha1::digest(fs::read(&path).unwrap()), Sha1::digest(&data));
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // failed download does not replace existing file
+    let mut response = srv.get("/error").send().await.unwrap();
+    assert!(response.save_to_file(&path).await.is_err());
+    assert_eq!(fs::read(&path).unwrap(), data);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // dropped future removes temporary file
+    let mut response = srv.get("/pending").send().await.unwrap();
+    let res = ntex::time::timeout(Millis(500), response.save_to_file(&path)).await;
+    assert!(res.is_err());
+    assert_eq!(fs::read(&path).unwrap(), data);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[ntex::test]
+async fn test_client_close() {
+    let closed = Arc::new(AtomicUsize::new(0));
+    let closed2 = closed.clone();
+
+    let srv = test_server(move || {
+        let closed = closed2.clone();
+        pipeline_factory(move |io: ntex::io::Io<_>| {
+            let closed = closed.clone();
+            let on_disconnect = io.on_disconnect();
+            ntex::rt::spawn(async move {
+                on_disconnect.await;
+                closed.fetch_add(1, Ordering::Relaxed);
+            });
+            Ready::Ok(io)
+        })
+        .and_then(HttpService::new(map_config(
+            App::new().service(
+                web::resource("/").route(web::to(|| async { HttpResponse::Ok() })),
+            ),
+            |_| AppConfig::default(),
+        )))
+    });
+
+    let client = Client::build().timeout(Seconds(10)).finish();
+    let response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    drop(response);
+
+    // connection is idle in the pool
+    sleep(Millis(100)).await;
+    assert_eq!(closed.load(Ordering::Relaxed), 0);
+
+    client.close().await;
+    sleep(Millis(100)).await;
+    assert_eq!(closed.load(Ordering::Relaxed), 1);
+
+    // new requests are rejected
+    let res = client.get(srv.url("/")).send().await;
+    assert!(matches!(res, Err(SendRequestError::Connect(_))));
+}
+
 #[ntex::test]
 async fn test_connection_force_close() {
     let num = Arc::new(AtomicUsize::new(0));
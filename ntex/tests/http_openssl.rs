@@ -70,6 +70,89 @@ async fn test_h2() -> io::Result<()> {
     Ok(())
 }
 
+#[ntex::test]
+async fn test_h2_server_header() -> io::Result<()> {
+    let srv = test_server(move || {
+        HttpService::build()
+            .server_header(Some("ntex-test"))
+            .h2(|req: Request| {
+                let res = if req.path() == "/custom" {
+                    Response::Ok().header(header::SERVER, "custom").finish()
+                } else {
+                    Response::Ok().finish()
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    let response = srv.srequest(Method::GET, "/").send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.header(header::SERVER).unwrap(), "ntex-test");
+
+    let response = srv.srequest(Method::GET, "/custom").send().await.unwrap();
+    assert_eq!(response.header(header::SERVER).unwrap(), "custom");
+    Ok(())
+}
+
+#[ntex::test]
+async fn test_h2_default_content_type() -> io::Result<()> {
+    let srv = test_server(move || {
+        HttpService::build()
+            .default_content_type("text/plain")
+            .h2(|req: Request| {
+                let res = match req.path() {
+                    "/json" => Response::Ok().content_type("application/json").body("{}"),
+                    "/empty" => Response::Ok().finish(),
+                    _ => Response::Ok().body("data"),
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    let response = srv.srequest(Method::GET, "/").send().await.unwrap();
+    assert_eq!(response.header(header::CONTENT_TYPE).unwrap(), "text/plain");
+
+    let response = srv.srequest(Method::GET, "/json").send().await.unwrap();
+    assert_eq!(
+        response.header(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+
+    let response = srv.srequest(Method::GET, "/empty").send().await.unwrap();
+    assert!(response.header(header::CONTENT_TYPE).is_none());
+    Ok(())
+}
+
+#[ntex::test]
+async fn test_h2_custom_reason() -> io::Result<()> {
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2(|req: Request| {
+                let res = if req.path() == "/reason" {
+                    Response::Ok().reason("Because").finish()
+                } else {
+                    Response::new(StatusCode::from_u16(599).unwrap())
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    // h2 does not support reason phrases, custom reason is dropped
+    let response = srv.srequest(Method::GET, "/reason").send().await.unwrap();
+    assert_eq!(response.version(), Version::HTTP_2);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = srv.srequest(Method::GET, "/unknown").send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 599);
+    Ok(())
+}
+
 #[ntex::test]
 async fn test_h1() -> io::Result<()> {
     let srv = test_server(move || {
@@ -399,7 +482,10 @@ async fn test_h2_response_http_error_handling() {
 
     // read response
     let bytes = srv.load_body(response).await.unwrap();
-    assert_eq!(bytes, Bytes::from_static(b"failed to parse header value"));
+    assert_eq!(
+        bytes,
+        Bytes::from_static(b"parse error: failed to parse header value")
+    );
 }
 
 #[ntex::test]
@@ -461,6 +547,146 @@ async fn test_h2_client_drop() -> io::Result<()> {
     Ok(())
 }
 
+#[ntex::test]
+async fn test_h2_client_reset() {
+    use std::io::Write;
+    use std::sync::atomic::AtomicBool;
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let canceled = Arc::new(AtomicBool::new(false));
+    let canceled2 = canceled.clone();
+
+    let srv = test_server(move || {
+        let canceled = canceled2.clone();
+        HttpService::build()
+            .h2(move |req: Request| {
+                let canceled = canceled.clone();
+                async move {
+                    // handler future is dropped on stream reset, spawned task is not
+                    let on_disconnect = req.on_disconnect();
+                    ntex::rt::spawn(async move {
+                        on_disconnect.await;
+                        canceled.store(true, Ordering::Relaxed);
+                    });
+                    sleep(Seconds(5)).await;
+                    Ok::<_, io::Error>(Response::Ok().finish())
+                }
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+    // HEADERS frame, GET https://localhost/
+    stream
+        .write_all(b"\0\0\x0e\x01\x05\0\0\0\x01\x82\x87\x84\x41\x09localhost")
+        .unwrap();
+    sleep(Millis(200)).await;
+    assert!(!canceled.load(Ordering::Relaxed));
+
+    // RST_STREAM frame with CANCEL error code
+    stream
+        .write_all(b"\0\0\x04\x03\0\0\0\0\x01\0\0\0\x08")
+        .unwrap();
+    sleep(Millis(200)).await;
+    assert!(canceled.load(Ordering::Relaxed));
+}
+
+#[ntex::test]
+async fn test_h2_early_hints() {
+    use ntex_h2::hpack::{Decoder, Header};
+    use std::io::{Cursor, Read, Write};
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2(|req: Request| async move {
+                let mut headers = header::HeaderMap::new();
+                headers.insert(
+                    header::LINK,
+                    HeaderValue::from_static("</style.css>; rel=preload; as=style"),
+                );
+                req.send_early_hints(&headers)?;
+                req.send_early_hints(&headers)?;
+                Ok::<_, io::Error>(
+                    Response::Ok()
+                        .header(header::LINK, "</style.css>; rel=preload; as=style")
+                        .body("done"),
+                )
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+    tcp.set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+    // HEADERS frame, GET https://localhost/
+    stream
+        .write_all(b"\0\0\x0e\x01\x05\0\0\0\x01\x82\x87\x84\x41\x09localhost")
+        .unwrap();
+
+    // all header blocks must be decoded by the same hpack decoder
+    let mut decoder = Decoder::new(4096);
+    let mut responses = Vec::new();
+    let mut data = Vec::new();
+    let mut buf = [0; 1024];
+    'outer: loop {
+        let n = stream.read(&mut buf).unwrap();
+        assert!(n != 0, "connection is closed");
+        data.extend_from_slice(&buf[..n]);
+
+        while data.len() >= 9 {
+            let len = (data[0] as usize) << 16 | (data[1] as usize) << 8 | data[2] as usize;
+            if data.len() < 9 + len {
+                break;
+            }
+            let frame: Vec<_> = data.drain(..9 + len).collect();
+            match frame[3] {
+                // HEADERS
+                0x1 => {
+                    let mut block = BytesMut::from(&frame[9..]);
+                    let mut status = None;
+                    let mut links = Vec::new();
+                    decoder
+                        .decode(&mut Cursor::new(&mut block), |h| match h {
+                            Header::Status(s) => status = Some(s),
+                            Header::Field { name, value } if name == header::LINK => {
+                                links.push(value)
+                            }
+                            _ => (),
+                        })
+                        .unwrap();
+                    responses.push((status.unwrap(), links));
+                }
+                // DATA with END_STREAM flag
+                0x0 if frame[4] & 0x1 != 0 => break 'outer,
+                _ => (),
+            }
+        }
+    }
+
+    let statuses: Vec<_> = responses.iter().map(|(s, _)| s.as_u16()).collect();
+    assert_eq!(statuses, vec![103, 103, 200]);
+    for (_, links) in responses {
+        assert_eq!(links, vec!["</style.css>; rel=preload; as=style"]);
+    }
+}
+
 #[ntex::test]
 async fn test_ssl_handshake_timeout() {
     use std::io::Read;
@@ -479,6 +705,169 @@ async fn test_ssl_handshake_timeout() {
     assert!(data.is_empty());
 }
 
+#[ntex::test]
+async fn test_h2_idle_timeout() {
+    use std::io::{Read, Write};
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2_idle_timeout(Seconds(1))
+            .h2(|_| async {
+                sleep(Millis(1500)).await;
+                Ok::<_, io::Error>(Response::Ok().finish())
+            })
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    // connection is not closed while stream is active
+    let response = srv.srequest(Method::GET, "/").send().await.unwrap();
+    assert!(response.status().is_success());
+
+    // idle connection gets closed with GOAWAY frame
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+    tcp.set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut data = Vec::new();
+    let mut buf = [0; 1024];
+    while let Ok(n) = stream.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    assert!(start.elapsed() >= std::time::Duration::from_millis(500));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    let mut frames = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 9 {
+        let len = (rest[0] as usize) << 16 | (rest[1] as usize) << 8 | rest[2] as usize;
+        let end = std::cmp::min(9 + len, rest.len());
+        frames.push((rest[3], rest[9..end].to_vec()));
+        rest = &rest[end..];
+    }
+    // GOAWAY frame without processed streams and NO_ERROR code
+    assert!(frames
+        .iter()
+        .any(|(ty, payload)| *ty == 0x7 && payload[..8] == [0, 0, 0, 0, 0, 0, 0, 0]));
+}
+
+#[ntex::test]
+async fn test_h2_keep_alive() {
+    use std::io::{Read, Write};
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2_keep_alive_interval(Seconds(1))
+            .h2_keep_alive_timeout(Seconds(1))
+            .h2(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    // client that does not respond to pings
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+    tcp.set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut data = Vec::new();
+    let mut buf = [0; 1024];
+    while let Ok(n) = stream.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    assert!(start.elapsed() >= std::time::Duration::from_millis(1500));
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    let mut frames = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 9 {
+        let len = (rest[0] as usize) << 16 | (rest[1] as usize) << 8 | rest[2] as usize;
+        frames.push(rest[3]);
+        rest = &rest[std::cmp::min(9 + len, rest.len())..];
+    }
+    // PING frame type, connection is closed after keep-alive timeout
+    assert!(frames.contains(&0x6));
+}
+
+#[ntex::test]
+async fn test_h2_keep_alive_ack() {
+    use std::io::{Read, Write};
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2_keep_alive_interval(Seconds(1))
+            .h2_keep_alive_timeout(Seconds(1))
+            .h2(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+
+    // client that acknowledges pings
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+    tcp.set_read_timeout(Some(std::time::Duration::from_millis(100)))
+        .unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    let mut data = Vec::new();
+    let mut pings = 0;
+    let mut buf = [0; 1024];
+    while start.elapsed() < std::time::Duration::from_millis(3500) {
+        match stream.read(&mut buf) {
+            Ok(0) => panic!("connection is closed"),
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+            Err(_) => continue,
+        }
+        while data.len() >= 9 {
+            let len = (data[0] as usize) << 16 | (data[1] as usize) << 8 | data[2] as usize;
+            if data.len() < 9 + len {
+                break;
+            }
+            let frame: Vec<_> = data.drain(..9 + len).collect();
+            assert_ne!(frame[3], 0x7, "unexpected GOAWAY");
+
+            // PING without ACK flag
+            if frame[3] == 0x6 && frame[4] & 0x1 == 0 {
+                pings += 1;
+                let mut ack = b"\0\0\x08\x06\x01\0\0\0\0".to_vec();
+                ack.extend_from_slice(&frame[9..]);
+                stream.write_all(&ack).unwrap();
+            }
+        }
+    }
+    assert!(pings >= 2);
+}
+
 #[ntex::test]
 async fn test_ws_transport() {
     let mut srv = test_server(|| {
@@ -6,7 +6,7 @@ use futures_util::stream::{once, StreamExt};
 use regex::Regex;
 
 use ntex::http::header::{self, HeaderName, HeaderValue};
-use ntex::http::test::server as test_server;
+use ntex::http::test::{read_raw, server as test_server};
 use ntex::http::{
     body, HttpService, KeepAlive, Method, Request, Response, StatusCode, Version,
 };
@@ -52,6 +52,132 @@ async fn test_h1_2() {
     assert!(!hdr.to_str().unwrap().starts_with("000"));
 }
 
+#[ntex::test]
+async fn test_h1_server_header() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .server_header(Some("ntex-test"))
+            .h1(|req: Request| {
+                let res = if req.path() == "/custom" {
+                    Response::Ok().header(header::SERVER, "custom").finish()
+                } else {
+                    Response::Ok().finish()
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+    });
+
+    let response = srv.request(Method::GET, "/").send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.header(header::SERVER).unwrap(), "ntex-test");
+
+    // handler's header takes precedence
+    let response = srv.request(Method::GET, "/custom").send().await.unwrap();
+    assert_eq!(response.header(header::SERVER).unwrap(), "custom");
+
+    // disabled
+    let srv = test_server(|| {
+        HttpService::build()
+            .server_header(Some("ntex-test"))
+            .server_header(None)
+            .finish(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+    });
+    let response = srv.request(Method::GET, "/").send().await.unwrap();
+    assert!(response.status().is_success());
+    assert!(response.header(header::SERVER).is_none());
+}
+
+#[ntex::test]
+async fn test_early_hints() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive(KeepAlive::Disabled)
+            .h1(fn_service(|req: Request| async move {
+                let mut headers = header::HeaderMap::new();
+                headers.insert(
+                    header::LINK,
+                    HeaderValue::from_static("</style.css>; rel=preload; as=style"),
+                );
+                if req.send_early_hints(&headers).is_err() {
+                    return Ok::<_, io::Error>(Response::Ok().body("unsupported"));
+                }
+                sleep(Millis(20)).await;
+
+                headers.insert(
+                    header::LINK,
+                    HeaderValue::from_static("</script.js>; rel=preload; as=script"),
+                );
+                req.send_early_hints(&headers)?;
+                Ok(Response::Ok().body("done"))
+            }))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with(
+        "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload; as=style\r\n\r\n\
+         HTTP/1.1 103 Early Hints\r\nlink: </script.js>; rel=preload; as=script\r\n\r\n\
+         HTTP/1.1 200 OK\r\n"
+    ));
+    assert!(data.ends_with("done"));
+
+    // informational responses are not allowed for http/1.0 clients
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.0\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.0 200 OK\r\n"));
+    assert!(data.ends_with("unsupported"));
+}
+
+#[ntex::test]
+async fn test_max_uri_length() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .max_uri_length(64)
+            .h1(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ =
+        stream.write_all(format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(63)).as_bytes());
+    let mut data = vec![0; 1024];
+    let n = stream.read(&mut data).unwrap();
+    assert!(data[..n].starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+    // connection is closed after response
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(format!("GET /{}", "a".repeat(1024)).as_bytes());
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 414 URI Too Long\r\n"));
+}
+
+#[ntex::test]
+async fn test_custom_reason() {
+    let srv = test_server(|| {
+        HttpService::build().h1(|req: Request| {
+            let res = match req.path() {
+                "/reason" => Response::Ok().reason("Because").finish(),
+                _ => Response::new(StatusCode::from_u16(499).unwrap()),
+            };
+            Ready::Ok::<_, io::Error>(res)
+        })
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /reason HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let n = stream.read(&mut data).unwrap();
+    assert!(data[..n].starts_with(b"HTTP/1.1 200 Because\r\n"));
+
+    let _ = stream.write_all(b"GET /unknown HTTP/1.1\r\n\r\n");
+    let n = stream.read(&mut data).unwrap();
+    assert!(data[..n].starts_with(b"HTTP/1.1 499 \r\n"));
+}
+
 #[ntex::test]
 async fn test_expect_continue() {
     let srv = test_server(|| {
@@ -211,6 +337,83 @@ async fn test_http1_keepalive_timeout() {
     assert_eq!(res, 0);
 }
 
+#[ntex::test]
+async fn test_http1_idle_connections() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive(1)
+            .client_timeout(Seconds(1))
+            .h1(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+    });
+
+    // idle connections share worker's timer wheel
+    let mut streams = Vec::new();
+    for idx in 0..500 {
+        let mut stream = srv.connect_tcp().unwrap();
+        if idx % 2 == 0 {
+            stream
+                .write_all(b"GET /test/tests/test HTTP/1.1\r\n\r\n")
+                .unwrap();
+        }
+        streams.push((idx, stream));
+    }
+
+    for (idx, mut stream) in streams {
+        let data = read_raw(&mut stream, Millis(10_000)).unwrap();
+        if idx % 2 == 0 {
+            assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+        } else {
+            assert!(data.starts_with(b"HTTP/1.1 408"));
+        }
+        // connection is closed by server, not by read timeout
+        let res = stream.read(&mut [0; 16]);
+        assert!(
+            matches!(res, Ok(0))
+                || matches!(res, Err(ref e) if e.kind() == io::ErrorKind::ConnectionReset)
+        );
+    }
+}
+
+#[ntex::test]
+async fn test_h1_default_content_type() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .default_content_type("text/plain")
+            .h1(|req: Request| {
+                let res = match req.path() {
+                    "/json" => Response::Ok().content_type("application/json").body("{}"),
+                    "/empty" => Response::Ok().finish(),
+                    _ => Response::Ok().body("data"),
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+    });
+
+    let response = srv.request(Method::GET, "/").send().await.unwrap();
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/plain"
+    );
+
+    // handler's value takes precedence
+    let response = srv.request(Method::GET, "/json").send().await.unwrap();
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+
+    // empty body
+    let response = srv.request(Method::GET, "/empty").send().await.unwrap();
+    assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+
+    // disabled by default
+    let srv = test_server(|| {
+        HttpService::build().h1(|_| Ready::Ok::<_, io::Error>(Response::Ok().body("data")))
+    });
+    let response = srv.request(Method::GET, "/").send().await.unwrap();
+    assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+}
+
 /// Keep-alive must occure only while waiting complete request
 #[ntex::test]
 async fn test_http1_no_keepalive_during_response() {
@@ -255,6 +458,38 @@ async fn test_http1_keepalive_close() {
     assert_eq!(res, 0);
 }
 
+#[ntex::test]
+async fn test_http1_response_force_close() {
+    let srv = test_server(|| {
+        HttpService::build().h1(|req: Request| async move {
+            let mut res = Response::Ok().finish();
+            if req.path() == "/close" {
+                res.force_close();
+            }
+            Ok::<_, io::Error>(res)
+        })
+    });
+
+    // regular responses reuse connection
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    for _ in 0..2 {
+        let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+        let mut data = vec![0; 1024];
+        let _ = stream.read(&mut data);
+        assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+    }
+
+    // connection is closed after force-close response
+    let _ = stream.write_all(b"GET /close HTTP/1.1\r\n\r\n");
+    let data = read_raw(&mut stream, Millis(5_000)).unwrap();
+    assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+    assert!(String::from_utf8_lossy(&data).contains("connection: close\r\n"));
+
+    let mut data = vec![0; 1024];
+    let res = stream.read(&mut data).unwrap();
+    assert_eq!(res, 0);
+}
+
 #[ntex::test]
 async fn test_http10_keepalive_default_close() {
     let srv = test_server(|| {
@@ -581,7 +816,10 @@ async fn test_h1_response_http_error_handling() {
 
     // read response
     let bytes = srv.load_body(response).await.unwrap();
-    assert_eq!(bytes, Bytes::from_static(b"failed to parse header value"));
+    assert_eq!(
+        bytes,
+        Bytes::from_static(b"parse error: failed to parse header value")
+    );
 }
 
 #[ntex::test]
@@ -636,3 +874,99 @@ async fn test_h1_client_drop() -> io::Result<()> {
     assert_eq!(count.load(Ordering::Relaxed), 1);
     Ok(())
 }
+
+#[ntex::test]
+async fn test_h1_body_flush_point() {
+    let srv = test_server(|| {
+        HttpService::build().h1(|_| {
+            let body = futures_util::stream::iter(vec![
+                Ok::<_, io::Error>(body::StreamChunk::Data(Bytes::from_static(b"first"))),
+                Ok(body::StreamChunk::Flush),
+            ])
+            .chain(futures_util::stream::pending());
+            Ready::Ok::<_, io::Error>(Response::Ok().body(body::FlushStream::new(body)))
+        })
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+
+    // flushed chunk is received while the stream is still pending
+    let mut data = Vec::new();
+    let mut buf = [0; 1024];
+    while !data.ends_with(b"5\r\nfirst\r\n") {
+        let n = stream.read(&mut buf).unwrap();
+        assert!(n > 0);
+        data.extend_from_slice(&buf[..n]);
+    }
+    let data = String::from_utf8(data).unwrap();
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(data.contains("transfer-encoding: chunked\r\n"));
+    assert!(!data.contains("0\r\n\r\n"));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_serve_connection_tokio() {
+    use ntex::http::{serve_connection, test::read_raw};
+    use ntex::service::ServiceFactory;
+
+    // plain multi-threaded tokio runtime, without ntex system
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let local = tokio::task::LocalSet::new();
+
+    let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let client = std::thread::spawn(move || {
+        // first connection stays idle while second one is served
+        let mut idle = net::TcpStream::connect(addr).unwrap();
+        let mut results = Vec::new();
+        let mut stream = net::TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET /two HTTP/1.1\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        results.push(read_raw(&mut stream, Millis(5_000)).unwrap());
+        idle.write_all(b"GET /one HTTP/1.1\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        results.push(read_raw(&mut idle, Millis(5_000)).unwrap());
+        results
+    });
+
+    rt.block_on(local.run_until(async move {
+        let srv = HttpService::build()
+            .h1(|req: Request| async move {
+                Ok::<_, io::Error>(Response::Ok().body(format!("path: {}", req.path())))
+            })
+            .new_service(())
+            .await
+            .unwrap();
+        let srv = std::rc::Rc::new(srv);
+
+        listener.set_nonblocking(true).unwrap();
+        let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+        let mut handles = Vec::new();
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().await.unwrap();
+            let io = ntex::rt::from_tcp_stream(stream.into_std().unwrap()).unwrap();
+            let srv = srv.clone();
+            handles.push(tokio::task::spawn_local(async move {
+                serve_connection(io, &*srv).await.unwrap();
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    }));
+
+    let results = client.join().unwrap();
+    assert!(results[0].starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert!(results[0].ends_with(b"path: /two"));
+    assert!(results[1].ends_with(b"path: /one"));
+}
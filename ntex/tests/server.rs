@@ -5,7 +5,7 @@ use ntex::codec::BytesCodec;
 use ntex::io::Io;
 use ntex::server::{Server, TestServer};
 use ntex::service::fn_service;
-use ntex::util::{Bytes, Ready};
+use ntex::util::{Bytes, BytesMut, Ready};
 
 #[test]
 fn test_bind() {
@@ -298,3 +298,66 @@ fn test_panic_in_worker() {
     sys.stop();
     let _ = h.join();
 }
+
+#[derive(Clone)]
+struct LineCodec;
+
+impl ntex::codec::Decoder for LineCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&self, src: &mut BytesMut) -> Result<Option<String>, io::Error> {
+        if let Some(pos) = src.iter().position(|b| *b == b'\n') {
+            let line = src.split_to(pos + 1);
+            String::from_utf8(line[..pos].to_vec())
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl ntex::codec::Encoder for LineCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn encode(&self, item: String, dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.extend_from_slice(item.as_bytes());
+        dst.extend_from_slice(b"\n");
+        Ok(())
+    }
+}
+
+#[ntex::test]
+async fn test_build_framed() {
+    let srv = ntex::server::test_server(|| {
+        ntex::server::build_framed(
+            LineCodec,
+            fn_service(|line: String| async move {
+                if line.is_empty() {
+                    Ok::<_, ()>(None)
+                } else {
+                    Ok(Some(line))
+                }
+            }),
+        )
+    });
+
+    let io = srv.connect().await.unwrap();
+    io.send("hello".to_string(), &LineCodec).await.unwrap();
+    assert_eq!(io.recv(&LineCodec).await.unwrap().unwrap(), "hello");
+
+    // multiple frames in one chunk, empty line has no response
+    io.send(Bytes::from_static(b"one\n\ntwo\n"), &BytesCodec)
+        .await
+        .unwrap();
+    assert_eq!(io.recv(&LineCodec).await.unwrap().unwrap(), "one");
+    assert_eq!(io.recv(&LineCodec).await.unwrap().unwrap(), "two");
+
+    // decoder error closes connection
+    io.send(Bytes::from(vec![0xff, b'\n']), &BytesCodec)
+        .await
+        .unwrap();
+    assert!(matches!(io.recv(&LineCodec).await, Ok(None) | Err(_)));
+}
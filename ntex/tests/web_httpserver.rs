@@ -30,6 +30,8 @@ async fn test_run() {
             .client_timeout(Seconds(5))
             .disconnect_timeout(Seconds(1))
             .ssl_handshake_timeout(Seconds(1))
+            .h2_keep_alive_interval(Seconds(10))
+            .h2_keep_alive_timeout(Seconds(5))
             .server_hostname("localhost")
             .stop_runtime()
             .disable_signals()
@@ -140,6 +142,101 @@ async fn test_openssl() {
     sys.stop();
 }
 
+#[ntex::test]
+#[cfg(feature = "openssl")]
+async fn test_h2_graceful_shutdown() {
+    use std::io::{Read, Write};
+    use tls_openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+
+    let addr = TestServer::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = ntex::rt::System::new("test");
+        let builder = ssl_acceptor().unwrap();
+
+        sys.run(move || {
+            let srv = HttpServer::new(|| {
+                App::new().service(web::resource("/").route(web::to(|| async {
+                    sleep(Duration::from_millis(500)).await;
+                    HttpResponse::Ok().body("test")
+                })))
+            })
+            .workers(1)
+            .shutdown_timeout(Seconds(10))
+            .stop_runtime()
+            .disable_signals()
+            .bind_openssl(format!("{}", addr), builder)
+            .unwrap()
+            .run();
+            let _ = tx.send((srv, ntex::rt::System::current()));
+            Ok(())
+        })
+    });
+    let (srv, sys) = rx.recv().unwrap();
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let tcp = std::net::TcpStream::connect(addr).unwrap();
+    tcp.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let mut stream = builder.build().connect("localhost", tcp).unwrap();
+
+    // preface, empty SETTINGS and "GET /" HEADERS frame for stream 1
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .unwrap();
+    stream
+        .write_all(b"\0\0\x0e\x01\x05\0\0\0\x01\x82\x87\x84\x41\x09localhost")
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    // GOAWAY is sent, in-flight stream completes, new streams are refused
+    let start = std::time::Instant::now();
+    let _ = srv.stop(true);
+    thread::sleep(Duration::from_millis(100));
+    stream
+        .write_all(b"\0\0\x0e\x01\x05\0\0\0\x03\x82\x87\x84\x41\x09localhost")
+        .unwrap();
+
+    let mut data = Vec::new();
+    let mut buf = [0; 1024];
+    while let Ok(n) = stream.read(&mut buf) {
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // (frame type, stream id, payload)
+    let mut frames = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 9 {
+        let len = (rest[0] as usize) << 16 | (rest[1] as usize) << 8 | rest[2] as usize;
+        let end = std::cmp::min(9 + len, rest.len());
+        frames.push((rest[3], rest[8], rest[9..end].to_vec()));
+        rest = &rest[end..];
+    }
+
+    // stream 3 is refused
+    assert!(frames
+        .iter()
+        .any(|(t, id, payload)| *t == 0x3 && *id == 3 && payload[..] == [0, 0, 0, 7]));
+
+    // GOAWAY with last stream id, followed by response HEADERS and DATA for stream 1
+    let goaway = frames.iter().position(|(t, _, _)| *t == 0x7).unwrap();
+    assert_eq!(&frames[goaway].2[..8], &[0, 0, 0, 1, 0, 0, 0, 0]);
+    assert!(frames[goaway..]
+        .iter()
+        .any(|(t, id, _)| *t == 0x1 && *id == 1));
+    assert!(frames[goaway..]
+        .iter()
+        .any(|(t, id, payload)| *t == 0x0 && *id == 1 && payload == b"test"));
+
+    sys.stop();
+}
+
 #[ntex::test]
 #[cfg(all(feature = "rustls", feature = "openssl"))]
 async fn test_rustls() {
@@ -9,12 +9,12 @@ use thiserror::Error;
 
 use ntex::http::body::Body;
 use ntex::http::header::{
-    ContentEncoding, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE,
-    TRANSFER_ENCODING,
+    ContentEncoding, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE,
+    CONTENT_TYPE, ETAG, IF_NONE_MATCH, RANGE, TRANSFER_ENCODING,
 };
 use ntex::http::{Method, StatusCode};
 use ntex::time::{sleep, Millis, Seconds, Sleep};
-use ntex::util::{ready, Bytes, Ready, Stream};
+use ntex::util::{ready, select, Bytes, Either, Ready, Stream};
 
 use ntex::web::middleware::Compress;
 use ntex::web::{
@@ -154,6 +154,45 @@ async fn test_body_gzip2() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 }
 
+#[ntex::test]
+async fn test_body_no_compress() {
+    let srv = test::server_with(test::config().h1(), || {
+        App::new()
+            .wrap(Compress::new(ContentEncoding::Gzip))
+            .service(web::resource("/").route(web::to(|| async {
+                HttpResponse::Ok().no_compress().body(STR)
+            })))
+            .service(
+                web::resource("/gzip")
+                    .route(web::to(|| async { HttpResponse::Ok().body(STR) })),
+            )
+    });
+
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .header(ACCEPT_ENCODING, "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+
+    // response is sent as is
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+
+    // other routes are compressed
+    let response = srv
+        .get("/gzip")
+        .no_decompress()
+        .header(ACCEPT_ENCODING, "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+}
+
 #[ntex::test]
 async fn test_body_encoding_override() {
     let srv = test::server_with(test::config().h1(), || {
@@ -392,6 +431,63 @@ async fn test_no_chunking() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+#[ntex::test]
+async fn test_named_file() {
+    let srv = test::server_with(test::config().h1(), || {
+        App::new().service(web::resource("/").route(web::to(|| async {
+            web::fs::NamedFile::open("tests/test.png").await
+        })))
+    });
+    let data = std::fs::read("tests/test.png").unwrap();
+
+    let mut response = srv.get("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "image/png");
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        &format!("{}", data.len())
+    );
+    assert!(!response.headers().contains_key(TRANSFER_ENCODING));
+    let etag = response.headers().get(ETAG).unwrap().clone();
+    let bytes = response.body().limit(1_048_576).await.unwrap();
+    assert_eq!(bytes, Bytes::from(data.clone()));
+
+    // head request
+    let mut response = srv.head("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        &format!("{}", data.len())
+    );
+    assert!(response.body().await.unwrap().is_empty());
+
+    // conditional request
+    let mut response = srv
+        .get("/")
+        .header(IF_NONE_MATCH, etag)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert!(response.body().await.unwrap().is_empty());
+
+    // range request
+    let mut response = srv
+        .get("/")
+        .header(RANGE, "bytes=10-19")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers().get(CONTENT_LENGTH).unwrap(), "10");
+    assert_eq!(
+        response.headers().get(CONTENT_RANGE).unwrap(),
+        &format!("bytes 10-19/{}", data.len())
+    );
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::copy_from_slice(&data[10..20]));
+}
+
 #[ntex::test]
 async fn test_body_deflate() {
     let srv = test::server_with(test::config().h1(), || {
@@ -1159,3 +1255,512 @@ async fn test_custom_error() {
     let tp = response.headers().get(CONTENT_TYPE).unwrap();
     assert_eq!("application/json", tp.to_str().unwrap());
 }
+
+#[derive(Default)]
+struct ConnCounter(usize);
+
+async fn conn_counter(req: HttpRequest) -> HttpResponse {
+    let prev = req.conn_data::<ConnCounter>().map(|c| c.0).unwrap_or(0);
+    if let Some(mut data) = req.conn_data_mut() {
+        data.insert(ConnCounter(prev + 1));
+    }
+    HttpResponse::Ok().body(format!("{}", prev))
+}
+
+#[ntex::test]
+async fn test_conn_data_h1() {
+    use std::net;
+
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(conn_counter)))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    for expected in ["0", "1"] {
+        let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\n");
+        let mut data = vec![0; 1024];
+        let size = stream.read(&mut data).unwrap();
+        let data = String::from_utf8_lossy(&data[..size]);
+        assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(data.ends_with(&format!("\r\n\r\n{}", expected)));
+    }
+
+    // new connection gets new container
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let size = stream.read(&mut data).unwrap();
+    assert!(String::from_utf8_lossy(&data[..size]).ends_with("\r\n\r\n0"));
+}
+
+#[cfg(feature = "openssl")]
+#[ntex::test]
+async fn test_conn_data_openssl_h2() {
+    use ntex::http::client::{Client, Connector};
+    use tls_openssl::ssl::{AlpnError, SslAcceptor, SslFiletype, SslMethod};
+    use tls_openssl::ssl::{SslConnector, SslVerifyMode};
+
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    builder
+        .set_private_key_file("./tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    builder
+        .set_certificate_chain_file("./tests/cert.pem")
+        .unwrap();
+    builder.set_alpn_select_callback(|_, protos| {
+        const H2: &[u8] = b"\x02h2";
+        if protos.windows(3).any(|window| window == H2) {
+            Ok(b"h2")
+        } else {
+            Err(AlpnError::NOACK)
+        }
+    });
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+
+    let srv = test::server_with(test::config().openssl(builder.build()).h2(), || {
+        App::new().service(web::resource("/").route(web::to(conn_counter)))
+    });
+
+    // test server client does not reuse connections
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+    let client = Client::build()
+        .connector(Connector::default().openssl(builder.build()).finish())
+        .finish();
+
+    for expected in ["0", "1"] {
+        let mut response = client.get(srv.url("/")).send().await.unwrap();
+        assert!(response.status().is_success());
+        let bytes = response.body().await.unwrap();
+        assert_eq!(bytes, Bytes::from(expected));
+    }
+}
+
+#[ntex::test]
+async fn test_client_disconnect() {
+    use std::net;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let chunks = Arc::new(AtomicUsize::new(0));
+    let disconnected = Arc::new(AtomicBool::new(false));
+    let (chunks2, disconnected2) = (chunks.clone(), disconnected.clone());
+
+    let srv = test::server(move || {
+        let (chunks, disconnected) = (chunks2.clone(), disconnected2.clone());
+        App::new().service(web::resource("/").route(web::to(move |req: HttpRequest| {
+            let (chunks, disconnected) = (chunks.clone(), disconnected.clone());
+            async move {
+                let on_disconnect = req.on_disconnect();
+                ntex::rt::spawn(async move {
+                    on_disconnect.await;
+                    disconnected.store(true, Ordering::Relaxed);
+                });
+
+                HttpResponse::Ok().streaming(Box::pin(futures_util::stream::unfold(
+                    chunks,
+                    |chunks| async move {
+                        // first chunk is sent immediately, next one is delayed
+                        if chunks.fetch_add(1, Ordering::Relaxed) > 0 {
+                            sleep(Millis(200)).await;
+                        }
+                        Some((Ok::<_, io::Error>(Bytes::from_static(b"chunk")), chunks))
+                    },
+                )))
+            }
+        })))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert!(data.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    drop(stream);
+
+    sleep(Millis(100)).await;
+    assert!(disconnected.load(Ordering::Relaxed));
+
+    // pending response body must be dropped
+    let count = chunks.load(Ordering::Relaxed);
+    sleep(Millis(300)).await;
+    assert_eq!(chunks.load(Ordering::Relaxed), count);
+}
+
+#[ntex::test]
+async fn test_client_disconnect_in_handler() {
+    use std::net;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let canceled = Arc::new(AtomicBool::new(false));
+    let canceled2 = canceled.clone();
+
+    let srv = test::server(move || {
+        let canceled = canceled2.clone();
+        App::new().service(web::resource("/").route(web::to(move |req: HttpRequest| {
+            let canceled = canceled.clone();
+            async move {
+                // long running handler, aborted on client disconnect
+                match select(req.on_disconnect(), sleep(Millis(5_000))).await {
+                    Either::Left(_) => {
+                        canceled.store(true, Ordering::Relaxed);
+                        HttpResponse::InternalServerError()
+                    }
+                    Either::Right(_) => HttpResponse::Ok(),
+                }
+            }
+        })))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\n");
+    sleep(Millis(100)).await;
+    assert!(!canceled.load(Ordering::Relaxed));
+    drop(stream);
+
+    sleep(Millis(200)).await;
+    assert!(canceled.load(Ordering::Relaxed));
+}
+
+#[cfg(feature = "msgpack")]
+#[ntex::test]
+async fn test_msgpack() {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Info {
+        name: String,
+        visits: u32,
+    }
+
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::post().to(
+            |info: web::types::MsgPack<Info>| async move {
+                let info = info.into_inner();
+                web::types::MsgPack(Info {
+                    visits: info.visits + 1,
+                    ..info
+                })
+            },
+        )))
+    });
+
+    let info = Info {
+        name: "ntex".to_string(),
+        visits: 1,
+    };
+    let mut response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/msgpack")
+        .send_body(rmp_serde::to_vec_named(&info).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/msgpack"
+    );
+    let bytes = response.body().await.unwrap();
+    let info: Info = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(
+        info,
+        Info {
+            name: "ntex".to_string(),
+            visits: 2,
+        }
+    );
+
+    // malformed payload
+    let response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/msgpack")
+        .send_body(Bytes::from_static(b"\xc1\xc1"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // wrong content type
+    let response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/json")
+        .send_body(rmp_serde::to_vec_named(&info).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[ntex::test]
+async fn test_body_min_rate() {
+    use std::net;
+
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::post().to(
+            |pl: web::types::Payload| async move {
+                let mut pl = pl.0.min_rate(100, Seconds(1));
+                let mut size = 0;
+                while let Some(chunk) = pl.recv().await {
+                    size += chunk?.len();
+                }
+                Ok::<_, web::Error>(HttpResponse::Ok().body(format!("{}", size)))
+            },
+        )))
+    });
+
+    // body is sent at sufficient rate
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"POST / HTTP/1.1\r\ncontent-length: 200\r\n\r\n");
+    let _ = stream.write_all(&[b'x'; 100]);
+    sleep(Millis(500)).await;
+    let _ = stream.write_all(&[b'x'; 100]);
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert!(data.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+    // slow client
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"POST / HTTP/1.1\r\ncontent-length: 200\r\n\r\n");
+    let _ = stream.write_all(&[b'x'; 10]);
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert!(data.starts_with(b"HTTP/1.1 408 Request Timeout\r\n"));
+}
+
+#[cfg(feature = "cbor")]
+#[ntex::test]
+async fn test_cbor() {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Info {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    let srv = test::server(|| {
+        App::new().service(
+            web::resource("/")
+                .state(web::types::CborConfig::default().limit(64))
+                .route(web::post().to(|info: web::types::Cbor<Info>| async move {
+                    let mut info = info.into_inner();
+                    info.tags.push("echo".to_string());
+                    web::types::Cbor(info)
+                })),
+        )
+    });
+
+    let info = Info {
+        name: "ntex".to_string(),
+        tags: vec!["web".to_string()],
+    };
+    let mut body = Vec::new();
+    ciborium::ser::into_writer(&info, &mut body).unwrap();
+    let mut response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/cbor")
+        .send_body(body)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/cbor"
+    );
+    let bytes = response.body().await.unwrap();
+    let info: Info = ciborium::de::from_reader(&bytes[..]).unwrap();
+    assert_eq!(info.tags, vec!["web".to_string(), "echo".to_string()]);
+
+    // payload is bigger than limit
+    let info = Info {
+        name: "ntex".repeat(20),
+        tags: Vec::new(),
+    };
+    let mut body = Vec::new();
+    ciborium::ser::into_writer(&info, &mut body).unwrap();
+    let response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/cbor")
+        .send_body(body)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[cfg(feature = "protobuf")]
+#[ntex::test]
+async fn test_protobuf() {
+    // Generated by prost-build from:
+    //
+    // message Info {
+    //   string name = 1;
+    //   repeated string tags = 2;
+    // }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Info {
+        #[prost(string, tag = "1")]
+        pub name: ::prost::alloc::string::String,
+        #[prost(string, repeated, tag = "2")]
+        pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    }
+
+    let srv = test::server(|| {
+        App::new().service(
+            web::resource("/")
+                .state(web::types::ProtoBufConfig::default().limit(64))
+                .route(
+                    web::post().to(|info: web::types::ProtoBuf<Info>| async move {
+                        let mut info = info.into_inner();
+                        info.tags.push("echo".to_string());
+                        web::types::ProtoBuf(info)
+                    }),
+                ),
+        )
+    });
+
+    let info = Info {
+        name: "ntex".to_string(),
+        tags: vec!["web".to_string()],
+    };
+    let mut response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/protobuf")
+        .send_body(prost::Message::encode_to_vec(&info))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/protobuf"
+    );
+    let bytes = response.body().await.unwrap();
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        &bytes.len().to_string()
+    );
+    let info: Info = prost::Message::decode(&bytes[..]).unwrap();
+    assert_eq!(info.tags, vec!["web".to_string(), "echo".to_string()]);
+
+    // malformed payload
+    let mut response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/protobuf")
+        .send_body(&b"\x0a\x10ntex"[..])
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"ProtoBuf deserialize error"));
+
+    // payload is bigger than limit
+    let info = Info {
+        name: "ntex".repeat(20),
+        tags: Vec::new(),
+    };
+    let response = srv
+        .post("/")
+        .header(CONTENT_TYPE, "application/protobuf")
+        .send_body(prost::Message::encode_to_vec(&info))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[ntex::test]
+async fn test_body_memory_limiter() {
+    let limiter = web::types::BodyMemoryLimiter::new(100 * 1024);
+    let limiter2 = limiter.clone();
+
+    let srv = test::server(move || {
+        App::new()
+            .state(limiter2.clone())
+            .service(
+                web::resource("/").route(web::post().to(|body: Bytes| async move {
+                    sleep(Millis(500)).await;
+                    body.len().to_string()
+                })),
+            )
+    });
+
+    let data = Bytes::from(vec![b'x'; 60 * 1024]);
+    let results = futures_util::future::join_all((0..4).map(|_| {
+        let req = srv.post("/").send_body(data.clone());
+        async move { req.await.unwrap().status() }
+    }))
+    .await;
+
+    let ok = results.iter().filter(|s| **s == StatusCode::OK).count();
+    let unavailable = results
+        .iter()
+        .filter(|s| **s == StatusCode::SERVICE_UNAVAILABLE)
+        .count();
+    assert_eq!(ok, 1);
+    assert_eq!(unavailable, 3);
+
+    // memory is released with request
+    sleep(Millis(100)).await;
+    assert_eq!(limiter.used(), 0);
+    let response = srv.post("/").send_body(data.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[ntex::test]
+async fn test_static_body() {
+    let srv = test::server(|| {
+        App::new()
+            .route("/health", web::get().to(|| async { "ok" }))
+            .route("/data", web::get().to(|| async { &b"static data"[..] }))
+    });
+
+    let mut response = srv.get("/health").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(CONTENT_LENGTH).unwrap(), "2");
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"ok"));
+
+    let mut response = srv.get("/data").send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(CONTENT_LENGTH).unwrap(), "11");
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"static data"));
+}
+
+#[ntex::test]
+async fn test_body_memory_limiter_stream() {
+    let limiter = web::types::BodyMemoryLimiter::new(100 * 1024);
+    let limiter2 = limiter.clone();
+
+    let srv = test::server(move || {
+        App::new()
+            .state(limiter2.clone())
+            .service(web::resource("/").route(web::post().to(
+                |mut body: web::types::Payload| async move {
+                    let mut size = 0;
+                    while let Some(chunk) = body.recv().await {
+                        size += chunk?.len();
+                    }
+                    sleep(Millis(500)).await;
+                    Ok::<_, ntex::http::error::PayloadError>(size.to_string())
+                },
+            )))
+    });
+
+    let data = Bytes::from(vec![b'x'; 60 * 1024]);
+    let results = futures_util::future::join_all((0..4).map(|_| {
+        let req = srv.post("/").send_body(data.clone());
+        async move { req.await.unwrap().status() }
+    }))
+    .await;
+
+    let ok = results.iter().filter(|s| **s == StatusCode::OK).count();
+    let unavailable = results
+        .iter()
+        .filter(|s| **s == StatusCode::SERVICE_UNAVAILABLE)
+        .count();
+    assert_eq!(ok, 1);
+    assert_eq!(unavailable, 3);
+
+    // memory is released with request
+    sleep(Millis(100)).await;
+    assert_eq!(limiter.used(), 0);
+    let mut response = srv.post("/").send_body(data.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"61440"));
+}
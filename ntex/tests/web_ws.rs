@@ -1,7 +1,10 @@
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use ntex::http::StatusCode;
+use ntex::http::{header, StatusCode};
 use ntex::service::{fn_factory_with_config, fn_service};
+use ntex::time::{sleep, timeout, Millis, Seconds};
 use ntex::util::{ByteString, Bytes};
 use ntex::web::{self, test, ws, App, HttpRequest, HttpResponse};
 use ntex::ws::error::WsClientError;
@@ -137,3 +140,379 @@ async fn web_ws_client() {
     // TODO fix
     on_disconnect.await
 }
+
+#[ntex::test]
+async fn web_ws_builder() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(
+            |req: HttpRequest| async move {
+                ws::WsBuilder::new()
+                    .max_frame_size(16)
+                    .protocols(vec!["v2", "chat"])
+                    .start::<_, _, web::Error>(
+                        req,
+                        fn_factory_with_config(|_| async {
+                            Ok::<_, web::Error>(fn_service(service))
+                        }),
+                    )
+                    .await
+            },
+        )))
+    });
+
+    // subprotocol negotiation
+    let conn = ntex::ws::WsClient::build(srv.url("/"))
+        .address(srv.addr())
+        .protocols(["chat", "v2"])
+        .finish()
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(
+        conn.response()
+            .headers()
+            .get(header::SEC_WEBSOCKET_PROTOCOL)
+            .unwrap(),
+        "v2"
+    );
+
+    let (io, codec, res) = srv.ws().await.unwrap().into_inner();
+    assert!(res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).is_none());
+
+    io.send(ws::Message::Text(ByteString::from_static("text")), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+
+    // oversized frame
+    io.send(ws::Message::Binary(Bytes::from_static(&[0; 32])), &codec)
+        .await
+        .unwrap();
+    assert!(!matches!(io.recv(&codec).await, Ok(Some(_))));
+}
+
+#[ntex::test]
+async fn web_ws_unmasked_frames() {
+    let srv = test::server(|| {
+        App::new()
+            .service(
+                web::resource("/").route(web::to(|req: HttpRequest| async move {
+                    ws::WsBuilder::new()
+                        .accept_unmasked_frames(true)
+                        .start::<_, _, web::Error>(
+                            req,
+                            fn_factory_with_config(|_| async {
+                                Ok::<_, web::Error>(fn_service(service))
+                            }),
+                        )
+                        .await
+                })),
+            )
+            .service(web::resource("/masked").route(web::to(
+                |req: HttpRequest| async move {
+                    ws::start::<_, _, web::Error>(
+                        req,
+                        fn_factory_with_config(|_| async {
+                            Ok::<_, web::Error>(fn_service(service))
+                        }),
+                    )
+                    .await
+                },
+            )))
+    });
+
+    // server mode codec does not mask frames
+    let unmasked = ntex::ws::Codec::new();
+
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    io.send(
+        ws::Message::Text(ByteString::from_static("text")),
+        &unmasked,
+    )
+    .await
+    .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+
+    let (io, codec, _) = srv.ws_at("/masked").await.unwrap().into_inner();
+    io.send(
+        ws::Message::Text(ByteString::from_static("text")),
+        &unmasked,
+    )
+    .await
+    .unwrap();
+    assert!(!matches!(io.recv(&codec).await, Ok(Some(_))));
+}
+
+#[ntex::test]
+async fn web_ws_invalid_utf8() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(
+            |req: HttpRequest| async move {
+                ws::start::<_, _, web::Error>(
+                    req,
+                    fn_factory_with_config(|_| async {
+                        Ok::<_, web::Error>(fn_service(|msg| async move {
+                            match msg {
+                                ws::Frame::Continuation(_) => Ok(None),
+                                msg => service(msg).await,
+                            }
+                        }))
+                    }),
+                )
+                .await
+            },
+        )))
+    });
+
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    io.send(
+        ws::Message::Continuation(ntex::ws::Item::FirstText(Bytes::from_static(
+            b"a\xe2\x82",
+        ))),
+        &codec,
+    )
+    .await
+    .unwrap();
+    io.send(
+        ws::Message::Continuation(ntex::ws::Item::Last(Bytes::from_static(b"b"))),
+        &codec,
+    )
+    .await
+    .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Invalid.into())));
+}
+
+#[ntex::test]
+async fn web_ws_idle_timeout() {
+    let srv = test::server(|| {
+        App::new()
+            .service(
+                web::resource("/").route(web::to(|req: HttpRequest| async move {
+                    ws::WsBuilder::new()
+                        .idle_timeout(Seconds(1))
+                        .start::<_, _, web::Error>(
+                            req,
+                            fn_factory_with_config(|_| async {
+                                Ok::<_, web::Error>(fn_service(service))
+                            }),
+                        )
+                        .await
+                })),
+            )
+            .service(
+                web::resource("/push").route(web::to(|req: HttpRequest| async move {
+                    ws::WsBuilder::new()
+                        .idle_timeout(Seconds(1))
+                        .start::<_, _, web::Error>(
+                            req,
+                            fn_factory_with_config(|sink: ws::WsSink| async move {
+                                // server sends messages, client is silent
+                                ntex::rt::spawn(async move {
+                                    for _ in 0..3 {
+                                        sleep(Millis(600)).await;
+                                        let _ = sink
+                                            .send(ws::Message::Text("push".into()))
+                                            .await;
+                                    }
+                                });
+                                Ok::<_, web::Error>(fn_service(service))
+                            }),
+                        )
+                        .await
+                })),
+            )
+    });
+
+    // pings reset idle timer
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    for _ in 0..2 {
+        sleep(Millis(700)).await;
+        io.send(ws::Message::Ping("ping".into()), &codec)
+            .await
+            .unwrap();
+        let item = io.recv(&codec).await.unwrap().unwrap();
+        assert_eq!(item, ws::Frame::Pong("ping".into()));
+    }
+    let item = timeout(Millis(3000), io.recv(&codec))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
+
+    // outgoing messages reset idle timer
+    let (io, codec, _) = srv.ws_at("/push").await.unwrap().into_inner();
+    for _ in 0..3 {
+        let item = io.recv(&codec).await.unwrap().unwrap();
+        assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"push")));
+    }
+    let item = timeout(Millis(3000), io.recv(&codec))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
+}
+
+#[ntex::test]
+async fn web_ws_session() {
+    let srv = test::server(|| {
+        App::new()
+            .service(
+                web::resource("/").route(web::to(|req: HttpRequest| async move {
+                    ws::start_session::<_, _, web::Error>(req, |session| async move {
+                        while let Some(Ok(frame)) = session.recv().await {
+                            match frame {
+                                ws::Frame::Text(text) => {
+                                    let text = String::from_utf8_lossy(&text).to_string();
+                                    session.text(text).await.unwrap();
+                                }
+                                ws::Frame::Binary(bin) => {
+                                    session.binary(bin).await.unwrap()
+                                }
+                                ws::Frame::Close(_) => break,
+                                _ => (),
+                            }
+                        }
+                    })
+                    .await
+                })),
+            )
+            .service(
+                web::resource("/bye").route(web::to(|req: HttpRequest| async move {
+                    ws::start_session::<_, _, web::Error>(req, |session| async move {
+                        session.text("bye").await.unwrap();
+                    })
+                    .await
+                })),
+            )
+    });
+
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    io.send(ws::Message::Text(ByteString::from_static("text")), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+
+    io.send(ws::Message::Binary("bin".into()), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Binary(Bytes::from_static(b"bin")));
+
+    // pings are handled by session
+    io.send(ws::Message::Ping("ping".into()), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Pong("ping".into()));
+
+    io.send(
+        ws::Message::Close(Some(ws::CloseCode::Normal.into())),
+        &codec,
+    )
+    .await
+    .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
+    assert!(matches!(io.recv(&codec).await, Ok(None) | Err(_)));
+
+    // dropping session handles closes connection
+    let (io, codec, _) = srv.ws_at("/bye").await.unwrap().into_inner();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"bye")));
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
+    assert!(matches!(io.recv(&codec).await, Ok(None) | Err(_)));
+}
+
+#[ntex::test]
+async fn web_ws_session_broadcast() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(
+            |req: HttpRequest| async move {
+                ws::start_session::<_, _, web::Error>(req, |session| async move {
+                    // server-initiated messages from background task
+                    let s = session.clone();
+                    ntex::rt::spawn(async move {
+                        for i in 0..3 {
+                            sleep(Millis(50)).await;
+                            if s.text(format!("tick {}", i)).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+
+                    while let Some(Ok(frame)) = session.recv().await {
+                        if let ws::Frame::Close(_) = frame {
+                            break;
+                        }
+                    }
+                })
+                .await
+            },
+        )))
+    });
+
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    for i in 0..3 {
+        let item = io.recv(&codec).await.unwrap().unwrap();
+        assert_eq!(item, ws::Frame::Text(format!("tick {}", i).into()));
+    }
+
+    // connection is still open while background task holds session handle
+    io.send(ws::Message::Ping("ping".into()), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Pong("ping".into()));
+}
+
+#[ntex::test]
+async fn web_ws_session_backpressure() {
+    const COUNT: usize = 800;
+    const SIZE: usize = 32 * 1024;
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let sent2 = sent.clone();
+    let srv = test::server(move || {
+        let sent = sent2.clone();
+        App::new().service(web::resource("/").route(web::to(move |req: HttpRequest| {
+            let sent = sent.clone();
+            async move {
+                ws::WsBuilder::new()
+                    .max_write_buffer(SIZE * 2)
+                    .start_session::<_, _, web::Error>(req, move |session| async move {
+                        let data = Bytes::from(vec![b'x'; SIZE]);
+                        for _ in 0..COUNT {
+                            session.binary(data.clone()).await.unwrap();
+                            sent.fetch_add(1, Ordering::Relaxed);
+                        }
+                    })
+                    .await
+            }
+        })))
+    });
+
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+
+    // slow reader, sender must wait for write buffer
+    sleep(Millis(500)).await;
+    let num = sent.load(Ordering::Relaxed);
+    sleep(Millis(500)).await;
+    assert_eq!(num, sent.load(Ordering::Relaxed), "sender is not blocked");
+    assert!(num < COUNT);
+
+    for _ in 0..COUNT {
+        let item = io.recv(&codec).await.unwrap().unwrap();
+        assert!(matches!(item, ws::Frame::Binary(ref b) if b.len() == SIZE));
+    }
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
+    assert_eq!(sent.load(Ordering::Relaxed), COUNT);
+}
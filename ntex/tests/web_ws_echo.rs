@@ -0,0 +1,174 @@
+#![cfg(feature = "tokio")]
+use std::{cell::RefCell, convert::TryFrom, io, task::Context, task::Poll};
+
+use futures_util::{SinkExt, StreamExt};
+use ntex::service::{fn_factory_with_config, Service};
+use ntex::util::{ByteString, Bytes, BytesMut, Ready};
+use ntex::web::{self, test, ws, App, HttpRequest};
+use ntex::ws::Item;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{CloseCode, Data, OpCode};
+use tokio_tungstenite::tungstenite::protocol::{frame::Frame, CloseFrame};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Websocket echo service.
+///
+/// Text messages are echoed as text, binary messages as binary.
+/// Fragmented messages are collected and echoed as single message.
+/// Close frame is echoed back, after that connection get closed.
+#[derive(Default)]
+struct WsEchoService {
+    /// type of fragmented message and collected payload
+    fragments: RefCell<Option<(bool, BytesMut)>>,
+}
+
+impl WsEchoService {
+    fn message(text: bool, payload: Bytes) -> ws::Message {
+        if text {
+            match ByteString::try_from(payload) {
+                Ok(s) => ws::Message::Text(s),
+                Err(_) => ws::Message::Close(Some(ws::CloseCode::Invalid.into())),
+            }
+        } else {
+            ws::Message::Binary(payload)
+        }
+    }
+
+    fn continuation(&self, item: Item) -> Option<ws::Message> {
+        let mut fragments = self.fragments.borrow_mut();
+        match item {
+            Item::FirstText(data) => {
+                *fragments = Some((true, BytesMut::from(&data[..])));
+                None
+            }
+            Item::FirstBinary(data) => {
+                *fragments = Some((false, BytesMut::from(&data[..])));
+                None
+            }
+            Item::Continue(data) => {
+                if let Some((_, ref mut buf)) = *fragments {
+                    buf.extend_from_slice(&data);
+                }
+                None
+            }
+            Item::Last(data) => fragments.take().map(|(text, mut buf)| {
+                buf.extend_from_slice(&data);
+                Self::message(text, buf.freeze())
+            }),
+        }
+    }
+}
+
+impl Service<ws::Frame> for WsEchoService {
+    type Response = Option<ws::Message>;
+    type Error = io::Error;
+    type Future = Ready<Self::Response, io::Error>;
+
+    fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&self, frame: ws::Frame) -> Self::Future {
+        let msg = match frame {
+            ws::Frame::Text(text) => Some(Self::message(true, text)),
+            ws::Frame::Binary(bin) => Some(Self::message(false, bin)),
+            ws::Frame::Continuation(item) => self.continuation(item),
+            ws::Frame::Ping(msg) => Some(ws::Message::Pong(msg)),
+            ws::Frame::Pong(_) => None,
+            ws::Frame::Close(reason) => Some(ws::Message::Close(reason)),
+        };
+        Ready::Ok(msg)
+    }
+}
+
+fn echo_server() -> test::TestServer {
+    test::server(|| {
+        App::new().service(web::resource("/").route(web::get().to(
+            |req: HttpRequest| async move {
+                ws::start::<_, _, web::Error>(
+                    req,
+                    fn_factory_with_config(|_| async {
+                        Ok::<_, web::Error>(WsEchoService::default())
+                    }),
+                )
+                .await
+            },
+        )))
+    })
+}
+
+#[ntex::test]
+async fn test_ws_echo() {
+    let srv = echo_server();
+    let (mut client, res) = connect_async(format!("ws://{}/", srv.addr()))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 101);
+
+    client.send(Message::Text("hello".into())).await.unwrap();
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::Text("hello".into()));
+
+    client.send(Message::Binary(vec![0, 1, 2])).await.unwrap();
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::Binary(vec![0, 1, 2]));
+
+    client.send(Message::Ping(b"ping".to_vec())).await.unwrap();
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::Pong(b"ping".to_vec()));
+
+    // fragmented text message
+    let frames = [
+        Frame::message(b"hello ".to_vec(), OpCode::Data(Data::Text), false),
+        Frame::message(b"fragmented ".to_vec(), OpCode::Data(Data::Continue), false),
+        Frame::message(b"world".to_vec(), OpCode::Data(Data::Continue), true),
+    ];
+    for frame in frames {
+        client.send(Message::Frame(frame)).await.unwrap();
+    }
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::Text("hello fragmented world".into()));
+
+    // fragmented binary message
+    let frames = [
+        Frame::message(vec![1, 2], OpCode::Data(Data::Binary), false),
+        Frame::message(vec![3, 4], OpCode::Data(Data::Continue), true),
+    ];
+    for frame in frames {
+        client.send(Message::Frame(frame)).await.unwrap();
+    }
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(msg, Message::Binary(vec![1, 2, 3, 4]));
+
+    // close handshake
+    client
+        .send(Message::Close(Some(CloseFrame {
+            code: CloseCode::Normal,
+            reason: "bye".into(),
+        })))
+        .await
+        .unwrap();
+    let msg = client.next().await.unwrap().unwrap();
+    assert_eq!(
+        msg,
+        Message::Close(Some(CloseFrame {
+            code: CloseCode::Normal,
+            reason: "bye".into(),
+        }))
+    );
+    assert!(client.next().await.is_none());
+}
+
+#[ntex::test]
+async fn test_ws_echo_invalid_utf8() {
+    let srv = echo_server();
+    let (mut client, _) = connect_async(format!("ws://{}/", srv.addr()))
+        .await
+        .unwrap();
+
+    let frame = Frame::message(vec![0xff, 0xfe], OpCode::Data(Data::Text), true);
+    client.send(Message::Frame(frame)).await.unwrap();
+    match client.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Invalid),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}